@@ -3,7 +3,9 @@ use clipcat_base::ClipEntryMetadata;
 use crate::{
     config,
     finder::{
-        external::ExternalProgram, finder_stream::ENTRY_SEPARATOR, FinderStream, SelectionMode,
+        external::ExternalProgram,
+        finder_stream::{menu_text, ENTRY_SEPARATOR},
+        FinderStream, SelectionMode,
     },
 };
 
@@ -45,7 +47,11 @@ impl ExternalProgram for Choose {
 
 impl FinderStream for Choose {
     fn generate_input(&self, clips: &[ClipEntryMetadata]) -> String {
-        clips.iter().map(|clip| clip.preview.clone()).collect::<Vec<_>>().join(ENTRY_SEPARATOR)
+        clips
+            .iter()
+            .map(|clip| menu_text(clip).into_owned())
+            .collect::<Vec<_>>()
+            .join(ENTRY_SEPARATOR)
     }
 
     fn parse_output(&self, data: &[u8]) -> Vec<usize> {
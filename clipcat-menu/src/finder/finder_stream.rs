@@ -1,14 +1,28 @@
+use std::borrow::Cow;
+
 use clipcat_base::ClipEntryMetadata;
 
 pub const ENTRY_SEPARATOR: &str = "\n";
 pub const INDEX_SEPARATOR: char = ':';
 
+// shown before the text of sticky notes
+const STICKY_MARKER: &str = "[sticky]";
+
+/// Returns the text of the clip shown in menus, sticky notes are marked.
+pub fn menu_text(clip: &ClipEntryMetadata) -> Cow<'_, str> {
+    if clip.sticky {
+        Cow::Owned(format!("{STICKY_MARKER} {}", clip.display_text()))
+    } else {
+        Cow::Borrowed(clip.display_text())
+    }
+}
+
 pub trait FinderStream: Send + Sync {
     fn generate_input(&self, clips: &[ClipEntryMetadata]) -> String {
         clips
             .iter()
             .enumerate()
-            .map(|(i, ClipEntryMetadata { preview, .. })| format!("{i}{INDEX_SEPARATOR} {preview}"))
+            .map(|(i, clip)| format!("{i}{INDEX_SEPARATOR} {}", menu_text(clip)))
             .collect::<Vec<_>>()
             .join(ENTRY_SEPARATOR)
     }
@@ -65,6 +79,16 @@ mod tests {
         assert_eq!(v, "0: abcde\n1: АбВГД\n2: あいうえお");
     }
 
+    #[test]
+    fn test_generate_input_sticky() {
+        let d = Dummy;
+        let mut note = ClipEntry::from_string("todo", ClipboardKind::Clipboard);
+        note.set_sticky(true);
+        let clips = vec![note.metadata(None)];
+        let v = d.generate_input(&clips);
+        assert_eq!(v, "0: [sticky] todo");
+    }
+
     #[test]
     fn test_parse_output() {
         let d = Dummy;
@@ -11,6 +11,7 @@ use std::{io::Write, sync::Arc};
 
 use clap::{CommandFactory, Parser, Subcommand};
 use clipcat_base::{ClipFilter, ClipboardKind};
+use clipcat_server::backend::SelectionChange;
 use serde::Serialize;
 use snafu::ResultExt;
 use time::OffsetDateTime;
@@ -100,7 +101,8 @@ impl Cli {
                         let mut subscriber =
                             backend.subscribe().context(error::SubscribeClipboardSnafu)?;
 
-                        while let Some((kind, mime)) = subscriber.next().await {
+                        while let Some(SelectionChange { kind, mime, .. }) = subscriber.next().await
+                        {
                             match kind {
                                 ClipboardKind::Clipboard if enable_clipboard => {}
                                 ClipboardKind::Primary if enable_primary => {}
@@ -126,6 +126,14 @@ pub enum Commands {
         no_id: bool,
     },
 
+    #[clap(aliases = &["find"], about = "Print clips containing all words of <query>")]
+    Search {
+        #[clap(long)]
+        no_id: bool,
+
+        query: Vec<String>,
+    },
+
     #[clap(about = "Update clip with <id>")]
     Update {
         #[clap(value_parser = parse_hex)]
@@ -271,6 +279,11 @@ impl Cli {
                 Some(Commands::List { no_id }) => {
                     print_list(&client, config.preview_length, no_id).await?;
                 }
+                Some(Commands::Search { no_id, query }) => {
+                    let metadata_list =
+                        client.search(&query.join(" "), config.preview_length).await?;
+                    print_metadata_list(metadata_list, no_id).await?;
+                }
                 Some(Commands::Get { id }) => {
                     let data = if let Some(id) = id {
                         client.get(id).await?.preview_information(None)
@@ -361,8 +374,8 @@ impl Cli {
                         kinds.sort_unstable();
                         kinds.dedup();
                     }
-                    for kind in kinds {
-                        if client.mark(id, kind).await? {
+                    if client.mark_kinds(id, &kinds).await? {
+                        for kind in kinds {
                             println!("Ok ({kind})");
                         }
                     }
@@ -455,7 +468,13 @@ fn print_watcher_state(state: ClipboardWatcherState) {
 }
 
 async fn print_list(client: &Client, preview_length: usize, no_id: bool) -> Result<(), Error> {
-    let metadata_list = client.list(preview_length).await?;
+    print_metadata_list(client.list(preview_length).await?, no_id).await
+}
+
+async fn print_metadata_list(
+    metadata_list: Vec<ClipEntryMetadata>,
+    no_id: bool,
+) -> Result<(), Error> {
     for metadata in metadata_list {
         let ClipEntryMetadata { id, preview, .. } = metadata;
         let output = if no_id { preview } else { format!("{id:016x}: {preview}\n") };
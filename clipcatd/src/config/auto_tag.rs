@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AutoTagConfig {
+    #[serde(default = "AutoTagConfig::default_enable")]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub rules: Vec<AutoTagRuleConfig>,
+}
+
+impl AutoTagConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+}
+
+impl Default for AutoTagConfig {
+    fn default() -> Self { Self { enable: Self::default_enable(), rules: Vec::new() } }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AutoTagRuleConfig {
+    pub pattern: String,
+
+    pub tag: String,
+}
+
+impl From<AutoTagConfig> for clipcat_server::config::AutoTagConfig {
+    fn from(AutoTagConfig { enable, rules }: AutoTagConfig) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|AutoTagRuleConfig { pattern, tag }| clipcat_server::AutoTagRule { pattern, tag })
+            .collect();
+        Self { enable, rules }
+    }
+}
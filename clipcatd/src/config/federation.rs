@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FederationConfig {
+    #[serde(default)]
+    pub peers: Vec<FederationPeerConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FederationPeerConfig {
+    #[serde(with = "http_serde::uri")]
+    pub endpoint: http::Uri,
+
+    #[serde(default)]
+    pub access_token: Option<String>,
+}
+
+impl From<FederationPeerConfig> for clipcat_server::config::FederationPeerConfig {
+    fn from(FederationPeerConfig { endpoint, access_token }: FederationPeerConfig) -> Self {
+        Self { endpoint, access_token }
+    }
+}
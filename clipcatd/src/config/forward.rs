@@ -0,0 +1,40 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ForwardConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub program: PathBuf,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    #[serde(default = "ForwardConfig::default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl ForwardConfig {
+    #[inline]
+    pub const fn default_timeout_ms() -> u64 { 5000 }
+}
+
+impl Default for ForwardConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            program: PathBuf::new(),
+            args: Vec::new(),
+            timeout_ms: Self::default_timeout_ms(),
+        }
+    }
+}
+
+impl From<ForwardConfig> for clipcat_server::config::ForwardConfig {
+    fn from(ForwardConfig { program, args, timeout_ms, .. }: ForwardConfig) -> Self {
+        Self { program, args, timeout: Duration::from_millis(timeout_ms) }
+    }
+}
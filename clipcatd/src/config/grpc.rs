@@ -27,6 +27,15 @@ pub struct GrpcConfig {
 
     #[serde(default = "GrpcConfig::default_access_token_file_path")]
     pub access_token_file_path: Option<PathBuf>,
+
+    #[serde(default)]
+    pub enable_web: bool,
+
+    #[serde(default)]
+    pub web_allowed_origins: Vec<String>,
+
+    #[serde(default = "GrpcConfig::default_max_inserts_per_second")]
+    pub max_inserts_per_second: u32,
 }
 
 impl GrpcConfig {
@@ -50,6 +59,9 @@ impl GrpcConfig {
 
     #[inline]
     pub const fn default_access_token_file_path() -> Option<PathBuf> { None }
+
+    #[inline]
+    pub const fn default_max_inserts_per_second() -> u32 { 100 }
 }
 
 impl Default for GrpcConfig {
@@ -62,6 +74,9 @@ impl Default for GrpcConfig {
             local_socket: clipcat_base::config::default_unix_domain_socket(),
             access_token: Self::default_access_token(),
             access_token_file_path: Self::default_access_token_file_path(),
+            enable_web: false,
+            web_allowed_origins: Vec::new(),
+            max_inserts_per_second: Self::default_max_inserts_per_second(),
         }
     }
 }
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryFlushConfig {
+    #[serde(default)]
+    pub idle_ms: Option<u64>,
+
+    #[serde(default)]
+    pub max_unsaved_changes: Option<u64>,
+}
+
+impl From<HistoryFlushConfig> for clipcat_server::config::HistoryFlushConfig {
+    fn from(HistoryFlushConfig { idle_ms, max_unsaved_changes }: HistoryFlushConfig) -> Self {
+        Self {
+            idle: idle_ms.filter(|&ms| ms > 0).map(Duration::from_millis),
+            max_unsaved_changes: max_unsaved_changes.filter(|&count| count > 0),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::net::{IpAddr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HttpApiConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default = "HttpApiConfig::default_host")]
+    pub host: IpAddr,
+
+    #[serde(default = "HttpApiConfig::default_port")]
+    pub port: u16,
+}
+
+impl HttpApiConfig {
+    #[inline]
+    pub const fn socket_address(&self) -> SocketAddr { SocketAddr::new(self.host, self.port) }
+
+    #[inline]
+    pub const fn default_host() -> IpAddr { clipcat_base::DEFAULT_HTTP_API_HOST }
+
+    #[inline]
+    pub const fn default_port() -> u16 { clipcat_base::DEFAULT_HTTP_API_PORT }
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        Self { enable: false, host: Self::default_host(), port: Self::default_port() }
+    }
+}
+
+impl From<HttpApiConfig> for clipcat_server::config::HttpApiConfig {
+    fn from(config: HttpApiConfig) -> Self { Self { listen_address: config.socket_address() } }
+}
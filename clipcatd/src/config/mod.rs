@@ -1,9 +1,19 @@
+mod auto_tag;
 mod dbus;
 mod desktop_notification;
 mod error;
+mod federation;
+mod forward;
 mod grpc;
+mod history_flush;
+mod http_api;
 mod metrics;
+mod redaction;
+mod replication;
+mod semantic_kind;
+mod size_limits;
 mod snippet;
+mod transform;
 mod watcher;
 
 use std::path::{Path, PathBuf};
@@ -15,10 +25,15 @@ use snafu::ResultExt;
 
 pub use self::error::Error;
 use self::{
-    dbus::DBusConfig, desktop_notification::DesktopNotificationConfig, grpc::GrpcConfig,
-    metrics::MetricsConfig, snippet::SnippetConfig, watcher::WatcherConfig,
+    auto_tag::AutoTagConfig, dbus::DBusConfig, desktop_notification::DesktopNotificationConfig,
+    federation::FederationConfig, forward::ForwardConfig, grpc::GrpcConfig,
+    history_flush::HistoryFlushConfig, http_api::HttpApiConfig, metrics::MetricsConfig,
+    redaction::RedactionConfig, replication::ReplicationConfig, semantic_kind::SemanticKindConfig,
+    size_limits::SizeLimitsConfig, snippet::SnippetConfig, transform::ContentTransformConfig,
+    watcher::WatcherConfig,
 };
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Config {
     pub daemonize: bool,
@@ -32,12 +47,36 @@ pub struct Config {
     #[serde(default = "Config::default_max_history")]
     pub max_history: usize,
 
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+
+    #[serde(default)]
+    pub dedupe_scope: clipcat_base::DedupeScope,
+
+    #[serde(default)]
+    pub normalize_whitespace_for_dedupe: bool,
+
+    #[serde(default = "Config::default_coalesce_growing_edits")]
+    pub coalesce_growing_edits: bool,
+
+    #[serde(default)]
+    pub read_only_mode: bool,
+
+    #[serde(default)]
+    pub testing_mode: bool,
+
+    #[serde(default)]
+    pub compression_threshold_bytes: Option<usize>,
+
     #[serde(default = "Config::default_synchronize_selection_with_clipboard")]
     pub synchronize_selection_with_clipboard: bool,
 
     #[serde(default = "Config::default_history_file_path")]
     pub history_file_path: PathBuf,
 
+    #[serde(default)]
+    pub history_flush: HistoryFlushConfig,
+
     #[serde(default)]
     pub log: clipcat_cli::config::LogConfig,
 
@@ -56,8 +95,35 @@ pub struct Config {
     #[serde(default)]
     pub desktop_notification: DesktopNotificationConfig,
 
+    #[serde(default)]
+    pub size_limits: SizeLimitsConfig,
+
+    #[serde(default)]
+    pub federation: FederationConfig,
+
     #[serde(default)]
     pub snippets: Vec<SnippetConfig>,
+
+    #[serde(default)]
+    pub content_transform: ContentTransformConfig,
+
+    #[serde(default)]
+    pub auto_tag: AutoTagConfig,
+
+    #[serde(default)]
+    pub semantic_kind: SemanticKindConfig,
+
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    #[serde(default)]
+    pub http_api: HttpApiConfig,
+
+    #[serde(default)]
+    pub forward: ForwardConfig,
+
+    #[serde(default)]
+    pub replication: ReplicationConfig,
 }
 
 impl Default for Config {
@@ -67,7 +133,15 @@ impl Default for Config {
             pid_file: Self::default_pid_file_path(),
             primary_threshold_ms: Self::default_primary_threshold_ms(),
             max_history: Self::default_max_history(),
+            max_age_secs: None,
+            dedupe_scope: clipcat_base::DedupeScope::default(),
+            normalize_whitespace_for_dedupe: false,
+            coalesce_growing_edits: Self::default_coalesce_growing_edits(),
+            read_only_mode: false,
+            testing_mode: false,
+            compression_threshold_bytes: None,
             history_file_path: Self::default_history_file_path(),
+            history_flush: HistoryFlushConfig::default(),
             synchronize_selection_with_clipboard:
                 Self::default_synchronize_selection_with_clipboard(),
             log: clipcat_cli::config::LogConfig::default(),
@@ -76,7 +150,16 @@ impl Default for Config {
             desktop_notification: DesktopNotificationConfig::default(),
             dbus: DBusConfig::default(),
             metrics: MetricsConfig::default(),
+            size_limits: SizeLimitsConfig::default(),
+            federation: FederationConfig::default(),
             snippets: Vec::new(),
+            content_transform: ContentTransformConfig::default(),
+            auto_tag: AutoTagConfig::default(),
+            semantic_kind: SemanticKindConfig::default(),
+            redaction: RedactionConfig::default(),
+            http_api: HttpApiConfig::default(),
+            forward: ForwardConfig::default(),
+            replication: ReplicationConfig::default(),
         }
     }
 }
@@ -131,6 +214,9 @@ impl Config {
     #[inline]
     pub const fn default_primary_threshold_ms() -> i64 { 5000 }
 
+    #[inline]
+    pub const fn default_coalesce_growing_edits() -> bool { true }
+
     #[inline]
     pub const fn default_max_history() -> usize { 50 }
 
@@ -185,6 +271,9 @@ impl Config {
 
         config.history_file_path = resolve_path(&config.history_file_path)?;
 
+        config.replication.targets =
+            config.replication.targets.iter().map(resolve_path).collect::<Result<_, _>>()?;
+
         Ok(config)
     }
 }
@@ -195,18 +284,43 @@ impl From<Config> for clipcat_server::Config {
             grpc,
             primary_threshold_ms,
             max_history,
+            max_age_secs,
+            dedupe_scope,
+            normalize_whitespace_for_dedupe,
+            coalesce_growing_edits,
+            read_only_mode,
+            testing_mode,
+            compression_threshold_bytes,
             synchronize_selection_with_clipboard,
             history_file_path,
+            history_flush,
             watcher,
             desktop_notification,
             dbus,
             metrics,
+            size_limits,
+            federation,
             snippets,
+            content_transform,
+            auto_tag,
+            semantic_kind,
+            redaction,
+            http_api,
+            forward,
+            replication,
             ..
         }: Config,
     ) -> Self {
         let primary_threshold = time::Duration::milliseconds(primary_threshold_ms);
+        let max_age = max_age_secs
+            .filter(|&secs| secs > 0)
+            .map(|secs| time::Duration::seconds(i64::try_from(secs).unwrap_or(i64::MAX)));
         let grpc_listen_address = grpc.enable_http.then_some(grpc.socket_address());
+        let grpc_web = grpc.enable_web.then(|| clipcat_server::config::GrpcWebConfig {
+            allowed_origins: grpc.web_allowed_origins.clone(),
+        });
+        let grpc_max_inserts_per_second =
+            (grpc.max_inserts_per_second > 0).then_some(grpc.max_inserts_per_second);
         let grpc_local_socket = grpc.enable_local_socket.then_some(grpc.local_socket);
         let grpc_access_token = if let Some(file_path) = grpc.access_token_file_path {
             if let Ok(token) = std::fs::read_to_string(file_path) {
@@ -224,20 +338,49 @@ impl From<Config> for clipcat_server::Config {
         let metrics = clipcat_server::config::MetricsConfig::from(metrics);
         let snippets =
             snippets.into_iter().map(clipcat_server::config::SnippetConfig::from).collect();
+        let content_transform =
+            clipcat_server::config::ContentTransformConfig::from(content_transform);
+        let auto_tag = clipcat_server::config::AutoTagConfig::from(auto_tag);
+        let semantic_kind = clipcat_server::config::SemanticKindConfig::from(semantic_kind);
+        let redaction = clipcat_server::config::RedactionConfig::from(redaction);
+        let http_api =
+            http_api.enable.then(|| clipcat_server::config::HttpApiConfig::from(http_api));
+        let forward = forward.enable.then(|| clipcat_server::config::ForwardConfig::from(forward));
+        let replication = (replication.enable && !replication.targets.is_empty())
+            .then(|| clipcat_server::config::ReplicationConfig::from(replication));
 
         Self {
             grpc_listen_address,
             grpc_local_socket,
             grpc_access_token,
+            grpc_web,
+            grpc_max_inserts_per_second,
+            size_limits: size_limits.into(),
+            federation_peers: federation.peers.into_iter().map(Into::into).collect(),
             primary_threshold,
             max_history,
+            max_age,
+            dedupe_scope,
+            normalize_whitespace_for_dedupe,
+            coalesce_growing_edits,
+            read_only: read_only_mode,
+            testing: testing_mode,
+            compression_threshold: compression_threshold_bytes,
             synchronize_selection_with_clipboard,
             history_file_path,
+            history_flush: history_flush.into(),
             watcher,
             dbus,
             desktop_notification,
             metrics,
             snippets,
+            content_transform,
+            auto_tag,
+            semantic_kind,
+            redaction,
+            http_api,
+            forward,
+            replication,
         }
     }
 }
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RedactionConfig {
+    #[serde(default = "RedactionConfig::default_enable")]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub rules: Vec<RedactionRuleConfig>,
+}
+
+impl RedactionConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self { Self { enable: Self::default_enable(), rules: Vec::new() } }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RedactionRuleConfig {
+    pub pattern: String,
+
+    #[serde(default = "RedactionRuleConfig::default_replacement")]
+    pub replacement: String,
+}
+
+impl RedactionRuleConfig {
+    #[inline]
+    pub fn default_replacement() -> String { "***".to_string() }
+}
+
+impl From<RedactionConfig> for clipcat_server::config::RedactionConfig {
+    fn from(RedactionConfig { enable, rules }: RedactionConfig) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|RedactionRuleConfig { pattern, replacement }| clipcat_server::RedactionRule {
+                pattern,
+                replacement,
+            })
+            .collect();
+        Self { enable, rules }
+    }
+}
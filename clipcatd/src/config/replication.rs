@@ -0,0 +1,42 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReplicationConfig {
+    #[serde(default)]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub targets: Vec<PathBuf>,
+
+    #[serde(default = "ReplicationConfig::default_interval_ms")]
+    pub interval_ms: u64,
+
+    #[serde(default)]
+    pub reconcile_on_startup: bool,
+}
+
+impl ReplicationConfig {
+    #[inline]
+    pub const fn default_interval_ms() -> u64 { 1000 }
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            targets: Vec::new(),
+            interval_ms: Self::default_interval_ms(),
+            reconcile_on_startup: false,
+        }
+    }
+}
+
+impl From<ReplicationConfig> for clipcat_server::config::ReplicationConfig {
+    fn from(
+        ReplicationConfig { targets, interval_ms, reconcile_on_startup, .. }: ReplicationConfig,
+    ) -> Self {
+        Self { targets, interval: Duration::from_millis(interval_ms), reconcile_on_startup }
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SemanticKindConfig {
+    #[serde(default = "SemanticKindConfig::default_enable")]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub rules: Vec<SemanticKindRuleConfig>,
+}
+
+impl SemanticKindConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+}
+
+impl Default for SemanticKindConfig {
+    fn default() -> Self { Self { enable: Self::default_enable(), rules: Vec::new() } }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SemanticKindRuleConfig {
+    pub pattern: String,
+
+    pub kind: SemanticKind,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SemanticKind {
+    Url,
+    Code,
+    Path,
+    Number,
+    Prose,
+    Other,
+}
+
+impl From<SemanticKind> for clipcat_base::SemanticKind {
+    fn from(kind: SemanticKind) -> Self {
+        match kind {
+            SemanticKind::Url => Self::Url,
+            SemanticKind::Code => Self::Code,
+            SemanticKind::Path => Self::Path,
+            SemanticKind::Number => Self::Number,
+            SemanticKind::Prose => Self::Prose,
+            SemanticKind::Other => Self::Other,
+        }
+    }
+}
+
+impl From<SemanticKindConfig> for clipcat_server::config::SemanticKindConfig {
+    fn from(SemanticKindConfig { enable, rules }: SemanticKindConfig) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|SemanticKindRuleConfig { pattern, kind }| clipcat_server::SemanticKindRule {
+                pattern,
+                kind: kind.into(),
+            })
+            .collect();
+        Self { enable, rules }
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SizeLimitsConfig {
+    #[serde(default = "SizeLimitsConfig::default_enable")]
+    pub enable: bool,
+
+    #[serde(default = "SizeLimitsConfig::default_default_limit")]
+    pub default_limit: u64,
+
+    #[serde(default = "SizeLimitsConfig::default_rules")]
+    pub rules: BTreeMap<String, u64>,
+}
+
+impl SizeLimitsConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+
+    #[inline]
+    pub const fn default_default_limit() -> u64 { 1 << 20 }
+
+    #[inline]
+    pub fn default_rules() -> BTreeMap<String, u64> {
+        BTreeMap::from([("image/*".to_string(), 10 << 20), ("text/*".to_string(), 50 << 20)])
+    }
+}
+
+impl Default for SizeLimitsConfig {
+    fn default() -> Self {
+        Self {
+            enable: Self::default_enable(),
+            default_limit: Self::default_default_limit(),
+            rules: Self::default_rules(),
+        }
+    }
+}
+
+impl From<SizeLimitsConfig> for Option<clipcat_base::SizeLimits> {
+    fn from(SizeLimitsConfig { enable, default_limit, rules }: SizeLimitsConfig) -> Self {
+        enable
+            .then(|| clipcat_base::SizeLimits { rules: rules.into_iter().collect(), default_limit })
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ContentTransformConfig {
+    #[serde(default = "ContentTransformConfig::default_enable")]
+    pub enable: bool,
+
+    #[serde(default)]
+    pub rules: Vec<ContentTransformRuleConfig>,
+}
+
+impl ContentTransformConfig {
+    #[inline]
+    pub const fn default_enable() -> bool { true }
+}
+
+impl Default for ContentTransformConfig {
+    fn default() -> Self { Self { enable: Self::default_enable(), rules: Vec::new() } }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ContentTransformRuleConfig {
+    #[serde(default = "ContentTransformRuleConfig::default_mime")]
+    pub mime: String,
+
+    #[serde(default)]
+    pub transforms: Vec<ContentTransform>,
+}
+
+impl ContentTransformRuleConfig {
+    #[inline]
+    pub fn default_mime() -> String { "text/*".to_string() }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContentTransform {
+    Trim,
+    CollapseWhitespace,
+    LowercaseUrls,
+}
+
+impl From<ContentTransform> for clipcat_server::ContentTransform {
+    fn from(transform: ContentTransform) -> Self {
+        match transform {
+            ContentTransform::Trim => Self::Trim,
+            ContentTransform::CollapseWhitespace => Self::CollapseWhitespace,
+            ContentTransform::LowercaseUrls => Self::LowercaseUrls,
+        }
+    }
+}
+
+impl From<ContentTransformConfig> for clipcat_server::config::ContentTransformConfig {
+    fn from(ContentTransformConfig { enable, rules }: ContentTransformConfig) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|ContentTransformRuleConfig { mime, transforms }| {
+                let mime = mime
+                    .parse()
+                    .map_err(|_| tracing::warn!("Ignore content transform rule of `{mime}`"))
+                    .ok()?;
+                let transforms = transforms.into_iter().map(Into::into).collect();
+                Some(clipcat_server::ContentTransformRule { mime, transforms })
+            })
+            .collect();
+        Self { enable, rules }
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
@@ -18,12 +18,18 @@ pub struct WatcherConfig {
     #[serde(default = "WatcherConfig::default_sensitive_x11_atoms")]
     pub sensitive_x11_atoms: HashSet<String>,
 
+    #[serde(default)]
+    pub denied_applications: HashSet<String>,
+
     #[serde(default = "WatcherConfig::default_filter_text_min_length")]
     pub filter_text_min_length: usize,
 
     #[serde(default = "WatcherConfig::default_filter_text_max_length")]
     pub filter_text_max_length: usize,
 
+    #[serde(default)]
+    pub truncate_text: bool,
+
     #[serde(default)]
     pub denied_text_regex_patterns: HashSet<String>,
 
@@ -32,6 +38,9 @@ pub struct WatcherConfig {
 
     #[serde(default = "WatcherConfig::default_filter_image_max_size")]
     pub filter_image_max_size: usize,
+
+    #[serde(default)]
+    pub commit_delay_ms: u64,
 }
 
 impl Default for WatcherConfig {
@@ -43,9 +52,12 @@ impl Default for WatcherConfig {
             capture_image: true,
             filter_text_min_length: Self::default_filter_text_min_length(),
             filter_text_max_length: Self::default_filter_text_max_length(),
+            truncate_text: false,
             denied_text_regex_patterns: HashSet::new(),
             filter_image_max_size: Self::default_filter_image_max_size(),
             sensitive_x11_atoms: Self::default_sensitive_x11_atoms(),
+            denied_applications: HashSet::new(),
+            commit_delay_ms: 0,
         }
     }
 }
@@ -59,9 +71,12 @@ impl From<WatcherConfig> for clipcat_server::ClipboardWatcherOptions {
             capture_image,
             filter_text_min_length,
             filter_text_max_length,
+            truncate_text,
             denied_text_regex_patterns,
             filter_image_max_size,
             sensitive_x11_atoms,
+            denied_applications,
+            commit_delay_ms,
         }: WatcherConfig,
     ) -> Self {
         Self {
@@ -71,9 +86,12 @@ impl From<WatcherConfig> for clipcat_server::ClipboardWatcherOptions {
             capture_image,
             filter_text_min_length,
             filter_text_max_length,
+            truncate_text,
             filter_image_max_size,
             denied_text_regex_patterns,
             sensitive_x11_atoms,
+            denied_applications,
+            commit_delay: Duration::from_millis(commit_delay_ms),
         }
     }
 }
@@ -0,0 +1,48 @@
+use crate::ClipboardKind;
+
+/// An operation on a clip, applied with others in one transaction.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ClipOp {
+    /// Removes the clip from history.
+    Remove { id: u64 },
+
+    /// Marks the clip as the current clip of `kind`.
+    Mark { id: u64, kind: ClipboardKind },
+
+    /// Bumps the timestamp of the clip to now, without counting it as a use.
+    Promote { id: u64 },
+
+    /// Replaces the tags of the clip.
+    SetTags { id: u64, tags: Vec<String> },
+}
+
+impl ClipOp {
+    /// Returns the id of the clip the operation applies to.
+    #[must_use]
+    pub const fn id(&self) -> u64 {
+        match self {
+            Self::Remove { id }
+            | Self::Mark { id, .. }
+            | Self::Promote { id }
+            | Self::SetTags { id, .. } => *id,
+        }
+    }
+}
+
+/// The outcome of a [`ClipOp`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ClipOpResult {
+    /// The operation is applied.
+    Applied,
+
+    /// The clip does not exist, e.g. it is removed by a previous operation.
+    NotFound,
+
+    /// The operation failed, like marking a clip as a disabled clipboard kind.
+    Failed { message: String },
+}
+
+impl ClipOpResult {
+    #[must_use]
+    pub const fn is_applied(&self) -> bool { matches!(self, Self::Applied) }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// The category of the content of a clip, derived from its mime type.
+///
+/// | mime type                            | category |
+/// |--------------------------------------|----------|
+/// | `text/html`, `application/xhtml+xml` | `Html`   |
+/// | other `text/*`                       | `Text`   |
+/// | `image/*`                            | `Image`  |
+/// | anything else                        | `Other`  |
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ContentKind {
+    Text,
+    Html,
+    Image,
+    Other,
+}
+
+impl ContentKind {
+    #[must_use]
+    pub fn from_mime(mime: &mime::Mime) -> Self {
+        if mime.essence_str() == "text/html" || mime.essence_str() == "application/xhtml+xml" {
+            Self::Html
+        } else if mime.type_() == mime::TEXT {
+            Self::Text
+        } else if mime.type_() == mime::IMAGE {
+            Self::Image
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Returns `true` for `Text` and `Html`.
+    #[inline]
+    #[must_use]
+    pub const fn is_text(self) -> bool { matches!(self, Self::Text | Self::Html) }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_image(self) -> bool { matches!(self, Self::Image) }
+
+    #[inline]
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "Text",
+            Self::Html => "Html",
+            Self::Image => "Image",
+            Self::Other => "Other",
+        }
+    }
+}
+
+impl fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
@@ -0,0 +1,29 @@
+/// The direction in which the current clip is moved through history, which is
+/// ordered newest first.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum CycleDirection {
+    /// Towards older clips.
+    #[default]
+    Next,
+
+    /// Towards newer clips.
+    Previous,
+}
+
+impl From<i32> for CycleDirection {
+    fn from(direction: i32) -> Self {
+        match direction {
+            1 => Self::Previous,
+            _ => Self::Next,
+        }
+    }
+}
+
+impl From<CycleDirection> for i32 {
+    fn from(direction: CycleDirection) -> Self {
+        match direction {
+            CycleDirection::Next => 0,
+            CycleDirection::Previous => 1,
+        }
+    }
+}
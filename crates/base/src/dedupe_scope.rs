@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// Which clips with the same content are merged into one history entry.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DedupeScope {
+    /// Every copy is a new entry, except the current clip being captured
+    /// again.
+    None,
+
+    /// Clips copied from the same clipboard kind are merged.
+    PerMode,
+
+    /// Clips are merged whichever clipboard kind they are copied from.
+    #[default]
+    Global,
+}
+
+impl DedupeScope {
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::None => "none",
+            Self::PerMode => "per-mode",
+            Self::Global => "global",
+        }
+    }
+}
+
+impl From<i32> for DedupeScope {
+    fn from(scope: i32) -> Self {
+        match scope {
+            1 => Self::PerMode,
+            2 => Self::None,
+            _ => Self::Global,
+        }
+    }
+}
+
+impl From<DedupeScope> for i32 {
+    fn from(scope: DedupeScope) -> Self {
+        match scope {
+            DedupeScope::Global => 0,
+            DedupeScope::PerMode => 1,
+            DedupeScope::None => 2,
+        }
+    }
+}
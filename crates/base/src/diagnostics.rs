@@ -0,0 +1,62 @@
+use time::OffsetDateTime;
+
+use crate::ClipboardKind;
+
+/// Health of the clipboard watcher of a Clipcat server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostics {
+    /// Whether the watcher worker is running, no clip is captured after it
+    /// stopped.
+    pub worker_running: bool,
+
+    pub kinds: Vec<KindDiagnostics>,
+
+    /// The latest errors of the watcher, oldest first.
+    pub recent_errors: Vec<DiagnosticError>,
+
+    /// Number of captured clips which clipcat stored into the clipboard
+    /// itself, like marked clips, they are not inserted again.
+    pub self_captures_suppressed: u64,
+
+    /// Number of changes of history which are not saved to the history file
+    /// yet, they are lost if the server crashes.
+    pub unsaved_changes: u64,
+
+    /// Number of clips which could not be forwarded to the forward program,
+    /// the reasons are in `recent_errors`.
+    pub forward_failures: u64,
+
+    /// Number of times a replica of the history could not be synced, the
+    /// reasons are in `recent_errors`.
+    pub replication_failures: u64,
+
+    /// Number of changes of history which are not mirrored to all replicas
+    /// yet.
+    pub replication_lag: u64,
+}
+
+/// Health of the clipboard watcher for a clipboard kind.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KindDiagnostics {
+    pub kind: ClipboardKind,
+
+    pub enabled: bool,
+
+    /// Time of the last clip captured from this clipboard.
+    pub last_capture: Option<OffsetDateTime>,
+
+    /// Whether the backend knows the targets offered by the owner of the
+    /// selection, the current targets are always empty otherwise.
+    pub targets_supported: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiagnosticError {
+    pub timestamp: OffsetDateTime,
+
+    /// The clipboard kind which the error occurred on, `None` if the error is
+    /// not specific to a clipboard.
+    pub kind: Option<ClipboardKind>,
+
+    pub message: String,
+}
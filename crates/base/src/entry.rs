@@ -1,5 +1,6 @@
 use std::{
     cmp::Ordering,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
     fmt,
     hash::{Hash, Hasher},
 };
@@ -9,16 +10,75 @@ use sha2::{Digest, Sha256};
 use snafu::{ResultExt, Snafu};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
 
-use crate::{ClipboardContent, ClipboardKind};
+use crate::{
+    utils::html_to_plaintext, ClipKind, ClipboardContent, ClipboardKind, ImageFormat, SemanticKind,
+    WindowInfo,
+};
 
 #[derive(Clone, Debug, Eq)]
 pub struct Entry {
+    // derived from the content, unless the server keeps clips with the same
+    // content apart, see `DedupeScope`, or the clip is copied from another
+    // history with its id
+    id: u64,
+
     content: ClipboardContent,
 
     clipboard_kind: ClipboardKind,
 
+    // time of the last update, which is used for sorting
     timestamp: OffsetDateTime,
 
+    // time of the first insertion into history
+    created_at: OffsetDateTime,
+
+    // full mime type, including parameters like `charset`
+    mime: mime::Mime,
+
+    // whether clipcat owned the selection when the clip was captured
+    owned_by_us: bool,
+
+    tags: BTreeSet<String>,
+
+    // opaque key-value pairs attached by clients
+    attributes: BTreeMap<String, String>,
+
+    // shown in place of the preview of the content
+    label: Option<String>,
+
+    // targets offered by the application which owned the selection
+    available_targets: Vec<String>,
+
+    // the clip is removed from history after this time
+    expires_at: Option<OffsetDateTime>,
+
+    // the clip is not evicted from history before this time
+    protected_until: Option<OffsetDateTime>,
+
+    // increased by the server on every change of the clip
+    version: u64,
+
+    // size of the content in bytes before it was truncated at capture time
+    original_size: Option<u64>,
+
+    // increased by the server every time the clip is marked or touched
+    use_count: u64,
+
+    // the window which owned the selection when the clip was captured
+    source_window: Option<WindowInfo>,
+
+    // the clip is kept in memory only, it is never saved or exported
+    private: bool,
+
+    // a note pinned to the top of history, edited in place
+    sticky: bool,
+
+    // the tags set by the tagging rules of the server, a subset of `tags`
+    auto_tags: BTreeSet<String>,
+
+    // assigned by the classifier of the server to text clips, or set by users
+    semantic_kind: Option<SemanticKind>,
+
     sha256_digest: Vec<u8>,
 }
 
@@ -31,8 +91,8 @@ impl Entry {
         clipboard_kind: ClipboardKind,
         timestamp: Option<OffsetDateTime>,
     ) -> Result<Self, Error> {
-        let content = if mime.type_() == mime::TEXT {
-            ClipboardContent::Plaintext(String::from_utf8_lossy(data).to_string())
+        let (content, mime) = if mime.type_() == mime::TEXT {
+            (ClipboardContent::Plaintext(String::from_utf8_lossy(data).to_string()), mime.clone())
         } else if mime.type_() == mime::IMAGE {
             let image_format = match mime.subtype() {
                 mime::PNG => image::ImageFormat::Png,
@@ -45,7 +105,7 @@ impl Entry {
             let cursor = std::io::Cursor::new(&data);
             let mut reader = image::ImageReader::new(cursor);
             reader.set_format(image_format);
-            reader
+            let content = reader
                 .decode()
                 .map(|img| {
                     let image = img.into_rgba8();
@@ -56,7 +116,10 @@ impl Entry {
                         bytes: bytes::Bytes::from(image.into_raw()),
                     }
                 })
-                .context(ConvertImageSnafu {})?
+                .context(ConvertImageSnafu {})?;
+            // images are decoded and always encoded as PNG
+            let mime = content.mime();
+            (content, mime)
         } else {
             return Err(Error::FormatNotAvailable);
         };
@@ -64,7 +127,30 @@ impl Entry {
         let sha256_digest = compute_sha256_digest(&content);
         let timestamp = timestamp.unwrap_or_else(OffsetDateTime::now_utc);
 
-        Ok(Self { content, clipboard_kind, timestamp, sha256_digest })
+        Ok(Self {
+            id: content.id(),
+            content,
+            clipboard_kind,
+            timestamp,
+            created_at: timestamp,
+            mime,
+            owned_by_us: false,
+            tags: BTreeSet::new(),
+            attributes: BTreeMap::new(),
+            label: None,
+            available_targets: Vec::new(),
+            expires_at: None,
+            protected_until: None,
+            version: 0,
+            original_size: None,
+            use_count: 0,
+            source_window: None,
+            private: false,
+            sticky: false,
+            auto_tags: BTreeSet::new(),
+            semantic_kind: None,
+            sha256_digest,
+        })
     }
 
     #[inline]
@@ -80,17 +166,40 @@ impl Entry {
         timestamp: Option<OffsetDateTime>,
     ) -> Self {
         let sha256_digest = compute_sha256_digest(&content);
+        let mime = content.mime();
+        let timestamp = timestamp.unwrap_or_else(OffsetDateTime::now_utc);
         Self {
+            id: content.id(),
             content,
             clipboard_kind,
-            timestamp: timestamp.unwrap_or_else(OffsetDateTime::now_utc),
+            timestamp,
+            created_at: timestamp,
+            mime,
+            owned_by_us: false,
+            tags: BTreeSet::new(),
+            attributes: BTreeMap::new(),
+            label: None,
+            available_targets: Vec::new(),
+            expires_at: None,
+            protected_until: None,
+            version: 0,
+            original_size: None,
+            use_count: 0,
+            source_window: None,
+            private: false,
+            sticky: false,
+            auto_tags: BTreeSet::new(),
+            semantic_kind: None,
             sha256_digest,
         }
     }
 
     #[inline]
     #[must_use]
-    pub fn id(&self) -> u64 { self.content.id() }
+    pub const fn id(&self) -> u64 { self.id }
+
+    #[inline]
+    pub fn set_id(&mut self, id: u64) { self.id = id; }
 
     #[inline]
     #[must_use]
@@ -103,6 +212,238 @@ impl Entry {
     #[inline]
     pub fn set_timestamp(&mut self, timestamp: OffsetDateTime) { self.timestamp = timestamp; }
 
+    /// Returns the time of the last update of the clip, it is the same as
+    /// [`Entry::timestamp`].
+    #[inline]
+    #[must_use]
+    pub const fn updated_at(&self) -> OffsetDateTime { self.timestamp }
+
+    /// Returns the time the clip was first inserted into history.
+    ///
+    /// It is kept when the clip is marked, touched, updated or inserted again.
+    #[inline]
+    #[must_use]
+    pub const fn created_at(&self) -> OffsetDateTime { self.created_at }
+
+    #[inline]
+    pub fn set_created_at(&mut self, created_at: OffsetDateTime) { self.created_at = created_at; }
+
+    /// Returns `true` if the selection was owned by clipcat when the clip was
+    /// captured, i.e. the clip was captured from clipcat's own assertion
+    /// instead of being set by another application.
+    #[inline]
+    #[must_use]
+    pub const fn owned_by_us(&self) -> bool { self.owned_by_us }
+
+    #[inline]
+    pub fn set_owned_by_us(&mut self, owned_by_us: bool) { self.owned_by_us = owned_by_us; }
+
+    #[inline]
+    #[must_use]
+    pub const fn tags(&self) -> &BTreeSet<String> { &self.tags }
+
+    /// Replaces the tags of the clip, empty tags are ignored.
+    #[inline]
+    pub fn set_tags<I, S>(&mut self, tags: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).filter(|tag| !tag.is_empty()).collect();
+        self.auto_tags.retain(|tag| self.tags.contains(tag));
+    }
+
+    /// Returns the tags set by the tagging rules of the server, the other
+    /// tags are set by users.
+    #[inline]
+    #[must_use]
+    pub const fn auto_tags(&self) -> &BTreeSet<String> { &self.auto_tags }
+
+    /// Replaces the tags set by tagging rules, they are added to the tags.
+    #[inline]
+    pub fn set_auto_tags<I, S>(&mut self, tags: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.auto_tags = tags.into_iter().map(Into::into).filter(|tag| !tag.is_empty()).collect();
+        self.tags.extend(self.auto_tags.iter().cloned());
+    }
+
+    /// Returns what the clip is about, like [`SemanticKind::Url`], `None` if
+    /// it is not classified, images are never classified.
+    #[inline]
+    #[must_use]
+    pub const fn semantic_kind(&self) -> Option<SemanticKind> { self.semantic_kind }
+
+    #[inline]
+    pub fn set_semantic_kind(&mut self, semantic_kind: Option<SemanticKind>) {
+        self.semantic_kind = semantic_kind;
+    }
+
+    /// Returns the key-value pairs attached to the clip by clients, like the
+    /// source URL, which are opaque to the server.
+    #[inline]
+    #[must_use]
+    pub const fn attributes(&self) -> &BTreeMap<String, String> { &self.attributes }
+
+    #[inline]
+    pub fn set_attributes<I, K, V>(&mut self, attributes: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.attributes =
+            attributes.into_iter().map(|(key, value)| (key.into(), value.into())).collect();
+    }
+
+    /// Returns the label shown in place of the preview of the content, like
+    /// "my SSH key".
+    #[inline]
+    #[must_use]
+    pub fn label(&self) -> Option<&str> { self.label.as_deref() }
+
+    /// Sets the label of the clip, an empty label is removed.
+    #[inline]
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label.filter(|label| !label.is_empty());
+    }
+
+    /// Returns the targets offered by the application which owned the
+    /// selection when the clip was captured, like `UTF8_STRING` and
+    /// `text/html` on X11, empty if they are not known.
+    ///
+    /// Only the target of the stored content can be offered again when the
+    /// clip is marked, the data of the other targets is not captured.
+    #[inline]
+    #[must_use]
+    pub fn available_targets(&self) -> &[String] { &self.available_targets }
+
+    #[inline]
+    pub fn set_available_targets(&mut self, targets: Vec<String>) {
+        self.available_targets = targets;
+    }
+
+    /// Sets the mime type of a text clip, like `text/uri-list` for a text
+    /// loaded from a list of files.
+    ///
+    /// The mime type of images and non-text mime types are ignored, since
+    /// images are always stored as PNG.
+    #[inline]
+    pub fn set_text_mime(&mut self, mime: mime::Mime) {
+        if matches!(self.content, ClipboardContent::Plaintext(_)) && mime.type_() == mime::TEXT {
+            self.mime = mime;
+        }
+    }
+
+    /// Returns the total size of the keys and the values of the attributes in
+    /// bytes.
+    #[inline]
+    #[must_use]
+    pub fn attributes_size(&self) -> usize {
+        self.attributes.iter().map(|(key, value)| key.len() + value.len()).sum()
+    }
+
+    /// Returns the time after which the clip is removed from history, `None`
+    /// if the clip does not expire.
+    #[inline]
+    #[must_use]
+    pub const fn expires_at(&self) -> Option<OffsetDateTime> { self.expires_at }
+
+    #[inline]
+    pub fn set_expires_at(&mut self, expires_at: Option<OffsetDateTime>) {
+        self.expires_at = expires_at;
+    }
+
+    /// Returns the time before which the clip is not evicted for the
+    /// capacity or the maximum age of history, `None` if it is not protected.
+    #[inline]
+    #[must_use]
+    pub const fn protected_until(&self) -> Option<OffsetDateTime> { self.protected_until }
+
+    #[inline]
+    pub fn set_protected_until(&mut self, protected_until: Option<OffsetDateTime>) {
+        self.protected_until = protected_until;
+    }
+
+    /// Returns `true` if the clip is protected from eviction at `now`.
+    #[inline]
+    #[must_use]
+    pub fn is_protected(&self, now: OffsetDateTime) -> bool {
+        self.protected_until.is_some_and(|protected_until| protected_until > now)
+    }
+
+    /// Returns the version of the clip, which is increased on every change of
+    /// the clip in history, including marking and touching it.
+    #[inline]
+    #[must_use]
+    pub const fn version(&self) -> u64 { self.version }
+
+    #[inline]
+    pub fn set_version(&mut self, version: u64) { self.version = version; }
+
+    /// Returns `true` if the content was truncated when it was captured
+    /// because it exceeded the size limit, it is not the full copy.
+    #[inline]
+    #[must_use]
+    pub const fn is_truncated(&self) -> bool { self.original_size.is_some() }
+
+    /// Returns the size in bytes of the content before it was truncated at
+    /// capture time, `None` if it is not truncated.
+    #[inline]
+    #[must_use]
+    pub const fn original_size(&self) -> Option<u64> { self.original_size }
+
+    #[inline]
+    pub fn set_original_size(&mut self, original_size: Option<u64>) {
+        self.original_size = original_size;
+    }
+
+    /// Returns how many times the clip was marked or touched, which is used to
+    /// list the most used clips.
+    #[inline]
+    #[must_use]
+    pub const fn use_count(&self) -> u64 { self.use_count }
+
+    #[inline]
+    pub fn set_use_count(&mut self, use_count: u64) { self.use_count = use_count; }
+
+    #[inline]
+    pub fn increment_use_count(&mut self) { self.use_count = self.use_count.saturating_add(1); }
+
+    /// Returns the window which owned the selection when the clip was
+    /// captured, `None` if it is not known, e.g. on Wayland or for clips
+    /// inserted by clients.
+    #[inline]
+    #[must_use]
+    pub const fn source_window(&self) -> Option<&WindowInfo> { self.source_window.as_ref() }
+
+    #[inline]
+    pub fn set_source_window(&mut self, source_window: Option<WindowInfo>) {
+        self.source_window = source_window;
+    }
+
+    /// Returns `true` if the clip is kept in memory only, private clips are
+    /// never saved into the history file nor exported, so they are dropped on
+    /// restart.
+    #[inline]
+    #[must_use]
+    pub const fn is_private(&self) -> bool { self.private }
+
+    #[inline]
+    pub fn set_private(&mut self, private: bool) { self.private = private; }
+
+    /// Returns `true` if the clip is a sticky note, which is listed before the
+    /// other clips, never evicted nor merged with other clips, and edited in
+    /// place.
+    #[inline]
+    #[must_use]
+    pub const fn is_sticky(&self) -> bool { self.sticky }
+
+    #[inline]
+    pub fn set_sticky(&mut self, sticky: bool) { self.sticky = sticky; }
+
     #[inline]
     #[must_use]
     pub const fn is_utf8_string(&self) -> bool { self.content.is_plaintext() }
@@ -125,12 +466,34 @@ impl Entry {
             .format(&Rfc3339)
             .unwrap_or_default();
         let size = humansize::format_size(self.content.len(), humansize::BINARY);
-        let content_type = self.content.mime();
-        format!("[{content_type} {size} {timestamp}]")
+        let content_type = self.mime.essence_str();
+        match self.content {
+            ClipboardContent::Image { width, height, .. } => {
+                format!("[{content_type} {width}x{height} {size} {timestamp}]")
+            }
+            ClipboardContent::Plaintext(_) => format!("[{content_type} {size} {timestamp}]"),
+        }
     }
 
+    /// Returns a one-line preview of the content, of at most `line_length`
+    /// characters.
+    ///
+    /// The preview of text is its first line, with the markup stripped for
+    /// HTML, and the preview of an image shows its dimensions.
     #[must_use]
     pub fn preview_information(&self, line_length: Option<usize>) -> String {
+        self.redacted_preview_information(line_length, |line| line)
+    }
+
+    /// Returns a preview like [`Self::preview_information`], `redact` is
+    /// applied to the line before it is truncated so no part of a redacted
+    /// text is shown.
+    #[must_use]
+    pub fn redacted_preview_information(
+        &self,
+        line_length: Option<usize>,
+        redact: impl FnOnce(String) -> String,
+    ) -> String {
         fn truncate(s: &str, max_chars: usize) -> &str {
             match s.char_indices().nth(max_chars) {
                 None => s,
@@ -138,32 +501,37 @@ impl Entry {
             }
         }
 
-        let data = match &self.content {
-            ClipboardContent::Plaintext(text) => text.clone(),
-            ClipboardContent::Image { .. } => self.basic_information(),
+        fn first_line(text: &str) -> (String, usize) {
+            let line = text.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+            (line.to_owned(), text.lines().count())
+        }
+
+        let (line, line_count) = match &self.content {
+            ClipboardContent::Plaintext(text) if self.mime.essence_str() == "text/html" => {
+                first_line(&html_to_plaintext(text))
+            }
+            ClipboardContent::Plaintext(text) => first_line(text),
+            ClipboardContent::Image { .. } => (self.basic_information(), 1),
         };
+        let line = redact(line);
 
-        let data = match line_length {
-            None | Some(0) => data,
-            Some(limit) => {
-                let char_count = data.chars().count();
-                let line_count = data.lines().count();
-                if char_count > limit {
-                    let line_info = if line_count > 1 {
-                        format!("...({line_count} lines)")
-                    } else {
-                        "...".to_owned()
-                    };
-                    let mut data = truncate(&data, limit - line_info.len()).to_owned();
-                    data.push_str(&line_info);
-                    data
-                } else {
-                    data
-                }
+        let limit = line_length.filter(|&limit| limit > 0);
+        let line_info = if line_count > 1 {
+            Some(format!("...({line_count} lines)"))
+        } else {
+            limit.filter(|&limit| line.chars().count() > limit).map(|_| "...".to_owned())
+        };
+        let data = match (line_info, limit) {
+            (None, _) => line,
+            (Some(line_info), Some(limit)) if line.chars().count() + line_info.len() > limit => {
+                let mut data = truncate(&line, limit.saturating_sub(line_info.len())).to_owned();
+                data.push_str(&line_info);
+                data
             }
+            (Some(line_info), _) => line + &line_info,
         };
 
-        data.replace('\n', "\\n").replace('\r', "\\r").replace('\t', "\\t")
+        data.replace('\r', "\\r").replace('\t', "\\t")
     }
 
     #[inline]
@@ -200,32 +568,182 @@ impl Entry {
         }
     }
 
+    /// Encodes an image clip in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clip is not an image or it can not be encoded.
+    pub fn encoded_as(&self, format: ImageFormat) -> Result<Vec<u8>, Error> {
+        let ClipboardContent::Image { width, height, bytes } = &self.content else {
+            return Err(Error::FormatNotAvailable);
+        };
+        let (width, height) =
+            (u32::try_from(*width).unwrap_or_default(), u32::try_from(*height).unwrap_or_default());
+        let image = image::RgbaImage::from_raw(width, height, bytes.to_vec())
+            .filter(|_| width > 0 && height > 0)
+            .ok_or(Error::EmptyImage)?;
+        let image = match format {
+            // JPEG has no alpha channel
+            ImageFormat::Jpeg => {
+                image::DynamicImage::ImageRgb8(image::DynamicImage::ImageRgba8(image).into_rgb8())
+            }
+            _ => image::DynamicImage::ImageRgba8(image),
+        };
+        let mut data = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut data, format.to_image_format()).context(ConvertImageSnafu {})?;
+        Ok(data.into_inner())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn mime(&self) -> mime::Mime { self.mime.clone() }
+
+    /// Returns the extension of a file holding the [encoded](Self::encoded)
+    /// content, like `png`, `html` or `txt`.
+    #[must_use]
+    pub fn file_extension(&self) -> &'static str {
+        match (&self.content, self.content_kind()) {
+            // images are always encoded as PNG
+            (ClipboardContent::Image { .. }, _) => "png",
+            (ClipboardContent::Plaintext(_), ClipKind::Html) => "html",
+            (ClipboardContent::Plaintext(_), _)
+                if self.mime.essence_str() == "application/json" =>
+            {
+                "json"
+            }
+            (ClipboardContent::Plaintext(_), _) => "txt",
+        }
+    }
+
+    /// Returns the full mime type, including parameters like `charset`.
+    #[inline]
+    #[must_use]
+    pub fn mime_full(&self) -> &str { self.mime.as_ref() }
+
+    /// Returns the category of the content, see [`ClipKind`] for the mapping
+    /// of mime types.
+    ///
+    /// [`Entry::kind`] is the clipboard the clip is copied from.
+    #[inline]
+    #[must_use]
+    pub fn content_kind(&self) -> ClipKind { ClipKind::from_mime(&self.mime) }
+
+    /// Returns `true` if the content is text, including HTML.
+    #[inline]
+    #[must_use]
+    pub fn is_text(&self) -> bool { self.content_kind().is_text() }
+
     #[inline]
     #[must_use]
-    pub const fn mime(&self) -> mime::Mime { self.content.mime() }
+    pub fn is_image(&self) -> bool { self.content_kind().is_image() }
 
     #[inline]
     pub fn metadata(&self, preview_length: Option<usize>) -> Metadata {
+        self.redacted_metadata(preview_length, |line| line)
+    }
+
+    /// Returns the metadata with the preview redacted like
+    /// [`Self::redacted_preview_information`].
+    pub fn redacted_metadata(
+        &self,
+        preview_length: Option<usize>,
+        redact: impl FnOnce(String) -> String,
+    ) -> Metadata {
         Metadata {
             id: self.id(),
             kind: self.clipboard_kind,
             timestamp: self.timestamp,
+            created_at: self.created_at,
             mime: self.mime(),
-            preview: self.preview_information(preview_length),
+            preview: self.redacted_preview_information(preview_length, redact),
+            etag: self.etag(),
+            label: self.label.clone(),
+            private: self.private,
+            sticky: self.sticky,
         }
     }
 
     pub fn sha256_digest(&self) -> &[u8] { &self.sha256_digest }
+
+    /// Returns the SHA-256 digest of the content in lowercase hex, images are
+    /// hashed as PNG.
+    #[must_use]
+    pub fn content_hash(&self) -> String { hex::encode(&self.sha256_digest) }
+
+    /// Returns a tag derived from the content and the mime type, which is
+    /// changed on any change of them, for validating cached clips.
+    ///
+    /// The tag of a truncated clip differs from the tag of the whole clip.
+    #[must_use]
+    pub fn etag(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.sha256_digest.hash(&mut hasher);
+        self.mime.as_ref().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the entry with its text truncated to at most `max_bytes` bytes,
+    /// and whether it is truncated.
+    ///
+    /// Text is cut at a character boundary, images are never truncated.
+    #[must_use]
+    pub fn prefix(&self, max_bytes: usize) -> (Self, bool) {
+        match &self.content {
+            ClipboardContent::Plaintext(text) if text.len() > max_bytes => {
+                let len = (0..=max_bytes).rev().find(|&n| text.is_char_boundary(n)).unwrap_or(0);
+                (self.with_text(text[..len].to_string()), true)
+            }
+            _ => (self.clone(), false),
+        }
+    }
+
+    /// Returns the entry with its content replaced by `text`, keeping the id,
+    /// the mime type and the metadata.
+    #[must_use]
+    pub fn with_text(&self, text: String) -> Self {
+        let content = ClipboardContent::Plaintext(text);
+        let sha256_digest = compute_sha256_digest(&content);
+        Self { content, sha256_digest, ..self.clone() }
+    }
+
+    /// Replaces the content without updating the id and the digest, returns the
+    /// previous content.
+    ///
+    /// This is for keeping a placeholder in place of a content stored
+    /// elsewhere, like a compressed content, the content must be swapped back
+    /// before the entry is used.
+    pub fn swap_content(&mut self, content: ClipboardContent) -> ClipboardContent {
+        std::mem::replace(&mut self.content, content)
+    }
 }
 
 impl Default for Entry {
     fn default() -> Self {
         let content = ClipboardContent::Plaintext(String::new());
         let sha256_digest = compute_sha256_digest(&content);
+        let timestamp = OffsetDateTime::now_utc();
         Self {
+            id: content.id(),
             content,
             clipboard_kind: ClipboardKind::Clipboard,
-            timestamp: OffsetDateTime::now_utc(),
+            timestamp,
+            created_at: timestamp,
+            mime: mime::TEXT_PLAIN_UTF_8,
+            owned_by_us: false,
+            tags: BTreeSet::new(),
+            attributes: BTreeMap::new(),
+            label: None,
+            available_targets: Vec::new(),
+            expires_at: None,
+            protected_until: None,
+            version: 0,
+            original_size: None,
+            use_count: 0,
+            source_window: None,
+            private: false,
+            sticky: false,
+            auto_tags: BTreeSet::new(),
+            semantic_kind: None,
             sha256_digest,
         }
     }
@@ -240,11 +758,14 @@ impl PartialOrd for Entry {
 }
 
 impl Ord for Entry {
+    // clips with the same timestamp are ordered by id, so the order does not
+    // change between listings
     fn cmp(&self, other: &Self) -> Ordering {
-        match other.timestamp.cmp(&self.timestamp) {
-            Ordering::Equal => self.clipboard_kind.cmp(&other.clipboard_kind),
-            ord => ord,
-        }
+        other
+            .timestamp
+            .cmp(&self.timestamp)
+            .then_with(|| self.clipboard_kind.cmp(&other.clipboard_kind))
+            .then_with(|| self.id.cmp(&other.id))
     }
 }
 
@@ -262,11 +783,54 @@ pub struct Metadata {
 
     pub kind: ClipboardKind,
 
+    /// Time of the last update.
     pub timestamp: OffsetDateTime,
 
+    /// Time of the first insertion into history.
+    pub created_at: OffsetDateTime,
+
     pub mime: mime::Mime,
 
     pub preview: String,
+
+    /// See [`Entry::etag`].
+    pub etag: u64,
+
+    /// See [`Entry::label`].
+    pub label: Option<String>,
+
+    /// See [`Entry::is_private`].
+    pub private: bool,
+
+    /// See [`Entry::is_sticky`].
+    pub sticky: bool,
+}
+
+impl Metadata {
+    /// Returns the category of the content, see [`ClipKind`] for the mapping
+    /// of mime types.
+    #[inline]
+    #[must_use]
+    pub fn content_kind(&self) -> ClipKind { ClipKind::from_mime(&self.mime) }
+
+    /// Returns the text shown in menus, the label if it is set, otherwise the
+    /// preview of the content.
+    #[inline]
+    #[must_use]
+    pub fn display_text(&self) -> &str { self.label.as_deref().unwrap_or(&self.preview) }
+
+    /// Returns the extension of a file holding the encoded content, like
+    /// [`Entry::file_extension`].
+    #[must_use]
+    pub fn file_extension(&self) -> &'static str {
+        match self.content_kind() {
+            // images are always encoded as PNG
+            ClipKind::Image => "png",
+            ClipKind::Html => "html",
+            _ if self.mime.essence_str() == "application/json" => "json",
+            _ => "txt",
+        }
+    }
 }
 
 impl PartialOrd for Metadata {
@@ -274,11 +838,13 @@ impl PartialOrd for Metadata {
 }
 
 impl Ord for Metadata {
+    // same order as `Entry`
     fn cmp(&self, other: &Self) -> Ordering {
-        match other.timestamp.cmp(&self.timestamp) {
-            Ordering::Equal => self.kind.cmp(&other.kind),
-            ord => ord,
-        }
+        other
+            .timestamp
+            .cmp(&self.timestamp)
+            .then_with(|| self.kind.cmp(&other.kind))
+            .then_with(|| self.id.cmp(&other.id))
     }
 }
 
@@ -0,0 +1,17 @@
+use crate::{ClipEntry, ClipEntryMetadata};
+
+/// A clip with its metadata computed by the server, taken from the same
+/// snapshot of history, for panes showing everything about one clip.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntryFull {
+    pub entry: ClipEntry,
+
+    pub metadata: ClipEntryMetadata,
+
+    /// Size of the content in bytes, the size of the original content for
+    /// compressed and truncated clips.
+    pub byte_len: u64,
+
+    /// Whether the clip is a snippet, which is never removed from history.
+    pub snippet: bool,
+}
@@ -0,0 +1,99 @@
+use crate::{ClipEntry, ClipEntryMetadata};
+
+/// A change applied to the clipboard history.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Change {
+    Added(ClipEntry),
+
+    Removed(u64),
+
+    /// The clip is removed by the server to enforce a retention policy, not
+    /// on request of a client.
+    Evicted {
+        id: u64,
+        reason: EvictionReason,
+    },
+
+    Updated(ClipEntry),
+
+    /// Like [`Change::Added`] without the content, sent to watchers asking
+    /// for the metadata only.
+    AddedMetadata(ClipEntryMetadata),
+
+    /// Like [`Change::Updated`] without the content, sent to watchers asking
+    /// for the metadata only.
+    UpdatedMetadata(ClipEntryMetadata),
+
+    /// Sent periodically on an idle watch stream, it keeps the connection
+    /// alive and does not change the history.
+    Heartbeat,
+
+    /// The history is changed without sending the changes, like after a bulk
+    /// import, a consumer should list the history again.
+    Resync,
+
+    /// The active profile is switched to `profile`, `None` is the default
+    /// profile. The clips of the previous profile are removed before it and
+    /// the clips of `profile` are added after it, a consumer may list the
+    /// history again instead.
+    ProfileChanged {
+        profile: Option<String>,
+    },
+}
+
+impl Change {
+    /// Returns the change with the added or updated clip replaced by its
+    /// metadata, with previews of at most `preview_length` characters.
+    #[must_use]
+    pub fn into_metadata(self, preview_length: usize) -> Self {
+        match self {
+            Self::Added(entry) => Self::AddedMetadata(entry.metadata(Some(preview_length))),
+            Self::Updated(entry) => Self::UpdatedMetadata(entry.metadata(Some(preview_length))),
+            change => change,
+        }
+    }
+}
+
+/// A change of the clipboard history tagged with its cursor.
+///
+/// Cursors are strictly increasing, a consumer could resume from the cursor of
+/// the last event it has applied.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Event {
+    pub cursor: u64,
+
+    pub change: Change,
+}
+
+/// Why a clip is evicted from the clipboard history by the server.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EvictionReason {
+    /// The history exceeds its capacity, the oldest clips are evicted.
+    Capacity,
+
+    /// The clip is older than the maximum age of clips.
+    Age,
+
+    /// The clip has expired, see [`ClipEntry::expires_at`].
+    Ttl,
+}
+
+impl From<i32> for EvictionReason {
+    fn from(reason: i32) -> Self {
+        match reason {
+            1 => Self::Age,
+            2 => Self::Ttl,
+            _ => Self::Capacity,
+        }
+    }
+}
+
+impl From<EvictionReason> for i32 {
+    fn from(reason: EvictionReason) -> Self {
+        match reason {
+            EvictionReason::Capacity => 0,
+            EvictionReason::Age => 1,
+            EvictionReason::Ttl => 2,
+        }
+    }
+}
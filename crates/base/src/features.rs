@@ -0,0 +1,71 @@
+//! Names of the features listed in [`ServerInfo::features`], a client checks
+//! them with [`ServerInfo::supports`] before using a newer method.
+//!
+//! Servers older than the feature list list no features.
+//!
+//! [`ServerInfo::features`]: crate::ServerInfo::features
+//! [`ServerInfo::supports`]: crate::ServerInfo::supports
+
+/// Searching clips by words or fuzzily.
+pub const SEARCH: &str = "search";
+
+/// Streaming the changes of history and of the current clips.
+pub const WATCH: &str = "watch";
+
+/// Clips which are kept in history and shared by all profiles.
+pub const SNIPPETS: &str = "snippets";
+
+/// Switching between separate histories.
+pub const PROFILES: &str = "profiles";
+
+/// Tagging and labelling clips.
+pub const TAGS: &str = "tags";
+
+/// Applying a batch of operations at once.
+pub const APPLY: &str = "apply";
+
+/// Marking a clip in several clipboard kinds at once.
+pub const MARK_KINDS: &str = "mark-kinds";
+
+/// Cycling the current clip through history.
+pub const CYCLE: &str = "cycle";
+
+/// Counting how often clips are used and listing the most used ones.
+pub const USE_COUNT: &str = "use-count";
+
+/// Recording the window which owned the selection of a clip.
+pub const SOURCE_WINDOW: &str = "source-window";
+
+/// Verifying and compacting the history file.
+pub const STORE_MAINTENANCE: &str = "store-maintenance";
+
+/// Polling the net changes of history after a cursor.
+pub const DIFF: &str = "diff";
+
+/// Paginating a snapshot of history.
+pub const SNAPSHOTS: &str = "snapshots";
+
+/// Sticky notes pinned to the top of history and edited in place.
+pub const STICKY_NOTES: &str = "sticky-notes";
+
+/// Exporting and importing the metadata of clips by their content hashes.
+pub const METADATA_TRANSFER: &str = "metadata-transfer";
+
+/// All features of this version.
+pub const ALL: &[&str] = &[
+    SEARCH,
+    WATCH,
+    SNIPPETS,
+    PROFILES,
+    TAGS,
+    APPLY,
+    MARK_KINDS,
+    CYCLE,
+    USE_COUNT,
+    SOURCE_WINDOW,
+    STORE_MAINTENANCE,
+    DIFF,
+    SNAPSHOTS,
+    STICKY_NOTES,
+    METADATA_TRANSFER,
+];
@@ -6,6 +6,7 @@ use crate::ClipboardContent;
 pub struct Filter {
     regex_set: regex::RegexSet,
     sensitive_atoms: HashSet<String>,
+    denied_applications: HashSet<String>,
     deny_image: bool,
     filter_text_min_length: usize,
     filter_text_max_length: usize,
@@ -20,6 +21,8 @@ impl Filter {
 
             sensitive_atoms: HashSet::new(),
 
+            denied_applications: HashSet::new(),
+
             deny_image: false,
 
             filter_text_min_length: 1,
@@ -39,6 +42,15 @@ impl Filter {
         self.sensitive_atoms.extend(sensitive_atoms);
     }
 
+    /// Adds names of applications whose clips are ignored, names are matched
+    /// case-insensitively.
+    pub fn add_denied_applications<I>(&mut self, applications: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.denied_applications.extend(applications.into_iter().map(|name| name.to_lowercase()));
+    }
+
     pub fn set_regex_patterns(&mut self, regex_patterns: regex::RegexSet) {
         self.regex_set = regex_patterns;
     }
@@ -74,6 +86,18 @@ impl Filter {
         atoms.any(|atom| self.sensitive_atoms.contains(atom))
     }
 
+    /// Returns `true` if one of the names of the application owning the
+    /// clipboard is denied.
+    #[inline]
+    pub fn filter_application<I, S>(&self, mut names: I) -> bool
+    where
+        I: Iterator<Item = S>,
+        S: AsRef<str>,
+    {
+        !self.denied_applications.is_empty()
+            && names.any(|name| self.denied_applications.contains(&name.as_ref().to_lowercase()))
+    }
+
     #[inline]
     #[must_use]
     pub fn filter_by_mime_type(&self, mime: &mime::Mime) -> bool {
@@ -119,6 +143,8 @@ impl Default for Filter {
 
             sensitive_atoms: HashSet::from(["x-kde-passwordManagerHint".to_string()]),
 
+            denied_applications: HashSet::new(),
+
             deny_image: false,
 
             filter_text_min_length: 1,
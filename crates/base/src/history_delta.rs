@@ -0,0 +1,21 @@
+use crate::ClipEntry;
+
+/// Changes of the clipboard history after a cursor of
+/// [`crate::ClipboardEvent`], folded into the net changes.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HistoryDelta {
+    /// Clips added or updated after the cursor, newest first, as they are
+    /// now.
+    pub added: Vec<ClipEntry>,
+
+    /// Ids of the clips removed after the cursor.
+    pub removed: Vec<u64>,
+
+    /// Cursor of the last change, pass it to get the following changes.
+    pub new_cursor: u64,
+
+    /// Whether the changes after the cursor are no longer retained by the
+    /// server, `added` and `removed` are empty then and the history should be
+    /// listed again before continuing from `new_cursor`.
+    pub resync_required: bool,
+}
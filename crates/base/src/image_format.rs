@@ -0,0 +1,79 @@
+/// The format an image clip is encoded in.
+///
+/// Images are decoded when they are captured, so a clip can be encoded in
+/// any of these formats regardless of the format it was copied in.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+
+    /// JPEG drops the alpha channel of the image.
+    Jpeg,
+
+    Gif,
+
+    Bmp,
+
+    Webp,
+}
+
+impl ImageFormat {
+    // SAFETY: it will never panic because `image/webp` is a valid mime type
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn mime(self) -> mime::Mime {
+        match self {
+            Self::Png => mime::IMAGE_PNG,
+            Self::Jpeg => mime::IMAGE_JPEG,
+            Self::Gif => mime::IMAGE_GIF,
+            Self::Bmp => mime::IMAGE_BMP,
+            // `mime` has no constant of WebP
+            Self::Webp => "image/webp".parse().expect("`image/webp` is a valid mime type; qed"),
+        }
+    }
+
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Gif => "gif",
+            Self::Bmp => "bmp",
+            Self::Webp => "webp",
+        }
+    }
+
+    pub(crate) const fn to_image_format(self) -> image::ImageFormat {
+        match self {
+            Self::Png => image::ImageFormat::Png,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::Gif => image::ImageFormat::Gif,
+            Self::Bmp => image::ImageFormat::Bmp,
+            Self::Webp => image::ImageFormat::WebP,
+        }
+    }
+}
+
+impl From<i32> for ImageFormat {
+    fn from(format: i32) -> Self {
+        match format {
+            1 => Self::Jpeg,
+            2 => Self::Gif,
+            3 => Self::Bmp,
+            4 => Self::Webp,
+            _ => Self::Png,
+        }
+    }
+}
+
+impl From<ImageFormat> for i32 {
+    fn from(format: ImageFormat) -> Self {
+        match format {
+            ImageFormat::Png => 0,
+            ImageFormat::Jpeg => 1,
+            ImageFormat::Gif => 2,
+            ImageFormat::Bmp => 3,
+            ImageFormat::Webp => 4,
+        }
+    }
+}
@@ -53,12 +53,17 @@ impl From<Kind> for i32 {
     }
 }
 
-impl From<i32> for Kind {
-    fn from(v: i32) -> Self {
+/// The numeric values are part of the protocol, see `ClipboardKind` in
+/// `manager.proto`.
+impl TryFrom<i32> for Kind {
+    type Error = Error;
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
         match v {
-            0 => Self::Clipboard,
-            1 => Self::Primary,
-            _ => Self::Secondary,
+            0 => Ok(Self::Clipboard),
+            1 => Ok(Self::Primary),
+            2 => Ok(Self::Secondary),
+            _ => Err(Error::UnknownValue { value: v }),
         }
     }
 }
@@ -92,4 +97,7 @@ impl fmt::Display for Kind {
 pub enum Error {
     #[snafu(display("Could not parse clipboard kind, value: {value}"))]
     Parse { value: String },
+
+    #[snafu(display("Unknown clipboard kind, value: {value}"))]
+    UnknownValue { value: i32 },
 }
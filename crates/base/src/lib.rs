@@ -1,10 +1,31 @@
+mod clip_op;
 pub mod config;
+mod content_kind;
+mod cycle_direction;
+mod dedupe_scope;
+mod diagnostics;
 mod entry;
+mod entry_full;
+mod event;
+pub mod features;
 mod filter;
+mod history_delta;
+mod image_format;
 mod kind;
+mod list_cursor;
+mod metadata_record;
+mod resource_usage;
+mod semantic_kind;
 pub mod serde;
+mod server_info;
+mod size_limits;
+mod sort_order;
+mod stats;
+mod store_report;
+mod tag_match;
 pub mod utils;
 mod watcher_state;
+mod window_info;
 
 use std::{
     collections::hash_map::DefaultHasher,
@@ -18,10 +39,30 @@ use directories::ProjectDirs;
 use once_cell::sync::Lazy;
 
 pub use self::{
+    clip_op::{ClipOp, ClipOpResult},
+    content_kind::ContentKind as ClipKind,
+    cycle_direction::CycleDirection,
+    dedupe_scope::DedupeScope,
+    diagnostics::{DiagnosticError, Diagnostics, KindDiagnostics},
     entry::{Entry as ClipEntry, Error as ClipEntryError, Metadata as ClipEntryMetadata},
+    entry_full::EntryFull as ClipEntryFull,
+    event::{Change as ClipboardChange, Event as ClipboardEvent, EvictionReason},
     filter::Filter as ClipFilter,
+    history_delta::HistoryDelta,
+    image_format::ImageFormat,
     kind::Kind as ClipboardKind,
+    list_cursor::ListCursor,
+    metadata_record::{MetadataImportReport, MetadataRecord},
+    resource_usage::ResourceUsage,
+    semantic_kind::SemanticKind,
+    server_info::ServerInfo,
+    size_limits::SizeLimits,
+    sort_order::{SortKey, SortOrder},
+    stats::HistoryStats,
+    store_report::{CompactReport, CorruptEntry, StoreReport},
+    tag_match::TagMatch,
     watcher_state::WatcherState as ClipboardWatcherState,
+    window_info::WindowInfo,
 };
 
 pub const PROJECT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -58,6 +99,10 @@ pub const MENU_CONFIG_NAME: &str = "clipcat-menu.toml";
 
 pub const NOTIFY_PROGRAM_NAME: &str = "clipcat-notify";
 
+/// The gRPC metadata key of the id correlating the requests of a client
+/// operation in the logs of the server.
+pub const GRPC_OPERATION_ID_KEY: &str = "x-clipcat-operation-id";
+
 pub const DEFAULT_GRPC_PORT: u16 = 45045;
 pub const DEFAULT_GRPC_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
@@ -67,6 +112,9 @@ pub const DEFAULT_WEBUI_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 pub const DEFAULT_METRICS_PORT: u16 = 45047;
 pub const DEFAULT_METRICS_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
+pub const DEFAULT_HTTP_API_PORT: u16 = 45048;
+pub const DEFAULT_HTTP_API_HOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
 pub const DEFAULT_MENU_PROMPT: &str = "Clipcat";
 
 pub static PROJECT_CONFIG_DIR: Lazy<PathBuf> = Lazy::new(|| {
@@ -0,0 +1,47 @@
+use std::cmp::Ordering;
+
+use time::OffsetDateTime;
+
+use crate::{ClipEntry, ClipboardKind};
+
+/// Position of a clip in history sorted newest first with sticky notes first,
+/// the cursor of the pages of history listed after it.
+///
+/// It holds the sort key of the clip, so the clips sharing its timestamp are
+/// listed after it in the same order as [`ClipEntry`] is sorted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ListCursor {
+    /// See [`ClipEntry::is_sticky`].
+    pub sticky: bool,
+
+    pub timestamp: OffsetDateTime,
+
+    pub kind: ClipboardKind,
+
+    pub id: u64,
+}
+
+impl ListCursor {
+    /// Returns `true` if `entry` is sorted after the position.
+    #[inline]
+    #[must_use]
+    pub fn precedes(&self, entry: &ClipEntry) -> bool {
+        self.sticky
+            .cmp(&entry.is_sticky())
+            .then_with(|| entry.timestamp().cmp(&self.timestamp).reverse())
+            .then_with(|| entry.kind().cmp(&self.kind))
+            .then_with(|| entry.id().cmp(&self.id))
+            == Ordering::Greater
+    }
+}
+
+impl From<&ClipEntry> for ListCursor {
+    fn from(entry: &ClipEntry) -> Self {
+        Self {
+            sticky: entry.is_sticky(),
+            timestamp: entry.timestamp(),
+            kind: entry.kind(),
+            id: entry.id(),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use time::OffsetDateTime;
+
+use crate::{ClipEntry, SemanticKind};
+
+/// The metadata users organize a clip with, without its content, keyed by the
+/// content hash so it can be applied to the clip with the same content in
+/// another history.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MetadataRecord {
+    /// See [`ClipEntry::content_hash`].
+    pub content_hash: String,
+
+    pub tags: BTreeSet<String>,
+
+    pub attributes: BTreeMap<String, String>,
+
+    pub label: Option<String>,
+
+    /// See [`ClipEntry::protected_until`].
+    pub protected_until: Option<OffsetDateTime>,
+
+    /// See [`ClipEntry::is_sticky`].
+    pub sticky: bool,
+
+    pub semantic_kind: Option<SemanticKind>,
+}
+
+impl MetadataRecord {
+    /// Returns `true` if the record holds no metadata to apply.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.attributes.is_empty()
+            && self.label.is_none()
+            && self.protected_until.is_none()
+            && !self.sticky
+            && self.semantic_kind.is_none()
+    }
+}
+
+impl From<&ClipEntry> for MetadataRecord {
+    fn from(entry: &ClipEntry) -> Self {
+        Self {
+            content_hash: entry.content_hash(),
+            tags: entry.tags().clone(),
+            attributes: entry.attributes().clone(),
+            label: entry.label().map(ToString::to_string),
+            protected_until: entry.protected_until(),
+            sticky: entry.is_sticky(),
+            semantic_kind: entry.semantic_kind(),
+        }
+    }
+}
+
+/// Result of applying metadata records to a history.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MetadataImportReport {
+    /// Number of clips the metadata is applied to.
+    pub applied: usize,
+
+    /// Content hashes of the records without a clip with the same content,
+    /// they are skipped.
+    pub unmatched: Vec<String>,
+}
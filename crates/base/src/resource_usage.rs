@@ -0,0 +1,14 @@
+/// Memory held by the clipboard history of the server.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResourceUsage {
+    /// Number of clips, including snippets.
+    pub entry_count: usize,
+
+    /// Estimated size in bytes of the clips and of the full-text index, the
+    /// content of a compressed clip counts with its compressed size.
+    pub store_bytes: usize,
+
+    /// Size of the content of the clips before compression divided by its
+    /// size in memory, `None` if compression is disabled.
+    pub compression_ratio: Option<f64>,
+}
@@ -0,0 +1,92 @@
+use std::{fmt, str::FromStr};
+
+use snafu::Snafu;
+
+/// What a text clip is about, assigned by the server when the clip is
+/// inserted, so clips sharing a mime type like `text/plain` can be grouped by
+/// meaning.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SemanticKind {
+    Url,
+    Code,
+    Path,
+    Number,
+    Prose,
+    Other,
+}
+
+impl SemanticKind {
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        match self {
+            Self::Url => "Url",
+            Self::Code => "Code",
+            Self::Path => "Path",
+            Self::Number => "Number",
+            Self::Prose => "Prose",
+            Self::Other => "Other",
+        }
+    }
+}
+
+impl FromStr for SemanticKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "url" => Ok(Self::Url),
+            "code" => Ok(Self::Code),
+            "path" => Ok(Self::Path),
+            "number" => Ok(Self::Number),
+            "prose" => Ok(Self::Prose),
+            "other" => Ok(Self::Other),
+            _ => Err(Error::Parse { value: s.to_string() }),
+        }
+    }
+}
+
+impl From<SemanticKind> for i32 {
+    fn from(kind: SemanticKind) -> Self {
+        match kind {
+            SemanticKind::Url => 0,
+            SemanticKind::Code => 1,
+            SemanticKind::Path => 2,
+            SemanticKind::Number => 3,
+            SemanticKind::Prose => 4,
+            SemanticKind::Other => 5,
+        }
+    }
+}
+
+/// The numeric values are part of the protocol, see `SemanticKind` in
+/// `manager.proto`.
+impl TryFrom<i32> for SemanticKind {
+    type Error = Error;
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Url),
+            1 => Ok(Self::Code),
+            2 => Ok(Self::Path),
+            3 => Ok(Self::Number),
+            4 => Ok(Self::Prose),
+            5 => Ok(Self::Other),
+            _ => Err(Error::UnknownValue { value: v }),
+        }
+    }
+}
+
+impl fmt::Display for SemanticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Could not parse semantic kind, value: {value}"))]
+    Parse { value: String },
+
+    #[snafu(display("Unknown semantic kind, value: {value}"))]
+    UnknownValue { value: i32 },
+}
@@ -0,0 +1,123 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use crate::{ClipboardKind, DedupeScope, SizeLimits};
+
+/// Information about a running Clipcat server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerInfo {
+    pub version: semver::Version,
+
+    /// Content transforms applied to clips before they are stored.
+    pub transforms: Vec<String>,
+
+    /// Rules tagging new text clips, like `url: https?://`.
+    pub auto_tag_rules: Vec<String>,
+
+    /// Patterns redacted from the previews of clips, the content of clips is
+    /// never redacted.
+    pub redaction_patterns: Vec<String>,
+
+    /// Applications whose clips are not captured.
+    pub denied_applications: Vec<String>,
+
+    /// Clipboard kinds captured by the server, clips can not be inserted into
+    /// or marked as the other kinds.
+    pub enabled_kinds: Vec<ClipboardKind>,
+
+    /// Which clips with the same content are merged.
+    pub dedupe_scope: DedupeScope,
+
+    /// Whether texts differing only in whitespaces are merged.
+    pub normalize_whitespace_for_dedupe: bool,
+
+    /// Whether the server only reads the selections, clips can not be marked
+    /// then.
+    pub read_only: bool,
+
+    /// Endpoints the server listens on, like `http://127.0.0.1:45045` or
+    /// `unix:/run/user/1000/clipcat/grpc.sock`.
+    pub listeners: Vec<String>,
+
+    /// Inserts each client may make per second, `None` if not limited.
+    pub max_inserts_per_second: Option<u32>,
+
+    /// Limits of the size of inserted clips, `None` if not limited.
+    pub size_limits: Option<SizeLimits>,
+
+    /// Whether capturing clips is paused, the history is still served then.
+    pub capture_paused: bool,
+
+    /// Features supported by the server, see [`crate::features`], empty for
+    /// older servers.
+    pub features: Vec<String>,
+
+    /// Delay after which a captured change is stored if it is still the
+    /// selection, `None` if changes are stored at once.
+    pub commit_delay: Option<std::time::Duration>,
+
+    /// Age after which clips are removed, counted from their last update,
+    /// `None` if clips are kept regardless of their age.
+    pub max_age: Option<std::time::Duration>,
+
+    /// Tag of the effective configuration, which is changed whenever the
+    /// server runs with a different configuration, `0` for older servers.
+    ///
+    /// Clients keeping the information may compare it after reconnecting,
+    /// see [`ServerInfo::compute_config_version`].
+    pub config_version: u64,
+}
+
+impl ServerInfo {
+    /// Returns `true` if the server lists `feature`, like
+    /// [`crate::features::SEARCH`].
+    #[must_use]
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|supported| supported == feature)
+    }
+
+    /// Returns a tag derived from the configured fields, the state of the
+    /// capture and `config_version` itself are left out.
+    #[must_use]
+    pub fn compute_config_version(&self) -> u64 {
+        let Self {
+            version,
+            transforms,
+            auto_tag_rules,
+            redaction_patterns,
+            denied_applications,
+            enabled_kinds,
+            dedupe_scope,
+            normalize_whitespace_for_dedupe,
+            read_only,
+            listeners,
+            max_inserts_per_second,
+            size_limits,
+            capture_paused: _,
+            features,
+            commit_delay,
+            max_age,
+            config_version: _,
+        } = self;
+        let mut hasher = DefaultHasher::new();
+        version.hash(&mut hasher);
+        transforms.hash(&mut hasher);
+        auto_tag_rules.hash(&mut hasher);
+        redaction_patterns.hash(&mut hasher);
+        denied_applications.hash(&mut hasher);
+        enabled_kinds.hash(&mut hasher);
+        dedupe_scope.hash(&mut hasher);
+        normalize_whitespace_for_dedupe.hash(&mut hasher);
+        read_only.hash(&mut hasher);
+        listeners.hash(&mut hasher);
+        max_inserts_per_second.hash(&mut hasher);
+        size_limits.hash(&mut hasher);
+        features.hash(&mut hasher);
+        commit_delay.hash(&mut hasher);
+        max_age.hash(&mut hasher);
+        // `0` is left for older servers
+        hasher.finish().max(1)
+    }
+}
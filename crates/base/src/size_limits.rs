@@ -0,0 +1,29 @@
+/// Upper bounds of the size of clips by mime type, in bytes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SizeLimits {
+    /// Limits of mime types like `image/png` or of top-level types like
+    /// `text/*`, a mime type takes precedence over its top-level type.
+    pub rules: Vec<(String, u64)>,
+
+    /// Limit of the mime types matching no rule.
+    pub default_limit: u64,
+}
+
+impl SizeLimits {
+    /// Returns the limit applied to clips of `mime` and the rule it comes
+    /// from, `*` for the default limit.
+    #[must_use]
+    pub fn limit_of(&self, mime: &mime::Mime) -> (u64, &str) {
+        let exact =
+            self.rules.iter().find(|(rule, _)| rule.eq_ignore_ascii_case(mime.essence_str()));
+        let wildcard = || {
+            self.rules.iter().find(|(rule, _)| {
+                rule.strip_suffix("/*")
+                    .is_some_and(|type_| type_.eq_ignore_ascii_case(mime.type_().as_str()))
+            })
+        };
+        exact
+            .or_else(wildcard)
+            .map_or((self.default_limit, "*"), |(rule, limit)| (*limit, rule.as_str()))
+    }
+}
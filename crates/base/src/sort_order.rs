@@ -0,0 +1,63 @@
+/// The order in which clips are listed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SortOrder {
+    /// Newest first.
+    #[default]
+    Recency,
+
+    /// Snippets first, then the other clips, each newest first.
+    SnippetsFirst,
+}
+
+impl From<i32> for SortOrder {
+    fn from(order: i32) -> Self {
+        match order {
+            1 => Self::SnippetsFirst,
+            _ => Self::Recency,
+        }
+    }
+}
+
+impl From<SortOrder> for i32 {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Recency => 0,
+            SortOrder::SnippetsFirst => 1,
+        }
+    }
+}
+
+/// The key by which [`SortOrder::Recency`] is rewritten when the history is
+/// reordered, the clip with the largest key becomes the newest.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SortKey {
+    /// The time the clip was first captured, ignoring later copies of it.
+    #[default]
+    CreatedAt,
+
+    /// The number of times the clip is marked.
+    UseCount,
+
+    /// The size in bytes of the content.
+    Size,
+}
+
+impl From<i32> for SortKey {
+    fn from(key: i32) -> Self {
+        match key {
+            1 => Self::UseCount,
+            2 => Self::Size,
+            _ => Self::CreatedAt,
+        }
+    }
+}
+
+impl From<SortKey> for i32 {
+    fn from(key: SortKey) -> Self {
+        match key {
+            SortKey::CreatedAt => 0,
+            SortKey::UseCount => 1,
+            SortKey::Size => 2,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/// Statistics of the clipboard history.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HistoryStats {
+    /// Number of clips, including snippets.
+    pub clip_count: usize,
+
+    /// Number of snippets, they are not evicted and not counted against the
+    /// capacity.
+    pub snippet_count: usize,
+
+    /// Maximum number of clips kept in history, the oldest clips are evicted
+    /// beyond it.
+    pub capacity: usize,
+
+    /// Size of the content of all clips in bytes, before compression.
+    pub content_bytes: usize,
+
+    /// Number of clips whose content is compressed in memory.
+    pub compressed_clip_count: usize,
+
+    /// Number of bytes saved by compressing large clips.
+    pub compression_saved_bytes: usize,
+
+    /// Number of distinct terms in the full-text index.
+    pub index_term_count: usize,
+
+    /// Approximate memory used by the full-text index in bytes.
+    pub index_memory_bytes: usize,
+}
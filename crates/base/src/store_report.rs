@@ -0,0 +1,77 @@
+use time::OffsetDateTime;
+
+/// Result of checking the history file of a Clipcat server.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StoreReport {
+    /// Number of entries read from the history file, including the corrupt
+    /// ones.
+    pub scanned: usize,
+
+    /// Entries which can not be loaded, they are skipped when the history is
+    /// loaded.
+    pub corrupt_entries: Vec<CorruptEntry>,
+
+    /// Size of the data at the end of the history file which can not be read
+    /// as an entry, like an entry partially written before a crash.
+    pub unreadable_bytes: u64,
+
+    /// Whether the corrupt entries and the unreadable data are dropped from
+    /// the history file, and the corrupt clips from the history.
+    pub repaired: bool,
+
+    /// Ids of the clips in the history whose content can not be read, they
+    /// are skipped when the clips are listed.
+    pub corrupt_clip_ids: Vec<u64>,
+}
+
+impl StoreReport {
+    /// Returns `true` if no corrupt entry, unreadable data or corrupt clip is
+    /// found.
+    #[inline]
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.corrupt_entries.is_empty()
+            && self.unreadable_bytes == 0
+            && self.corrupt_clip_ids.is_empty()
+    }
+}
+
+/// An entry of the history file which can not be loaded.
+///
+/// An entry is identified by its position and its timestamp.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CorruptEntry {
+    /// Position of the entry in the history file, from 0.
+    pub index: usize,
+
+    pub timestamp: OffsetDateTime,
+
+    pub mime: mime::Mime,
+
+    pub reason: String,
+}
+
+/// Result of compacting the history file of a Clipcat server.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompactReport {
+    /// Number of entries written into the compacted history file.
+    pub entries: usize,
+
+    /// Size of the history file before it is compacted.
+    pub bytes_before: u64,
+
+    /// Size of the history file after it is compacted.
+    pub bytes_after: u64,
+
+    /// Number of image files removed because no entry refers to them.
+    pub image_files_removed: usize,
+}
+
+impl CompactReport {
+    /// Returns the number of bytes the history file shrinks by.
+    #[inline]
+    #[must_use]
+    pub const fn bytes_reclaimed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeSet;
+
+/// How the tags of a clip are matched against a set of tags.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TagMatch {
+    /// The clip has all tags of the set.
+    #[default]
+    All,
+
+    /// The clip has at least one tag of the set.
+    Any,
+}
+
+impl TagMatch {
+    /// Returns `true` if `clip_tags` match `tags`, any clip matches an empty
+    /// set of tags.
+    #[must_use]
+    pub fn matches(self, clip_tags: &BTreeSet<String>, tags: &[String]) -> bool {
+        match self {
+            _ if tags.is_empty() => true,
+            Self::All => tags.iter().all(|tag| clip_tags.contains(tag)),
+            Self::Any => tags.iter().any(|tag| clip_tags.contains(tag)),
+        }
+    }
+}
+
+impl From<i32> for TagMatch {
+    fn from(mode: i32) -> Self {
+        match mode {
+            1 => Self::Any,
+            _ => Self::All,
+        }
+    }
+}
+
+impl From<TagMatch> for i32 {
+    fn from(mode: TagMatch) -> Self {
+        match mode {
+            TagMatch::All => 0,
+            TagMatch::Any => 1,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// elements whose content is not rendered as text
+static INVISIBLE_ELEMENTS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<(script|style|head)\b[^>]*>.*?</(script|style|head)\s*>|<!--.*?-->")
+        .expect("regular expression is valid; qed")
+});
+
+// elements ending a line of text
+static LINE_BREAKS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)<br\s*/?>|</(p|div|li|tr|h[1-6])\s*>")
+        .expect("regular expression is valid; qed")
+});
+
+static TAGS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<[^>]*>").expect("regular expression is valid; qed"));
+
+/// Strips the markup of an HTML document, keeping the text and the line
+/// breaks.
+#[must_use]
+pub fn html_to_plaintext(html: &str) -> String {
+    let html = INVISIBLE_ELEMENTS.replace_all(html, "");
+    let html = LINE_BREAKS.replace_all(&html, "\n");
+    let text = TAGS.replace_all(&html, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
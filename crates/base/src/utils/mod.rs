@@ -1,4 +1,10 @@
 pub mod fs;
+mod html;
 mod retry_interval;
+mod uri_list;
 
-pub use self::retry_interval::RetryInterval;
+pub use self::{
+    html::html_to_plaintext,
+    retry_interval::RetryInterval,
+    uri_list::{is_uri_list, paths_to_uri_list, uri_list_mime, uri_list_to_paths},
+};
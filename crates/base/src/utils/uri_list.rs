@@ -0,0 +1,79 @@
+use std::{
+    ffi::OsStr,
+    fmt::Write,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, CONTROLS};
+
+// characters escaped in the path of a `file` URI, non-ASCII bytes are always
+// escaped
+const PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+const URI_LIST: &str = "text/uri-list";
+
+// SAFETY: it will never panic because `text/uri-list` is a valid mime type
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn uri_list_mime() -> mime::Mime {
+    URI_LIST.parse().expect("`text/uri-list` is a valid mime type; qed")
+}
+
+/// Returns `true` if `mime` is `text/uri-list`, the format file managers copy
+/// files in.
+#[must_use]
+pub fn is_uri_list(mime: &mime::Mime) -> bool { mime.essence_str() == URI_LIST }
+
+/// Formats `paths` as a `text/uri-list` of `file` URIs, as defined by RFC
+/// 2483.
+///
+/// The paths should be absolute, relative paths are not resolved.
+pub fn paths_to_uri_list<P>(paths: &[P]) -> String
+where
+    P: AsRef<Path>,
+{
+    paths.iter().fold(String::new(), |mut list, path| {
+        let _unused = write!(
+            list,
+            "file://{}\r\n",
+            percent_encode(path.as_ref().as_os_str().as_bytes(), PATH)
+        );
+        list
+    })
+}
+
+/// Returns the paths of the local `file` URIs of a `text/uri-list`, comments
+/// and other URIs are skipped.
+#[must_use]
+pub fn uri_list_to_paths(list: &str) -> Vec<PathBuf> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|uri| uri.strip_prefix("file://"))
+        // the host is empty or `localhost` for local files
+        .filter_map(
+            |rest| if rest.starts_with('/') { Some(rest) } else { rest.strip_prefix("localhost") },
+        )
+        .filter(|path| path.starts_with('/'))
+        .map(|path| {
+            let bytes: Vec<u8> = percent_decode_str(path).collect();
+            PathBuf::from(OsStr::from_bytes(&bytes))
+        })
+        .collect()
+}
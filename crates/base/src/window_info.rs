@@ -0,0 +1,19 @@
+/// The window which owned the selection when a clip was captured.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WindowInfo {
+    /// The title of the window, empty if it is not known.
+    pub title: String,
+
+    /// The class of the window on X11 or the app ID on Wayland, like
+    /// `firefox`, empty if it is not known.
+    pub class: String,
+}
+
+impl WindowInfo {
+    /// Returns `true` if the class of the window is `class`, ignoring ASCII
+    /// case.
+    #[must_use]
+    pub fn has_class(&self, class: &str) -> bool {
+        !class.is_empty() && self.class.eq_ignore_ascii_case(class)
+    }
+}
@@ -3,7 +3,8 @@ use std::{fs::OpenOptions, path::PathBuf};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use tracing_subscriber::{
-    layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt, Layer,
+    fmt::writer::BoxMakeWriter, layer::SubscriberExt, registry::LookupSpan,
+    util::SubscriberInitExt, Layer,
 };
 
 #[serde_as]
@@ -24,6 +25,22 @@ pub struct LogConfig {
     #[serde(default = "LogConfig::default_log_level")]
     #[serde_as(as = "DisplayFromStr")]
     pub level: tracing::Level,
+
+    #[serde(default)]
+    pub format: LogFormat,
+}
+
+/// Format of the log messages emitted to a log file, stdout and stderr.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable messages spanning several lines.
+    #[default]
+    Pretty,
+
+    /// One JSON object per line with the fields of the event flattened, for
+    /// log pipelines.
+    Json,
 }
 
 impl Default for LogConfig {
@@ -34,6 +51,7 @@ impl Default for LogConfig {
             emit_stdout: Self::default_emit_stdout(),
             emit_stderr: Self::default_emit_stderr(),
             level: Self::default_log_level(),
+            format: LogFormat::default(),
         }
     }
 }
@@ -60,16 +78,17 @@ impl LogConfig {
     pub const fn default_emit_stderr() -> bool { false }
 
     pub fn registry(&self) {
-        let Self { emit_journald, file_path, emit_stdout, emit_stderr, level: log_level } = self;
+        let Self { emit_journald, file_path, emit_stdout, emit_stderr, level: log_level, format } =
+            self;
 
         let filter_layer = tracing_subscriber::filter::LevelFilter::from_level(*log_level);
 
         tracing_subscriber::registry()
             .with(filter_layer)
-            .with(emit_journald.then(|| LogDriver::Journald.layer()))
-            .with(file_path.clone().map(|path| LogDriver::File(path).layer()))
-            .with(emit_stdout.then(|| LogDriver::Stdout.layer()))
-            .with(emit_stderr.then(|| LogDriver::Stderr.layer()))
+            .with(emit_journald.then(|| LogDriver::Journald.layer(*format)))
+            .with(file_path.clone().map(|path| LogDriver::File(path).layer(*format)))
+            .with(emit_stdout.then(|| LogDriver::Stdout.layer(*format)))
+            .with(emit_stderr.then(|| LogDriver::Stderr.layer(*format)))
             .init();
     }
 }
@@ -84,24 +103,30 @@ enum LogDriver {
 
 impl LogDriver {
     #[allow(clippy::type_repetition_in_bounds)]
-    fn layer<S>(self) -> Option<Box<dyn Layer<S> + Send + Sync + 'static>>
+    fn layer<S>(self, format: LogFormat) -> Option<Box<dyn Layer<S> + Send + Sync + 'static>>
     where
         S: tracing::Subscriber,
         for<'a> S: LookupSpan<'a>,
     {
         // Shared configuration regardless of where logs are output to.
-        let fmt =
-            tracing_subscriber::fmt::layer().pretty().with_thread_ids(true).with_thread_names(true);
+        let fmt = tracing_subscriber::fmt::layer().with_thread_ids(true).with_thread_names(true);
 
         // Configure the writer based on the desired log target:
-        match self {
-            Self::Stdout => Some(Box::new(fmt.with_writer(std::io::stdout))),
-            Self::Stderr => Some(Box::new(fmt.with_writer(std::io::stderr))),
+        let writer = match self {
+            Self::Stdout => BoxMakeWriter::new(std::io::stdout),
+            Self::Stderr => BoxMakeWriter::new(std::io::stderr),
             Self::File(path) => {
                 let file = OpenOptions::new().create(true).append(true).open(path).ok()?;
-                Some(Box::new(fmt.with_writer(file)))
+                BoxMakeWriter::new(file)
             }
-            Self::Journald => Some(Box::new(tracing_journald::layer().ok()?)),
+            // journald keeps the fields of events itself
+            Self::Journald => return Some(Box::new(tracing_journald::layer().ok()?)),
+        };
+        match format {
+            LogFormat::Pretty => Some(Box::new(fmt.pretty().with_writer(writer))),
+            LogFormat::Json => Some(Box::new(
+                fmt.json().flatten_event(true).with_current_span(false).with_writer(writer),
+            )),
         }
     }
 }
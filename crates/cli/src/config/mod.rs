@@ -1,3 +1,3 @@
 mod log;
 
-pub use self::log::LogConfig;
+pub use self::log::{LogConfig, LogFormat};
@@ -0,0 +1,299 @@
+use time::OffsetDateTime;
+
+/// Format of the archive built by [`Manager::export_archive`].
+///
+/// [`Manager::export_archive`]: crate::Manager::export_archive
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ArchiveFormat {
+    /// A POSIX `ustar` archive.
+    #[default]
+    Tar,
+
+    /// A ZIP archive with uncompressed entries, without ZIP64 the archive is
+    /// limited to 4 GiB and 65535 entries.
+    Zip,
+}
+
+/// The archive exceeds the limits of its format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TooLarge;
+
+const TAR_BLOCK_SIZE: usize = 512;
+
+const ZIP_VERSION: u16 = 20;
+
+// the CRC and the sizes follow the data in a data descriptor
+const ZIP_FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
+/// Writes the entries of an archive piece by piece, the content of the entries
+/// is not buffered.
+///
+/// Each entry is written by [`start_entry`](Self::start_entry), then its
+/// content is passed to [`write_data`](Self::write_data) and followed by
+/// [`finish_entry`](Self::finish_entry).
+#[derive(Debug)]
+pub struct ArchiveWriter {
+    format: ArchiveFormat,
+
+    // bytes written so far
+    offset: u64,
+
+    zip_entries: Vec<ZipEntry>,
+
+    current: Option<CurrentEntry>,
+}
+
+#[derive(Debug)]
+struct ZipEntry {
+    name: String,
+    dos_time: u16,
+    dos_date: u16,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+#[derive(Debug)]
+struct CurrentEntry {
+    written: u64,
+    hasher: crc32fast::Hasher,
+}
+
+impl ArchiveWriter {
+    pub(crate) const fn new(format: ArchiveFormat) -> Self {
+        Self { format, offset: 0, zip_entries: Vec::new(), current: None }
+    }
+
+    /// Returns the header of an entry of `size` bytes.
+    pub(crate) fn start_entry(
+        &mut self,
+        name: &str,
+        size: u64,
+        modified: OffsetDateTime,
+    ) -> Result<Vec<u8>, TooLarge> {
+        let header = match self.format {
+            ArchiveFormat::Tar => tar_header(name, size, modified)?,
+            ArchiveFormat::Zip => {
+                let (dos_time, dos_date) = dos_date_time(modified);
+                let entry = ZipEntry {
+                    name: name.to_string(),
+                    dos_time,
+                    dos_date,
+                    crc: 0,
+                    size: 0,
+                    offset: u32::try_from(self.offset).map_err(|_| TooLarge)?,
+                };
+                let header = zip_local_header(&entry);
+                self.zip_entries.push(entry);
+                header
+            }
+        };
+        self.current = Some(CurrentEntry { written: 0, hasher: crc32fast::Hasher::new() });
+        self.offset += header.len() as u64;
+        Ok(header)
+    }
+
+    pub(crate) fn write_data(&mut self, data: &[u8]) {
+        if let Some(current) = self.current.as_mut() {
+            current.written += data.len() as u64;
+            current.hasher.update(data);
+        }
+        self.offset += data.len() as u64;
+    }
+
+    /// Returns the bytes following the content of the current entry.
+    pub(crate) fn finish_entry(&mut self) -> Result<Vec<u8>, TooLarge> {
+        let Some(CurrentEntry { written, hasher }) = self.current.take() else {
+            return Ok(Vec::new());
+        };
+        let trailer = match self.format {
+            ArchiveFormat::Tar => vec![0; padding(written)],
+            ArchiveFormat::Zip => {
+                let size = u32::try_from(written).map_err(|_| TooLarge)?;
+                let crc = hasher.finalize();
+                if let Some(entry) = self.zip_entries.last_mut() {
+                    entry.crc = crc;
+                    entry.size = size;
+                }
+                let mut descriptor = Vec::with_capacity(16);
+                descriptor.extend_from_slice(&0x0807_4b50_u32.to_le_bytes());
+                descriptor.extend_from_slice(&crc.to_le_bytes());
+                descriptor.extend_from_slice(&size.to_le_bytes());
+                descriptor.extend_from_slice(&size.to_le_bytes());
+                descriptor
+            }
+        };
+        self.offset += trailer.len() as u64;
+        Ok(trailer)
+    }
+
+    /// Returns the end of the archive.
+    pub(crate) fn finish(mut self) -> Result<Vec<u8>, TooLarge> {
+        let mut end = self.finish_entry()?;
+        match self.format {
+            ArchiveFormat::Tar => end.extend_from_slice(&[0; 2 * TAR_BLOCK_SIZE]),
+            ArchiveFormat::Zip => {
+                let count = u16::try_from(self.zip_entries.len()).map_err(|_| TooLarge)?;
+                let directory_offset = u32::try_from(self.offset).map_err(|_| TooLarge)?;
+                let directory_start = end.len();
+                for entry in &self.zip_entries {
+                    zip_central_header(&mut end, entry);
+                }
+                let directory_size =
+                    u32::try_from(end.len() - directory_start).map_err(|_| TooLarge)?;
+                end.extend_from_slice(&0x0605_4b50_u32.to_le_bytes());
+                end.extend_from_slice(&0_u16.to_le_bytes());
+                end.extend_from_slice(&0_u16.to_le_bytes());
+                end.extend_from_slice(&count.to_le_bytes());
+                end.extend_from_slice(&count.to_le_bytes());
+                end.extend_from_slice(&directory_size.to_le_bytes());
+                end.extend_from_slice(&directory_offset.to_le_bytes());
+                end.extend_from_slice(&0_u16.to_le_bytes());
+            }
+        }
+        Ok(end)
+    }
+}
+
+fn padding(size: u64) -> usize {
+    match usize::try_from(size % TAR_BLOCK_SIZE as u64).unwrap_or_default() {
+        0 => 0,
+        remainder => TAR_BLOCK_SIZE - remainder,
+    }
+}
+
+fn tar_header(name: &str, size: u64, modified: OffsetDateTime) -> Result<Vec<u8>, TooLarge> {
+    fn octal(field: &mut [u8], value: u64) -> Result<(), TooLarge> {
+        let digits = format!("{value:0width$o}", width = field.len() - 1);
+        if digits.len() >= field.len() {
+            return Err(TooLarge);
+        }
+        field[..digits.len()].copy_from_slice(digits.as_bytes());
+        Ok(())
+    }
+
+    let mut header = vec![0; TAR_BLOCK_SIZE];
+    let name = name.as_bytes();
+    header[..name.len().min(100)].copy_from_slice(&name[..name.len().min(100)]);
+    octal(&mut header[100..108], 0o644)?;
+    octal(&mut header[108..116], 0)?;
+    octal(&mut header[116..124], 0)?;
+    octal(&mut header[124..136], size)?;
+    octal(&mut header[136..148], u64::try_from(modified.unix_timestamp()).unwrap_or_default())?;
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // the checksum is computed with the checksum field filled with spaces
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|&byte| u64::from(byte)).sum::<u64>();
+    octal(&mut header[148..155], checksum)?;
+    header[154] = 0;
+    Ok(header)
+}
+
+// MS-DOS time and date, which start from 1980
+fn dos_date_time(time: OffsetDateTime) -> (u16, u16) {
+    if time.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let dos_time = (u16::from(time.hour()) << 11)
+        | (u16::from(time.minute()) << 5)
+        | (u16::from(time.second()) / 2);
+    let year = u16::try_from(time.year() - 1980).unwrap_or_default().min(127);
+    let dos_date = (year << 9) | (u16::from(u8::from(time.month())) << 5) | u16::from(time.day());
+    (dos_time, dos_date)
+}
+
+fn zip_local_header(entry: &ZipEntry) -> Vec<u8> {
+    let mut header = Vec::with_capacity(30 + entry.name.len());
+    header.extend_from_slice(&0x0403_4b50_u32.to_le_bytes());
+    header.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+    header.extend_from_slice(&ZIP_FLAG_DATA_DESCRIPTOR.to_le_bytes());
+    // stored without compression
+    header.extend_from_slice(&0_u16.to_le_bytes());
+    header.extend_from_slice(&entry.dos_time.to_le_bytes());
+    header.extend_from_slice(&entry.dos_date.to_le_bytes());
+    // the CRC and the sizes are in the data descriptor
+    header.extend_from_slice(&[0; 12]);
+    header.extend_from_slice(&name_length(&entry.name).to_le_bytes());
+    header.extend_from_slice(&0_u16.to_le_bytes());
+    header.extend_from_slice(&entry.name.as_bytes()[..usize::from(name_length(&entry.name))]);
+    header
+}
+
+fn zip_central_header(buf: &mut Vec<u8>, entry: &ZipEntry) {
+    buf.extend_from_slice(&0x0201_4b50_u32.to_le_bytes());
+    buf.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+    buf.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+    buf.extend_from_slice(&ZIP_FLAG_DATA_DESCRIPTOR.to_le_bytes());
+    buf.extend_from_slice(&0_u16.to_le_bytes());
+    buf.extend_from_slice(&entry.dos_time.to_le_bytes());
+    buf.extend_from_slice(&entry.dos_date.to_le_bytes());
+    buf.extend_from_slice(&entry.crc.to_le_bytes());
+    buf.extend_from_slice(&entry.size.to_le_bytes());
+    buf.extend_from_slice(&entry.size.to_le_bytes());
+    buf.extend_from_slice(&name_length(&entry.name).to_le_bytes());
+    // the lengths of the extra field and the comment, the disk number and the
+    // file attributes
+    buf.extend_from_slice(&[0; 12]);
+    buf.extend_from_slice(&entry.offset.to_le_bytes());
+    buf.extend_from_slice(&entry.name.as_bytes()[..usize::from(name_length(&entry.name))]);
+}
+
+fn name_length(name: &str) -> u16 { u16::try_from(name.len()).unwrap_or(u16::MAX) }
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::{ArchiveFormat, ArchiveWriter, TAR_BLOCK_SIZE};
+
+    fn write(format: ArchiveFormat, entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ArchiveWriter::new(format);
+        let mut archive = Vec::new();
+        for (name, data) in entries {
+            if !archive.is_empty() {
+                archive.extend(writer.finish_entry().unwrap());
+            }
+            archive.extend(
+                writer.start_entry(name, data.len() as u64, OffsetDateTime::UNIX_EPOCH).unwrap(),
+            );
+            writer.write_data(data);
+            archive.extend_from_slice(data);
+        }
+        archive.extend(writer.finish().unwrap());
+        archive
+    }
+
+    #[test]
+    fn test_tar() {
+        let archive = write(ArchiveFormat::Tar, &[("a.txt", b"clipcat"), ("b.txt", b"")]);
+        assert_eq!(archive.len(), 5 * TAR_BLOCK_SIZE);
+        assert_eq!(&archive[..5], b"a.txt");
+        assert_eq!(&archive[124..135], b"00000000007");
+        assert_eq!(&archive[257..262], b"ustar");
+        assert_eq!(&archive[TAR_BLOCK_SIZE..TAR_BLOCK_SIZE + 7], b"clipcat");
+        assert_eq!(&archive[2 * TAR_BLOCK_SIZE..2 * TAR_BLOCK_SIZE + 5], b"b.txt");
+        assert!(archive[3 * TAR_BLOCK_SIZE..].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn test_zip() {
+        let archive = write(ArchiveFormat::Zip, &[("a.txt", b"clipcat"), ("b.txt", b"cat")]);
+        assert_eq!(&archive[..4], &[0x50, 0x4b, 0x03, 0x04]);
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(&end[..4], &[0x50, 0x4b, 0x05, 0x06]);
+        // two entries
+        assert_eq!(&end[8..12], &[2, 0, 2, 0]);
+        let directory_offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(&archive[directory_offset..directory_offset + 4], &[0x50, 0x4b, 0x01, 0x02]);
+        // the CRC of the first entry in its data descriptor
+        let descriptor = 30 + 5 + 7;
+        assert_eq!(&archive[descriptor..descriptor + 4], &[0x50, 0x4b, 0x07, 0x08]);
+        assert_eq!(
+            u32::from_le_bytes(archive[descriptor + 4..descriptor + 8].try_into().unwrap()),
+            crc32fast::hash(b"clipcat")
+        );
+    }
+}
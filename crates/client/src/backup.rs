@@ -0,0 +1,145 @@
+use clipcat_base::ClipEntry;
+use clipcat_proto as proto;
+use prost::Message;
+
+use crate::error::ImportBinaryError;
+
+// the first bytes of a backup written by `Manager::export_binary`
+const MAGIC: &[u8; 8] = b"CLIPCATB";
+
+pub const VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = MAGIC.len() + 2 + 8;
+
+/// Returns the header of a backup of `count` clips.
+///
+/// A backup is the header followed by the clips, each clip is prefixed by the
+/// size of its encoded message in 8 bytes. Integers are little endian.
+pub fn encode_header(count: u64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(HEADER_SIZE);
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&VERSION.to_le_bytes());
+    buf.extend_from_slice(&count.to_le_bytes());
+    buf
+}
+
+/// Returns the clip encoded as a protobuf message with its size prefixed,
+/// the content is stored as is.
+pub fn encode_entry(clip: ClipEntry) -> Vec<u8> {
+    let message = proto::ClipEntry::from(clip).encode_to_vec();
+    let mut buf = Vec::with_capacity(8 + message.len());
+    buf.extend_from_slice(&(message.len() as u64).to_le_bytes());
+    buf.extend(message);
+    buf
+}
+
+/// Reads the clips of a backup, oldest first.
+#[derive(Debug)]
+pub struct BackupReader<'a> {
+    data: &'a [u8],
+
+    // clips not read yet
+    remaining: u64,
+}
+
+impl<'a> BackupReader<'a> {
+    /// Checks the header of the backup.
+    pub(crate) fn new(data: &'a [u8]) -> Result<Self, ImportBinaryError> {
+        if data.len() < HEADER_SIZE || !data.starts_with(MAGIC) {
+            return Err(ImportBinaryError::InvalidFormat);
+        }
+        let (header, data) = data.split_at(HEADER_SIZE);
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != VERSION {
+            return Err(ImportBinaryError::UnsupportedVersion { version });
+        }
+        let remaining = u64::from_le_bytes(header[10..].try_into().unwrap_or_default());
+        Ok(Self { data, remaining })
+    }
+
+    fn read_entry(&mut self) -> Result<proto::ClipEntry, ImportBinaryError> {
+        let size = self.take(8)?;
+        let size = u64::from_le_bytes(size.try_into().unwrap_or_default());
+        let message =
+            self.take(usize::try_from(size).map_err(|_| ImportBinaryError::Truncated)?)?;
+        proto::ClipEntry::decode(message).map_err(|_| ImportBinaryError::InvalidFormat)
+    }
+
+    fn take(&mut self, size: usize) -> Result<&'a [u8], ImportBinaryError> {
+        if self.data.len() < size {
+            return Err(ImportBinaryError::Truncated);
+        }
+        let (taken, rest) = self.data.split_at(size);
+        self.data = rest;
+        Ok(taken)
+    }
+}
+
+impl Iterator for BackupReader<'_> {
+    type Item = Result<proto::ClipEntry, ImportBinaryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            // the header counts all clips of the backup
+            let trailing = std::mem::take(&mut self.data);
+            return (!trailing.is_empty()).then_some(Err(ImportBinaryError::InvalidFormat));
+        }
+        self.remaining -= 1;
+        let entry = self.read_entry();
+        if entry.is_err() {
+            self.remaining = 0;
+            self.data = &[];
+        }
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardContent, ClipboardKind};
+
+    use super::{encode_entry, encode_header, BackupReader, HEADER_SIZE};
+    use crate::error::ImportBinaryError;
+
+    #[test]
+    fn test_backup() {
+        let image = ClipboardContent::Image {
+            width: 1,
+            height: 1,
+            bytes: bytes::Bytes::from_static(&[255, 0, 0, 255]),
+        };
+        let png = ClipEntry::from_clipboard_content(image, ClipboardKind::Primary, None)
+            .encoded()
+            .unwrap();
+        let clips = [
+            ClipEntry::from_string("clipcat", ClipboardKind::Clipboard),
+            ClipEntry::new(&png, &mime::IMAGE_PNG, ClipboardKind::Primary, None).unwrap(),
+        ];
+        let mut backup = encode_header(clips.len() as u64);
+        assert_eq!(backup.len(), HEADER_SIZE);
+        for clip in &clips {
+            backup.extend(encode_entry(clip.clone()));
+        }
+
+        let entries = BackupReader::new(&backup).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(&entries[0].data[..], b"clipcat");
+        assert_eq!(entries[1].data, png);
+        assert_eq!(entries[1].mime, "image/png");
+
+        let truncated = BackupReader::new(&backup[..backup.len() - 1]).unwrap().last();
+        assert!(matches!(truncated, Some(Err(ImportBinaryError::Truncated))));
+        let mut trailing = backup.clone();
+        trailing.push(0);
+        assert!(matches!(
+            BackupReader::new(&trailing).unwrap().last(),
+            Some(Err(ImportBinaryError::InvalidFormat))
+        ));
+        assert!(matches!(BackupReader::new(b"clipcat"), Err(ImportBinaryError::InvalidFormat)));
+        backup[8] = 2;
+        assert!(matches!(
+            BackupReader::new(&backup),
+            Err(ImportBinaryError::UnsupportedVersion { version: 2 })
+        ));
+    }
+}
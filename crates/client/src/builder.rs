@@ -0,0 +1,158 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use bytes::Bytes;
+use clipcat_base::ClipboardKind;
+
+/// A clip to be inserted with [`Manager::insert_built`], like
+/// `ClipEntryBuilder::text("hello").mode(ClipboardKind::Primary)`.
+///
+/// [`Manager::insert_built`]: crate::Manager::insert_built
+// SAFETY: each flag is enabled by its own builder method
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClipEntryBuilder {
+    pub(crate) data: Bytes,
+    pub(crate) mime: mime::Mime,
+    pub(crate) kind: ClipboardKind,
+    pub(crate) tags: Vec<String>,
+    pub(crate) attributes: BTreeMap<String, String>,
+    pub(crate) ttl: Option<Duration>,
+    pub(crate) silent: bool,
+    pub(crate) propagate: bool,
+    pub(crate) private: bool,
+    pub(crate) position: Option<usize>,
+    pub(crate) force_new: bool,
+}
+
+impl ClipEntryBuilder {
+    /// Creates a clip with `data` of type `mime`, stored in the clipboard.
+    #[must_use]
+    pub fn new(data: impl Into<Vec<u8>>, mime: mime::Mime) -> Self {
+        Self::from_bytes(Bytes::from(data.into()), mime)
+    }
+
+    /// Creates a clip with `data` of type `mime` like
+    /// [`ClipEntryBuilder::new`], the buffer is sent without copying it.
+    #[must_use]
+    pub fn from_bytes(data: Bytes, mime: mime::Mime) -> Self {
+        Self {
+            data,
+            mime,
+            kind: ClipboardKind::Clipboard,
+            tags: Vec::new(),
+            attributes: BTreeMap::new(),
+            ttl: None,
+            silent: false,
+            propagate: false,
+            private: false,
+            position: None,
+            force_new: false,
+        }
+    }
+
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self { Self::new(text.into(), mime::TEXT_PLAIN_UTF_8) }
+
+    #[must_use]
+    pub fn html(html: impl Into<String>) -> Self { Self::new(html.into(), mime::TEXT_HTML_UTF_8) }
+
+    /// Creates a clip with an encoded PNG image.
+    #[must_use]
+    pub fn image_png(bytes: impl Into<Vec<u8>>) -> Self { Self::new(bytes, mime::IMAGE_PNG) }
+
+    /// Sets the clipboard kind the clip is inserted into.
+    #[must_use]
+    pub const fn mode(mut self, kind: ClipboardKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    #[must_use]
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Attaches an opaque key-value pair to the clip, like the source URL,
+    /// which is returned with the clip.
+    ///
+    /// The server rejects the clip if the total size of the keys and the
+    /// values exceeds its limit.
+    #[must_use]
+    pub fn attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let _previous = self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Removes the clip from history after `ttl`, expiring clips are not
+    /// saved to the history file.
+    #[must_use]
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Inserts the clip without sending the changes to watchers and without
+    /// marking it, for bulk imports.
+    ///
+    /// Call [`Manager::notify_resync`] after the import so watchers list the
+    /// history again.
+    ///
+    /// [`Manager::notify_resync`]: crate::Manager::notify_resync
+    #[must_use]
+    pub const fn silent(mut self) -> Self {
+        self.silent = true;
+        self
+    }
+
+    /// Inserts the clip into the peers of the server too, like a daemon on
+    /// another machine.
+    ///
+    /// The server retries unreachable peers a few times and skips them then.
+    /// Servers without peers ignore it.
+    #[must_use]
+    pub const fn propagate(mut self) -> Self {
+        self.propagate = true;
+        self
+    }
+
+    /// Keeps the clip in memory only, for secrets, it is dropped when the
+    /// server restarts.
+    ///
+    /// Private clips are never saved to the history file, nor exported with
+    /// [`Manager::export_archive`], nor propagated to peers. Combine it with
+    /// [`ClipEntryBuilder::ttl`] to remove the clip earlier.
+    ///
+    /// [`Manager::export_archive`]: crate::Manager::export_archive
+    #[must_use]
+    pub const fn private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+
+    /// Places the clip at `position` of history, newest first, instead of
+    /// making it the newest clip, for imports reconstructing the order of
+    /// another history.
+    ///
+    /// Positions beyond the end place the clip last. The clip is not marked,
+    /// marking would make it the newest clip.
+    #[must_use]
+    pub const fn position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Adds the clip as a new clip even if a clip with the same content
+    /// exists, instead of promoting that clip.
+    ///
+    /// Capturing the same content again still promotes the newest clip.
+    #[must_use]
+    pub const fn force_new(mut self) -> Self {
+        self.force_new = true;
+        self
+    }
+}
@@ -0,0 +1,994 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use clipcat_base::{
+    ClipEntry, ClipEntryFull, ClipEntryMetadata, ClipOp, ClipOpResult, ClipboardChange,
+    ClipboardEvent, ClipboardKind, CompactReport, CycleDirection, HistoryDelta, HistoryStats,
+    ImageFormat, ListCursor, MetadataImportReport, ResourceUsage, SemanticKind, SortKey, SortOrder,
+    StoreReport, TagMatch,
+};
+use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+#[cfg(feature = "testing")]
+use crate::error::SetHistoryError;
+use crate::{
+    error::{
+        ApplyError, BatchRemoveClipError, ClearClipError, CompactStoreError, DiffError,
+        ExportEntryError, ExportMetadataError, GetActiveProfileError, GetClipError,
+        GetCurrentClipError, GetImageError, GetLengthError, GetStatsError, ImportMetadataError,
+        InsertClipError, ListClipError, MarkClipError, NotifyResyncError, RemoveClipError,
+        ReorderError, SearchClipError, SetActiveProfileError, UpdateClipError, VerifyStoreError,
+        WatchClipError,
+    },
+    manager::{ClipDataStream, ClipboardEventStream, CurrentClipStream},
+    ClipEntryBuilder, Manager, SearchMatch, SearchOptions, SnapshotHandle, WatchOptions,
+};
+
+/// A [`Manager`] caching the clips fetched with [`Manager::get`] and
+/// [`Manager::batch_metadata`], for UIs whose components fetch the same clips.
+///
+/// At most `capacity` clips are cached, the least recently used ones are
+/// dropped first, and a clip is fetched again after `ttl`. The cached clips
+/// are dropped on the changes of the history streamed by
+/// [`Manager::sync_changes`], and on the changes made through the wrapper.
+/// Nothing is cached any more once the stream ends, e.g. when the server shuts
+/// down, since the changes are not known then.
+pub struct CachingManager<M> {
+    inner: M,
+    cache: Arc<parking_lot::Mutex<Cache>>,
+    // applies the changes of the history to the cache, aborted on drop
+    invalidator: tokio::task::JoinHandle<()>,
+}
+
+impl<M> CachingManager<M>
+where
+    M: Manager + Send + Sync,
+{
+    /// Wraps `inner`, caching at most `capacity` clips for at most `ttl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changes of the history could not be watched,
+    /// the cache could not be kept correct then.
+    pub async fn new(inner: M, capacity: usize, ttl: Duration) -> Result<Self, WatchClipError> {
+        let mut changes = inner.sync_changes(None).await?;
+        let cache = Arc::new(parking_lot::Mutex::new(Cache::new(capacity, ttl)));
+        let invalidator = tokio::spawn({
+            let cache = Arc::clone(&cache);
+            async move {
+                while let Some(Ok(ClipboardEvent { change, .. })) = changes.next().await {
+                    cache.lock().apply(&change);
+                }
+                cache.lock().disable();
+            }
+        });
+        Ok(Self { inner, cache, invalidator })
+    }
+}
+
+impl<M> CachingManager<M> {
+    #[inline]
+    pub const fn inner(&self) -> &M { &self.inner }
+
+    /// Drops all cached clips.
+    pub fn invalidate_all(&self) { self.cache.lock().invalidate_all(); }
+
+    fn invalidate(&self, ids: &[u64]) {
+        let mut cache = self.cache.lock();
+        for &id in ids {
+            cache.invalidate(id);
+        }
+    }
+}
+
+impl<M> Drop for CachingManager<M> {
+    fn drop(&mut self) { self.invalidator.abort(); }
+}
+
+/// The clips cached by [`CachingManager`].
+pub struct Cache {
+    capacity: usize,
+
+    ttl: Duration,
+
+    // `false` once the changes of the history are not watched any more
+    enabled: bool,
+
+    // bumped by each invalidation, a clip fetched before is not cached
+    epoch: u64,
+
+    tick: u64,
+
+    clips: HashMap<u64, CachedClip>,
+
+    // ids by the tick of their last use, the least recently used first
+    order: BTreeMap<u64, u64>,
+}
+
+struct CachedClip {
+    cached_at: Instant,
+
+    tick: u64,
+
+    entry: Option<ClipEntry>,
+
+    // the metadata by preview length
+    metadata: HashMap<usize, ClipEntryMetadata>,
+}
+
+impl Cache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            enabled: true,
+            epoch: 0,
+            tick: 0,
+            clips: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the epoch to pass to the inserts of the clips fetched next.
+    pub const fn epoch(&self) -> u64 { self.epoch }
+
+    pub fn entry(&mut self, id: u64, now: Instant) -> Option<ClipEntry> {
+        if !self.touch(id, now) {
+            return None;
+        }
+        self.clips.get(&id).and_then(|clip| clip.entry.clone())
+    }
+
+    pub fn metadata(
+        &mut self,
+        id: u64,
+        preview_length: usize,
+        now: Instant,
+    ) -> Option<ClipEntryMetadata> {
+        if !self.touch(id, now) {
+            return None;
+        }
+        let clip = self.clips.get(&id)?;
+        clip.metadata
+            .get(&preview_length)
+            .cloned()
+            .or_else(|| clip.entry.as_ref().map(|entry| entry.metadata(Some(preview_length))))
+    }
+
+    /// Caches `entry` unless the cache is invalidated since `epoch`.
+    pub fn insert_entry(&mut self, epoch: u64, entry: ClipEntry, now: Instant) {
+        if let Some(clip) = self.slot(entry.id(), epoch, now) {
+            clip.entry = Some(entry);
+        }
+    }
+
+    /// Caches `metadata` unless the cache is invalidated since `epoch`.
+    pub fn insert_metadata(
+        &mut self,
+        epoch: u64,
+        preview_length: usize,
+        metadata: ClipEntryMetadata,
+        now: Instant,
+    ) {
+        if let Some(clip) = self.slot(metadata.id, epoch, now) {
+            let _previous = clip.metadata.insert(preview_length, metadata);
+        }
+    }
+
+    pub fn invalidate(&mut self, id: u64) {
+        self.epoch += 1;
+        if let Some(clip) = self.clips.remove(&id) {
+            let _id = self.order.remove(&clip.tick);
+        }
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.epoch += 1;
+        self.clips.clear();
+        self.order.clear();
+    }
+
+    pub fn apply(&mut self, change: &ClipboardChange) {
+        match change {
+            ClipboardChange::Added(clip) | ClipboardChange::Updated(clip) => {
+                self.invalidate(clip.id());
+            }
+            ClipboardChange::AddedMetadata(metadata)
+            | ClipboardChange::UpdatedMetadata(metadata) => self.invalidate(metadata.id),
+            ClipboardChange::Removed(id) | ClipboardChange::Evicted { id, .. } => {
+                self.invalidate(*id);
+            }
+            ClipboardChange::Resync | ClipboardChange::ProfileChanged { .. } => {
+                self.invalidate_all();
+            }
+            ClipboardChange::Heartbeat => {}
+        }
+    }
+
+    pub fn disable(&mut self) {
+        self.enabled = false;
+        self.invalidate_all();
+    }
+
+    // marks the clip as the most recently used one, returns `false` if it is
+    // not cached or expired
+    fn touch(&mut self, id: u64, now: Instant) -> bool {
+        let Some(cached_at) = self.clips.get(&id).map(|clip| clip.cached_at) else {
+            return false;
+        };
+        if now.duration_since(cached_at) >= self.ttl {
+            if let Some(clip) = self.clips.remove(&id) {
+                let _id = self.order.remove(&clip.tick);
+            }
+            return false;
+        }
+        self.tick += 1;
+        if let Some(clip) = self.clips.get_mut(&id) {
+            let _id = self.order.remove(&clip.tick);
+            clip.tick = self.tick;
+        }
+        let _previous = self.order.insert(self.tick, id);
+        true
+    }
+
+    // returns the cached clip `id`, adding it if it is not cached
+    fn slot(&mut self, id: u64, epoch: u64, now: Instant) -> Option<&mut CachedClip> {
+        if !self.enabled || epoch != self.epoch || self.capacity == 0 {
+            return None;
+        }
+        if !self.touch(id, now) {
+            self.tick += 1;
+            let _previous = self.order.insert(self.tick, id);
+            let _previous = self.clips.insert(
+                id,
+                CachedClip {
+                    cached_at: now,
+                    tick: self.tick,
+                    entry: None,
+                    metadata: HashMap::new(),
+                },
+            );
+            while self.clips.len() > self.capacity {
+                let Some((_, oldest)) = self.order.pop_first() else {
+                    break;
+                };
+                let _clip = self.clips.remove(&oldest);
+            }
+        }
+        self.clips.get_mut(&id)
+    }
+}
+
+#[async_trait]
+impl<M> Manager for CachingManager<M>
+where
+    M: Manager + Send + Sync,
+{
+    async fn get(&self, id: u64) -> Result<ClipEntry, GetClipError> {
+        let epoch = {
+            let mut cache = self.cache.lock();
+            if let Some(entry) = cache.entry(id, Instant::now()) {
+                return Ok(entry);
+            }
+            cache.epoch()
+        };
+        let entry = self.inner.get(id).await?;
+        self.cache.lock().insert_entry(epoch, entry.clone(), Instant::now());
+        Ok(entry)
+    }
+
+    async fn get_full(
+        &self,
+        id: u64,
+        preview_length: usize,
+    ) -> Result<ClipEntryFull, GetClipError> {
+        let epoch = self.cache.lock().epoch();
+        let full = self.inner.get_full(id, preview_length).await?;
+        {
+            let mut cache = self.cache.lock();
+            let now = Instant::now();
+            cache.insert_entry(epoch, full.entry.clone(), now);
+            cache.insert_metadata(epoch, preview_length, full.metadata.clone(), now);
+        }
+        Ok(full)
+    }
+
+    async fn get_bytes(&self, id: u64) -> Result<Bytes, GetClipError> {
+        self.inner.get_bytes(id).await
+    }
+
+    async fn get_prefix(&self, id: u64, max_bytes: u64) -> Result<(ClipEntry, bool), GetClipError> {
+        self.inner.get_prefix(id, max_bytes).await
+    }
+
+    async fn get_stream(&self, id: u64) -> Result<ClipDataStream, GetClipError> {
+        self.inner.get_stream(id).await
+    }
+
+    async fn get_stream_from(&self, id: u64, offset: u64) -> Result<ClipDataStream, GetClipError> {
+        self.inner.get_stream_from(id, offset).await
+    }
+
+    async fn get_image_as(&self, id: u64, format: ImageFormat) -> Result<Vec<u8>, GetImageError> {
+        self.inner.get_image_as(id, format).await
+    }
+
+    async fn export_entry(&self, id: u64, path: &Path) -> Result<PathBuf, ExportEntryError> {
+        self.inner.export_entry(id, path).await
+    }
+
+    async fn get_current_clip(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<ClipEntry, GetCurrentClipError> {
+        self.inner.get_current_clip(kind).await
+    }
+
+    async fn get_current_any(
+        &self,
+        kinds: &[ClipboardKind],
+    ) -> Result<ClipEntry, GetCurrentClipError> {
+        self.inner.get_current_any(kinds).await
+    }
+
+    async fn take_current(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, GetCurrentClipError> {
+        self.inner.take_current(kind).await
+    }
+
+    async fn current_entry_id(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Option<u64>, GetCurrentClipError> {
+        self.inner.current_entry_id(kind).await
+    }
+
+    async fn is_current(&self, id: u64, kind: ClipboardKind) -> Result<bool, GetCurrentClipError> {
+        self.inner.is_current(id, kind).await
+    }
+
+    async fn current_targets(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<String>, GetCurrentClipError> {
+        self.inner.current_targets(kind).await
+    }
+
+    async fn get_current_all_formats(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<(mime::Mime, Vec<u8>)>, GetCurrentClipError> {
+        self.inner.get_current_all_formats(kind).await
+    }
+
+    async fn rank(&self, id: u64) -> Result<Option<u64>, GetClipError> { self.inner.rank(id).await }
+
+    async fn neighbor(
+        &self,
+        id: u64,
+        direction: CycleDirection,
+    ) -> Result<Option<ClipEntry>, GetClipError> {
+        self.inner.neighbor(id, direction).await
+    }
+
+    async fn nth_recent(
+        &self,
+        n: u64,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, GetCurrentClipError> {
+        self.inner.nth_recent(n, kind).await
+    }
+
+    async fn latest(&self, kind: ClipboardKind) -> Result<Option<ClipEntry>, GetCurrentClipError> {
+        self.inner.latest(kind).await
+    }
+
+    async fn update(
+        &self,
+        id: u64,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<(bool, u64), UpdateClipError> {
+        let result = self.inner.update(id, data, mime).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn update_if(
+        &self,
+        id: u64,
+        expected_version: u64,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<(bool, u64), UpdateClipError> {
+        let result = self.inner.update_if(id, expected_version, data, mime).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn append(
+        &self,
+        id: u64,
+        data: &[u8],
+        separator: Option<&[u8]>,
+    ) -> Result<(bool, u64), UpdateClipError> {
+        let result = self.inner.append(id, data, separator).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn mark(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError> {
+        let result = self.inner.mark(id, kind).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn mark_and_propagate(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+    ) -> Result<bool, MarkClipError> {
+        let result = self.inner.mark_and_propagate(id, kind).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn mark_kinds(&self, id: u64, kinds: &[ClipboardKind]) -> Result<bool, MarkClipError> {
+        let result = self.inner.mark_kinds(id, kinds).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn cycle(
+        &self,
+        kind: ClipboardKind,
+        direction: CycleDirection,
+        wrap: bool,
+    ) -> Result<Option<u64>, MarkClipError> {
+        let result = self.inner.cycle(kind, direction, wrap).await;
+        if let Ok(Some(id)) = result {
+            self.invalidate(&[id]);
+        }
+        result
+    }
+
+    async fn mark_as(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        target_mime: mime::Mime,
+    ) -> Result<bool, MarkClipError> {
+        let result = self.inner.mark_as(id, kind, target_mime).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn mark_and_remove(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError> {
+        let result = self.inner.mark_and_remove(id, kind).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn touch(&self, id: u64) -> Result<bool, MarkClipError> {
+        let result = self.inner.touch(id).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn protect_until(&self, id: u64, until: SystemTime) -> Result<bool, MarkClipError> {
+        let result = self.inner.protect_until(id, until).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn set_label(&self, id: u64, label: Option<String>) -> Result<bool, UpdateClipError> {
+        let result = self.inner.set_label(id, label).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn set_sticky(&self, id: u64, sticky: bool) -> Result<bool, UpdateClipError> {
+        let result = self.inner.set_sticky(id, sticky).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn pin_current(&self, kind: ClipboardKind) -> Result<Option<u64>, UpdateClipError> {
+        let result = self.inner.pin_current(kind).await;
+        if let Ok(Some(id)) = result {
+            self.invalidate(&[id]);
+        }
+        result
+    }
+
+    async fn set_semantic_kind(
+        &self,
+        id: u64,
+        kind: SemanticKind,
+    ) -> Result<bool, UpdateClipError> {
+        let result = self.inner.set_semantic_kind(id, kind).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn swap(&self, a: u64, b: u64) -> Result<bool, MarkClipError> {
+        let result = self.inner.swap(a, b).await;
+        self.invalidate(&[a, b]);
+        result
+    }
+
+    async fn reorder(&self, key: SortKey) -> Result<(), ReorderError> {
+        let result = self.inner.reorder(key).await;
+        // the versions of the moved clips are unknown here
+        self.invalidate_all();
+        result
+    }
+
+    #[cfg(feature = "testing")]
+    async fn set_history(&self, entries: Vec<ClipEntry>) -> Result<(), SetHistoryError> {
+        let result = self.inner.set_history(entries).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn wait_active(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        timeout: Duration,
+    ) -> Result<bool, MarkClipError> {
+        self.inner.wait_active(id, kind, timeout).await
+    }
+
+    async fn set_transient(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        kind: ClipboardKind,
+    ) -> Result<(), MarkClipError> {
+        self.inner.set_transient(data, mime, kind).await
+    }
+
+    async fn replace_active(
+        &self,
+        kind: ClipboardKind,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<u64, MarkClipError> {
+        let result = self.inner.replace_active(kind, data, mime).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn insert(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
+        let (id, mime) = self.inner.insert(data, mime, clipboard_kind).await?;
+        self.invalidate(&[id]);
+        Ok((id, mime))
+    }
+
+    async fn insert_bytes(
+        &self,
+        data: Bytes,
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
+        let (id, mime) = self.inner.insert_bytes(data, mime, clipboard_kind).await?;
+        self.invalidate(&[id]);
+        Ok((id, mime))
+    }
+
+    async fn insert_with_evicted(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, Vec<u64>), InsertClipError> {
+        let (id, evicted) = self.inner.insert_with_evicted(data, mime, clipboard_kind).await?;
+        self.invalidate(&[id]);
+        self.invalidate(&evicted);
+        Ok((id, evicted))
+    }
+
+    async fn insert_with_id(
+        &self,
+        id: u64,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<u64, InsertClipError> {
+        let result = self.inner.insert_with_id(id, data, mime, clipboard_kind).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn get_or_insert(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, bool), InsertClipError> {
+        let (id, inserted) = self.inner.get_or_insert(data, mime, clipboard_kind).await?;
+        if inserted {
+            self.invalidate(&[id]);
+        }
+        Ok((id, inserted))
+    }
+
+    async fn duplicate(&self, id: u64) -> Result<u64, InsertClipError> {
+        let copy_id = self.inner.duplicate(id).await?;
+        self.invalidate(&[copy_id]);
+        Ok(copy_id)
+    }
+
+    async fn insert_built(&self, clip: ClipEntryBuilder) -> Result<u64, InsertClipError> {
+        let id = self.inner.insert_built(clip).await?;
+        self.invalidate(&[id]);
+        Ok(id)
+    }
+
+    async fn length(&self) -> Result<usize, GetLengthError> { self.inner.length().await }
+
+    async fn list(&self, preview_length: usize) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        self.inner.list(preview_length).await
+    }
+
+    async fn list_sorted(
+        &self,
+        preview_length: usize,
+        order: SortOrder,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        self.inner.list_sorted(preview_length, order).await
+    }
+
+    async fn list_with_skipped(
+        &self,
+        preview_length: usize,
+    ) -> Result<(Vec<ClipEntryMetadata>, usize), ListClipError> {
+        self.inner.list_with_skipped(preview_length).await
+    }
+
+    async fn list_cancellable(
+        &self,
+        preview_length: usize,
+        token: CancellationToken,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        self.inner.list_cancellable(preview_length, token).await
+    }
+
+    async fn list_range(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), ListClipError> {
+        self.inner.list_range(offset, limit).await
+    }
+
+    async fn open_snapshot(&self) -> Result<SnapshotHandle, ListClipError> {
+        self.inner.open_snapshot().await
+    }
+
+    async fn list_snapshot_range(
+        &self,
+        snapshot: &SnapshotHandle,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), ListClipError> {
+        self.inner.list_snapshot_range(snapshot, offset, limit).await
+    }
+
+    async fn close_snapshot(&self, snapshot: SnapshotHandle) -> Result<bool, ListClipError> {
+        self.inner.close_snapshot(snapshot).await
+    }
+
+    async fn list_most_used(&self, limit: usize) -> Result<Vec<ClipEntry>, ListClipError> {
+        self.inner.list_most_used(limit).await
+    }
+
+    async fn list_after(
+        &self,
+        cursor: Option<ListCursor>,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, Option<ListCursor>), ListClipError> {
+        self.inner.list_after(cursor, limit).await
+    }
+
+    async fn list_since(&self, since: SystemTime) -> Result<Vec<ClipEntry>, ListClipError> {
+        self.inner.list_since(since).await
+    }
+
+    async fn list_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+    ) -> Result<Vec<ClipEntry>, ListClipError> {
+        self.inner.list_by_tags(tags, mode).await
+    }
+
+    async fn list_by_source_class(&self, class: &str) -> Result<Vec<ClipEntry>, ListClipError> {
+        self.inner.list_by_source_class(class).await
+    }
+
+    async fn list_by_semantic_kind(
+        &self,
+        kind: SemanticKind,
+    ) -> Result<Vec<ClipEntry>, ListClipError> {
+        self.inner.list_by_semantic_kind(kind).await
+    }
+
+    async fn list_by_size(
+        &self,
+        min: Option<u64>,
+        max: Option<u64>,
+    ) -> Result<Vec<ClipEntry>, ListClipError> {
+        self.inner.list_by_size(min, max).await
+    }
+
+    async fn list_grouped(&self) -> Result<HashMap<ClipboardKind, Vec<ClipEntry>>, ListClipError> {
+        self.inner.list_grouped().await
+    }
+
+    async fn list_grouped_sorted(
+        &self,
+        order: SortOrder,
+    ) -> Result<HashMap<ClipboardKind, Vec<ClipEntry>>, ListClipError> {
+        self.inner.list_grouped_sorted(order).await
+    }
+
+    async fn entries_with_content_hash(&self, hash: &str) -> Result<Vec<u64>, ListClipError> {
+        self.inner.entries_with_content_hash(hash).await
+    }
+
+    async fn list_ids(&self) -> Result<Vec<(u64, SystemTime)>, ListClipError> {
+        self.inner.list_ids().await
+    }
+
+    async fn batch_metadata(
+        &self,
+        ids: &[u64],
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        let (mut found, missing, epoch) = {
+            let mut cache = self.cache.lock();
+            let now = Instant::now();
+            let mut found = HashMap::new();
+            let mut missing = Vec::new();
+            for &id in ids {
+                match cache.metadata(id, preview_length, now) {
+                    Some(metadata) => {
+                        let _previous = found.insert(id, metadata);
+                    }
+                    None => missing.push(id),
+                }
+            }
+            (found, missing, cache.epoch())
+        };
+        if !missing.is_empty() {
+            let fetched = self.inner.batch_metadata(&missing, preview_length).await?;
+            let now = Instant::now();
+            let mut cache = self.cache.lock();
+            for metadata in &fetched {
+                cache.insert_metadata(epoch, preview_length, metadata.clone(), now);
+            }
+            drop(cache);
+            found.extend(fetched.into_iter().map(|metadata| (metadata.id, metadata)));
+        }
+        Ok(ids.iter().filter_map(|id| found.get(id).cloned()).collect())
+    }
+
+    async fn batch_etags(&self, ids: &[u64]) -> Result<Vec<(u64, u64)>, ListClipError> {
+        self.inner.batch_etags(ids).await
+    }
+
+    async fn remove(&self, id: u64) -> Result<bool, RemoveClipError> {
+        let result = self.inner.remove(id).await;
+        self.invalidate(&[id]);
+        result
+    }
+
+    async fn batch_remove(&self, ids: &[u64]) -> Result<Vec<u64>, BatchRemoveClipError> {
+        let result = self.inner.batch_remove(ids).await;
+        self.invalidate(ids);
+        result
+    }
+
+    async fn apply(&self, ops: &[ClipOp]) -> Result<Vec<ClipOpResult>, ApplyError> {
+        let result = self.inner.apply(ops).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn remove_by_content(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        dry_run: bool,
+    ) -> Result<Vec<u64>, BatchRemoveClipError> {
+        let ids = self.inner.remove_by_content(data, mime, dry_run).await?;
+        if !dry_run {
+            self.invalidate(&ids);
+        }
+        Ok(ids)
+    }
+
+    async fn trim_to(
+        &self,
+        max_count: u64,
+        kind: Option<ClipboardKind>,
+        dry_run: bool,
+    ) -> Result<Vec<u64>, BatchRemoveClipError> {
+        let ids = self.inner.trim_to(max_count, kind, dry_run).await?;
+        if !dry_run {
+            self.invalidate(&ids);
+        }
+        Ok(ids)
+    }
+
+    async fn clear(&self) -> Result<(), ClearClipError> {
+        let result = self.inner.clear().await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn drain(
+        &self,
+        keep_snippets: bool,
+        keep_sticky: bool,
+    ) -> Result<Vec<ClipEntry>, ClearClipError> {
+        let result = self.inner.drain(keep_snippets, keep_sticky).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn notify_resync(&self) -> Result<(), NotifyResyncError> {
+        let result = self.inner.notify_resync().await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn list_profile(
+        &self,
+        profile: Option<&str>,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        self.inner.list_profile(profile, preview_length).await
+    }
+
+    async fn clear_profile(&self, profile: Option<&str>) -> Result<(), ClearClipError> {
+        let result = self.inner.clear_profile(profile).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn set_active_profile(&self, profile: Option<&str>) -> Result<(), SetActiveProfileError> {
+        let result = self.inner.set_active_profile(profile).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn get_active_profile(&self) -> Result<Option<String>, GetActiveProfileError> {
+        self.inner.get_active_profile().await
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, SearchClipError> {
+        self.inner.search(query, preview_length).await
+    }
+
+    async fn search_cancellable(
+        &self,
+        query: &str,
+        preview_length: usize,
+        token: CancellationToken,
+    ) -> Result<Vec<ClipEntryMetadata>, SearchClipError> {
+        self.inner.search_cancellable(query, preview_length, token).await
+    }
+
+    async fn search_with(
+        &self,
+        query: &str,
+        preview_length: usize,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchMatch>, SearchClipError> {
+        self.inner.search_with(query, preview_length, options).await
+    }
+
+    async fn first_matching(
+        &self,
+        query: &str,
+        options: SearchOptions,
+    ) -> Result<Option<ClipEntry>, SearchClipError> {
+        self.inner.first_matching(query, options).await
+    }
+
+    async fn stats(&self) -> Result<HistoryStats, GetStatsError> { self.inner.stats().await }
+
+    async fn resource_usage(&self) -> Result<ResourceUsage, GetStatsError> {
+        self.inner.resource_usage().await
+    }
+
+    async fn mime_types(&self) -> Result<Vec<(String, u64)>, GetStatsError> {
+        self.inner.mime_types().await
+    }
+
+    async fn content_manifest(&self) -> Result<Vec<(u64, String)>, GetStatsError> {
+        self.inner.content_manifest().await
+    }
+
+    async fn export_metadata(&self) -> Result<Vec<u8>, ExportMetadataError> {
+        self.inner.export_metadata().await
+    }
+
+    async fn import_metadata(
+        &self,
+        data: &[u8],
+    ) -> Result<MetadataImportReport, ImportMetadataError> {
+        let result = self.inner.import_metadata(data).await;
+        self.invalidate_all();
+        result
+    }
+
+    async fn verify_store(&self, repair: bool) -> Result<StoreReport, VerifyStoreError> {
+        self.inner.verify_store(repair).await
+    }
+
+    async fn compact_store(&self) -> Result<CompactReport, CompactStoreError> {
+        self.inner.compact_store().await
+    }
+
+    async fn activity(
+        &self,
+        bucket: Duration,
+        since: SystemTime,
+    ) -> Result<Vec<(SystemTime, u64)>, GetStatsError> {
+        self.inner.activity(bucket, since).await
+    }
+
+    async fn time_bounds(
+        &self,
+        kind: Option<ClipboardKind>,
+    ) -> Result<Option<(SystemTime, SystemTime)>, GetStatsError> {
+        self.inner.time_bounds(kind).await
+    }
+
+    async fn sync_changes(
+        &self,
+        since_cursor: Option<u64>,
+    ) -> Result<ClipboardEventStream, WatchClipError> {
+        self.inner.sync_changes(since_cursor).await
+    }
+
+    async fn sync_changes_with(
+        &self,
+        options: WatchOptions,
+    ) -> Result<ClipboardEventStream, WatchClipError> {
+        self.inner.sync_changes_with(options).await
+    }
+
+    async fn diff_since(&self, cursor: u64) -> Result<HistoryDelta, DiffError> {
+        self.inner.diff_since(cursor).await
+    }
+
+    async fn watch_current(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<CurrentClipStream, WatchClipError> {
+        self.inner.watch_current(kind).await
+    }
+}
@@ -0,0 +1,113 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use snafu::ResultExt;
+use tokio::net::UnixStream;
+
+use crate::error::{self, ParseEndpointError, Result};
+
+/// The endpoint of a Clipcat server, parsed from strings like
+/// `http://127.0.0.1:45045`, `unix:///run/user/1000/clipcat/grpc.sock` or a
+/// bare path of a local socket.
+///
+/// TLS is not supported, `https` endpoints are rejected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClipcatEndpoint {
+    /// An HTTP endpoint, host names are resolved when connecting.
+    Http(http::Uri),
+
+    /// The path of a Unix domain socket.
+    LocalSocket(PathBuf),
+}
+
+impl ClipcatEndpoint {
+    /// Returns the endpoint as a URI, for reporting connection errors.
+    pub(crate) fn uri(&self) -> http::Uri {
+        match self {
+            Self::Http(uri) => uri.clone(),
+            Self::LocalSocket(path) => {
+                http::Uri::try_from(path.to_string_lossy().as_ref()).unwrap_or_default()
+            }
+        }
+    }
+
+    // SAFETY: it will never panic because the uri is a valid URL
+    pub(crate) async fn connect(&self) -> Result<tonic::transport::Channel> {
+        match self {
+            Self::Http(grpc_endpoint) => {
+                tonic::transport::Endpoint::from_shared(grpc_endpoint.to_string())
+                    .expect("`grpc_endpoint` is a valid URL; qed")
+                    .connect()
+                    .await
+                    .with_context(|_| error::ConnectToClipcatServerViaHttpSnafu {
+                        endpoint: grpc_endpoint.clone(),
+                    })
+            }
+            Self::LocalSocket(socket_path) => {
+                let path = socket_path.clone();
+                // We will ignore this uri because uds do not use it
+                tonic::transport::Endpoint::from_static("http://[::]:50051")
+                    .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                        let path = path.clone();
+                        async move {
+                            // Connect to a Uds socket
+                            Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(
+                                UnixStream::connect(path).await?,
+                            ))
+                        }
+                    }))
+                    .await
+                    .with_context(|_| error::ConnectToClipcatServerViaLocalSocketSnafu {
+                        socket: socket_path.clone(),
+                    })
+            }
+        }
+    }
+}
+
+impl FromStr for ClipcatEndpoint {
+    type Err = ParseEndpointError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix://").or_else(|| s.strip_prefix("unix:")) {
+            if path.is_empty() {
+                return Err(ParseEndpointError::Invalid { value: s.to_string() });
+            }
+            return Ok(Self::LocalSocket(PathBuf::from(path)));
+        }
+        if s.starts_with('/') {
+            return Ok(Self::LocalSocket(PathBuf::from(s)));
+        }
+
+        let uri = http::Uri::from_str(s)
+            .map_err(|_| ParseEndpointError::Invalid { value: s.to_string() })?;
+        match uri.scheme_str() {
+            Some("http") if uri.authority().is_some() => Ok(Self::Http(uri)),
+            Some("https") => Err(ParseEndpointError::TlsNotSupported { value: s.to_string() }),
+            Some(scheme) if scheme != "http" => {
+                Err(ParseEndpointError::UnsupportedScheme { scheme: scheme.to_string() })
+            }
+            _ => Err(ParseEndpointError::Invalid { value: s.to_string() }),
+        }
+    }
+}
+
+impl fmt::Display for ClipcatEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(uri) => uri.fmt(f),
+            Self::LocalSocket(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl From<http::Uri> for ClipcatEndpoint {
+    /// Takes URIs with the `http` scheme as HTTP endpoints and the others as
+    /// the paths of local sockets, like [`crate::Client::new`].
+    fn from(uri: http::Uri) -> Self {
+        if uri.scheme() == Some(&http::uri::Scheme::HTTP) {
+            Self::Http(uri)
+        } else {
+            Self::LocalSocket(PathBuf::from(uri.path()))
+        }
+    }
+}
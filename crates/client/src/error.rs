@@ -1,6 +1,6 @@
 #![allow(clippy::module_name_repetitions)]
 
-use std::{fmt, path::PathBuf};
+use std::{fmt, path::PathBuf, time::Duration};
 
 use clipcat_base::ClipboardKind;
 use snafu::{Backtrace, Snafu};
@@ -32,15 +32,168 @@ pub enum Error {
     },
 }
 
+impl Error {
+    fn into_transport_error(self) -> tonic::transport::Error {
+        match self {
+            Self::ConnectToClipcatServerViaHttp { source, .. }
+            | Self::ConnectToClipcatServerViaLocalSocket { source, .. } => source,
+        }
+    }
+}
+
+/// Error of [`Client::try_connect`](crate::Client::try_connect).
+#[derive(Debug)]
+pub enum ConnectError {
+    /// Nothing is listening at the endpoint.
+    NotRunning {
+        endpoint: http::Uri,
+    },
+    TimedOut {
+        endpoint: http::Uri,
+        timeout: Duration,
+    },
+    /// The server rejected the access token.
+    AuthFailed {
+        endpoint: http::Uri,
+    },
+    Transport {
+        endpoint: http::Uri,
+        source: tonic::transport::Error,
+    },
+    Status {
+        endpoint: http::Uri,
+        source: tonic::Status,
+    },
+}
+
+impl ConnectError {
+    pub(crate) fn from_connect_error(endpoint: http::Uri, err: Error) -> Self {
+        let source = err.into_transport_error();
+        // the socket file does not exist or nothing is listening at it
+        let mut cause = std::error::Error::source(&source);
+        while let Some(err) = cause {
+            if let Some(err) = err.downcast_ref::<std::io::Error>() {
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+                ) {
+                    return Self::NotRunning { endpoint };
+                }
+            }
+            cause = err.source();
+        }
+        Self::Transport { endpoint, source }
+    }
+
+    pub(crate) fn from_status(endpoint: http::Uri, source: tonic::Status) -> Self {
+        match error_code(&source) {
+            clipcat_proto::ErrorCode::Unauthenticated => Self::AuthFailed { endpoint },
+            _ => Self::Status { endpoint, source },
+        }
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRunning { endpoint } => {
+                write!(f, "Clipcat server is not running at `{endpoint}`, is clipcatd running?")
+            }
+            Self::TimedOut { endpoint, timeout } => write!(
+                f,
+                "Timed out after {timeout_ms} ms while connecting to Clipcat server at \
+                 `{endpoint}`",
+                timeout_ms = timeout.as_millis()
+            ),
+            Self::AuthFailed { endpoint } => {
+                write!(f, "Clipcat server at `{endpoint}` rejected the access token")
+            }
+            Self::Transport { endpoint, source } => {
+                write!(f, "Could not connect to Clipcat server at `{endpoint}`, error: {source}")
+            }
+            Self::Status { endpoint, source } => {
+                write!(f, "Clipcat server at `{endpoint}` is not ready, error: {source}")
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum InsertClipError {
-    Status { source: tonic::Status },
+    Status {
+        source: tonic::Status,
+    },
+    InvalidMime {
+        value: String,
+    },
+    IdTaken {
+        id: u64,
+    },
+    KindDisabled {
+        kind: ClipboardKind,
+    },
+    /// The total size of the attributes exceeds `limit` bytes, see
+    /// [`ClipEntryBuilder::attribute`].
+    ///
+    /// [`ClipEntryBuilder::attribute`]: crate::ClipEntryBuilder::attribute
+    AttributesTooLarge {
+        limit: u64,
+    },
+    /// The client made `count` inserts in the current second, exceeding the
+    /// `limit` per second allowed by the server, see
+    /// [`ServerInfo::max_inserts_per_second`].
+    ///
+    /// [`ServerInfo::max_inserts_per_second`]: clipcat_base::ServerInfo::max_inserts_per_second
+    RateLimited {
+        count: u64,
+        limit: u64,
+        retry_after: Duration,
+    },
+    /// The clip of `size` bytes exceeds the `limit` in bytes of `rule`, see
+    /// [`ServerInfo::size_limits`].
+    ///
+    /// [`ServerInfo::size_limits`]: clipcat_base::ServerInfo::size_limits
+    TooLarge {
+        size: u64,
+        limit: u64,
+        rule: String,
+    },
+    /// The history holds `count` clips of a `capacity` and all of them are
+    /// protected, so no clip can be removed to make room.
+    Full {
+        count: u64,
+        capacity: u64,
+    },
+    /// There is no clip with `id` to copy, see [`Manager::duplicate`].
+    ///
+    /// [`Manager::duplicate`]: crate::Manager::duplicate
+    NotFound {
+        id: u64,
+    },
 }
 
 impl fmt::Display for InsertClipError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Status { source } => source.fmt(f),
+            Self::InvalidMime { value } => write!(f, "Invalid mime type `{value}`"),
+            Self::IdTaken { id } => write!(f, "Clip with id {id:016x} already exists"),
+            Self::KindDisabled { kind } => write!(f, "Clipboard kind {kind} is not enabled"),
+            Self::AttributesTooLarge { limit } => {
+                write!(f, "Attributes of the clip exceed the limit of {limit} bytes")
+            }
+            Self::RateLimited { count, limit, retry_after } => write!(
+                f,
+                "Too many inserts ({count}/{limit} per second), retry after {} ms",
+                retry_after.as_millis()
+            ),
+            Self::TooLarge { size, limit, rule } => {
+                write!(f, "Clip of {size} bytes exceeds the limit of {limit} bytes of `{rule}`")
+            }
+            Self::Full { count, capacity } => {
+                write!(f, "History is full of protected clips ({count}/{capacity})")
+            }
+            Self::NotFound { id } => write!(f, "Clip {id:016x} does not exist"),
         }
     }
 }
@@ -49,6 +202,7 @@ impl fmt::Display for InsertClipError {
 pub enum GetClipError {
     Status { source: tonic::Status, id: u64 },
     Empty,
+    NotUriList { id: u64 },
 }
 
 impl fmt::Display for GetClipError {
@@ -56,6 +210,106 @@ impl fmt::Display for GetClipError {
         match self {
             Self::Status { source, .. } => source.fmt(f),
             Self::Empty => f.write_str("Clipboard is empty"),
+            Self::NotUriList { id } => write!(f, "Clip {id:016x} is not a list of files"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GetImageError {
+    Status { source: tonic::Status, id: u64 },
+    NotFound { id: u64 },
+    NotAnImage { id: u64 },
+}
+
+impl fmt::Display for GetImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+            Self::NotFound { id } => write!(f, "Clip {id:016x} does not exist"),
+            Self::NotAnImage { id } => write!(f, "Clip {id:016x} is not an image"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportEntryError {
+    Status { source: tonic::Status, id: u64 },
+    NotFound { id: u64 },
+    Encode { source: clipcat_base::ClipEntryError, id: u64 },
+    Write { source: std::io::Error, path: PathBuf },
+}
+
+impl fmt::Display for ExportEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source, .. } => source.fmt(f),
+            Self::NotFound { id } => write!(f, "Clip {id:016x} does not exist"),
+            Self::Encode { source, id } => write!(f, "Could not encode clip {id:016x}: {source}"),
+            Self::Write { source, path } => {
+                write!(f, "Could not write file `{}`: {source}", path.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportArchiveError {
+    List {
+        source: ListClipError,
+    },
+    Get {
+        source: GetClipError,
+    },
+    NotFound {
+        id: u64,
+    },
+    /// The archive exceeds the limits of its format, see [`ArchiveFormat`].
+    ///
+    /// [`ArchiveFormat`]: crate::ArchiveFormat
+    TooLarge,
+}
+
+impl fmt::Display for ExportArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::List { source } => source.fmt(f),
+            Self::Get { source } => source.fmt(f),
+            Self::NotFound { id } => write!(f, "Clip {id:016x} does not exist"),
+            Self::TooLarge => f.write_str("Archive exceeds the limits of its format"),
+        }
+    }
+}
+
+/// Error of [`Manager::import_binary`](crate::Manager::import_binary).
+#[derive(Debug)]
+pub enum ImportBinaryError {
+    /// The data is not a backup written by
+    /// [`Manager::export_binary`](crate::Manager::export_binary).
+    InvalidFormat,
+    UnsupportedVersion {
+        version: u16,
+    },
+    /// The backup ends before its last clip.
+    Truncated,
+    Insert {
+        source: InsertClipError,
+    },
+    NotifyResync {
+        source: NotifyResyncError,
+    },
+}
+
+impl fmt::Display for ImportBinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => f.write_str("Data is not a Clipcat backup"),
+            Self::UnsupportedVersion { version } => {
+                write!(f, "Clipcat backup of version {version} is not supported")
+            }
+            Self::Truncated => f.write_str("Clipcat backup is truncated"),
+            Self::Insert { source } => source.fmt(f),
+            Self::NotifyResync { source } => source.fmt(f),
         }
     }
 }
@@ -64,6 +318,7 @@ impl fmt::Display for GetClipError {
 pub enum GetCurrentClipError {
     Status { source: tonic::Status, kind: ClipboardKind },
     Empty,
+    InvalidMime { value: String },
 }
 
 impl fmt::Display for GetCurrentClipError {
@@ -71,32 +326,127 @@ impl fmt::Display for GetCurrentClipError {
         match self {
             Self::Status { source, .. } => source.fmt(f),
             Self::Empty => f.write_str("Clipboard is empty"),
+            Self::InvalidMime { value } => write!(f, "Invalid mime type `{value}`"),
         }
     }
 }
 
 #[derive(Debug)]
 pub enum UpdateClipError {
-    Status { source: tonic::Status },
+    Status {
+        source: tonic::Status,
+    },
+    InvalidMime {
+        value: String,
+    },
+    VersionConflict {
+        id: u64,
+        expected_version: u64,
+    },
+    NotText {
+        id: u64,
+    },
+    /// The profile has as many sticky notes as the server allows.
+    TooManyStickyNotes {
+        limit: u64,
+    },
 }
 
 impl fmt::Display for UpdateClipError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Status { source } => source.fmt(f),
+            Self::InvalidMime { value } => write!(f, "Invalid mime type `{value}`"),
+            Self::VersionConflict { id, expected_version } => write!(
+                f,
+                "Clip with id {id:016x} is changed or removed since version {expected_version}"
+            ),
+            Self::NotText { id } => write!(f, "Clip {id:016x} is not a text clip"),
+            Self::TooManyStickyNotes { limit } => {
+                write!(f, "Profile has {limit} sticky notes already")
+            }
         }
     }
 }
 
 #[derive(Debug)]
 pub enum MarkClipError {
-    Status { source: tonic::Status, id: u64, kind: ClipboardKind },
+    Status {
+        source: tonic::Status,
+        id: u64,
+        kind: ClipboardKind,
+    },
+    Touch {
+        source: tonic::Status,
+        id: u64,
+    },
+    Protect {
+        source: tonic::Status,
+        id: u64,
+    },
+    Swap {
+        source: tonic::Status,
+        a: u64,
+        b: u64,
+    },
+    SetTransient {
+        source: tonic::Status,
+        kind: ClipboardKind,
+    },
+    Cycle {
+        source: tonic::Status,
+        kind: ClipboardKind,
+    },
+    ReplaceActive {
+        source: tonic::Status,
+        kind: ClipboardKind,
+    },
+    /// Storing the clip into the clipboard of `kind` failed, the clipboards
+    /// stored before it are restored.
+    StoreFailed {
+        source: tonic::Status,
+        id: u64,
+        kind: ClipboardKind,
+    },
+    UnsupportedConversion {
+        id: u64,
+        mime: mime::Mime,
+    },
+    KindDisabled {
+        kind: ClipboardKind,
+    },
+    /// The content of type `mime` can not be stored into the clipboard by the
+    /// server, like an image on a clipboard supporting only text.
+    UnsupportedMime {
+        mime: mime::Mime,
+    },
+    /// The server only reads the selections, see [`ServerInfo::read_only`].
+    ///
+    /// [`ServerInfo::read_only`]: clipcat_base::ServerInfo::read_only
+    ReadOnly,
 }
 
 impl fmt::Display for MarkClipError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Status { source, .. } => source.fmt(f),
+            Self::Status { source, .. }
+            | Self::Touch { source, .. }
+            | Self::Protect { source, .. }
+            | Self::Swap { source, .. }
+            | Self::SetTransient { source, .. }
+            | Self::Cycle { source, .. }
+            | Self::ReplaceActive { source, .. } => source.fmt(f),
+            Self::StoreFailed { id, kind, .. } => {
+                write!(f, "Clip with id {id:016x} can not be stored into {kind}")
+            }
+            Self::UnsupportedConversion { id, mime } => {
+                write!(f, "Clip with id {id:016x} can not be converted to {mime}")
+            }
+            Self::KindDisabled { kind } => write!(f, "Clipboard kind {kind} is not enabled"),
+            Self::UnsupportedMime { mime } => {
+                write!(f, "Content of type {mime} can not be stored into the clipboard")
+            }
+            Self::ReadOnly => f.write_str("Clipcat server is in read-only mode"),
         }
     }
 }
@@ -127,6 +477,19 @@ impl fmt::Display for BatchRemoveClipError {
     }
 }
 
+#[derive(Debug)]
+pub enum ApplyError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ClearClipError {
     Status { source: tonic::Status },
@@ -140,6 +503,19 @@ impl fmt::Display for ClearClipError {
     }
 }
 
+#[derive(Debug)]
+pub enum NotifyResyncError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for NotifyResyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum GetLengthError {
     Status { source: tonic::Status },
@@ -155,10 +531,216 @@ impl fmt::Display for GetLengthError {
 
 #[derive(Debug)]
 pub enum ListClipError {
-    Status { source: tonic::Status },
+    Status {
+        source: tonic::Status,
+    },
+    /// The request is cancelled by the token passed to
+    /// [`Manager::list_cancellable`].
+    ///
+    /// [`Manager::list_cancellable`]: crate::Manager::list_cancellable
+    Cancelled,
+    /// The snapshot passed to [`Manager::list_snapshot_range`] is closed or
+    /// expired, open another one.
+    ///
+    /// [`Manager::list_snapshot_range`]: crate::Manager::list_snapshot_range
+    SnapshotNotFound {
+        handle: u64,
+    },
 }
 
 impl fmt::Display for ListClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::Cancelled => f.write_str("Listing clips is cancelled"),
+            Self::SnapshotNotFound { handle } => {
+                write!(f, "Snapshot {handle} is closed or expired")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SearchClipError {
+    Status {
+        source: tonic::Status,
+    },
+    /// The request is cancelled by the token passed to
+    /// [`Manager::search_cancellable`].
+    ///
+    /// [`Manager::search_cancellable`]: crate::Manager::search_cancellable
+    Cancelled,
+}
+
+impl fmt::Display for SearchClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::Cancelled => f.write_str("Searching clips is cancelled"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GetStatsError {
+    Status {
+        source: tonic::Status,
+    },
+    /// The activity is requested in more buckets than the server allows.
+    TooManyBuckets {
+        limit: u64,
+    },
+}
+
+impl fmt::Display for GetStatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::TooManyBuckets { limit } => {
+                write!(f, "Activity is requested in more than {limit} buckets")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyStoreError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for VerifyStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportMetadataError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ExportMetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+/// Error of [`Manager::import_metadata`](crate::Manager::import_metadata).
+#[derive(Debug)]
+pub enum ImportMetadataError {
+    /// The data is not metadata written by
+    /// [`Manager::export_metadata`](crate::Manager::export_metadata).
+    InvalidFormat,
+    Status {
+        source: tonic::Status,
+    },
+}
+
+impl fmt::Display for ImportMetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => f.write_str("Data is not Clipcat metadata"),
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReorderError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for ReorderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+#[derive(Debug)]
+pub enum SetHistoryError {
+    Status { source: tonic::Status },
+    TestingDisabled { source: tonic::Status },
+}
+
+#[cfg(feature = "testing")]
+impl fmt::Display for SetHistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::TestingDisabled { .. } => {
+                f.write_str("History can be replaced only when the server runs in testing mode")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CompactStoreError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for CompactStoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetActiveProfileError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for SetActiveProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GetActiveProfileError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for GetActiveProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WatchClipError {
+    Status { source: tonic::Status },
+    ServerShutdown,
+}
+
+impl fmt::Display for WatchClipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+            Self::ServerShutdown => f.write_str("Clipcat server is shutting down"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DiffError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for DiffError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Status { source } => source.fmt(f),
@@ -218,6 +800,19 @@ impl fmt::Display for GetWatcherStateError {
     }
 }
 
+#[derive(Debug)]
+pub enum CaptureError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum GetSystemVersionError {
     Status { source: tonic::Status },
@@ -230,3 +825,76 @@ impl fmt::Display for GetSystemVersionError {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum GetServerInfoError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for GetServerInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GetDiagnosticsError {
+    Status { source: tonic::Status },
+}
+
+impl fmt::Display for GetDiagnosticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { source } => source.fmt(f),
+        }
+    }
+}
+
+/// Returns the code of the error detail attached to `status` by the server.
+///
+/// Older servers attach no detail, the code is derived from the gRPC status
+/// code then, which does not tell apart the kinds of invalid arguments.
+pub(crate) fn error_code(status: &tonic::Status) -> clipcat_proto::ErrorCode {
+    use clipcat_proto::ErrorCode;
+
+    clipcat_proto::ErrorDetail::from_status(status).map_or_else(
+        || match status.code() {
+            tonic::Code::InvalidArgument => ErrorCode::InvalidArgument,
+            tonic::Code::NotFound => ErrorCode::ClipNotFound,
+            tonic::Code::AlreadyExists => ErrorCode::IdTaken,
+            tonic::Code::Unimplemented => ErrorCode::KindDisabled,
+            tonic::Code::Aborted => ErrorCode::VersionConflict,
+            tonic::Code::OutOfRange => ErrorCode::OffsetOutOfRange,
+            tonic::Code::DataLoss => ErrorCode::WatcherLagged,
+            tonic::Code::Unavailable => ErrorCode::ShuttingDown,
+            tonic::Code::Unauthenticated => ErrorCode::Unauthenticated,
+            tonic::Code::Internal => ErrorCode::Internal,
+            _ => ErrorCode::Unknown,
+        },
+        |detail| detail.code(),
+    )
+}
+
+/// Error of parsing a [`ClipcatEndpoint`](crate::ClipcatEndpoint).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseEndpointError {
+    Invalid { value: String },
+    UnsupportedScheme { scheme: String },
+    TlsNotSupported { value: String },
+}
+
+impl fmt::Display for ParseEndpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid { value } => write!(f, "Invalid endpoint `{value}`"),
+            Self::UnsupportedScheme { scheme } => {
+                write!(f, "Unsupported endpoint scheme `{scheme}`, expected `http` or `unix`")
+            }
+            Self::TlsNotSupported { value } => {
+                write!(f, "Endpoint `{value}` requires TLS which is not supported")
+            }
+        }
+    }
+}
@@ -5,6 +5,8 @@ use tonic::{metadata::AsciiMetadataValue, Request, Status};
 #[derive(Clone, Debug, Default)]
 pub struct Interceptor {
     authorization_metadata_value: Arc<Option<AsciiMetadataValue>>,
+
+    operation_id_metadata_value: Option<AsciiMetadataValue>,
 }
 
 impl Interceptor {
@@ -18,7 +20,21 @@ impl Interceptor {
             None => None,
         };
 
-        Self { authorization_metadata_value: Arc::new(authorization_metadata_value) }
+        Self {
+            authorization_metadata_value: Arc::new(authorization_metadata_value),
+            operation_id_metadata_value: None,
+        }
+    }
+
+    /// Returns a clone of the interceptor tagging requests with
+    /// `operation_id`, an id which is not a valid metadata value is dropped.
+    pub fn with_operation_id(&self, operation_id: String) -> Self {
+        let operation_id_metadata_value = AsciiMetadataValue::try_from(operation_id)
+            .map_err(|err| {
+                tracing::warn!("Operation id is dropped, error: {err}");
+            })
+            .ok();
+        Self { operation_id_metadata_value, ..self.clone() }
     }
 }
 
@@ -27,6 +43,12 @@ impl tonic::service::Interceptor for Interceptor {
         if let Some(ref token) = self.authorization_metadata_value.as_ref() {
             drop(req.metadata_mut().insert("authorization", token.clone()));
         }
+        if let Some(ref operation_id) = self.operation_id_metadata_value {
+            drop(
+                req.metadata_mut()
+                    .insert(clipcat_base::GRPC_OPERATION_ID_KEY, operation_id.clone()),
+            );
+        }
         Ok(req)
     }
 }
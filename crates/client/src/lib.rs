@@ -1,26 +1,55 @@
+mod archive;
+mod backup;
+mod builder;
+mod caching;
+mod endpoint;
 pub mod error;
 mod interceptor;
 mod manager;
 mod system;
+mod watch_control;
 mod watcher;
 
-use std::fmt;
+use std::{fmt, sync::Arc, time::Duration};
 
-use snafu::ResultExt;
-use tokio::net::UnixStream;
+pub use tokio_util::sync::CancellationToken;
 
 use self::interceptor::Interceptor;
 pub use self::{
-    error::{Error, Result},
-    manager::Manager,
+    archive::ArchiveFormat,
+    builder::ClipEntryBuilder,
+    caching::CachingManager,
+    endpoint::ClipcatEndpoint,
+    error::{ConnectError, Error, ParseEndpointError, Result},
+    manager::{
+        ArchiveStream, ClipDataChunk, ClipDataStream, ClipboardEventStream, CurrentClipStream,
+        Manager, SearchMatch, SearchOptions, SnapshotHandle, TextClipStream, WatchOptions,
+    },
     system::System,
+    watch_control::{WatchControl, PAUSED_CHANGES_LIMIT},
     watcher::Watcher,
 };
 
+// the server information asked for on connecting is given up after it
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A client of Clipcat server.
+///
+/// Cloning a `Client` is cheap, all clones share the same connection to the
+/// server, the underlying [`tonic::transport::Channel`] is a handle of a shared
+/// connection and the access token is shared via [`std::sync::Arc`]. Hand a
+/// clone to each task instead of connecting again.
+///
+/// Call [`Client::close`] for an orderly shutdown.
 #[derive(Clone, Debug)]
 pub struct Client {
-    channel: tonic::transport::Channel,
+    // shared by the clones, replaced by `Client::reconnect`
+    channel: Arc<parking_lot::RwLock<tonic::transport::Channel>>,
+    endpoint: ClipcatEndpoint,
     interceptor: Interceptor,
+    closed: Arc<tokio::sync::watch::Sender<bool>>,
+    // shared by the clones, refreshed by `Client::reconnect`
+    server_features: Arc<parking_lot::RwLock<Arc<[String]>>>,
 }
 
 impl Client {
@@ -30,59 +59,450 @@ impl Client {
         A: fmt::Display + Send,
     {
         tracing::info!("Connect to server via endpoint `{grpc_endpoint}`");
-        let scheme = grpc_endpoint.scheme();
-        if scheme == Some(&http::uri::Scheme::HTTP) {
-            Self::connect_http(grpc_endpoint, access_token).await
-        } else {
-            Self::connect_local_socket(grpc_endpoint, access_token).await
-        }
+        Self::connect(ClipcatEndpoint::from(grpc_endpoint), access_token).await
     }
 
+    /// Connects to the server at `endpoint`, e.g. parsed from
+    /// `unix:///run/user/1000/clipcat/grpc.sock`.
+    ///
     /// # Errors
     ///
     /// This function will an error if the server is not connected.
-    // SAFETY: it will never panic because `grpc_endpoint` is a valid URL
+    // SAFETY: it will never panic because HTTP endpoints are valid URLs
     #[allow(clippy::missing_panics_doc)]
-    pub async fn connect_http<A>(grpc_endpoint: http::Uri, access_token: Option<A>) -> Result<Self>
+    pub async fn connect<A>(endpoint: ClipcatEndpoint, access_token: Option<A>) -> Result<Self>
     where
         A: fmt::Display + Send,
     {
         let interceptor = Interceptor::new(access_token);
-        let channel = tonic::transport::Endpoint::from_shared(grpc_endpoint.to_string())
-            .expect("`grpc_endpoint` is a valid URL; qed")
-            .connect()
+        let channel = endpoint.connect().await?;
+        let client = Self::from_channel(channel, endpoint, interceptor);
+        client.refresh_server_features().await;
+        Ok(client)
+    }
+
+    /// Connects to the server like [`Client::new`] and checks the access
+    /// token, giving up after `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error telling whether the server is not
+    /// running, the connection timed out, the access token is rejected or the
+    /// transport failed.
+    pub async fn try_connect<A>(
+        grpc_endpoint: http::Uri,
+        access_token: Option<A>,
+        timeout: Duration,
+    ) -> std::result::Result<Self, ConnectError>
+    where
+        A: fmt::Display + Send,
+    {
+        let endpoint = grpc_endpoint.clone();
+        let connect = async {
+            let client = Self::new(grpc_endpoint, access_token)
+                .await
+                .map_err(|err| ConnectError::from_connect_error(endpoint.clone(), err))?;
+            // the access token is only checked by requests
+            match client.get_version().await {
+                Ok(_) => Ok(client),
+                Err(error::GetSystemVersionError::Status { source }) => {
+                    Err(ConnectError::from_status(endpoint.clone(), source))
+                }
+            }
+        };
+        tokio::time::timeout(timeout, connect)
             .await
-            .with_context(|_| error::ConnectToClipcatServerViaHttpSnafu {
-                endpoint: grpc_endpoint.clone(),
-            })?;
-        Ok(Self { channel, interceptor })
+            .unwrap_or_else(|_| Err(ConnectError::TimedOut { endpoint: endpoint.clone(), timeout }))
+    }
+
+    /// # Errors
+    ///
+    /// This function will an error if the server is not connected.
+    pub async fn connect_http<A>(grpc_endpoint: http::Uri, access_token: Option<A>) -> Result<Self>
+    where
+        A: fmt::Display + Send,
+    {
+        Self::connect(ClipcatEndpoint::Http(grpc_endpoint), access_token).await
     }
 
     /// # Errors
     ///
     /// This function will an error if the server is not connected.
-    // SAFETY: it will never panic because `uri` is a valid URL
-    #[allow(clippy::missing_panics_doc)]
     pub async fn connect_local_socket<A>(uri: http::Uri, access_token: Option<A>) -> Result<Self>
     where
         A: fmt::Display + Send,
     {
-        let interceptor = Interceptor::new(access_token);
-        let socket_path = uri.path();
-
-        // We will ignore this uri because uds do not use it
-        let channel = tonic::transport::Endpoint::try_from(format!("file://[::]/{socket_path}"))
-            .expect("`uri` is a valid URL; qed")
-            .connect_with_connector(tower::service_fn(|uri: tonic::transport::Uri| async move {
-                // Connect to a Uds socket
-                Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(
-                    UnixStream::connect(uri.path()).await?,
-                ))
-            }))
-            .await
-            .with_context(|_| error::ConnectToClipcatServerViaLocalSocketSnafu {
-                socket: socket_path,
-            })?;
-        Ok(Self { channel, interceptor })
+        Self::connect(ClipcatEndpoint::LocalSocket(uri.path().into()), access_token).await
+    }
+
+    fn from_channel(
+        channel: tonic::transport::Channel,
+        endpoint: ClipcatEndpoint,
+        interceptor: Interceptor,
+    ) -> Self {
+        let (closed, _) = tokio::sync::watch::channel(false);
+        Self {
+            channel: Arc::new(parking_lot::RwLock::new(channel)),
+            endpoint,
+            interceptor,
+            closed: Arc::new(closed),
+            server_features: Arc::new(parking_lot::RwLock::new(Arc::from(Vec::new()))),
+        }
+    }
+
+    /// Returns the features of the server, see [`clipcat_base::features`],
+    /// asked for on connecting and on [`Client::reconnect`], so checking them
+    /// makes no request.
+    ///
+    /// No feature is listed if the server is older than the feature list or
+    /// could not be asked, the methods available since the first version
+    /// still work then. Use [`System::supports`] to ask the server again.
+    #[must_use]
+    pub fn server_features(&self) -> Arc<[String]> { self.server_features.read().clone() }
+
+    // an older server without `GetServerInfo`, a rejected access token or a
+    // server not answering in time is taken as supporting no feature
+    async fn refresh_server_features(&self) {
+        let features = match tokio::time::timeout(HANDSHAKE_TIMEOUT, self.get_server_info()).await {
+            Ok(Ok(info)) => info.features,
+            Ok(Err(err)) => {
+                tracing::debug!("Could not get features of server, error: {err}");
+                Vec::new()
+            }
+            Err(_) => {
+                tracing::debug!("Could not get features of server in time");
+                Vec::new()
+            }
+        };
+        *self.server_features.write() = features.into();
+    }
+
+    /// Returns a clone of the client tagging each request with
+    /// `operation_id`, the server logs the handling of the requests in a span
+    /// with the id, so an operation of the client can be traced end to end in
+    /// the logs of the server.
+    ///
+    /// The clone shares the connection of the client, the client itself is
+    /// not changed. Requests without an operation id are given an id by the
+    /// server.
+    #[must_use]
+    pub fn with_operation_id(&self, operation_id: String) -> Self {
+        Self { interceptor: self.interceptor.with_operation_id(operation_id), ..self.clone() }
+    }
+
+    /// Connects to the endpoint of the client again and replaces the
+    /// connection of the client and of its clones, e.g. when the connection
+    /// goes bad.
+    ///
+    /// Requests in flight and watch streams keep the old connection until they
+    /// end, later requests use the new one. The old connection is kept if
+    /// connecting fails, otherwise [`Client::server_features`] is refreshed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error telling whether the server is not
+    /// running or the transport failed.
+    pub async fn reconnect(&self) -> std::result::Result<(), ConnectError> {
+        self.replace_channel(
+            self.endpoint
+                .connect()
+                .await
+                .map_err(|err| ConnectError::from_connect_error(self.endpoint.uri(), err))?,
+        );
+        self.refresh_server_features().await;
+        Ok(())
+    }
+
+    fn channel(&self) -> tonic::transport::Channel { self.channel.read().clone() }
+
+    fn replace_channel(&self, channel: tonic::transport::Channel) {
+        *self.channel.write() = channel;
+    }
+
+    /// Closes the client.
+    ///
+    /// The watch streams of this client and of its clones end, the connection
+    /// to the server is torn down once all clones are closed or dropped.
+    /// Dropping a client with active watch streams keeps the connection open
+    /// until the streams are dropped.
+    pub async fn close(self) {
+        let _ = self.closed.send_replace(true);
+        drop(self);
+        // let the background task of the connection observe the closed channel
+        tokio::task::yield_now().await;
+    }
+
+    /// Returns a future resolved when the client is closed.
+    fn closed(&self) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let mut closed = self.closed.subscribe();
+        async move {
+            // the sender is never dropped while the client is alive
+            drop(closed.wait_for(|closed| *closed).await);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use clipcat_proto as proto;
+    use tokio::{net::UnixStream, sync::Notify};
+
+    use crate::{
+        caching::Cache,
+        error::{error_code, ListClipError, SearchClipError},
+        interceptor::Interceptor,
+        CancellationToken, Client, ClipcatEndpoint, ConnectError, Manager, ParseEndpointError,
+        System,
+    };
+
+    #[tokio::test]
+    async fn test_try_connect_not_running() {
+        let endpoint: http::Uri = "/nonexistent/clipcat/grpc.sock".parse().unwrap();
+        let timeout = std::time::Duration::from_secs(5);
+        assert!(matches!(
+            Client::try_connect(endpoint, None::<String>, timeout).await,
+            Err(ConnectError::NotRunning { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_connection() {
+        let connection_count = Arc::new(AtomicUsize::new(0));
+        let connecting = Arc::new(Notify::new());
+        let client = Client::from_channel(
+            tonic::transport::Endpoint::from_static("http://[::]:50051")
+                .connect_with_connector_lazy(tower::service_fn({
+                    let connection_count = connection_count.clone();
+                    let connecting = connecting.clone();
+                    move |_uri: tonic::transport::Uri| {
+                        let _ = connection_count.fetch_add(1, Ordering::SeqCst);
+                        connecting.notify_one();
+                        // never connected, requests keep waiting for the shared
+                        // connection
+                        futures::future::pending::<
+                            std::io::Result<hyper_util::rt::TokioIo<UnixStream>>,
+                        >()
+                    }
+                })),
+            ClipcatEndpoint::Http(http::Uri::from_static("http://[::]:50051")),
+            Interceptor::new(Some("token")),
+        );
+
+        let requests = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.get_version().await })
+            })
+            .collect::<Vec<_>>();
+        connecting.notified().await;
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(connection_count.load(Ordering::SeqCst), 1);
+
+        for request in requests {
+            request.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_cancellable() {
+        let client =
+            Client::from_channel(
+                tonic::transport::Endpoint::from_static("http://[::]:50051")
+                    .connect_with_connector_lazy(tower::service_fn(
+                        |_uri: tonic::transport::Uri| {
+                            // never connected, the request waits until it is
+                            // cancelled
+                            futures::future::pending::<
+                                std::io::Result<hyper_util::rt::TokioIo<UnixStream>>,
+                            >()
+                        },
+                    )),
+                ClipcatEndpoint::Http(http::Uri::from_static("http://[::]:50051")),
+                Interceptor::new(None::<String>),
+            );
+
+        let token = CancellationToken::new();
+        let _canceller = tokio::spawn({
+            let token = token.clone();
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                token.cancel();
+            }
+        });
+        assert!(matches!(
+            client.list_cancellable(30, token.clone()).await,
+            Err(ListClipError::Cancelled)
+        ));
+
+        // a request with a cancelled token is not sent
+        let result = client.search_cancellable("clip", 30, token).await;
+        drop(client);
+        assert!(matches!(result, Err(SearchClipError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_not_running() {
+        let client = Client::from_channel(
+            tonic::transport::Endpoint::from_static("http://[::]:50051").connect_lazy(),
+            ClipcatEndpoint::LocalSocket("/nonexistent/clipcat/grpc.sock".into()),
+            Interceptor::new(None::<String>),
+        );
+        let result = client.reconnect().await;
+        drop(client);
+        assert!(matches!(result, Err(ConnectError::NotRunning { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_server_features_fallback() {
+        let client = Client::from_channel(
+            tonic::transport::Endpoint::from_static("http://[::]:50051")
+                .connect_with_connector_lazy(tower::service_fn(|_uri: tonic::transport::Uri| {
+                    futures::future::ready(Err::<hyper_util::rt::TokioIo<UnixStream>, _>(
+                        std::io::Error::from(std::io::ErrorKind::ConnectionRefused),
+                    ))
+                })),
+            ClipcatEndpoint::Http(http::Uri::from_static("http://[::]:50051")),
+            Interceptor::new(None::<String>),
+        );
+        *client.server_features.write() =
+            Arc::from(vec![clipcat_base::features::SEARCH.to_string()]);
+        let clone = client.clone();
+
+        // the server could not be asked, no feature is assumed
+        client.refresh_server_features().await;
+        drop(client);
+        let features = clone.server_features();
+        drop(clone);
+        assert!(features.is_empty());
+    }
+
+    #[test]
+    fn test_interceptor_operation_id() {
+        use tonic::service::Interceptor as _;
+
+        let interceptor = Interceptor::new(Some("token"));
+        let mut tagged = interceptor.with_operation_id("copy-1".to_string());
+        let request = tagged.call(tonic::Request::new(())).unwrap();
+        assert_eq!(request.metadata().get(clipcat_base::GRPC_OPERATION_ID_KEY).unwrap(), "copy-1");
+        assert!(request.metadata().get("authorization").is_some());
+
+        let request = interceptor.clone().call(tonic::Request::new(())).unwrap();
+        assert!(request.metadata().get(clipcat_base::GRPC_OPERATION_ID_KEY).is_none());
+
+        // an id which is not a valid metadata value is dropped
+        let mut invalid = interceptor.with_operation_id("copy\n1".to_string());
+        let request = invalid.call(tonic::Request::new(())).unwrap();
+        assert!(request.metadata().get(clipcat_base::GRPC_OPERATION_ID_KEY).is_none());
+    }
+
+    #[test]
+    fn test_error_code() {
+        let status = proto::ErrorDetail::new(proto::ErrorCode::IdTaken)
+            .with_id(1)
+            .into_status(tonic::Code::AlreadyExists, "Clip with id 1 already exists");
+        assert_eq!(error_code(&status), proto::ErrorCode::IdTaken);
+        assert_eq!(proto::ErrorDetail::from_status(&status).and_then(|detail| detail.id), Some(1));
+
+        let status = proto::ErrorDetail::new(proto::ErrorCode::InvalidKind)
+            .into_status(tonic::Code::InvalidArgument, "Unknown clipboard kind 3");
+        assert_eq!(error_code(&status), proto::ErrorCode::InvalidKind);
+
+        // older servers attach no detail
+        let status = tonic::Status::aborted("Clip is changed");
+        assert_eq!(proto::ErrorDetail::from_status(&status), None);
+        assert_eq!(error_code(&status), proto::ErrorCode::VersionConflict);
+    }
+
+    #[test]
+    fn test_cache() {
+        use std::time::{Duration, Instant};
+
+        use clipcat_base::{ClipEntry, ClipboardChange, ClipboardKind};
+
+        let mut cache = Cache::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+        let clips = ["a", "b", "c"]
+            .map(|text| ClipEntry::from_string(text, ClipboardKind::Clipboard))
+            .to_vec();
+        cache.insert_entry(cache.epoch(), clips[0].clone(), now);
+        cache.insert_entry(cache.epoch(), clips[1].clone(), now);
+        assert_eq!(cache.entry(clips[0].id(), now), Some(clips[0].clone()));
+        assert_eq!(cache.metadata(clips[0].id(), 5, now), Some(clips[0].metadata(Some(5))));
+
+        // the least recently used clip is dropped
+        cache.insert_entry(cache.epoch(), clips[2].clone(), now);
+        assert_eq!(cache.entry(clips[1].id(), now), None);
+        assert!(cache.entry(clips[0].id(), now).is_some());
+
+        // clips expire after the TTL
+        assert_eq!(cache.entry(clips[2].id(), now + Duration::from_secs(10)), None);
+
+        // a clip fetched before an invalidation is not cached
+        let epoch = cache.epoch();
+        cache.apply(&ClipboardChange::Removed(clips[0].id()));
+        assert_eq!(cache.entry(clips[0].id(), now), None);
+        cache.insert_entry(epoch, clips[0].clone(), now);
+        assert_eq!(cache.entry(clips[0].id(), now), None);
+
+        cache.insert_metadata(cache.epoch(), 5, clips[1].metadata(Some(5)), now);
+        assert_eq!(cache.metadata(clips[1].id(), 5, now), Some(clips[1].metadata(Some(5))));
+        assert_eq!(cache.metadata(clips[1].id(), 6, now), None);
+
+        // nothing is cached once the changes are not watched
+        cache.disable();
+        cache.insert_entry(cache.epoch(), clips[0].clone(), now);
+        assert_eq!(cache.entry(clips[0].id(), now), None);
+    }
+
+    #[test]
+    fn test_uri_list() {
+        use std::path::PathBuf;
+
+        use clipcat_base::utils::{paths_to_uri_list, uri_list_to_paths};
+
+        let paths = [PathBuf::from("/home/user/My Files/résumé #1.pdf"), PathBuf::from("/tmp/a%b")];
+        let list = paths_to_uri_list(&paths);
+        assert_eq!(
+            list,
+            "file:///home/user/My%20Files/r%C3%A9sum%C3%A9%20%231.pdf\r\nfile:///tmp/a%25b\r\n"
+        );
+        assert_eq!(uri_list_to_paths(&list), paths);
+
+        let list = "# comment\nhttps://example.com/\nfile://localhost/tmp/x\nfile://host/tmp/y\n";
+        assert_eq!(uri_list_to_paths(list), [PathBuf::from("/tmp/x")]);
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            "http://127.0.0.1:45045".parse(),
+            Ok(ClipcatEndpoint::Http(http::Uri::from_static("http://127.0.0.1:45045")))
+        );
+        for value in ["unix:///run/clipcat.sock", "unix:/run/clipcat.sock", "/run/clipcat.sock"] {
+            assert_eq!(value.parse(), Ok(ClipcatEndpoint::LocalSocket("/run/clipcat.sock".into())));
+        }
+        assert_eq!(
+            "https://localhost:45045".parse::<ClipcatEndpoint>(),
+            Err(ParseEndpointError::TlsNotSupported { value: "https://localhost:45045".into() })
+        );
+        assert_eq!(
+            "ftp://localhost".parse::<ClipcatEndpoint>(),
+            Err(ParseEndpointError::UnsupportedScheme { scheme: "ftp".into() })
+        );
+        assert_eq!(
+            "unix://".parse::<ClipcatEndpoint>(),
+            Err(ParseEndpointError::Invalid { value: "unix://".into() })
+        );
+        assert_eq!(
+            ClipcatEndpoint::LocalSocket("/run/clipcat.sock".into()).to_string(),
+            "unix:/run/clipcat.sock"
+        );
     }
 }
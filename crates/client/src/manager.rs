@@ -1,23 +1,458 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
 use async_trait::async_trait;
-use clipcat_base::{ClipEntry, ClipEntryMetadata, ClipboardKind};
+use bytes::Bytes;
+use clipcat_base::{
+    ClipEntry, ClipEntryFull, ClipEntryMetadata, ClipOp, ClipOpResult, ClipboardChange,
+    ClipboardEvent, ClipboardKind, CompactReport, CycleDirection, HistoryDelta, HistoryStats,
+    ImageFormat, ListCursor, MetadataImportReport, ResourceUsage, SemanticKind, SortKey, SortOrder,
+    StoreReport, TagMatch,
+};
 use clipcat_proto as proto;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use prost::Message;
+use tokio_util::sync::CancellationToken;
 use tonic::Request;
 
+#[cfg(feature = "testing")]
+use crate::error::SetHistoryError;
 use crate::{
+    archive::ArchiveWriter,
+    backup::{self, BackupReader},
     error::{
-        BatchRemoveClipError, ClearClipError, GetClipError, GetCurrentClipError, GetLengthError,
-        InsertClipError, ListClipError, MarkClipError, RemoveClipError, UpdateClipError,
+        error_code, ApplyError, BatchRemoveClipError, ClearClipError, CompactStoreError, DiffError,
+        ExportArchiveError, ExportEntryError, ExportMetadataError, GetActiveProfileError,
+        GetClipError, GetCurrentClipError, GetImageError, GetLengthError, GetStatsError,
+        ImportBinaryError, ImportMetadataError, InsertClipError, ListClipError, MarkClipError,
+        NotifyResyncError, RemoveClipError, ReorderError, SearchClipError, SetActiveProfileError,
+        UpdateClipError, VerifyStoreError, WatchClipError,
     },
-    Client,
+    watch_control::{self, WatchControl, PAUSED_CHANGES_LIMIT},
+    ArchiveFormat, Client, ClipEntryBuilder,
 };
 
+pub type ClipboardEventStream = BoxStream<'static, Result<ClipboardEvent, WatchClipError>>;
+
+pub type CurrentClipStream = BoxStream<'static, Result<Option<ClipEntry>, WatchClipError>>;
+
+pub type TextClipStream = BoxStream<'static, Result<String, WatchClipError>>;
+
+pub type ClipDataStream = BoxStream<'static, Result<ClipDataChunk, GetClipError>>;
+
+pub type ArchiveStream<'a> = BoxStream<'a, Result<Vec<u8>, ExportArchiveError>>;
+
+/// A chunk of the encoded content of a clip.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClipDataChunk {
+    /// Byte offset of `data` in the content.
+    pub offset: u64,
+
+    pub data: Vec<u8>,
+
+    /// Size of the whole content in bytes.
+    pub total_size: u64,
+}
+
+/// Options of [`Manager::sync_changes_with`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct WatchOptions {
+    /// Resume from the changes after this cursor.
+    pub since_cursor: Option<u64>,
+
+    /// Ask the server to send a [`ClipboardChange::Heartbeat`] event every
+    /// interval, the server uses at least one second.
+    ///
+    /// A consumer could treat a stream without heartbeats for a few intervals
+    /// as dead and reconnect.
+    ///
+    /// [`ClipboardChange::Heartbeat`]: clipcat_base::ClipboardChange::Heartbeat
+    pub heartbeat_interval: Option<Duration>,
+
+    /// Ask the server to send the metadata of the added and updated clips in
+    /// place of the clips, with previews of at most this many characters.
+    ///
+    /// The events are [`ClipboardChange::AddedMetadata`] and
+    /// [`ClipboardChange::UpdatedMetadata`] then, for consumers which do not
+    /// need the content of every clip, like large images. By default the
+    /// clips are sent with their content, so no [`Manager::get`] is needed to
+    /// render them.
+    ///
+    /// [`ClipboardChange::AddedMetadata`]: clipcat_base::ClipboardChange::AddedMetadata
+    /// [`ClipboardChange::UpdatedMetadata`]: clipcat_base::ClipboardChange::UpdatedMetadata
+    pub metadata_preview_length: Option<usize>,
+
+    /// Start the stream of [`Manager::sync_changes_pausable`] paused, like for
+    /// a hidden window, the other streams are never paused.
+    pub start_paused: bool,
+}
+
+/// Options of [`Manager::search_with`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchOptions {
+    /// Match the characters of the query in order instead of its words, like
+    /// `clpct` matching `clipcat`.
+    pub fuzzy: bool,
+}
+
+/// A clip found by [`Manager::search_with`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub metadata: ClipEntryMetadata,
+
+    /// How well the clip matches the query, at most 1 and higher is better.
+    ///
+    /// Fuzzy matches score higher if the characters are consecutive or start
+    /// words, other matches score higher the earlier the first word of the
+    /// query is found. Older servers do not compute scores, they are 0.
+    pub score: f32,
+}
+
+/// A point-in-time view of the history opened by [`Manager::open_snapshot`].
+///
+/// The server keeps the snapshot until it is closed by
+/// [`Manager::close_snapshot`] or it is not listed for a while.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SnapshotHandle {
+    handle: u64,
+
+    total: usize,
+}
+
+impl SnapshotHandle {
+    /// Returns the handle sent to the server.
+    #[inline]
+    #[must_use]
+    pub const fn handle(&self) -> u64 { self.handle }
+
+    /// Returns the count of clips in the snapshot.
+    #[inline]
+    #[must_use]
+    pub const fn total(&self) -> usize { self.total }
+}
+
 #[async_trait]
 pub trait Manager {
     async fn get(&self, id: u64) -> Result<ClipEntry, GetClipError>;
 
+    /// Gets the clips with `ids` with at most `concurrency` requests in
+    /// flight, the results are in the order of `ids`.
+    ///
+    /// Each clip is fetched with [`Manager::get`], a clip which can not be
+    /// fetched fails only its own result, like [`GetClipError::Empty`] for a
+    /// removed clip. A `concurrency` of 0 is treated as 1.
+    async fn get_all(&self, ids: &[u64], concurrency: usize) -> Vec<Result<ClipEntry, GetClipError>>
+    where
+        Self: Sync,
+    {
+        futures::stream::iter(ids.iter().copied())
+            .map(|id| self.get(id))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Gets the clip with its metadata computed by the server, like the
+    /// preview of `preview_length` characters, from the same snapshot of
+    /// history, for panes showing everything about one clip.
+    async fn get_full(&self, id: u64, preview_length: usize)
+        -> Result<ClipEntryFull, GetClipError>;
+
+    /// Gets the encoded content of the clip like [`ClipEntry::encoded`],
+    /// moved out of the response without copying it.
+    async fn get_bytes(&self, id: u64) -> Result<Bytes, GetClipError>;
+
+    /// Gets the clip with its text truncated to at most `max_bytes` bytes, and
+    /// whether it is truncated.
+    ///
+    /// The id of a truncated entry is computed from the truncated content, use
+    /// `id` to refer to the clip.
+    async fn get_prefix(&self, id: u64, max_bytes: u64) -> Result<(ClipEntry, bool), GetClipError>;
+
+    /// Streams the encoded content of the clip in chunks, which suits large
+    /// clips like images.
+    async fn get_stream(&self, id: u64) -> Result<ClipDataStream, GetClipError>;
+
+    /// Streams the encoded content of the clip starting at byte `offset`.
+    ///
+    /// Pass the number of bytes received so far to resume an interrupted
+    /// `get_stream`.
+    async fn get_stream_from(&self, id: u64, offset: u64) -> Result<ClipDataStream, GetClipError>;
+
+    /// Returns the image of the clip encoded in `format` by the server, like
+    /// [`ClipEntry::encoded`] in another format.
+    ///
+    /// Images are decoded when they are captured, so any image clip can be
+    /// encoded in any format. Non-image clips are rejected with
+    /// [`GetImageError::NotAnImage`].
+    async fn get_image_as(&self, id: u64, format: ImageFormat) -> Result<Vec<u8>, GetImageError>;
+
+    /// Writes the encoded content of the clip to `path` with the extension of
+    /// [`ClipEntry::file_extension`], like `clip.png` for an image at `clip`,
+    /// and returns the path of the file.
+    async fn export_entry(&self, id: u64, path: &Path) -> Result<PathBuf, ExportEntryError>;
+
+    /// Streams a tar or a zip archive of the clips with `ids`, each file is
+    /// named by the id of its clip in hex with the extension of
+    /// [`ClipEntryMetadata::file_extension`], like `00000000000000ff.png`.
+    ///
+    /// The clips are streamed one after another with
+    /// [`Manager::get_stream`], so the memory used does not grow with the size
+    /// of the archive. A missing clip fails the stream with
+    /// [`ExportArchiveError::NotFound`] before anything is yielded, private
+    /// clips are skipped, see [`ClipEntry::is_private`].
+    fn export_archive(&self, ids: &[u64], format: ArchiveFormat) -> ArchiveStream<'_>
+    where
+        Self: Sync,
+    {
+        enum State {
+            Start(Vec<u64>),
+            Next {
+                entries: std::vec::IntoIter<ClipEntryMetadata>,
+                writer: ArchiveWriter,
+                pending: Vec<u8>,
+            },
+            Data {
+                entries: std::vec::IntoIter<ClipEntryMetadata>,
+                writer: ArchiveWriter,
+                data: ClipDataStream,
+            },
+            Done,
+        }
+
+        futures::stream::try_unfold(State::Start(ids.to_vec()), move |mut state| async move {
+            loop {
+                state = match state {
+                    State::Start(ids) => {
+                        let mut metadata = self
+                            .batch_metadata(&ids, 0)
+                            .await
+                            .map_err(|source| ExportArchiveError::List { source })?;
+                        if let Some(&id) =
+                            ids.iter().find(|&&id| metadata.iter().all(|entry| entry.id != id))
+                        {
+                            return Err(ExportArchiveError::NotFound { id });
+                        }
+                        metadata.retain(|entry| !entry.private);
+                        State::Next {
+                            entries: metadata.into_iter(),
+                            writer: ArchiveWriter::new(format),
+                            pending: Vec::new(),
+                        }
+                    }
+                    State::Next { mut entries, mut writer, mut pending } => {
+                        let Some(entry) = entries.next() else {
+                            pending
+                                .extend(writer.finish().map_err(|_| ExportArchiveError::TooLarge)?);
+                            return Ok(Some((pending, State::Done)));
+                        };
+                        let mut data = self
+                            .get_stream(entry.id)
+                            .await
+                            .map_err(|source| ExportArchiveError::Get { source })?;
+                        let first = data
+                            .try_next()
+                            .await
+                            .map_err(|source| ExportArchiveError::Get { source })?;
+                        let name = format!("{:016x}.{}", entry.id, entry.file_extension());
+                        let size = first.as_ref().map_or(0, |chunk| chunk.total_size);
+                        pending.extend(
+                            writer
+                                .start_entry(&name, size, entry.timestamp)
+                                .map_err(|_| ExportArchiveError::TooLarge)?,
+                        );
+                        if let Some(chunk) = first {
+                            writer.write_data(&chunk.data);
+                            pending.extend(chunk.data);
+                        }
+                        return Ok(Some((pending, State::Data { entries, writer, data })));
+                    }
+                    State::Data { entries, mut writer, mut data } => {
+                        if let Some(chunk) = data
+                            .try_next()
+                            .await
+                            .map_err(|source| ExportArchiveError::Get { source })?
+                        {
+                            writer.write_data(&chunk.data);
+                            return Ok(Some((chunk.data, State::Data { entries, writer, data })));
+                        }
+                        let pending =
+                            writer.finish_entry().map_err(|_| ExportArchiveError::TooLarge)?;
+                        State::Next { entries, writer, pending }
+                    }
+                    State::Done => return Ok(None),
+                };
+            }
+        })
+        .boxed()
+    }
+
+    /// Streams a compact backup of the history, oldest first, which is
+    /// restored with [`Manager::import_binary`].
+    ///
+    /// The content of the clips is stored as is, with their kinds, tags and
+    /// attributes. Private clips are skipped, see [`ClipEntry::is_private`], a
+    /// clip removed during the export fails the stream.
+    fn export_binary(&self) -> ArchiveStream<'_>
+    where
+        Self: Sync,
+    {
+        enum State {
+            Start,
+            Next(std::vec::IntoIter<u64>),
+        }
+
+        futures::stream::try_unfold(State::Start, move |state| async move {
+            match state {
+                State::Start => {
+                    let metadata =
+                        self.list(0).await.map_err(|source| ExportArchiveError::List { source })?;
+                    let ids = metadata
+                        .into_iter()
+                        .rev()
+                        .filter(|entry| !entry.private)
+                        .map(|entry| entry.id)
+                        .collect::<Vec<_>>();
+                    let header = backup::encode_header(ids.len() as u64);
+                    Ok(Some((header, State::Next(ids.into_iter()))))
+                }
+                State::Next(mut ids) => {
+                    let Some(id) = ids.next() else {
+                        return Ok(None);
+                    };
+                    let clip =
+                        self.get(id).await.map_err(|source| ExportArchiveError::Get { source })?;
+                    Ok(Some((backup::encode_entry(clip), State::Next(ids))))
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// Inserts the clips of a backup written by [`Manager::export_binary`]
+    /// silently, oldest first, and asks watchers to list the history again.
+    /// Returns the number of clips inserted.
+    ///
+    /// The backup is checked while it is read, the clips before an invalid one
+    /// are kept.
+    async fn import_binary(&self, data: &[u8]) -> Result<usize, ImportBinaryError> {
+        let mut count = 0;
+        for entry in BackupReader::new(data)? {
+            let proto::ClipEntry { data, mime, kind, tags, attributes, .. } = entry?;
+            let mime = mime.parse().unwrap_or(mime::APPLICATION_OCTET_STREAM);
+            let kind = ClipboardKind::try_from(kind).unwrap_or_default();
+            let clip = attributes.into_iter().fold(
+                ClipEntryBuilder::from_bytes(data, mime).mode(kind).tags(tags).silent(),
+                |clip, (key, value)| clip.attribute(key, value),
+            );
+            let _id = self
+                .insert_built(clip)
+                .await
+                .map_err(|source| ImportBinaryError::Insert { source })?;
+            count += 1;
+        }
+        self.notify_resync().await.map_err(|source| ImportBinaryError::NotifyResync { source })?;
+        Ok(count)
+    }
+
     async fn get_current_clip(&self, kind: ClipboardKind)
         -> Result<ClipEntry, GetCurrentClipError>;
 
+    /// Returns the current clip of the first of `kinds` which has one, e.g.
+    /// the primary selection if the clipboard is empty.
+    ///
+    /// The kinds are checked by the server at once, returns
+    /// [`GetCurrentClipError::Empty`] only if none of them has a current clip.
+    async fn get_current_any(
+        &self,
+        kinds: &[ClipboardKind],
+    ) -> Result<ClipEntry, GetCurrentClipError>;
+
+    /// Returns the id of the newest clip whose content is the content of the
+    /// clipboard of `kind`, or `None` if the clipboard is empty or no clip
+    /// matches.
+    ///
+    /// Unlike [`Manager::get_current_clip`], the clipboard is read by the
+    /// server, so content set outside of clipcat is matched too.
+    async fn current_entry_id(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Option<u64>, GetCurrentClipError>;
+
+    /// Returns whether the content of the clip is the content of the
+    /// clipboard of `kind`, comparing their content hashes, `false` if the
+    /// clip does not exist or the clipboard is empty.
+    ///
+    /// The clipboard is read by the server like [`Manager::current_entry_id`],
+    /// check it before [`Manager::mark`] to skip marking a clip which is
+    /// already in the clipboard.
+    async fn is_current(&self, id: u64, kind: ClipboardKind) -> Result<bool, GetCurrentClipError>;
+
+    /// Returns the targets offered by the owner of the clipboard of `kind`,
+    /// like `UTF8_STRING` and `text/html`, for debugging pastes, whether the
+    /// selection is in history or not.
+    ///
+    /// The list is empty if the server does not know the targets, see
+    /// [`KindDiagnostics::targets_supported`].
+    ///
+    /// [`KindDiagnostics::targets_supported`]: clipcat_base::KindDiagnostics::targets_supported
+    async fn current_targets(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<String>, GetCurrentClipError>;
+
+    /// Returns every representation of the content of the clipboard of
+    /// `kind` which the server can read, like plain text and PNG, as pairs
+    /// of MIME type and data, whether the selection is in history or not.
+    ///
+    /// The list is empty if the clipboard is empty.
+    async fn get_current_all_formats(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<(mime::Mime, Vec<u8>)>, GetCurrentClipError>;
+
+    /// Returns the current clip of `kind` and clears the clipboard of `kind`
+    /// at once, for cutting the clip into another application, or `None` if
+    /// there is no current clip.
+    ///
+    /// The clip is kept in history.
+    async fn take_current(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, GetCurrentClipError>;
+
+    /// Returns the 0-based position of the clip in the history sorted newest
+    /// first, or `None` if the clip does not exist.
+    async fn rank(&self, id: u64) -> Result<Option<u64>, GetClipError>;
+
+    /// Returns the clip next to the clip with `id` in the history sorted
+    /// newest first, towards older clips for [`CycleDirection::Next`], e.g.
+    /// for navigating a preview with the keyboard.
+    ///
+    /// Returns `None` at the ends of history or if the clip does not exist.
+    async fn neighbor(
+        &self,
+        id: u64,
+        direction: CycleDirection,
+    ) -> Result<Option<ClipEntry>, GetClipError>;
+
+    /// Returns the `n`-th newest clip of `kind`, e.g. for pasting with a digit
+    /// key, or `None` if there are less than `n` clips.
+    ///
+    /// `n` is 1-based, the newest clip is the first one.
+    async fn nth_recent(
+        &self,
+        n: u64,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, GetCurrentClipError>;
+
+    /// Returns the newest clip of `kind`, or `None` if there is no clip of
+    /// `kind`, like [`Manager::nth_recent`] with `n` of 1.
+    async fn latest(&self, kind: ClipboardKind) -> Result<Option<ClipEntry>, GetCurrentClipError>;
+
     async fn update(
         &self,
         id: u64,
@@ -25,43 +460,809 @@ pub trait Manager {
         mime: mime::Mime,
     ) -> Result<(bool, u64), UpdateClipError>;
 
+    /// Updates the clip only if its version is still `expected_version`, see
+    /// [`ClipEntry::version`].
+    ///
+    /// The update is rejected with [`UpdateClipError::VersionConflict`] if the
+    /// clip is changed or removed since, the caller could fetch the clip again
+    /// and retry.
+    async fn update_if(
+        &self,
+        id: u64,
+        expected_version: u64,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<(bool, u64), UpdateClipError>;
+
+    /// Appends `data` to the text of the clip on the server, after `separator`
+    /// if any, to accumulate several copies into one clip without a race
+    /// between getting and updating it.
+    ///
+    /// Returns the new id of the clip like [`Manager::update`], appending to a
+    /// non-text clip is rejected with [`UpdateClipError::NotText`].
+    async fn append(
+        &self,
+        id: u64,
+        data: &[u8],
+        separator: Option<&[u8]>,
+    ) -> Result<(bool, u64), UpdateClipError>;
+
     async fn mark(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError>;
 
+    /// Marks the clip and inserts it into the peers of the server too, see
+    /// [`ClipEntryBuilder::propagate`].
+    async fn mark_and_propagate(&self, id: u64, kind: ClipboardKind)
+        -> Result<bool, MarkClipError>;
+
+    /// Marks the clip and stores it into the clipboard as `target_mime`,
+    /// e.g. `text/html` clips can be pasted as `text/plain`.
+    async fn mark_as(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        target_mime: mime::Mime,
+    ) -> Result<bool, MarkClipError>;
+
+    /// Marks the clip and removes it from history in one step, for secrets
+    /// which should be pasted only once.
+    ///
+    /// The clipboard keeps the content until it is overwritten, the server does
+    /// not capture it again.
+    async fn mark_and_remove(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError>;
+
+    /// Marks the clip in each of `kinds`, all or none of the clipboards are
+    /// changed.
+    ///
+    /// If storing into a clipboard fails, the server restores the clipboards
+    /// stored before it and the error names the failed kind, see
+    /// [`MarkClipError::StoreFailed`].
+    async fn mark_kinds(&self, id: u64, kinds: &[ClipboardKind]) -> Result<bool, MarkClipError>;
+
+    /// Marks the clip before or after the current clip of `kind` in history,
+    /// returns its id or `None` if there is no clip to move to, e.g. at the
+    /// oldest clip without `wrap`.
+    ///
+    /// The order of history is not changed, so repeated calls walk through
+    /// it. Without a current clip, cycling starts at the newest clip.
+    async fn cycle(
+        &self,
+        kind: ClipboardKind,
+        direction: CycleDirection,
+        wrap: bool,
+    ) -> Result<Option<u64>, MarkClipError>;
+
+    /// Bumps the timestamp of the clip, which is used for sorting, without
+    /// changing its content or marking it as the current clip.
+    ///
+    /// The clip is counted as used, like when it is marked, see
+    /// [`Manager::list_most_used`].
+    async fn touch(&self, id: u64) -> Result<bool, MarkClipError>;
+
+    /// Protects the clip from eviction for the capacity or the maximum age of
+    /// history until `until`, like "keep it for an hour", see
+    /// [`ClipEntry::protected_until`]. A time in the past removes the
+    /// protection. Returns `false` if the clip does not exist.
+    ///
+    /// The clip is still removed when it expires or on request.
+    async fn protect_until(&self, id: u64, until: SystemTime) -> Result<bool, MarkClipError>;
+
+    /// Sets the label shown in menus in place of the preview of the clip, like
+    /// "my SSH key", `None` removes the label. Returns `false` if the clip
+    /// does not exist.
+    ///
+    /// The content of the clip is not changed, see
+    /// [`ClipEntryMetadata::display_text`].
+    async fn set_label(&self, id: u64, label: Option<String>) -> Result<bool, UpdateClipError>;
+
+    /// Makes the text clip a sticky note, or a regular clip again if `sticky`
+    /// is `false`. Returns `false` if the clip does not exist.
+    ///
+    /// Sticky notes are listed first by every listing and search, they never
+    /// expire nor are evicted, and [`Manager::update`] edits them in place,
+    /// keeping their ids. A profile holds a few sticky notes, more are rejected
+    /// with [`UpdateClipError::TooManyStickyNotes`], and a non-text clip with
+    /// [`UpdateClipError::NotText`].
+    async fn set_sticky(&self, id: u64, sticky: bool) -> Result<bool, UpdateClipError>;
+
+    /// Makes the current clip of `kind` a sticky note like
+    /// [`Manager::set_sticky`], returns its id, or `None` if there is no
+    /// current clip.
+    ///
+    /// The server looks up the current clip and pins it under one lock, so a
+    /// clip captured in between is not pinned instead.
+    async fn pin_current(&self, kind: ClipboardKind) -> Result<Option<u64>, UpdateClipError>;
+
+    /// Sets the semantic kind of the clip, overriding the kind assigned by the
+    /// classifier of the server. Returns `false` if the clip does not exist.
+    async fn set_semantic_kind(&self, id: u64, kind: SemanticKind)
+        -> Result<bool, UpdateClipError>;
+
+    /// Exchanges the positions of two clips in history, returns `false` if
+    /// either clip does not exist.
+    ///
+    /// Positions are timestamps, so the swap also changes which clip is
+    /// evicted first.
+    async fn swap(&self, a: u64, b: u64) -> Result<bool, MarkClipError>;
+
+    /// Reorders the whole history by `key` and saves it, the clip with the
+    /// largest key becomes the newest, like [`SortKey::CreatedAt`] to restore
+    /// the order the clips were captured in after importing them.
+    ///
+    /// Unlike [`Manager::list_sorted`] the order stays, the timestamps of the
+    /// clips are rewritten, so it also changes which clip is evicted first.
+    /// The order is changed under the lock of the history and the history
+    /// file is replaced at once, it is either in the previous order or in the
+    /// new one if the server crashes.
+    async fn reorder(&self, key: SortKey) -> Result<(), ReorderError>;
+
+    /// Replaces the whole history with `entries` at once and saves it, their
+    /// ids, timestamps and the other fields are kept, so integration tests
+    /// start each test from a known state. Snippets are kept.
+    ///
+    /// Only servers running in testing mode accept it, the others fail with
+    /// [`SetHistoryError::TestingDisabled`].
+    #[cfg(feature = "testing")]
+    async fn set_history(&self, entries: Vec<ClipEntry>) -> Result<(), SetHistoryError>;
+
+    /// Waits until the clip is the current clip of `kind`, returns `false` if
+    /// it is not after `timeout`.
+    ///
+    /// A clip becomes current once the server captures it from the clipboard,
+    /// e.g. after [`Manager::mark`] the selection is owned and reported back
+    /// by the clipboard.
+    async fn wait_active(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        timeout: Duration,
+    ) -> Result<bool, MarkClipError>;
+
+    /// Stores the content into the clipboard without adding it to history.
+    ///
+    /// The server does not capture the content when it is reported by the
+    /// clipboard watcher, which suits secrets like one-time passwords.
+    async fn set_transient(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        kind: ClipboardKind,
+    ) -> Result<(), MarkClipError>;
+
+    /// Inserts the clip and makes it the current clip of `kind` in one server
+    /// operation, returns its id.
+    ///
+    /// The previous current clip stays in history unless it is evicted.
+    async fn replace_active(
+        &self,
+        kind: ClipboardKind,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<u64, MarkClipError>;
+
+    /// Inserts a clip, returns its id and the mime type the server stores it
+    /// with, e.g. images are stored as `image/png` whatever mime type they are
+    /// inserted with.
     async fn insert(
         &self,
         data: &[u8],
         mime: mime::Mime,
         clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError>;
+
+    /// Inserts a clip like [`Manager::insert`], `data` is moved into the
+    /// request without copying it.
+    async fn insert_bytes(
+        &self,
+        data: Bytes,
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError>;
+
+    /// Inserts a clip, returns its id and the ids of the clips removed from
+    /// the history by the insertion, e.g. the oldest clips exceeding the
+    /// capacity.
+    async fn insert_with_evicted(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, Vec<u64>), InsertClipError>;
+
+    /// Inserts a clip copied from another history, keeping its id.
+    ///
+    /// The clip is stored under `id` even if it is not the id of the content,
+    /// it fails only if a clip with `id` already exists.
+    async fn insert_with_id(
+        &self,
+        id: u64,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
     ) -> Result<u64, InsertClipError>;
 
+    /// Returns the id of a clip with the content of `data` with `false`, like
+    /// [`Manager::remove_by_content`] matches the content, otherwise inserts
+    /// the clip like [`Manager::insert`] and returns its id with `true`.
+    ///
+    /// The server looks up and inserts the clip at once, so calling it again
+    /// with the same content never inserts a second clip.
+    async fn get_or_insert(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, bool), InsertClipError>;
+
+    /// Inserts a copy of the content of the clip with `id` as the newest clip
+    /// and returns the id of the copy, the original clip is kept unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertClipError::NotFound`] if there is no clip with `id`.
+    async fn duplicate(&self, id: u64) -> Result<u64, InsertClipError>;
+
+    /// Inserts a clip built with [`ClipEntryBuilder`], with its tags and
+    /// time to live.
+    async fn insert_built(&self, clip: ClipEntryBuilder) -> Result<u64, InsertClipError>;
+
     async fn insert_clipboard(
         &self,
         data: &[u8],
         mime: mime::Mime,
-    ) -> Result<u64, InsertClipError> {
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
         self.insert(data, mime, ClipboardKind::Clipboard).await
     }
 
-    async fn insert_primary(&self, data: &[u8], mime: mime::Mime) -> Result<u64, InsertClipError> {
+    async fn insert_primary(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
         self.insert(data, mime, ClipboardKind::Primary).await
     }
 
+    /// Inserts `paths` as a `text/uri-list` of `file` URIs, the format file
+    /// managers copy files in.
+    async fn insert_uris(
+        &self,
+        paths: &[PathBuf],
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
+        let list = clipcat_base::utils::paths_to_uri_list(paths);
+        self.insert(list.as_bytes(), clipcat_base::utils::uri_list_mime(), clipboard_kind).await
+    }
+
+    /// Returns the paths of the files of a `text/uri-list` clip.
+    async fn get_uris(&self, id: u64) -> Result<Vec<PathBuf>, GetClipError> {
+        let clip = self.get(id).await?;
+        if !clipcat_base::utils::is_uri_list(&clip.mime()) {
+            return Err(GetClipError::NotUriList { id });
+        }
+        Ok(clipcat_base::utils::uri_list_to_paths(&clip.as_utf8_string()))
+    }
+
     async fn length(&self) -> Result<usize, GetLengthError>;
 
+    /// Lists the metadata of all clips, newest first.
+    ///
+    /// The list is sorted by the server, it is sorted on the client only for
+    /// servers which do not sort it.
     async fn list(&self, preview_length: usize) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
 
+    /// Lists the metadata of all clips in `order`, like
+    /// [`SortOrder::SnippetsFirst`] to show the snippets at the top of a menu.
+    ///
+    /// Servers which do not know the order list the clips newest first.
+    async fn list_sorted(
+        &self,
+        preview_length: usize,
+        order: SortOrder,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
+
+    /// Lists the metadata of all clips like [`Manager::list`], and returns
+    /// the number of clips skipped because their content can not be read.
+    ///
+    /// The ids of the skipped clips are reported by [`Manager::verify_store`],
+    /// which drops them if `repair` is set. Older servers always report no
+    /// skipped clip.
+    async fn list_with_skipped(
+        &self,
+        preview_length: usize,
+    ) -> Result<(Vec<ClipEntryMetadata>, usize), ListClipError>;
+
+    /// Lists the metadata of all clips like [`Manager::list`] until `token`
+    /// is cancelled.
+    ///
+    /// Cancelling `token` drops the in-flight request, which resets its HTTP/2
+    /// stream so the server drops its handler too, the server stops at its
+    /// next await point like waiting for the history lock but a listing
+    /// already being built is finished and discarded. Returns
+    /// [`ListClipError::Cancelled`] if `token` is cancelled before the
+    /// response is received, including if it is cancelled already.
+    ///
+    /// Dropping the returned future cancels the request the same way.
+    async fn list_cancellable(
+        &self,
+        preview_length: usize,
+        token: CancellationToken,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
+
+    /// Lists at most `limit` clips from `offset`, newest first, with the total
+    /// count of clips.
+    async fn list_range(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), ListClipError>;
+
+    /// Opens a snapshot of the history, the pages listed from it with
+    /// [`Manager::list_snapshot_range`] do not drift while clips are inserted
+    /// or removed, e.g. for infinite scrolling.
+    ///
+    /// The server keeps a copy of the clips, close the snapshot with
+    /// [`Manager::close_snapshot`] once the pages are listed. A snapshot not
+    /// listed for a while is closed by the server.
+    async fn open_snapshot(&self) -> Result<SnapshotHandle, ListClipError>;
+
+    /// Lists at most `limit` clips from `offset` of the snapshot, newest
+    /// first, with the count of its clips, like [`Manager::list_range`] at the
+    /// time the snapshot was opened.
+    ///
+    /// Returns [`ListClipError::SnapshotNotFound`] if the snapshot is closed
+    /// or expired.
+    async fn list_snapshot_range(
+        &self,
+        snapshot: &SnapshotHandle,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), ListClipError>;
+
+    /// Closes the snapshot and frees it on the server, returns `false` if it
+    /// is closed or expired already.
+    async fn close_snapshot(&self, snapshot: SnapshotHandle) -> Result<bool, ListClipError>;
+
+    /// Lists at most `limit` clips which were marked or touched, the most used
+    /// first, see [`ClipEntry::use_count`].
+    async fn list_most_used(&self, limit: usize) -> Result<Vec<ClipEntry>, ListClipError>;
+
+    /// Lists at most `limit` clips after `cursor`, newest first, with the
+    /// cursor of the next page, `None` after the last page.
+    ///
+    /// Pass `None` for the first page. Unlike [`Manager::list_range`], clips
+    /// are not skipped or repeated if clips are inserted or removed between
+    /// pages, the cursor is the sort key of the last clip of a page.
+    async fn list_after(
+        &self,
+        cursor: Option<ListCursor>,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, Option<ListCursor>), ListClipError>;
+
+    /// Lists the clips added or updated since `since`, newest first.
+    ///
+    /// Removed clips are not listed, see [`Manager::sync_changes`] to track
+    /// removals.
+    async fn list_since(&self, since: SystemTime) -> Result<Vec<ClipEntry>, ListClipError>;
+
+    /// Lists the clips tagged with all or any of `tags`, newest first, all
+    /// clips are listed if `tags` is empty.
+    async fn list_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+    ) -> Result<Vec<ClipEntry>, ListClipError>;
+
+    /// Lists the clips captured from windows of `class`, like `firefox`,
+    /// newest first, see [`ClipEntry::source_window`].
+    ///
+    /// The class is matched ignoring ASCII case, clips whose source window is
+    /// not known are never listed.
+    async fn list_by_source_class(&self, class: &str) -> Result<Vec<ClipEntry>, ListClipError>;
+
+    /// Lists the clips of the semantic kind, newest first, see
+    /// [`ClipEntry::semantic_kind`].
+    ///
+    /// Clips which are not classified, like images, are never listed.
+    async fn list_by_semantic_kind(
+        &self,
+        kind: SemanticKind,
+    ) -> Result<Vec<ClipEntry>, ListClipError>;
+
+    /// Lists the clips whose size in bytes is within `min` and `max`, both
+    /// inclusive, newest first, e.g. to find the large clips to remove with
+    /// [`Manager::batch_remove`].
+    ///
+    /// A missing bound is not checked, all clips are listed without bounds.
+    async fn list_by_size(
+        &self,
+        min: Option<u64>,
+        max: Option<u64>,
+    ) -> Result<Vec<ClipEntry>, ListClipError>;
+
+    /// Lists the clips of each clipboard kind, newest first, from one snapshot
+    /// of the history.
+    ///
+    /// Every clipboard kind is present, kinds without clips map to no clips.
+    async fn list_grouped(&self) -> Result<HashMap<ClipboardKind, Vec<ClipEntry>>, ListClipError>;
+
+    /// Lists the clips of each clipboard kind like [`Manager::list_grouped`],
+    /// each group in `order`.
+    async fn list_grouped_sorted(
+        &self,
+        order: SortOrder,
+    ) -> Result<HashMap<ClipboardKind, Vec<ClipEntry>>, ListClipError>;
+
+    /// Returns the ids of the clips whose content has `hash`, newest first,
+    /// e.g. to find duplicates kept before enabling global deduplication.
+    ///
+    /// `hash` is the SHA-256 digest in hex like [`ClipEntry::content_hash`],
+    /// so it can be computed on the client, other hashes are rejected as an
+    /// invalid argument.
+    async fn entries_with_content_hash(&self, hash: &str) -> Result<Vec<u64>, ListClipError>;
+
+    /// Lists only the ids and the timestamps of all clips, newest first.
+    async fn list_ids(&self) -> Result<Vec<(u64, SystemTime)>, ListClipError>;
+
+    /// Returns the metadata of the clips with `ids` in the same order, missing
+    /// clips are omitted.
+    async fn batch_metadata(
+        &self,
+        ids: &[u64],
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
+
+    /// Returns the ids and the etags of the clips with `ids` in the same
+    /// order, missing clips are omitted.
+    ///
+    /// A cached clip is stale if its etag, see [`ClipEntry::etag`], differs.
+    async fn batch_etags(&self, ids: &[u64]) -> Result<Vec<(u64, u64)>, ListClipError>;
+
+    /// Walks through all clips, newest first, fetching `page_size` clips at a
+    /// time with [`Manager::list_range`].
+    ///
+    /// The walk is not a snapshot of the history. When the count of clips
+    /// changes between two pages, the offset is shifted by the difference
+    /// because new clips are inserted at the front, so clips inserted during
+    /// the walk are not yielded. A clip is never yielded twice, but a clip
+    /// could be missed if clips are removed or marked while walking.
+    fn iter_all(&self, page_size: usize) -> BoxStream<'_, Result<ClipEntry, ListClipError>>
+    where
+        Self: Sync,
+    {
+        struct State {
+            offset: usize,
+            total: Option<usize>,
+            seen: HashSet<u64>,
+            done: bool,
+        }
+
+        let page_size = page_size.max(1);
+        let state = State { offset: 0, total: None, seen: HashSet::new(), done: false };
+        futures::stream::try_unfold(state, move |mut state| async move {
+            if state.done {
+                return Ok(None);
+            }
+
+            let (entries, total) = loop {
+                let (entries, total) = self.list_range(state.offset, page_size).await?;
+                match state.total.replace(total) {
+                    Some(previous) if previous != total => {
+                        state.offset = (state.offset + total).saturating_sub(previous);
+                    }
+                    _ => break (entries, total),
+                }
+            };
+
+            state.offset += entries.len();
+            state.done = entries.len() < page_size || state.offset >= total;
+            let page = entries
+                .into_iter()
+                .filter(|entry| state.seen.insert(entry.id()))
+                .map(Ok)
+                .collect::<Vec<_>>();
+            Ok(Some((futures::stream::iter(page), state)))
+        })
+        .try_flatten()
+        .boxed()
+    }
+
     async fn remove(&self, id: u64) -> Result<bool, RemoveClipError>;
 
     async fn batch_remove(&self, ids: &[u64]) -> Result<Vec<u64>, BatchRemoveClipError>;
 
+    /// Applies `ops` in order as one transaction, returns the result of each
+    /// operation.
+    ///
+    /// No other request is served between the operations, so e.g. removing
+    /// several clips and marking a survivor does not flicker the clipboard of
+    /// other clients. An operation failing does not stop the following ones.
+    /// Watchers still receive a change for each operation.
+    async fn apply(&self, ops: &[ClipOp]) -> Result<Vec<ClipOpResult>, ApplyError>;
+
+    /// Removes the clips with the same content and returns their ids.
+    ///
+    /// With `dry_run`, returns the ids without removing the clips, e.g. for
+    /// asking for confirmation. Older servers ignore `dry_run` and remove the
+    /// clips.
+    async fn remove_by_content(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        dry_run: bool,
+    ) -> Result<Vec<u64>, BatchRemoveClipError>;
+
+    /// Removes the clips beyond the newest `max_count` ones and returns their
+    /// ids, only the clips of `kind` are counted and removed if it is set.
+    ///
+    /// Snippets are kept. Unlike the capacity of history, the trim is applied
+    /// once. With `dry_run`, returns the ids without removing the clips. Older
+    /// servers ignore `dry_run` and remove the clips.
+    async fn trim_to(
+        &self,
+        max_count: u64,
+        kind: Option<ClipboardKind>,
+        dry_run: bool,
+    ) -> Result<Vec<u64>, BatchRemoveClipError>;
+
     async fn clear(&self) -> Result<(), ClearClipError>;
+
+    /// Clears the history and returns the removed clips, newest first, like
+    /// [`Manager::list`] and [`Manager::clear`] at once.
+    ///
+    /// The server does both under one lock, so no clip inserted in between
+    /// is dropped without being returned. Snippets are removed too unless
+    /// `keep_snippets`, sticky notes are kept if `keep_sticky`.
+    async fn drain(
+        &self,
+        keep_snippets: bool,
+        keep_sticky: bool,
+    ) -> Result<Vec<ClipEntry>, ClearClipError>;
+
+    /// Tells watchers to list the history again, after silent inserts with
+    /// [`ClipEntryBuilder::silent`].
+    async fn notify_resync(&self) -> Result<(), NotifyResyncError>;
+
+    /// Lists the clips of `profile`, `None` is the default profile.
+    async fn list_profile(
+        &self,
+        profile: Option<&str>,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError>;
+
+    /// Clears the clips of `profile`, `None` is the default profile.
+    async fn clear_profile(&self, profile: Option<&str>) -> Result<(), ClearClipError>;
+
+    /// Switches the history to `profile`, `None` is the default profile.
+    ///
+    /// Each profile has its own history, new clips are inserted into the
+    /// active profile. Snippets are shared by all profiles.
+    async fn set_active_profile(&self, profile: Option<&str>) -> Result<(), SetActiveProfileError>;
+
+    async fn get_active_profile(&self) -> Result<Option<String>, GetActiveProfileError>;
+
+    /// Searches clips containing all words of `query`, newest first.
+    ///
+    /// Words are matched case-insensitively.
+    async fn search(
+        &self,
+        query: &str,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, SearchClipError>;
+
+    /// Searches clips like [`Manager::search`] until `token` is cancelled.
+    ///
+    /// The request is cancelled like [`Manager::list_cancellable`], returns
+    /// [`SearchClipError::Cancelled`] if `token` is cancelled before the
+    /// response is received.
+    async fn search_cancellable(
+        &self,
+        query: &str,
+        preview_length: usize,
+        token: CancellationToken,
+    ) -> Result<Vec<ClipEntryMetadata>, SearchClipError>;
+
+    /// Searches clips like [`Manager::search`] with `options`, newest first,
+    /// with the score of each clip.
+    async fn search_with(
+        &self,
+        query: &str,
+        preview_length: usize,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchMatch>, SearchClipError>;
+
+    /// Returns the newest clip matching `query` with `options`, or `None` if
+    /// no clip matches.
+    ///
+    /// The server stops at the first match, which suits autofill better than
+    /// taking the first result of [`Manager::search_with`].
+    async fn first_matching(
+        &self,
+        query: &str,
+        options: SearchOptions,
+    ) -> Result<Option<ClipEntry>, SearchClipError>;
+
+    async fn stats(&self) -> Result<HistoryStats, GetStatsError>;
+
+    /// Returns the memory held by the history of the server and the number of
+    /// clips in it.
+    async fn resource_usage(&self) -> Result<ResourceUsage, GetStatsError>;
+
+    /// Returns the distinct mime types of the clips in history with the number
+    /// of clips of each, the most common first, like for a filter of content
+    /// types.
+    async fn mime_types(&self) -> Result<Vec<(String, u64)>, GetStatsError>;
+
+    /// Returns the id and the content hash of each clip in history ordered by
+    /// id, without transferring the content.
+    ///
+    /// The hashes match [`ClipEntry::content_hash`], two peers compare their
+    /// manifests to fetch only the clips which differ.
+    async fn content_manifest(&self) -> Result<Vec<(u64, String)>, GetStatsError>;
+
+    /// Exports the tags, attributes, labels, protections, sticky notes and
+    /// semantic kinds of the clips without their content, keyed by the
+    /// content hashes of the clips, which is applied to another history with
+    /// [`Manager::import_metadata`].
+    ///
+    /// Snippets, private clips and the clips without such metadata are
+    /// skipped.
+    async fn export_metadata(&self) -> Result<Vec<u8>, ExportMetadataError>;
+
+    /// Applies the metadata written by [`Manager::export_metadata`] to the
+    /// clips in history with the same content, like after rebuilding the
+    /// history from a fresh capture. The tags and attributes are added to the
+    /// ones of the clips, the other metadata replaces theirs if it is set.
+    ///
+    /// The records without a clip with the same content are skipped and
+    /// reported in [`MetadataImportReport::unmatched`].
+    async fn import_metadata(
+        &self,
+        data: &[u8],
+    ) -> Result<MetadataImportReport, ImportMetadataError>;
+
+    /// Checks the history file of the server for entries which can not be
+    /// loaded, like after a crash, and drops them if `repair` is set.
+    ///
+    /// Corrupt entries are already skipped when the server loads the history
+    /// and the history file is rewritten from memory on shutdown, repairing
+    /// keeps the file loadable if the server crashes again. Check
+    /// [`StoreReport::is_healthy`] to decide whether to restore a backup
+    /// before repairing.
+    async fn verify_store(&self, repair: bool) -> Result<StoreReport, VerifyStoreError>;
+
+    /// Rewrites the history file of the server with only the clips in
+    /// history, dropping the entries of removed clips and the older copies of
+    /// clips captured again, and reports the bytes reclaimed.
+    ///
+    /// The server keeps serving requests, clips captured meanwhile are
+    /// persisted after the compaction. The compacted file replaces the history
+    /// file at once, so it is intact if the server crashes in between.
+    async fn compact_store(&self) -> Result<CompactReport, CompactStoreError>;
+
+    /// Counts the clips copied in each `bucket` since `since`, oldest bucket
+    /// first, the buckets without clips are counted as 0.
+    ///
+    /// A clip is counted once in the bucket of its latest copy, since the
+    /// history keeps one clip per content.
+    async fn activity(
+        &self,
+        bucket: Duration,
+        since: SystemTime,
+    ) -> Result<Vec<(SystemTime, u64)>, GetStatsError>;
+
+    /// Returns the timestamps of the oldest and the newest clips, only the
+    /// clips of `kind` are counted if it is set, or `None` if there are no
+    /// such clips. Snippets are not counted.
+    ///
+    /// The clips are not transferred, pair it with [`Manager::list_since`] to
+    /// list a range of history.
+    async fn time_bounds(
+        &self,
+        kind: Option<ClipboardKind>,
+    ) -> Result<Option<(SystemTime, SystemTime)>, GetStatsError>;
+
+    /// Streams the changes of the clipboard history.
+    ///
+    /// With `since_cursor`, the changes after it which are still retained by
+    /// the server are sent first.
+    ///
+    /// The stream yields [`WatchClipError::ServerShutdown`] and ends when the
+    /// server is shutting down, the consumer could reconnect later and resume
+    /// from the last received cursor.
+    async fn sync_changes(
+        &self,
+        since_cursor: Option<u64>,
+    ) -> Result<ClipboardEventStream, WatchClipError>;
+
+    /// Streams the changes of the clipboard history like
+    /// [`Manager::sync_changes`], with `options`.
+    async fn sync_changes_with(
+        &self,
+        options: WatchOptions,
+    ) -> Result<ClipboardEventStream, WatchClipError>;
+
+    /// Streams the changes of the clipboard history like
+    /// [`Manager::sync_changes_with`], the stream is paused and resumed with
+    /// the returned [`WatchControl`].
+    ///
+    /// The subscription is kept while paused, the changes are kept and yielded
+    /// on resume. Beyond [`PAUSED_CHANGES_LIMIT`] changes, they are replaced
+    /// by a [`ClipboardChange::Resync`] with the cursor of the last change, so
+    /// the consumer lists the history again.
+    ///
+    /// [`PAUSED_CHANGES_LIMIT`]: crate::PAUSED_CHANGES_LIMIT
+    /// [`ClipboardChange::Resync`]: clipcat_base::ClipboardChange::Resync
+    async fn sync_changes_pausable(
+        &self,
+        options: WatchOptions,
+    ) -> Result<(ClipboardEventStream, WatchControl), WatchClipError> {
+        let changes = self.sync_changes_with(options).await?;
+        let (control, paused) = WatchControl::new(options.start_paused);
+        Ok((watch_control::pausable(changes, paused, PAUSED_CHANGES_LIMIT), control))
+    }
+
+    /// Returns the net changes of the clipboard history after `cursor`, the
+    /// polling counterpart of [`Manager::sync_changes`].
+    ///
+    /// If the server no longer retains the changes,
+    /// [`HistoryDelta::resync_required`] is set, list the history again and
+    /// continue from [`HistoryDelta::new_cursor`].
+    async fn diff_since(&self, cursor: u64) -> Result<HistoryDelta, DiffError>;
+
+    /// Waits for the next change of the clipboard history after
+    /// `since_cursor`, `None` if nothing is changed within `timeout`.
+    ///
+    /// Without `since_cursor`, only the changes made after the call are
+    /// returned.
+    async fn wait_for_change(
+        &self,
+        since_cursor: Option<u64>,
+        timeout: Duration,
+    ) -> Result<Option<ClipboardEvent>, WatchClipError> {
+        let mut changes = self.sync_changes(since_cursor).await?;
+        match tokio::time::timeout(timeout, changes.next()).await {
+            Ok(Some(event)) => event.map(Some),
+            Ok(None) | Err(_) => Ok(None),
+        }
+    }
+
+    /// Streams the text of each clip added to the history after the call,
+    /// like a log of the copied text. Images and other binary clips are
+    /// skipped, the other changes of the history are not sent.
+    ///
+    /// The stream yields [`WatchClipError::ServerShutdown`] and ends when the
+    /// server is shutting down.
+    async fn tail_text(&self) -> Result<TextClipStream, WatchClipError> {
+        let changes = self.sync_changes(None).await?;
+        Ok(changes
+            .filter_map(|event| async move {
+                match event {
+                    Ok(ClipboardEvent { change: ClipboardChange::Added(entry), .. })
+                        if entry.is_text() =>
+                    {
+                        Some(Ok(entry.as_utf8_string()))
+                    }
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                }
+            })
+            .boxed())
+    }
+
+    /// Streams the current clip of `kind`, first the current one and then
+    /// each time it is changed, `None` if there is no current clip.
+    ///
+    /// Unlike [`Manager::sync_changes`], other changes of the history are not
+    /// sent. The stream yields [`WatchClipError::ServerShutdown`] and ends
+    /// when the server is shutting down.
+    async fn watch_current(&self, kind: ClipboardKind)
+        -> Result<CurrentClipStream, WatchClipError>;
 }
 
 #[async_trait]
 impl Manager for Client {
     async fn get(&self, id: u64) -> Result<ClipEntry, GetClipError> {
-        proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-            .get(Request::new(proto::GetRequest { id }))
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get(Request::new(proto::GetRequest { id, max_bytes: None }))
             .await
             .map_err(|source| GetClipError::Status { source, id })?
             .into_inner()
@@ -69,97 +1270,1044 @@ impl Manager for Client {
             .map_or_else(|| Err(GetClipError::Empty), |data| Ok(data.into()))
     }
 
-    async fn get_current_clip(
+    async fn get_full(
         &self,
-        kind: ClipboardKind,
-    ) -> Result<ClipEntry, GetCurrentClipError> {
-        proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-            .get_current_clip(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+        id: u64,
+        preview_length: usize,
+    ) -> Result<ClipEntryFull, GetClipError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_full(Request::new(proto::GetFullRequest {
+                id,
+                preview_length: u64::try_from(preview_length).unwrap_or(30),
+            }))
             .await
-            .map_err(|source| GetCurrentClipError::Status { source, kind })?
+            .map_err(|source| GetClipError::Status { source, id })?
             .into_inner()
             .data
-            .map_or_else(|| Err(GetCurrentClipError::Empty), |data| Ok(data.into()))
+            .map_or_else(|| Err(GetClipError::Empty), |data| Ok(data.into()))
     }
 
-    async fn update(
-        &self,
-        id: u64,
-        data: &[u8],
-        mime: mime::Mime,
-    ) -> Result<(bool, u64), UpdateClipError> {
-        let proto::UpdateResponse { ok, new_id } =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .update(Request::new(proto::UpdateRequest {
-                    id,
-                    data: data.to_owned(),
-                    mime: mime.essence_str().to_owned(),
-                }))
-                .await
-                .map_err(|source| UpdateClipError::Status { source })?
-                .into_inner();
+    async fn get_bytes(&self, id: u64) -> Result<Bytes, GetClipError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get(Request::new(proto::GetRequest { id, max_bytes: None }))
+            .await
+            .map_err(|source| GetClipError::Status { source, id })?
+            .into_inner()
+            .data
+            .map_or_else(|| Err(GetClipError::Empty), |entry| Ok(entry.data))
+    }
+
+    async fn get_image_as(&self, id: u64, format: ImageFormat) -> Result<Vec<u8>, GetImageError> {
+        let proto::GetImageAsResponse { data, .. } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get_image_as(Request::new(proto::GetImageAsRequest { id, format: format.into() }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::ClipNotFound => GetImageError::NotFound { id },
+                    proto::ErrorCode::NotAnImage => GetImageError::NotAnImage { id },
+                    _ => GetImageError::Status { source, id },
+                })?
+                .into_inner();
+        Ok(data)
+    }
+
+    async fn export_entry(&self, id: u64, path: &Path) -> Result<PathBuf, ExportEntryError> {
+        let clip: ClipEntry =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get(Request::new(proto::GetRequest { id, max_bytes: None }))
+                .await
+                .map_err(|source| ExportEntryError::Status { source, id })?
+                .into_inner()
+                .data
+                .ok_or(ExportEntryError::NotFound { id })?
+                .into();
+        let data = clip.encoded().map_err(|source| ExportEntryError::Encode { source, id })?;
+        let path = path.with_extension(clip.file_extension());
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|source| ExportEntryError::Write { source, path: path.clone() })?;
+        Ok(path)
+    }
+
+    async fn get_prefix(&self, id: u64, max_bytes: u64) -> Result<(ClipEntry, bool), GetClipError> {
+        let proto::GetResponse { data, truncated } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get(Request::new(proto::GetRequest { id, max_bytes: Some(max_bytes) }))
+                .await
+                .map_err(|source| GetClipError::Status { source, id })?
+                .into_inner();
+        data.map_or_else(|| Err(GetClipError::Empty), |data| Ok((data.into(), truncated)))
+    }
+
+    async fn get_stream(&self, id: u64) -> Result<ClipDataStream, GetClipError> {
+        self.get_stream_from(id, 0).await
+    }
+
+    async fn get_stream_from(&self, id: u64, offset: u64) -> Result<ClipDataStream, GetClipError> {
+        let stream =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get_stream(Request::new(proto::GetStreamRequest { id, offset }))
+                .await
+                .map_err(|source| GetClipError::Status { source, id })?
+                .into_inner();
+        Ok(stream
+            .take_until(self.closed())
+            .map(move |chunk| {
+                chunk
+                    .map(|proto::GetStreamChunk { offset, data, total_size }| ClipDataChunk {
+                        offset,
+                        data,
+                        total_size,
+                    })
+                    .map_err(|source| GetClipError::Status { source, id })
+            })
+            .boxed())
+    }
+
+    async fn rank(&self, id: u64) -> Result<Option<u64>, GetClipError> {
+        let proto::RankResponse { rank } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .rank(Request::new(proto::RankRequest { id }))
+                .await
+                .map_err(|source| GetClipError::Status { source, id })?
+                .into_inner();
+        Ok(rank)
+    }
+
+    async fn neighbor(
+        &self,
+        id: u64,
+        direction: CycleDirection,
+    ) -> Result<Option<ClipEntry>, GetClipError> {
+        let proto::NeighborResponse { data } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .neighbor(Request::new(proto::NeighborRequest { id, direction: direction.into() }))
+                .await
+                .map_err(|source| GetClipError::Status { source, id })?
+                .into_inner();
+        Ok(data.map(ClipEntry::from))
+    }
+
+    async fn nth_recent(
+        &self,
+        n: u64,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, GetCurrentClipError> {
+        let proto::NthRecentResponse { data } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .nth_recent(Request::new(proto::NthRecentRequest { n, kind: kind.into() }))
+                .await
+                .map_err(|source| GetCurrentClipError::Status { source, kind })?
+                .into_inner();
+        Ok(data.map(ClipEntry::from))
+    }
+
+    async fn latest(&self, kind: ClipboardKind) -> Result<Option<ClipEntry>, GetCurrentClipError> {
+        self.nth_recent(1, kind).await
+    }
+
+    async fn get_current_clip(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<ClipEntry, GetCurrentClipError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_current_clip(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+            .await
+            .map_err(|source| GetCurrentClipError::Status { source, kind })?
+            .into_inner()
+            .data
+            .map_or_else(|| Err(GetCurrentClipError::Empty), |data| Ok(data.into()))
+    }
+
+    async fn get_current_any(
+        &self,
+        kinds: &[ClipboardKind],
+    ) -> Result<ClipEntry, GetCurrentClipError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_current_any(Request::new(proto::GetCurrentAnyRequest {
+                kinds: kinds.iter().map(|&kind| proto::ClipboardKind::from(kind).into()).collect(),
+            }))
+            .await
+            .map_err(|source| GetCurrentClipError::Status {
+                source,
+                kind: kinds.first().copied().unwrap_or_default(),
+            })?
+            .into_inner()
+            .data
+            .map_or_else(|| Err(GetCurrentClipError::Empty), |data| Ok(data.into()))
+    }
+
+    async fn take_current(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, GetCurrentClipError> {
+        let proto::GetCurrentClipResponse { data } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .take_current(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+                .await
+                .map_err(|source| GetCurrentClipError::Status { source, kind })?
+                .into_inner();
+        Ok(data.map(ClipEntry::from))
+    }
+
+    async fn current_entry_id(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Option<u64>, GetCurrentClipError> {
+        let proto::CurrentEntryIdResponse { id } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .current_entry_id(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+                .await
+                .map_err(|source| GetCurrentClipError::Status { source, kind })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn is_current(&self, id: u64, kind: ClipboardKind) -> Result<bool, GetCurrentClipError> {
+        let proto::IsCurrentResponse { current } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .is_current(Request::new(proto::IsCurrentRequest { id, kind: kind.into() }))
+                .await
+                .map_err(|source| GetCurrentClipError::Status { source, kind })?
+                .into_inner();
+        Ok(current)
+    }
+
+    async fn current_targets(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<String>, GetCurrentClipError> {
+        let proto::CurrentTargetsResponse { targets } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .current_targets(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+                .await
+                .map_err(|source| GetCurrentClipError::Status { source, kind })?
+                .into_inner();
+        Ok(targets)
+    }
+
+    async fn get_current_all_formats(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<(mime::Mime, Vec<u8>)>, GetCurrentClipError> {
+        let proto::CurrentFormatsResponse { formats } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .current_formats(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+                .await
+                .map_err(|source| GetCurrentClipError::Status { source, kind })?
+                .into_inner();
+        formats
+            .into_iter()
+            .map(|proto::CurrentFormat { mime, data }| {
+                mime.parse()
+                    .map(|mime| (mime, data))
+                    .map_err(|_| GetCurrentClipError::InvalidMime { value: mime })
+            })
+            .collect()
+    }
+
+    async fn update(
+        &self,
+        id: u64,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<(bool, u64), UpdateClipError> {
+        update_clip(
+            self,
+            proto::UpdateRequest {
+                id,
+                data: data.to_owned(),
+                mime: mime.to_string(),
+                expected_version: None,
+            },
+        )
+        .await
+    }
+
+    async fn update_if(
+        &self,
+        id: u64,
+        expected_version: u64,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<(bool, u64), UpdateClipError> {
+        update_clip(
+            self,
+            proto::UpdateRequest {
+                id,
+                data: data.to_owned(),
+                mime: mime.to_string(),
+                expected_version: Some(expected_version),
+            },
+        )
+        .await
+    }
+
+    async fn append(
+        &self,
+        id: u64,
+        data: &[u8],
+        separator: Option<&[u8]>,
+    ) -> Result<(bool, u64), UpdateClipError> {
+        let proto::UpdateResponse { ok, new_id } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .append(Request::new(proto::AppendRequest {
+                    id,
+                    data: data.to_owned(),
+                    separator: separator.map(<[u8]>::to_vec),
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::NotText => UpdateClipError::NotText { id },
+                    _ => UpdateClipError::Status { source },
+                })?
+                .into_inner();
         Ok((ok, new_id))
     }
 
-    async fn mark(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError> {
-        let proto::MarkResponse { ok } =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .mark(Request::new(proto::MarkRequest { id, kind: kind.into() }))
+    async fn mark(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError> {
+        let proto::MarkResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .mark(Request::new(proto::MarkRequest { id, kind: kind.into() }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                    proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                    proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                    _ => MarkClipError::Status { source, id, kind },
+                })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn mark_and_remove(&self, id: u64, kind: ClipboardKind) -> Result<bool, MarkClipError> {
+        let proto::MarkResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .mark_and_remove(Request::new(proto::MarkRequest { id, kind: kind.into() }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                    proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                    proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                    _ => MarkClipError::Status { source, id, kind },
+                })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn mark_kinds(&self, id: u64, kinds: &[ClipboardKind]) -> Result<bool, MarkClipError> {
+        let proto::MarkResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .mark_kinds(Request::new(proto::MarkKindsRequest {
+                    id,
+                    kinds: kinds
+                        .iter()
+                        .map(|&kind| proto::ClipboardKind::from(kind).into())
+                        .collect(),
+                }))
+                .await
+                .map_err(|source| {
+                    let kind = proto::ErrorDetail::from_status(&source)
+                        .and_then(|detail| detail.kind)
+                        .and_then(|kind| ClipboardKind::try_from(kind).ok());
+                    match (error_code(&source), kind) {
+                        (proto::ErrorCode::KindDisabled, Some(kind)) => {
+                            MarkClipError::KindDisabled { kind }
+                        }
+                        (proto::ErrorCode::StoreFailed, Some(kind)) => {
+                            MarkClipError::StoreFailed { source, id, kind }
+                        }
+                        (proto::ErrorCode::ReadOnly, _) => MarkClipError::ReadOnly,
+                        (proto::ErrorCode::UnsupportedMime, _) => unsupported_mime(&source),
+                        _ => MarkClipError::Status {
+                            source,
+                            id,
+                            kind: kinds.first().copied().unwrap_or_default(),
+                        },
+                    }
+                })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn cycle(
+        &self,
+        kind: ClipboardKind,
+        direction: CycleDirection,
+        wrap: bool,
+    ) -> Result<Option<u64>, MarkClipError> {
+        let proto::CycleResponse { id } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .cycle(Request::new(proto::CycleRequest {
+                    kind: kind.into(),
+                    direction: direction.into(),
+                    wrap,
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                    proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                    proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                    _ => MarkClipError::Cycle { source, kind },
+                })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn mark_and_propagate(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+    ) -> Result<bool, MarkClipError> {
+        let proto::MarkResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .mark_and_propagate(Request::new(proto::MarkRequest { id, kind: kind.into() }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                    proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                    proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                    _ => MarkClipError::Status { source, id, kind },
+                })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn mark_as(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        target_mime: mime::Mime,
+    ) -> Result<bool, MarkClipError> {
+        let proto::MarkResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .mark_as(Request::new(proto::MarkAsRequest {
+                    id,
+                    kind: kind.into(),
+                    mime: target_mime.to_string(),
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::UnsupportedConversion | proto::ErrorCode::InvalidArgument => {
+                        MarkClipError::UnsupportedConversion { id, mime: target_mime }
+                    }
+                    proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                    proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                    proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                    _ => MarkClipError::Status { source, id, kind },
+                })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn set_transient(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        kind: ClipboardKind,
+    ) -> Result<(), MarkClipError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .set_transient(Request::new(proto::SetTransientRequest {
+                kind: kind.into(),
+                data: data.to_owned(),
+                mime: mime.to_string(),
+            }))
+            .await
+            .map(|_| ())
+            .map_err(|source| match error_code(&source) {
+                proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                _ => MarkClipError::SetTransient { source, kind },
+            })
+    }
+
+    async fn replace_active(
+        &self,
+        kind: ClipboardKind,
+        data: &[u8],
+        mime: mime::Mime,
+    ) -> Result<u64, MarkClipError> {
+        let proto::InsertResponse { id, .. } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .replace_active(Request::new(proto::ReplaceActiveRequest {
+                    kind: kind.into(),
+                    data: data.to_owned(),
+                    mime: mime.to_string(),
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::KindDisabled => MarkClipError::KindDisabled { kind },
+                    proto::ErrorCode::ReadOnly => MarkClipError::ReadOnly,
+                    proto::ErrorCode::UnsupportedMime => unsupported_mime(&source),
+                    _ => MarkClipError::ReplaceActive { source, kind },
+                })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn wait_active(
+        &self,
+        id: u64,
+        kind: ClipboardKind,
+        timeout: Duration,
+    ) -> Result<bool, MarkClipError> {
+        let proto::WaitActiveResponse { active } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .wait_active(Request::new(proto::WaitActiveRequest {
+                    id,
+                    kind: kind.into(),
+                    timeout_ms: u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+                }))
+                .await
+                .map_err(|source| {
+                    if error_code(&source) == proto::ErrorCode::KindDisabled {
+                        MarkClipError::KindDisabled { kind }
+                    } else {
+                        MarkClipError::Status { source, id, kind }
+                    }
+                })?
+                .into_inner();
+        Ok(active)
+    }
+
+    async fn touch(&self, id: u64) -> Result<bool, MarkClipError> {
+        let proto::TouchResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .touch(Request::new(proto::TouchRequest { id }))
+                .await
+                .map_err(|source| MarkClipError::Touch { source, id })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn protect_until(&self, id: u64, until: SystemTime) -> Result<bool, MarkClipError> {
+        let proto::ProtectResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .protect(Request::new(proto::ProtectRequest {
+                    id,
+                    until: Some(prost_types::Timestamp::from(until)),
+                }))
+                .await
+                .map_err(|source| MarkClipError::Protect { source, id })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn set_label(&self, id: u64, label: Option<String>) -> Result<bool, UpdateClipError> {
+        let proto::SetLabelResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .set_label(Request::new(proto::SetLabelRequest { id, label }))
+                .await
+                .map_err(|source| UpdateClipError::Status { source })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn set_sticky(&self, id: u64, sticky: bool) -> Result<bool, UpdateClipError> {
+        let proto::SetStickyResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .set_sticky(Request::new(proto::SetStickyRequest { id, sticky }))
+                .await
+                .map_err(|source| match proto::ErrorDetail::from_status(&source) {
+                    Some(detail) if detail.code() == proto::ErrorCode::NotText => {
+                        UpdateClipError::NotText { id }
+                    }
+                    Some(detail) if detail.code() == proto::ErrorCode::TooManyStickyNotes => {
+                        UpdateClipError::TooManyStickyNotes {
+                            limit: detail.limit.unwrap_or_default(),
+                        }
+                    }
+                    _ => UpdateClipError::Status { source },
+                })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn pin_current(&self, kind: ClipboardKind) -> Result<Option<u64>, UpdateClipError> {
+        let proto::PinCurrentResponse { id } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .pin_current(Request::new(proto::GetCurrentClipRequest { kind: kind.into() }))
+                .await
+                .map_err(|source| match proto::ErrorDetail::from_status(&source) {
+                    Some(detail) if detail.code() == proto::ErrorCode::NotText => {
+                        UpdateClipError::NotText { id: detail.id.unwrap_or_default() }
+                    }
+                    Some(detail) if detail.code() == proto::ErrorCode::TooManyStickyNotes => {
+                        UpdateClipError::TooManyStickyNotes {
+                            limit: detail.limit.unwrap_or_default(),
+                        }
+                    }
+                    _ => UpdateClipError::Status { source },
+                })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn set_semantic_kind(
+        &self,
+        id: u64,
+        kind: SemanticKind,
+    ) -> Result<bool, UpdateClipError> {
+        let proto::SetSemanticKindResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .set_semantic_kind(Request::new(proto::SetSemanticKindRequest {
+                    id,
+                    kind: proto::SemanticKind::from(kind).into(),
+                }))
+                .await
+                .map_err(|source| UpdateClipError::Status { source })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn swap(&self, a: u64, b: u64) -> Result<bool, MarkClipError> {
+        let proto::SwapResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .swap(Request::new(proto::SwapRequest { a, b }))
+                .await
+                .map_err(|source| MarkClipError::Swap { source, a, b })?
+                .into_inner();
+        Ok(ok)
+    }
+
+    async fn reorder(&self, key: SortKey) -> Result<(), ReorderError> {
+        let _response =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .reorder(Request::new(proto::ReorderRequest { key: key.into() }))
+                .await
+                .map_err(|source| ReorderError::Status { source })?;
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    async fn set_history(&self, entries: Vec<ClipEntry>) -> Result<(), SetHistoryError> {
+        let clips = entries.into_iter().map(proto::ClipEntry::from).collect();
+        let _response =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .set_history(Request::new(proto::SetHistoryRequest { clips }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::TestingDisabled => {
+                        SetHistoryError::TestingDisabled { source }
+                    }
+                    _ => SetHistoryError::Status { source },
+                })?;
+        Ok(())
+    }
+
+    async fn insert(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
+        insert_clip(self, ClipEntryBuilder::new(data, mime).mode(clipboard_kind))
+            .await
+            .map(|(id, _, mime)| (id, mime))
+    }
+
+    async fn insert_bytes(
+        &self,
+        data: Bytes,
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, mime::Mime), InsertClipError> {
+        insert_clip(self, ClipEntryBuilder::from_bytes(data, mime).mode(clipboard_kind))
+            .await
+            .map(|(id, _, mime)| (id, mime))
+    }
+
+    async fn insert_with_evicted(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, Vec<u64>), InsertClipError> {
+        insert_clip(self, ClipEntryBuilder::new(data, mime).mode(clipboard_kind))
+            .await
+            .map(|(id, evicted, _)| (id, evicted))
+    }
+
+    async fn insert_built(&self, clip: ClipEntryBuilder) -> Result<u64, InsertClipError> {
+        insert_clip(self, clip).await.map(|(id, ..)| id)
+    }
+
+    async fn insert_with_id(
+        &self,
+        id: u64,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<u64, InsertClipError> {
+        let proto::InsertResponse { id, .. } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .insert_with_id(Request::new(proto::InsertWithIdRequest {
+                    id,
+                    kind: clipboard_kind.into(),
+                    data: data.to_owned(),
+                    mime: mime.to_string(),
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::InvalidMime | proto::ErrorCode::InvalidArgument => {
+                        InsertClipError::InvalidMime { value: mime.to_string() }
+                    }
+                    proto::ErrorCode::IdTaken => InsertClipError::IdTaken { id },
+                    proto::ErrorCode::KindDisabled => {
+                        InsertClipError::KindDisabled { kind: clipboard_kind }
+                    }
+                    proto::ErrorCode::RateLimited => rate_limited(&source),
+                    proto::ErrorCode::TooLarge => too_large(&source),
+                    proto::ErrorCode::HistoryFull => history_full(&source),
+                    _ => InsertClipError::Status { source },
+                })?
+                .into_inner();
+        Ok(id)
+    }
+
+    async fn get_or_insert(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<(u64, bool), InsertClipError> {
+        let proto::GetOrInsertResponse { id, inserted } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get_or_insert(Request::new(proto::GetOrInsertRequest {
+                    kind: clipboard_kind.into(),
+                    data: data.to_owned(),
+                    mime: mime.to_string(),
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::InvalidMime | proto::ErrorCode::InvalidArgument => {
+                        InsertClipError::InvalidMime { value: mime.to_string() }
+                    }
+                    proto::ErrorCode::KindDisabled => {
+                        InsertClipError::KindDisabled { kind: clipboard_kind }
+                    }
+                    proto::ErrorCode::RateLimited => rate_limited(&source),
+                    proto::ErrorCode::TooLarge => too_large(&source),
+                    proto::ErrorCode::HistoryFull => history_full(&source),
+                    _ => InsertClipError::Status { source },
+                })?
+                .into_inner();
+        Ok((id, inserted))
+    }
+
+    async fn duplicate(&self, id: u64) -> Result<u64, InsertClipError> {
+        let proto::DuplicateResponse { id: copy_id } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .duplicate(Request::new(proto::DuplicateRequest { id }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::ClipNotFound => InsertClipError::NotFound { id },
+                    _ => InsertClipError::Status { source },
+                })?
+                .into_inner();
+        Ok(copy_id)
+    }
+
+    async fn length(&self) -> Result<usize, GetLengthError> {
+        let proto::LengthResponse { length } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .length(Request::new(()))
+                .await
+                .map_err(|source| GetLengthError::Status { source })?
+                .into_inner();
+        Ok(usize::try_from(length).unwrap_or(0))
+    }
+
+    async fn list(&self, preview_length: usize) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        self.list_sorted(preview_length, SortOrder::Recency).await
+    }
+
+    async fn list_sorted(
+        &self,
+        preview_length: usize,
+        order: SortOrder,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        list_metadata(
+            self,
+            proto::ListRequest {
+                preview_length: u64::try_from(preview_length).unwrap_or(30),
+                profile: None,
+                order: order.into(),
+            },
+        )
+        .await
+    }
+
+    async fn list_with_skipped(
+        &self,
+        preview_length: usize,
+    ) -> Result<(Vec<ClipEntryMetadata>, usize), ListClipError> {
+        list_metadata_with_skipped(
+            self,
+            proto::ListRequest {
+                preview_length: u64::try_from(preview_length).unwrap_or(30),
+                profile: None,
+                order: SortOrder::Recency.into(),
+            },
+        )
+        .await
+    }
+
+    async fn list_cancellable(
+        &self,
+        preview_length: usize,
+        token: CancellationToken,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        until_cancelled(&token, self.list(preview_length)).await.ok_or(ListClipError::Cancelled)?
+    }
+
+    async fn list_range(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), ListClipError> {
+        let proto::ListRangeResponse { entries, total } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_range(Request::new(proto::ListRangeRequest {
+                    offset: offset as u64,
+                    limit: limit as u64,
+                }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        let entries = entries.into_iter().map(ClipEntry::from).collect();
+        Ok((entries, usize::try_from(total).unwrap_or(usize::MAX)))
+    }
+
+    async fn open_snapshot(&self) -> Result<SnapshotHandle, ListClipError> {
+        let proto::OpenSnapshotResponse { handle, total } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .open_snapshot(Request::new(()))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(SnapshotHandle { handle, total: usize::try_from(total).unwrap_or(usize::MAX) })
+    }
+
+    async fn list_snapshot_range(
+        &self,
+        snapshot: &SnapshotHandle,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), ListClipError> {
+        let handle = snapshot.handle;
+        let proto::ListRangeResponse { entries, total } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_snapshot_range(Request::new(proto::ListSnapshotRangeRequest {
+                    handle,
+                    offset: offset as u64,
+                    limit: limit as u64,
+                }))
+                .await
+                .map_err(|source| match error_code(&source) {
+                    proto::ErrorCode::SnapshotNotFound => {
+                        ListClipError::SnapshotNotFound { handle }
+                    }
+                    _ => ListClipError::Status { source },
+                })?
+                .into_inner();
+        let entries = entries.into_iter().map(ClipEntry::from).collect();
+        Ok((entries, usize::try_from(total).unwrap_or(usize::MAX)))
+    }
+
+    async fn close_snapshot(&self, snapshot: SnapshotHandle) -> Result<bool, ListClipError> {
+        let proto::CloseSnapshotResponse { ok } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .close_snapshot(Request::new(proto::CloseSnapshotRequest {
+                    handle: snapshot.handle,
+                }))
                 .await
-                .map_err(|source| MarkClipError::Status { source, id, kind })?
+                .map_err(|source| ListClipError::Status { source })?
                 .into_inner();
         Ok(ok)
     }
 
-    async fn insert(
+    async fn list_most_used(&self, limit: usize) -> Result<Vec<ClipEntry>, ListClipError> {
+        let proto::ListMostUsedResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_most_used(Request::new(proto::ListMostUsedRequest { limit: limit as u64 }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
+    }
+
+    async fn list_after(
         &self,
-        data: &[u8],
-        mime: mime::Mime,
-        clipboard_kind: ClipboardKind,
-    ) -> Result<u64, InsertClipError> {
-        let proto::InsertResponse { id } =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .insert(Request::new(proto::InsertRequest {
-                    kind: clipboard_kind.into(),
-                    data: data.to_owned(),
-                    mime: mime.essence_str().to_owned(),
+        cursor: Option<ListCursor>,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, Option<ListCursor>), ListClipError> {
+        let proto::ListAfterResponse { entries, next_cursor } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_after(Request::new(proto::ListAfterRequest {
+                    limit: limit as u64,
+                    cursor: cursor.map(proto::ListCursor::from),
                 }))
                 .await
-                .map_err(|source| InsertClipError::Status { source })?
+                .map_err(|source| ListClipError::Status { source })?
                 .into_inner();
-        Ok(id)
+        Ok((entries.into_iter().map(ClipEntry::from).collect(), next_cursor.map(ListCursor::from)))
     }
 
-    async fn length(&self) -> Result<usize, GetLengthError> {
-        let proto::LengthResponse { length } =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .length(Request::new(()))
+    async fn list_since(&self, since: SystemTime) -> Result<Vec<ClipEntry>, ListClipError> {
+        let proto::ListSinceResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_since(Request::new(proto::ListSinceRequest {
+                    since: Some(prost_types::Timestamp::from(since)),
+                }))
                 .await
-                .map_err(|source| GetLengthError::Status { source })?
+                .map_err(|source| ListClipError::Status { source })?
                 .into_inner();
-        Ok(usize::try_from(length).unwrap_or(0))
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
     }
 
-    async fn list(&self, preview_length: usize) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
-        let mut list: Vec<_> =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-                .list(Request::new(proto::ListRequest {
+    async fn list_by_tags(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+    ) -> Result<Vec<ClipEntry>, ListClipError> {
+        let proto::ListByTagsResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_by_tags(Request::new(proto::ListByTagsRequest {
+                    tags: tags.to_vec(),
+                    mode: mode.into(),
+                }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
+    }
+
+    async fn list_by_source_class(&self, class: &str) -> Result<Vec<ClipEntry>, ListClipError> {
+        let proto::ListBySourceClassResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_by_source_class(Request::new(proto::ListBySourceClassRequest {
+                    class: class.to_string(),
+                }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
+    }
+
+    async fn list_by_semantic_kind(
+        &self,
+        kind: SemanticKind,
+    ) -> Result<Vec<ClipEntry>, ListClipError> {
+        let proto::ListBySemanticKindResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_by_semantic_kind(Request::new(proto::ListBySemanticKindRequest {
+                    kind: proto::SemanticKind::from(kind).into(),
+                }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
+    }
+
+    async fn list_by_size(
+        &self,
+        min: Option<u64>,
+        max: Option<u64>,
+    ) -> Result<Vec<ClipEntry>, ListClipError> {
+        let proto::ListBySizeResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_by_size(Request::new(proto::ListBySizeRequest { min, max }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
+    }
+
+    async fn batch_metadata(
+        &self,
+        ids: &[u64],
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        let proto::BatchMetadataResponse { metadata } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .batch_metadata(Request::new(proto::BatchMetadataRequest {
+                    ids: ids.to_vec(),
                     preview_length: u64::try_from(preview_length).unwrap_or(30),
                 }))
                 .await
                 .map_err(|source| ListClipError::Status { source })?
-                .into_inner()
-                .metadata
-                .into_iter()
-                .map(ClipEntryMetadata::from)
-                .collect();
-        list.sort_unstable();
-        Ok(list)
+                .into_inner();
+        Ok(metadata.into_iter().map(ClipEntryMetadata::from).collect())
+    }
+
+    async fn batch_etags(&self, ids: &[u64]) -> Result<Vec<(u64, u64)>, ListClipError> {
+        let proto::BatchEtagsResponse { etags } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .batch_etags(Request::new(proto::BatchEtagsRequest { ids: ids.to_vec() }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(etags.into_iter().map(|proto::ClipEtag { id, etag }| (id, etag)).collect())
+    }
+
+    async fn list_grouped(&self) -> Result<HashMap<ClipboardKind, Vec<ClipEntry>>, ListClipError> {
+        self.list_grouped_sorted(SortOrder::Recency).await
+    }
+
+    async fn list_grouped_sorted(
+        &self,
+        order: SortOrder,
+    ) -> Result<HashMap<ClipboardKind, Vec<ClipEntry>>, ListClipError> {
+        let proto::ListGroupedResponse { groups } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_grouped(Request::new(proto::ListGroupedRequest { order: order.into() }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(groups
+            .into_iter()
+            .filter_map(|proto::ClipGroup { kind, entries }| {
+                let kind = ClipboardKind::try_from(kind).ok()?;
+                Some((kind, entries.into_iter().map(ClipEntry::from).collect()))
+            })
+            .collect())
+    }
+
+    async fn entries_with_content_hash(&self, hash: &str) -> Result<Vec<u64>, ListClipError> {
+        let proto::EntriesWithContentHashResponse { ids } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .entries_with_content_hash(Request::new(proto::EntriesWithContentHashRequest {
+                    hash: hash.to_string(),
+                }))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(ids)
+    }
+
+    async fn list_ids(&self) -> Result<Vec<(u64, SystemTime)>, ListClipError> {
+        let proto::ListIdsResponse { ids } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .list_ids(Request::new(()))
+                .await
+                .map_err(|source| ListClipError::Status { source })?
+                .into_inner();
+        Ok(ids
+            .into_iter()
+            .map(|proto::ClipIdTimestamp { id, timestamp }| {
+                let timestamp = timestamp
+                    .and_then(|timestamp| SystemTime::try_from(timestamp).ok())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                (id, timestamp)
+            })
+            .collect())
     }
 
     async fn remove(&self, id: u64) -> Result<bool, RemoveClipError> {
         let proto::RemoveResponse { ok } =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .remove(Request::new(proto::RemoveRequest { id }))
                 .await
                 .map_err(|source| RemoveClipError::Status { source })?
@@ -167,9 +2315,20 @@ impl Manager for Client {
         Ok(ok)
     }
 
+    async fn apply(&self, ops: &[ClipOp]) -> Result<Vec<ClipOpResult>, ApplyError> {
+        let ops = ops.iter().cloned().map(proto::ClipOp::from).collect();
+        let proto::ApplyResponse { results } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .apply(Request::new(proto::ApplyRequest { ops }))
+                .await
+                .map_err(|source| ApplyError::Status { source })?
+                .into_inner();
+        Ok(results.into_iter().map(ClipOpResult::from).collect())
+    }
+
     async fn batch_remove(&self, ids: &[u64]) -> Result<Vec<u64>, BatchRemoveClipError> {
         let proto::BatchRemoveResponse { ids } =
-            proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .batch_remove(Request::new(proto::BatchRemoveRequest { ids: Vec::from(ids) }))
                 .await
                 .map_err(|source| BatchRemoveClipError::Status { source })?
@@ -177,11 +2336,542 @@ impl Manager for Client {
         Ok(ids)
     }
 
+    async fn remove_by_content(
+        &self,
+        data: &[u8],
+        mime: mime::Mime,
+        dry_run: bool,
+    ) -> Result<Vec<u64>, BatchRemoveClipError> {
+        let proto::BatchRemoveResponse { ids } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .remove_by_content(Request::new(proto::RemoveByContentRequest {
+                    data: data.to_owned(),
+                    mime: mime.to_string(),
+                    dry_run,
+                }))
+                .await
+                .map_err(|source| BatchRemoveClipError::Status { source })?
+                .into_inner();
+        Ok(ids)
+    }
+
+    async fn trim_to(
+        &self,
+        max_count: u64,
+        kind: Option<ClipboardKind>,
+        dry_run: bool,
+    ) -> Result<Vec<u64>, BatchRemoveClipError> {
+        let proto::BatchRemoveResponse { ids } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .trim_to(Request::new(proto::TrimToRequest {
+                    max_count,
+                    kind: kind.map(i32::from),
+                    dry_run,
+                }))
+                .await
+                .map_err(|source| BatchRemoveClipError::Status { source })?
+                .into_inner();
+        Ok(ids)
+    }
+
+    async fn notify_resync(&self) -> Result<(), NotifyResyncError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .notify_resync(Request::new(()))
+            .await
+            .map(|_| ())
+            .map_err(|source| NotifyResyncError::Status { source })
+    }
+
     async fn clear(&self) -> Result<(), ClearClipError> {
-        proto::ManagerClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
-            .clear(Request::new(()))
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .clear(Request::new(proto::ClearRequest { profile: None }))
+            .await
+            .map(|_| ())
+            .map_err(|source| ClearClipError::Status { source })
+    }
+
+    async fn drain(
+        &self,
+        keep_snippets: bool,
+        keep_sticky: bool,
+    ) -> Result<Vec<ClipEntry>, ClearClipError> {
+        let proto::DrainResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .drain(Request::new(proto::DrainRequest {
+                    drain_snippets: !keep_snippets,
+                    keep_sticky_notes: keep_sticky,
+                }))
+                .await
+                .map_err(|source| ClearClipError::Status { source })?
+                .into_inner();
+        Ok(entries.into_iter().map(ClipEntry::from).collect())
+    }
+
+    async fn list_profile(
+        &self,
+        profile: Option<&str>,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+        list_metadata(
+            self,
+            proto::ListRequest {
+                preview_length: u64::try_from(preview_length).unwrap_or(30),
+                profile: Some(profile.unwrap_or_default().to_string()),
+                order: SortOrder::Recency.into(),
+            },
+        )
+        .await
+    }
+
+    async fn clear_profile(&self, profile: Option<&str>) -> Result<(), ClearClipError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .clear(Request::new(proto::ClearRequest {
+                profile: Some(profile.unwrap_or_default().to_string()),
+            }))
             .await
             .map(|_| ())
             .map_err(|source| ClearClipError::Status { source })
     }
+
+    async fn set_active_profile(&self, profile: Option<&str>) -> Result<(), SetActiveProfileError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .set_active_profile(Request::new(proto::SetActiveProfileRequest {
+                profile: profile.map(ToString::to_string),
+            }))
+            .await
+            .map(|_| ())
+            .map_err(|source| SetActiveProfileError::Status { source })
+    }
+
+    async fn get_active_profile(&self) -> Result<Option<String>, GetActiveProfileError> {
+        let proto::ActiveProfileResponse { profile } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get_active_profile(Request::new(()))
+                .await
+                .map_err(|source| GetActiveProfileError::Status { source })?
+                .into_inner();
+        Ok(profile)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        preview_length: usize,
+    ) -> Result<Vec<ClipEntryMetadata>, SearchClipError> {
+        Ok(proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .search(Request::new(proto::SearchRequest {
+                query: query.to_string(),
+                preview_length: u64::try_from(preview_length).unwrap_or(30),
+                fuzzy: false,
+            }))
+            .await
+            .map_err(|source| SearchClipError::Status { source })?
+            .into_inner()
+            .metadata
+            .into_iter()
+            .map(ClipEntryMetadata::from)
+            .collect())
+    }
+
+    async fn search_cancellable(
+        &self,
+        query: &str,
+        preview_length: usize,
+        token: CancellationToken,
+    ) -> Result<Vec<ClipEntryMetadata>, SearchClipError> {
+        until_cancelled(&token, self.search(query, preview_length))
+            .await
+            .ok_or(SearchClipError::Cancelled)?
+    }
+
+    async fn search_with(
+        &self,
+        query: &str,
+        preview_length: usize,
+        options: SearchOptions,
+    ) -> Result<Vec<SearchMatch>, SearchClipError> {
+        let proto::SearchResponse { metadata, scores } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .search(Request::new(proto::SearchRequest {
+                    query: query.to_string(),
+                    preview_length: u64::try_from(preview_length).unwrap_or(30),
+                    fuzzy: options.fuzzy,
+                }))
+                .await
+                .map_err(|source| SearchClipError::Status { source })?
+                .into_inner();
+        let scores = scores.into_iter().chain(std::iter::repeat(0.0));
+        Ok(metadata
+            .into_iter()
+            .zip(scores)
+            .map(|(metadata, score)| SearchMatch { metadata: metadata.into(), score })
+            .collect())
+    }
+
+    async fn first_matching(
+        &self,
+        query: &str,
+        options: SearchOptions,
+    ) -> Result<Option<ClipEntry>, SearchClipError> {
+        let proto::FirstMatchingResponse { data } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .first_matching(Request::new(proto::FirstMatchingRequest {
+                    query: query.to_string(),
+                    fuzzy: options.fuzzy,
+                }))
+                .await
+                .map_err(|source| SearchClipError::Status { source })?
+                .into_inner();
+        Ok(data.map(ClipEntry::from))
+    }
+
+    async fn stats(&self) -> Result<HistoryStats, GetStatsError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_stats(Request::new(()))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| GetStatsError::Status { source })
+    }
+
+    async fn resource_usage(&self) -> Result<ResourceUsage, GetStatsError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_resource_usage(Request::new(()))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| GetStatsError::Status { source })
+    }
+
+    async fn mime_types(&self) -> Result<Vec<(String, u64)>, GetStatsError> {
+        let proto::MimeTypesResponse { mime_types } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .mime_types(Request::new(()))
+                .await
+                .map_err(|source| GetStatsError::Status { source })?
+                .into_inner();
+        Ok(mime_types
+            .into_iter()
+            .map(|proto::MimeTypeCount { mime, count }| (mime, count))
+            .collect())
+    }
+
+    async fn content_manifest(&self) -> Result<Vec<(u64, String)>, GetStatsError> {
+        let proto::ContentManifestResponse { entries } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .content_manifest(Request::new(()))
+                .await
+                .map_err(|source| GetStatsError::Status { source })?
+                .into_inner();
+        Ok(entries
+            .into_iter()
+            .map(|proto::ManifestEntry { id, content_hash }| (id, content_hash))
+            .collect())
+    }
+
+    async fn export_metadata(&self) -> Result<Vec<u8>, ExportMetadataError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .export_metadata(Request::new(()))
+            .await
+            .map(|response| response.into_inner().encode_to_vec())
+            .map_err(|source| ExportMetadataError::Status { source })
+    }
+
+    async fn import_metadata(
+        &self,
+        data: &[u8],
+    ) -> Result<MetadataImportReport, ImportMetadataError> {
+        let records = proto::ClipMetadataRecords::decode(data)
+            .map_err(|_| ImportMetadataError::InvalidFormat)?;
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .import_metadata(Request::new(records))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| ImportMetadataError::Status { source })
+    }
+
+    async fn verify_store(&self, repair: bool) -> Result<StoreReport, VerifyStoreError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .verify_store(Request::new(proto::VerifyStoreRequest { repair }))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| VerifyStoreError::Status { source })
+    }
+
+    async fn compact_store(&self) -> Result<CompactReport, CompactStoreError> {
+        proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .compact_store(Request::new(()))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| CompactStoreError::Status { source })
+    }
+
+    async fn activity(
+        &self,
+        bucket: Duration,
+        since: SystemTime,
+    ) -> Result<Vec<(SystemTime, u64)>, GetStatsError> {
+        let proto::ActivityResponse { buckets } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get_activity(Request::new(proto::ActivityRequest {
+                    bucket_ms: u64::try_from(bucket.as_millis()).unwrap_or(u64::MAX),
+                    since: Some(prost_types::Timestamp::from(since)),
+                }))
+                .await
+                .map_err(|source| match proto::ErrorDetail::from_status(&source) {
+                    Some(detail) if detail.code() == proto::ErrorCode::TooManyBuckets => {
+                        GetStatsError::TooManyBuckets { limit: detail.limit.unwrap_or_default() }
+                    }
+                    _ => GetStatsError::Status { source },
+                })?
+                .into_inner();
+        Ok(buckets
+            .into_iter()
+            .map(|proto::ActivityBucket { start, count }| {
+                let start = start
+                    .and_then(|start| SystemTime::try_from(start).ok())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                (start, count)
+            })
+            .collect())
+    }
+
+    async fn time_bounds(
+        &self,
+        kind: Option<ClipboardKind>,
+    ) -> Result<Option<(SystemTime, SystemTime)>, GetStatsError> {
+        let proto::TimeBoundsResponse { oldest, newest } =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .time_bounds(Request::new(proto::TimeBoundsRequest { kind: kind.map(i32::from) }))
+                .await
+                .map_err(|source| GetStatsError::Status { source })?
+                .into_inner();
+        Ok(oldest.zip(newest).and_then(|(oldest, newest)| {
+            Some((SystemTime::try_from(oldest).ok()?, SystemTime::try_from(newest).ok()?))
+        }))
+    }
+
+    async fn sync_changes(
+        &self,
+        since_cursor: Option<u64>,
+    ) -> Result<ClipboardEventStream, WatchClipError> {
+        self.sync_changes_with(WatchOptions { since_cursor, ..WatchOptions::default() }).await
+    }
+
+    async fn sync_changes_with(
+        &self,
+        WatchOptions { since_cursor, heartbeat_interval, metadata_preview_length, .. }: WatchOptions,
+    ) -> Result<ClipboardEventStream, WatchClipError> {
+        let heartbeat_interval_ms = heartbeat_interval
+            .map(|interval| u64::try_from(interval.as_millis()).unwrap_or(u64::MAX));
+        let metadata_preview_length =
+            metadata_preview_length.map(|length| u64::try_from(length).unwrap_or(u64::MAX));
+        let stream =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .watch(Request::new(proto::WatchRequest {
+                    since_cursor,
+                    heartbeat_interval_ms,
+                    metadata_preview_length,
+                }))
+                .await
+                .map_err(|source| WatchClipError::Status { source })?
+                .into_inner();
+        Ok(stream
+            .take_until(self.closed())
+            .filter_map(|event| async move {
+                match event {
+                    Ok(proto::ClipboardEvent { shutdown: true, .. }) => {
+                        Some(Err(WatchClipError::ServerShutdown))
+                    }
+                    Ok(proto::ClipboardEvent { cursor, change, .. }) => {
+                        change.map(|change| Ok(ClipboardEvent { cursor, change: change.into() }))
+                    }
+                    Err(source) => Some(Err(WatchClipError::Status { source })),
+                }
+            })
+            .boxed())
+    }
+
+    async fn diff_since(&self, cursor: u64) -> Result<HistoryDelta, DiffError> {
+        let delta =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .diff_since(Request::new(proto::DiffSinceRequest { cursor }))
+                .await
+                .map_err(|source| DiffError::Status { source })?
+                .into_inner();
+        Ok(delta.into())
+    }
+
+    async fn watch_current(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<CurrentClipStream, WatchClipError> {
+        let stream =
+            proto::ManagerClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .watch_current(Request::new(proto::WatchCurrentRequest { kind: kind.into() }))
+                .await
+                .map_err(|source| WatchClipError::Status { source })?
+                .into_inner();
+        Ok(stream
+            .take_until(self.closed())
+            .map(|event| match event {
+                Ok(proto::CurrentClipEvent { shutdown: true, .. }) => {
+                    Err(WatchClipError::ServerShutdown)
+                }
+                Ok(proto::CurrentClipEvent { entry, .. }) => Ok(entry.map(ClipEntry::from)),
+                Err(source) => Err(WatchClipError::Status { source }),
+            })
+            .boxed())
+    }
+}
+
+// the detail carries the mime type which can not be stored into the clipboard
+fn unsupported_mime(status: &tonic::Status) -> MarkClipError {
+    let mime = proto::ErrorDetail::from_status(status)
+        .and_then(|detail| detail.mime)
+        .and_then(|mime| mime.parse().ok())
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+    MarkClipError::UnsupportedMime { mime }
+}
+
+async fn update_clip(
+    client: &Client,
+    request: proto::UpdateRequest,
+) -> Result<(bool, u64), UpdateClipError> {
+    let (id, expected_version, mime) = (request.id, request.expected_version, request.mime.clone());
+    let proto::UpdateResponse { ok, new_id } =
+        proto::ManagerClient::with_interceptor(client.channel(), client.interceptor.clone())
+            .update(Request::new(request))
+            .await
+            .map_err(|source| match (error_code(&source), expected_version) {
+                (proto::ErrorCode::InvalidMime | proto::ErrorCode::InvalidArgument, _) => {
+                    UpdateClipError::InvalidMime { value: mime }
+                }
+                (proto::ErrorCode::VersionConflict, Some(expected_version)) => {
+                    UpdateClipError::VersionConflict { id, expected_version }
+                }
+                _ => UpdateClipError::Status { source },
+            })?
+            .into_inner();
+    Ok((ok, new_id))
+}
+
+// returns `None` if `token` is cancelled before `future` completes, `future` is
+// dropped then, which cancels its gRPC request
+async fn until_cancelled<F>(token: &CancellationToken, future: F) -> Option<F::Output>
+where
+    F: std::future::Future + Send,
+{
+    tokio::select! {
+        biased;
+        () = token.cancelled() => None,
+        output = future => Some(output),
+    }
+}
+
+async fn list_metadata(
+    client: &Client,
+    request: proto::ListRequest,
+) -> Result<Vec<ClipEntryMetadata>, ListClipError> {
+    list_metadata_with_skipped(client, request).await.map(|(list, _skipped)| list)
+}
+
+async fn list_metadata_with_skipped(
+    client: &Client,
+    request: proto::ListRequest,
+) -> Result<(Vec<ClipEntryMetadata>, usize), ListClipError> {
+    let proto::ListResponse { metadata, sorted, skipped } =
+        proto::ManagerClient::with_interceptor(client.channel(), client.interceptor.clone())
+            .list(Request::new(request))
+            .await
+            .map_err(|source| ListClipError::Status { source })?
+            .into_inner();
+    let mut list: Vec<_> = metadata.into_iter().map(ClipEntryMetadata::from).collect();
+    // the list is sorted by the server, avoid sorting it again
+    if !sorted {
+        list.sort_unstable();
+    }
+    Ok((list, usize::try_from(skipped).unwrap_or(usize::MAX)))
+}
+
+async fn insert_clip(
+    client: &Client,
+    ClipEntryBuilder {
+        data,
+        mime,
+        kind: clipboard_kind,
+        tags,
+        attributes,
+        ttl,
+        silent,
+        propagate,
+        private,
+        position,
+        force_new,
+    }: ClipEntryBuilder,
+) -> Result<(u64, Vec<u64>, mime::Mime), InsertClipError> {
+    let ttl_ms = ttl.map(|ttl| u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX));
+    let proto::InsertResponse { id, evicted, mime: stored_mime } =
+        proto::ManagerClient::with_interceptor(client.channel(), client.interceptor.clone())
+            .insert(Request::new(proto::InsertRequest {
+                kind: clipboard_kind.into(),
+                data,
+                mime: mime.to_string(),
+                tags,
+                ttl_ms,
+                silent,
+                attributes: attributes.into_iter().collect(),
+                propagate,
+                idempotency_key: None,
+                private,
+                position: position.map(|position| position as u64),
+                force_new,
+            }))
+            .await
+            .map_err(|source| match error_code(&source) {
+                proto::ErrorCode::InvalidMime | proto::ErrorCode::InvalidArgument => {
+                    InsertClipError::InvalidMime { value: mime.to_string() }
+                }
+                proto::ErrorCode::KindDisabled => {
+                    InsertClipError::KindDisabled { kind: clipboard_kind }
+                }
+                proto::ErrorCode::AttributesTooLarge => InsertClipError::AttributesTooLarge {
+                    limit: proto::ErrorDetail::from_status(&source)
+                        .and_then(|detail| detail.limit)
+                        .unwrap_or_default(),
+                },
+                proto::ErrorCode::RateLimited => rate_limited(&source),
+                proto::ErrorCode::TooLarge => too_large(&source),
+                proto::ErrorCode::HistoryFull => history_full(&source),
+                _ => InsertClipError::Status { source },
+            })?
+            .into_inner();
+    // servers before the stored mime type is returned leave it empty
+    let stored_mime = stored_mime.parse().unwrap_or(mime);
+    Ok((id, evicted, stored_mime))
+}
+
+// the usage is 0 in the responses of servers before it is reported
+fn rate_limited(source: &tonic::Status) -> InsertClipError {
+    let detail = proto::ErrorDetail::from_status(source).unwrap_or_default();
+    InsertClipError::RateLimited {
+        count: detail.usage.unwrap_or_default(),
+        limit: detail.limit.unwrap_or_default(),
+        retry_after: Duration::from_millis(detail.retry_after_ms.unwrap_or_default()),
+    }
+}
+
+fn too_large(source: &tonic::Status) -> InsertClipError {
+    let detail = proto::ErrorDetail::from_status(source).unwrap_or_default();
+    InsertClipError::TooLarge {
+        size: detail.usage.unwrap_or_default(),
+        limit: detail.limit.unwrap_or_default(),
+        rule: detail.mime.unwrap_or_default(),
+    }
+}
+
+fn history_full(source: &tonic::Status) -> InsertClipError {
+    let detail = proto::ErrorDetail::from_status(source).unwrap_or_default();
+    InsertClipError::Full {
+        count: detail.usage.unwrap_or_default(),
+        capacity: detail.limit.unwrap_or_default(),
+    }
 }
@@ -2,18 +2,36 @@ use async_trait::async_trait;
 use clipcat_proto as proto;
 use tonic::Request;
 
-use crate::{error::GetSystemVersionError, Client};
+use crate::{
+    error::{GetServerInfoError, GetSystemVersionError},
+    Client,
+};
 
 #[async_trait]
 pub trait System {
     async fn get_version(&self) -> Result<semver::Version, GetSystemVersionError>;
+
+    async fn get_server_info(&self) -> Result<clipcat_base::ServerInfo, GetServerInfoError>;
+
+    /// Returns `true` if the server supports `feature`, like
+    /// [`clipcat_base::features::SEARCH`], to fall back gracefully before
+    /// calling a newer method.
+    ///
+    /// Returns `false` if the server information could not be retrieved or
+    /// the server is older than the feature list.
+    async fn supports(&self, feature: &str) -> bool
+    where
+        Self: Sync,
+    {
+        self.get_server_info().await.is_ok_and(|info| info.supports(feature))
+    }
 }
 
 #[async_trait]
 impl System for Client {
     async fn get_version(&self) -> Result<semver::Version, GetSystemVersionError> {
         let proto::GetSystemVersionResponse { major, minor, patch } =
-            proto::SystemClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::SystemClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .get_version(Request::new(()))
                 .await
                 .map_err(|source| GetSystemVersionError::Status { source })?
@@ -26,4 +44,12 @@ impl System for Client {
             build: semver::BuildMetadata::EMPTY,
         })
     }
+
+    async fn get_server_info(&self) -> Result<clipcat_base::ServerInfo, GetServerInfoError> {
+        proto::SystemClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_server_info(Request::new(()))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| GetServerInfoError::Status { source })
+    }
 }
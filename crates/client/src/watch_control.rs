@@ -0,0 +1,175 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use clipcat_base::{ClipboardChange, ClipboardEvent};
+use futures::StreamExt;
+
+use crate::manager::ClipboardEventStream;
+
+/// The number of changes kept while a stream of
+/// [`Manager::sync_changes_pausable`] is paused, a
+/// [`ClipboardChange::Resync`] is sent in place of them beyond it.
+///
+/// [`Manager::sync_changes_pausable`]: crate::Manager::sync_changes_pausable
+pub const PAUSED_CHANGES_LIMIT: usize = 1024;
+
+/// Pauses and resumes a stream of
+/// [`Manager::sync_changes_pausable`](crate::Manager::sync_changes_pausable).
+///
+/// Cloning a `WatchControl` is cheap, all clones control the same stream. The
+/// stream is resumed when all clones are dropped.
+#[derive(Clone, Debug)]
+pub struct WatchControl {
+    paused: Arc<tokio::sync::watch::Sender<bool>>,
+}
+
+impl WatchControl {
+    pub(crate) fn new(paused: bool) -> (Self, tokio::sync::watch::Receiver<bool>) {
+        let (sender, receiver) = tokio::sync::watch::channel(paused);
+        (Self { paused: Arc::new(sender) }, receiver)
+    }
+
+    /// Stops yielding changes, they are kept until the stream is resumed.
+    pub fn pause(&self) { let _ = self.paused.send_replace(true); }
+
+    /// Yields the changes kept while the stream was paused, then the new ones.
+    pub fn resume(&self) { let _ = self.paused.send_replace(false); }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool { *self.paused.borrow() }
+}
+
+struct State {
+    changes: ClipboardEventStream,
+
+    // `None` once all controls are dropped
+    paused: Option<tokio::sync::watch::Receiver<bool>>,
+
+    kept: VecDeque<ClipboardEvent>,
+
+    // the cursor of the last change dropped beyond the limit
+    dropped_cursor: Option<u64>,
+
+    limit: usize,
+}
+
+impl State {
+    fn is_paused(&self) -> bool { self.paused.as_ref().is_some_and(|paused| *paused.borrow()) }
+
+    fn keep(&mut self, event: ClipboardEvent) {
+        if event.change == ClipboardChange::Heartbeat {
+            return;
+        }
+        if self.dropped_cursor.is_some() || self.kept.len() >= self.limit {
+            self.kept.clear();
+            self.dropped_cursor = Some(event.cursor);
+        } else {
+            self.kept.push_back(event);
+        }
+    }
+}
+
+/// Wraps `changes` so they are kept while `paused` is set, at most `limit` of
+/// them, a [`ClipboardChange::Resync`] is yielded in place of them beyond it.
+///
+/// The wrapped stream is read while paused, so the server does not drop the
+/// subscription for lagging behind. Errors are yielded at once.
+pub fn pausable(
+    changes: ClipboardEventStream,
+    paused: tokio::sync::watch::Receiver<bool>,
+    limit: usize,
+) -> ClipboardEventStream {
+    let state =
+        State { changes, paused: Some(paused), kept: VecDeque::new(), dropped_cursor: None, limit };
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            let is_paused = state.is_paused();
+            if !is_paused {
+                if let Some(cursor) = state.dropped_cursor.take() {
+                    let change = ClipboardChange::Resync;
+                    return Some((Ok(ClipboardEvent { cursor, change }), state));
+                }
+                if let Some(event) = state.kept.pop_front() {
+                    return Some((Ok(event), state));
+                }
+            }
+
+            let Some(paused) = state.paused.as_mut() else {
+                // the stream is never paused again
+                let event = state.changes.next().await?;
+                return Some((event, state));
+            };
+            tokio::select! {
+                event = state.changes.next() => match event? {
+                    Ok(event) if is_paused => state.keep(event),
+                    event => return Some((event, state)),
+                },
+                pause_changed = paused.changed() => {
+                    if pause_changed.is_err() {
+                        state.paused = None;
+                    }
+                }
+            }
+        }
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clipcat_base::{ClipboardChange, ClipboardEvent};
+    use futures::StreamExt;
+
+    use super::{pausable, WatchControl};
+    use crate::manager::ClipboardEventStream;
+
+    async fn next(changes: &mut ClipboardEventStream) -> Option<ClipboardEvent> {
+        tokio::time::timeout(Duration::from_millis(10), changes.next())
+            .await
+            .ok()
+            .flatten()
+            .map(Result::unwrap)
+    }
+
+    fn removed(cursor: u64) -> ClipboardEvent {
+        ClipboardEvent { cursor, change: ClipboardChange::Removed(cursor) }
+    }
+
+    #[tokio::test]
+    async fn test_pausable() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let (control, paused) = WatchControl::new(true);
+        let mut changes = pausable(receiver.boxed(), paused, 2);
+        sender.unbounded_send(Ok(removed(1))).unwrap();
+        sender.unbounded_send(Ok(removed(2))).unwrap();
+        assert!(next(&mut changes).await.is_none());
+        control.resume();
+        assert_eq!(next(&mut changes).await, Some(removed(1)));
+        assert_eq!(next(&mut changes).await, Some(removed(2)));
+        sender.unbounded_send(Ok(removed(3))).unwrap();
+        assert_eq!(next(&mut changes).await, Some(removed(3)));
+
+        // the kept changes are replaced by a resync beyond the limit
+        control.pause();
+        assert!(control.is_paused());
+        for cursor in 4..=6 {
+            sender.unbounded_send(Ok(removed(cursor))).unwrap();
+        }
+        assert!(next(&mut changes).await.is_none());
+        control.resume();
+        assert_eq!(
+            next(&mut changes).await,
+            Some(ClipboardEvent { cursor: 6, change: ClipboardChange::Resync })
+        );
+
+        // dropping the controls resumes the stream
+        control.pause();
+        sender.unbounded_send(Ok(removed(7))).unwrap();
+        assert!(next(&mut changes).await.is_none());
+        drop(control);
+        assert_eq!(next(&mut changes).await, Some(removed(7)));
+        drop(sender);
+        assert!(changes.next().await.is_none());
+    }
+}
@@ -1,10 +1,13 @@
 use async_trait::async_trait;
-use clipcat_base::ClipboardWatcherState;
+use clipcat_base::{ClipboardWatcherState, Diagnostics};
 use clipcat_proto as proto;
 use tonic::Request;
 
 use crate::{
-    error::{DisableWatcherError, EnableWatcherError, GetWatcherStateError, ToggleWatcherError},
+    error::{
+        CaptureError, DisableWatcherError, EnableWatcherError, GetDiagnosticsError,
+        GetWatcherStateError, ToggleWatcherError,
+    },
     Client,
 };
 
@@ -17,13 +20,28 @@ pub trait Watcher {
     async fn toggle_watcher(&self) -> Result<ClipboardWatcherState, ToggleWatcherError>;
 
     async fn get_watcher_state(&self) -> Result<ClipboardWatcherState, GetWatcherStateError>;
+
+    /// Pauses or resumes capturing clips, e.g. during sensitive work.
+    ///
+    /// The server still serves the history while capturing is paused, but
+    /// records no new clips. The state is reported in
+    /// [`ServerInfo::capture_paused`].
+    ///
+    /// [`ServerInfo::capture_paused`]: clipcat_base::ServerInfo::capture_paused
+    async fn set_capture_enabled(&self, enabled: bool) -> Result<(), CaptureError>;
+
+    async fn is_capture_enabled(&self) -> Result<bool, CaptureError>;
+
+    /// Reports the health of the clipboard watcher of the server, including
+    /// the last capture time of each clipboard and the latest errors.
+    async fn diagnostics(&self) -> Result<Diagnostics, GetDiagnosticsError>;
 }
 
 #[async_trait]
 impl Watcher for Client {
     async fn enable_watcher(&self) -> Result<ClipboardWatcherState, EnableWatcherError> {
         let proto::WatcherStateReply { state } =
-            proto::WatcherClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .enable_watcher(Request::new(()))
                 .await
                 .map_err(|source| EnableWatcherError::Status { source })?
@@ -33,7 +51,7 @@ impl Watcher for Client {
 
     async fn disable_watcher(&self) -> Result<ClipboardWatcherState, DisableWatcherError> {
         let proto::WatcherStateReply { state } =
-            proto::WatcherClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .disable_watcher(Request::new(()))
                 .await
                 .map_err(|source| DisableWatcherError::Status { source })?
@@ -43,7 +61,7 @@ impl Watcher for Client {
 
     async fn toggle_watcher(&self) -> Result<ClipboardWatcherState, ToggleWatcherError> {
         let proto::WatcherStateReply { state } =
-            proto::WatcherClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .toggle_watcher(Request::new(()))
                 .await
                 .map_err(|source| ToggleWatcherError::Status { source })?
@@ -53,11 +71,41 @@ impl Watcher for Client {
 
     async fn get_watcher_state(&self) -> Result<ClipboardWatcherState, GetWatcherStateError> {
         let proto::WatcherStateReply { state } =
-            proto::WatcherClient::with_interceptor(self.channel.clone(), self.interceptor.clone())
+            proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone())
                 .get_watcher_state(Request::new(()))
                 .await
                 .map_err(|source| GetWatcherStateError::Status { source })?
                 .into_inner();
         Ok(state.into())
     }
+
+    async fn set_capture_enabled(&self, enabled: bool) -> Result<(), CaptureError> {
+        let mut client =
+            proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone());
+        let response = if enabled {
+            client.enable_watcher(Request::new(())).await
+        } else {
+            client.disable_watcher(Request::new(())).await
+        };
+        let _state = response.map_err(|source| CaptureError::Status { source })?;
+        Ok(())
+    }
+
+    async fn is_capture_enabled(&self) -> Result<bool, CaptureError> {
+        let proto::WatcherStateReply { state } =
+            proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone())
+                .get_watcher_state(Request::new(()))
+                .await
+                .map_err(|source| CaptureError::Status { source })?
+                .into_inner();
+        Ok(ClipboardWatcherState::from(state) == ClipboardWatcherState::Enabled)
+    }
+
+    async fn diagnostics(&self) -> Result<Diagnostics, GetDiagnosticsError> {
+        proto::WatcherClient::with_interceptor(self.channel(), self.interceptor.clone())
+            .get_diagnostics(Request::new(()))
+            .await
+            .map(|response| response.into_inner().into())
+            .map_err(|source| GetDiagnosticsError::Status { source })
+    }
 }
@@ -148,6 +148,33 @@ impl Clipboard {
     }
 }
 
+impl Clipboard {
+    /// Returns the targets offered by the owner of the selection, like
+    /// `UTF8_STRING` and `text/html`, or `None` if the listener does not know
+    /// them, like on macOS.
+    ///
+    /// # Errors
+    pub fn targets(&self) -> Result<Option<Vec<String>>, Error> {
+        #[cfg(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        ))]
+        {
+            self.listener.subscribe().map(|subscriber| Some(subscriber.latest_targets()))
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            Ok(None)
+        }
+    }
+}
+
 impl ClipboardSubscribe for Clipboard {
     type Subscriber = Subscriber;
 
@@ -5,7 +5,7 @@ mod local;
 mod pubsub;
 mod traits;
 
-pub use clipcat_base::ClipboardKind;
+pub use clipcat_base::{ClipboardKind, WindowInfo};
 
 #[cfg(all(
     unix,
@@ -34,3 +34,19 @@ pub enum ListenerKind {
     X11,
     Wayland,
 }
+
+/// A change of the clipboard reported by [`ClipboardWait::wait_for_change`].
+#[derive(Clone, Debug)]
+pub struct SelectionChange {
+    pub kind: ClipboardKind,
+
+    /// The mime type in which the content is loaded.
+    pub mime: mime::Mime,
+
+    /// The targets offered by the owner of the selection, like `UTF8_STRING`
+    /// and `text/html` on X11, empty if they are not known.
+    pub targets: Vec<String>,
+
+    /// The window which owns the selection, `None` if it is not known.
+    pub source_window: Option<WindowInfo>,
+}
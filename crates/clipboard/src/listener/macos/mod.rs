@@ -107,7 +107,7 @@ fn build_thread(
                     unsafe { objc::msg_send![pasteboard, dataForType: NSPasteboardTypeTIFF] };
 
                 let mime = if obj.is_some() { mime::IMAGE_PNG } else { mime::TEXT_PLAIN_UTF_8 };
-                notifier.notify_all(mime);
+                notifier.notify_all(mime, Vec::new(), None);
             }
 
             drop(notifier);
@@ -84,19 +84,22 @@ fn build_thread(
 
                 match wl_clipboard_get_mime_types(clipboard_type, Seat::Unspecified) {
                     Ok(mime_types) => {
+                        let targets = mime_types.iter().cloned().collect::<Vec<_>>();
                         let mut mime_types = mime_types.into_iter().collect::<Vec<_>>();
                         mime_types.sort_unstable_by_key(|format| {
                             if format.starts_with("image") {
                                 1
-                            } else if format.starts_with("text") {
+                            } else if format == "text/uri-list" {
                                 2
+                            } else if format.starts_with("text") {
+                                3
                             } else {
                                 u8::MAX
                             }
                         });
                         for mime_type in mime_types {
                             if let Ok(mime) = mime_type.parse() {
-                                notifier.notify_all(mime);
+                                notifier.notify_all(mime, targets, None);
                                 break;
                             }
                         }
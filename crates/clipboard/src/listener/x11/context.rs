@@ -8,6 +8,7 @@ use std::{
 use snafu::ResultExt;
 use x11rb::{
     connection::Connection,
+    properties::WmClass,
     protocol::{
         xfixes,
         xfixes::ConnectionExt as _,
@@ -176,6 +177,68 @@ impl Context {
         Ok(Vec::new())
     }
 
+    /// Returns the instance and class names in `WM_CLASS` of `window`, or of
+    /// its client leader if `window` has no `WM_CLASS`.
+    ///
+    /// Returns nothing if the names could not be retrieved.
+    pub fn get_window_class(&self, window: xproto::Window) -> Vec<String> {
+        let get_class = |window| {
+            WmClass::get(&self.connection, window).ok()?.reply_unchecked().ok().flatten().map(
+                |class| {
+                    [class.instance(), class.class()]
+                        .iter()
+                        .filter(|name| !name.is_empty())
+                        .map(|name| String::from_utf8_lossy(name).to_string())
+                        .collect::<Vec<_>>()
+                },
+            )
+        };
+        if let Some(names) = get_class(window) {
+            return names;
+        }
+        self.get_client_leader(window).and_then(get_class).unwrap_or_default()
+    }
+
+    /// Returns the title in `_NET_WM_NAME` or `WM_NAME` of `window`, or of its
+    /// client leader if `window` has no title.
+    ///
+    /// Returns `None` if the title could not be retrieved.
+    pub fn get_window_title(&self, window: xproto::Window) -> Option<String> {
+        let get_title = |window| {
+            [
+                (self.atom_cache.net_wm_name, self.atom_cache.utf8_string),
+                (xproto::AtomEnum::WM_NAME.into(), xproto::AtomEnum::ANY.into()),
+            ]
+            .into_iter()
+            .find_map(|(property, type_)| {
+                let reply = self
+                    .connection
+                    .get_property(false, window, property, type_, 0, 1024)
+                    .ok()?
+                    .reply()
+                    .ok()?;
+                let title = String::from_utf8_lossy(&reply.value).to_string();
+                (!title.is_empty()).then_some(title)
+            })
+        };
+        get_title(window).or_else(|| self.get_client_leader(window).and_then(get_title))
+    }
+
+    fn get_client_leader(&self, window: xproto::Window) -> Option<xproto::Window> {
+        self.connection
+            .get_property(
+                false,
+                window,
+                self.atom_cache.wm_client_leader,
+                xproto::AtomEnum::WINDOW,
+                0,
+                1,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().and_then(|mut windows| windows.next()))
+    }
+
     pub fn display_name(&self) -> String {
         let display_name = self.display_name.as_deref().unwrap_or(":0");
         format!("display: {display_name}")
@@ -194,6 +257,9 @@ struct AtomCache {
     primary_selection: xproto::Atom,
     secondary_selection: xproto::Atom,
     targets: xproto::Atom,
+    wm_client_leader: xproto::Atom,
+    net_wm_name: xproto::Atom,
+    utf8_string: xproto::Atom,
 }
 
 impl AtomCache {
@@ -205,6 +271,9 @@ impl AtomCache {
             primary_selection: xproto::AtomEnum::PRIMARY.into(),
             secondary_selection: xproto::AtomEnum::SECONDARY.into(),
             targets: get_intern_atom(conn, b"TARGETS")?,
+            wm_client_leader: get_intern_atom(conn, b"WM_CLIENT_LEADER")?,
+            net_wm_name: get_intern_atom(conn, b"_NET_WM_NAME")?,
+            utf8_string: get_intern_atom(conn, b"UTF8_STRING")?,
         })
     }
 }
@@ -21,12 +21,23 @@ use crate::{
     listener::x11::error::InitializeMioPollSnafu,
     pubsub::{self, Subscriber},
     traits::EventObserver,
-    ClipboardKind, ClipboardSubscribe, ListenerKind,
+    ClipboardKind, ClipboardSubscribe, ListenerKind, WindowInfo,
 };
 
 const CONTEXT_TOKEN: mio::Token = mio::Token(0);
 const MAX_RETRY_COUNT: usize = 10 * 24 * 60 * 60;
 
+// the X11 targets holding text, in the order of preference, they are all
+// captured as one `text/plain; charset=utf-8` clip
+//
+// | target          | encoding                   |
+// |-----------------|----------------------------|
+// | `UTF8_STRING`   | UTF-8                      |
+// | `COMPOUND_TEXT` | ISO 2022, mostly ASCII     |
+// | `TEXT`          | chosen by the owner        |
+// | `STRING`        | ISO Latin-1                |
+const TEXT_TARGETS: [&str; 4] = ["UTF8_STRING", "COMPOUND_TEXT", "TEXT", "STRING"];
+
 #[derive(Debug)]
 pub struct Listener {
     is_running: Arc<AtomicBool>,
@@ -119,7 +130,15 @@ fn build_thread(
                 for event in &events {
                     if event.token() == CONTEXT_TOKEN {
                         match context.poll_for_event() {
-                            Ok(X11Event::XfixesSelectionNotify(_event)) => {
+                            Ok(X11Event::XfixesSelectionNotify(event)) => {
+                                let window_class = context.get_window_class(event.owner);
+                                if clip_filter.filter_application(window_class.iter()) {
+                                    tracing::info!(
+                                        "Clipboard is owned by a denied application, ignore it"
+                                    );
+                                    continue;
+                                }
+
                                 match context.get_available_formats() {
                                     Ok(mut formats) => {
                                         // filter sensitive content
@@ -128,8 +147,15 @@ fn build_thread(
                                             continue;
                                         }
 
+                                        // keep the targets in the order of the
+                                        // owner
+                                        let targets = formats.clone();
                                         if let Some(mime) = extract_mime(&mut formats) {
-                                            notifier.notify_all(mime);
+                                            let source_window = window_info(
+                                                context.get_window_title(event.owner),
+                                                &window_class,
+                                            );
+                                            notifier.notify_all(mime, targets, source_window);
                                         }
                                     }
                                     Err(err) => {
@@ -171,6 +197,13 @@ fn build_thread(
         .expect("build thread for listening X11 clipboard")
 }
 
+// the class name is the last name in `WM_CLASS`, after the instance name
+fn window_info(title: Option<String>, window_class: &[String]) -> Option<WindowInfo> {
+    let class = window_class.last().cloned();
+    (title.is_some() || class.is_some())
+        .then(|| WindowInfo { title: title.unwrap_or_default(), class: class.unwrap_or_default() })
+}
+
 // SAFETY: the function is complex because of `tracing`
 #[allow(clippy::cognitive_complexity)]
 #[inline]
@@ -233,17 +266,20 @@ fn extract_mime(formats: &mut Vec<String>) -> Option<mime::Mime> {
             1
         } else if format.starts_with("image") {
             2
-        } else if format.starts_with("text") {
+        } else if format == "text/uri-list" {
+            // copied files, other text formats hold their paths as plain text
             3
-        } else if format == "UTF8_STRING" {
+        } else if format.starts_with("text") {
             4
+        } else if let Some(index) = TEXT_TARGETS.iter().position(|target| target == format) {
+            5 + u8::try_from(index).unwrap_or(0)
         } else {
             u8::MAX
         }
     });
 
     for format in formats.iter() {
-        if format == "UTF8_STRING" {
+        if TEXT_TARGETS.contains(&format.as_str()) {
             return Some(mime::TEXT_PLAIN_UTF_8);
         }
         if let Ok(mime) = format.parse() {
@@ -68,7 +68,7 @@ impl ClipboardStore for Clipboard {
         match self.data.write() {
             Ok(mut data) => {
                 *data = Some(content);
-                self.publisher.notify_all(mime);
+                self.publisher.notify_all(mime, Vec::new(), None);
                 Ok(())
             }
             Err(_err) => Err(Error::PrimitivePoisoned),
@@ -2,9 +2,11 @@ use std::sync::Arc;
 
 use parking_lot::{Condvar, Mutex};
 
-use crate::{ClipboardKind, ClipboardWait, Error};
+use crate::{ClipboardKind, ClipboardWait, Error, SelectionChange, WindowInfo};
 
-type StateData = Mutex<(State, Option<mime::Mime>)>;
+// the mime type of the content, the targets offered by the owner of the
+// selection and its window
+type StateData = Mutex<(State, Option<(mime::Mime, Vec<String>, Option<WindowInfo>)>)>;
 
 pub fn new(kind: ClipboardKind) -> (Publisher, Subscriber) {
     let inner = Arc::new((Mutex::new((State::Running, None)), Condvar::new()));
@@ -23,9 +25,14 @@ enum State {
 pub struct Publisher(Arc<(StateData, Condvar)>);
 
 impl Publisher {
-    pub fn notify_all(&self, mime: mime::Mime) {
+    pub fn notify_all(
+        &self,
+        mime: mime::Mime,
+        targets: Vec<String>,
+        source_window: Option<WindowInfo>,
+    ) {
         let (lock, condvar) = &*self.0;
-        *lock.lock() = (State::Running, Some(mime));
+        *lock.lock() = (State::Running, Some((mime, targets, source_window)));
         let _unused = condvar.notify_all();
     }
 }
@@ -48,12 +55,28 @@ pub struct Subscriber {
 #[allow(clippy::significant_drop_in_scrutinee)]
 impl ClipboardWait for Subscriber {
     fn wait(&self) -> Result<(ClipboardKind, mime::Mime), Error> {
+        self.wait_with_targets().map(|(kind, mime, _targets)| (kind, mime))
+    }
+
+    fn wait_with_targets(&self) -> Result<(ClipboardKind, mime::Mime, Vec<String>), Error> {
+        self.wait_for_change()
+            .map(|SelectionChange { kind, mime, targets, .. }| (kind, mime, targets))
+    }
+
+    fn wait_for_change(&self) -> Result<SelectionChange, Error> {
         let (lock, condvar) = &*self.inner;
         let result = {
             let mut state = lock.lock();
             condvar.wait(&mut state);
             match *state {
-                (State::Running, Some(ref mime)) => Ok((self.kind, mime.clone())),
+                (State::Running, Some((ref mime, ref targets, ref source_window))) => {
+                    Ok(SelectionChange {
+                        kind: self.kind,
+                        mime: mime.clone(),
+                        targets: targets.clone(),
+                        source_window: source_window.clone(),
+                    })
+                }
                 (State::Running | State::Stopped, _) => Err(Error::NotifierClosed),
             }
         };
@@ -65,4 +88,12 @@ impl Subscriber {
     #[inline]
     #[must_use]
     pub const fn clipboard_kind(&self) -> ClipboardKind { self.kind }
+
+    /// Returns the targets offered by the owner of the selection at the last
+    /// change, without waiting for a change, empty if no change is seen yet.
+    #[must_use]
+    pub fn latest_targets(&self) -> Vec<String> {
+        let (lock, _condvar) = &*self.inner;
+        lock.lock().1.as_ref().map(|(_mime, targets, _window)| targets.clone()).unwrap_or_default()
+    }
 }
@@ -1,6 +1,6 @@
 use clipcat_base::ClipboardContent;
 
-use crate::{ClipboardKind, Error, ListenerKind};
+use crate::{ClipboardKind, Error, ListenerKind, SelectionChange};
 
 pub trait Load {
     /// # Errors
@@ -20,6 +20,28 @@ pub trait Store {
 pub trait Wait {
     /// # Errors
     fn wait(&self) -> Result<(ClipboardKind, mime::Mime), Error>;
+
+    /// Waits like [`Wait::wait`], also returns the targets offered by the owner
+    /// of the selection, like `UTF8_STRING` and `text/html` on X11, empty if
+    /// they are not known.
+    ///
+    /// # Errors
+    fn wait_with_targets(&self) -> Result<(ClipboardKind, mime::Mime, Vec<String>), Error> {
+        self.wait().map(|(kind, mime)| (kind, mime, Vec::new()))
+    }
+
+    /// Waits like [`Wait::wait`], also returns the targets and the window
+    /// which owns the selection, if they are known.
+    ///
+    /// # Errors
+    fn wait_for_change(&self) -> Result<SelectionChange, Error> {
+        self.wait_with_targets().map(|(kind, mime, targets)| SelectionChange {
+            kind,
+            mime,
+            targets,
+            source_window: None,
+        })
+    }
 }
 
 pub trait Subscribe: Send + Sync {
@@ -21,7 +21,7 @@ pub struct Entry {
 
 impl From<clipcat_base::ClipEntry> for Entry {
     fn from(entry: clipcat_base::ClipEntry) -> Self {
-        let mime = entry.mime().essence_str().to_owned();
+        let mime = entry.mime_full().to_owned();
         let data = entry.encoded().unwrap_or_default();
         let id = entry.id();
         let kind = entry.kind();
@@ -51,9 +51,15 @@ pub struct EntryMetadata {
 
 impl From<clipcat_base::ClipEntryMetadata> for EntryMetadata {
     fn from(metadata: clipcat_base::ClipEntryMetadata) -> Self {
-        let clipcat_base::ClipEntryMetadata { id, kind: clipboard_kind, timestamp, mime, preview } =
-            metadata;
-        let mime = mime.essence_str().to_owned();
+        let clipcat_base::ClipEntryMetadata {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            mime,
+            preview,
+            ..
+        } = metadata;
+        let mime = mime.to_string();
         let timestamp = timestamp.unix_timestamp();
         Self { id, preview, kind: clipboard_kind.into(), mime, timestamp }
     }
@@ -65,6 +71,19 @@ impl From<EntryMetadata> for clipcat_base::ClipEntryMetadata {
             .unwrap_or_else(|_| OffsetDateTime::now_utc());
         let clipboard_kind = clipcat_base::ClipboardKind::from(kind);
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self { id, kind: clipboard_kind, timestamp, mime, preview }
+        // the creation time, the etag, the label, the privacy and whether the
+        // clip is a sticky note are not sent over D-Bus
+        Self {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            created_at: timestamp,
+            mime,
+            preview,
+            etag: 0,
+            label: None,
+            private: false,
+            sticky: false,
+        }
     }
 }
@@ -3,6 +3,8 @@ use prost_build::Config;
 fn prost_config() -> Config {
     let mut config = Config::new();
     let _ = config.protoc_arg("--experimental_allow_proto3_optional");
+    // large contents are moved into and out of the messages without copying
+    let _ = config.bytes([".clipcat.ClipEntry.data", ".clipcat.InsertRequest.data"]);
     config
 }
 
@@ -10,7 +12,7 @@ fn prost_config() -> Config {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure().compile_with_config(
         prost_config(),
-        &["proto/manager.proto", "proto/system.proto", "proto/watcher.proto"],
+        &["proto/error.proto", "proto/manager.proto", "proto/system.proto", "proto/watcher.proto"],
         &["proto/"],
     )?;
     Ok(())
@@ -28,18 +28,52 @@ use std::str::FromStr;
 
 use time::OffsetDateTime;
 
-pub use self::proto::{
-    manager_client::ManagerClient,
-    manager_server::{Manager, ManagerServer},
-    system_client::SystemClient,
-    system_server::{System, SystemServer},
-    watcher_client::WatcherClient,
-    watcher_server::{Watcher, WatcherServer},
-    BatchRemoveRequest, BatchRemoveResponse, ClipEntry, ClipEntryMetadata, ClipboardKind,
-    GetCurrentClipRequest, GetCurrentClipResponse, GetRequest, GetResponse,
-    GetSystemVersionResponse, InsertRequest, InsertResponse, LengthResponse, ListRequest,
-    ListResponse, MarkRequest, MarkResponse, RemoveRequest, RemoveResponse, UpdateRequest,
-    UpdateResponse, WatcherState, WatcherStateReply,
+pub use self::{
+    proto::{
+        clip_op, clipboard_event,
+        manager_client::ManagerClient,
+        manager_server::{Manager, ManagerServer},
+        system_client::SystemClient,
+        system_server::{System, SystemServer},
+        watcher_client::WatcherClient,
+        watcher_server::{Watcher, WatcherServer},
+        ActiveProfileResponse, ActivityBucket, ActivityRequest, ActivityResponse, AppendRequest,
+        ApplyRequest, ApplyResponse, BatchEtagsRequest, BatchEtagsResponse, BatchMetadataRequest,
+        BatchMetadataResponse, BatchRemoveRequest, BatchRemoveResponse, ClearRequest, ClipEntry,
+        ClipEntryFull, ClipEntryMetadata, ClipEtag, ClipGroup, ClipIdTimestamp, ClipMetadataRecord,
+        ClipMetadataRecords, ClipOp, ClipOpOutcome, ClipOpResult, ClipboardEvent, ClipboardKind,
+        CloseSnapshotRequest, CloseSnapshotResponse, CompactReport, ContentManifestResponse,
+        CorruptEntry, CurrentClipEvent, CurrentEntryIdResponse, CurrentFormat,
+        CurrentFormatsResponse, CurrentTargetsResponse, CycleDirection, CycleRequest,
+        CycleResponse, DedupeScope, DiagnosticError, Diagnostics, DiffSinceRequest, DrainRequest,
+        DrainResponse, DuplicateRequest, DuplicateResponse, EntriesWithContentHashRequest,
+        EntriesWithContentHashResponse, ErrorCode, ErrorDetail, Eviction, EvictionReason,
+        FirstMatchingRequest, FirstMatchingResponse, GetCurrentAnyRequest, GetCurrentClipRequest,
+        GetCurrentClipResponse, GetFullRequest, GetFullResponse, GetImageAsRequest,
+        GetImageAsResponse, GetOrInsertRequest, GetOrInsertResponse, GetRequest, GetResponse,
+        GetStreamChunk, GetStreamRequest, GetSystemVersionResponse, HistoryDelta, HistoryStats,
+        ImageFormat, ImportMetadataResponse, InsertRequest, InsertResponse, InsertWithIdRequest,
+        IsCurrentRequest, IsCurrentResponse, KindDiagnostics, LengthResponse, ListAfterRequest,
+        ListAfterResponse, ListBySemanticKindRequest, ListBySemanticKindResponse,
+        ListBySizeRequest, ListBySizeResponse, ListBySourceClassRequest, ListBySourceClassResponse,
+        ListByTagsRequest, ListByTagsResponse, ListCursor, ListGroupedRequest, ListGroupedResponse,
+        ListIdsResponse, ListMostUsedRequest, ListMostUsedResponse, ListRangeRequest,
+        ListRangeResponse, ListRequest, ListResponse, ListSinceRequest, ListSinceResponse,
+        ListSnapshotRangeRequest, ManifestEntry, MarkAsRequest, MarkKindsRequest, MarkRequest,
+        MarkResponse, MimeTypeCount, MimeTypesResponse, NeighborRequest, NeighborResponse,
+        NthRecentRequest, NthRecentResponse, OpenSnapshotResponse, PinCurrentResponse,
+        ProfileChange, ProtectRequest, ProtectResponse, RankRequest, RankResponse,
+        RemoveByContentRequest, RemoveRequest, RemoveResponse, ReorderRequest,
+        ReplaceActiveRequest, ResourceUsage, RpcStatus, SearchRequest, SearchResponse,
+        SemanticKind, ServerInfo, SetActiveProfileRequest, SetHistoryRequest, SetLabelRequest,
+        SetLabelResponse, SetSemanticKindRequest, SetSemanticKindResponse, SetStickyRequest,
+        SetStickyResponse, SetTagsOp, SetTransientRequest, SizeLimit, SizeLimits, SortKey,
+        SortOrder, StoreReport, SwapRequest, SwapResponse, TagMatch, TimeBoundsRequest,
+        TimeBoundsResponse, TouchRequest, TouchResponse, TrimToRequest, UpdateRequest,
+        UpdateResponse, VerifyStoreRequest, WaitActiveRequest, WaitActiveResponse,
+        WatchCurrentRequest, WatchRequest, WatcherState, WatcherStateReply, WindowInfo,
+    },
+    utils::{datetime_to_timestamp, timestamp_to_datetime},
 };
 
 impl From<ClipboardKind> for clipcat_base::ClipboardKind {
@@ -62,45 +96,749 @@ impl From<clipcat_base::ClipboardKind> for ClipboardKind {
     }
 }
 
+impl From<SemanticKind> for clipcat_base::SemanticKind {
+    fn from(kind: SemanticKind) -> Self {
+        match kind {
+            SemanticKind::Url => Self::Url,
+            SemanticKind::Code => Self::Code,
+            SemanticKind::Path => Self::Path,
+            SemanticKind::Number => Self::Number,
+            SemanticKind::Prose => Self::Prose,
+            SemanticKind::Other => Self::Other,
+        }
+    }
+}
+
+impl From<clipcat_base::SemanticKind> for SemanticKind {
+    fn from(kind: clipcat_base::SemanticKind) -> Self {
+        match kind {
+            clipcat_base::SemanticKind::Url => Self::Url,
+            clipcat_base::SemanticKind::Code => Self::Code,
+            clipcat_base::SemanticKind::Path => Self::Path,
+            clipcat_base::SemanticKind::Number => Self::Number,
+            clipcat_base::SemanticKind::Prose => Self::Prose,
+            clipcat_base::SemanticKind::Other => Self::Other,
+        }
+    }
+}
+
 impl From<clipcat_base::ClipEntry> for ClipEntry {
     fn from(entry: clipcat_base::ClipEntry) -> Self {
-        let mime = entry.mime().essence_str().to_owned();
-        let data = entry.encoded().unwrap_or_default();
+        let mime = entry.mime_full().to_owned();
+        let data = entry.encoded().unwrap_or_default().into();
         let id = entry.id();
         let kind = entry.kind();
-        let timestamp = utils::datetime_to_timestamp(&entry.timestamp());
+        let timestamp = datetime_to_timestamp(&entry.timestamp());
+        let created_at = datetime_to_timestamp(&entry.created_at());
 
-        Self { id, data, kind: kind.into(), mime, timestamp: Some(timestamp) }
+        Self {
+            id,
+            data,
+            kind: kind.into(),
+            mime,
+            timestamp: Some(timestamp),
+            created_at: Some(created_at),
+            owned_by_us: entry.owned_by_us(),
+            tags: entry.tags().iter().cloned().collect(),
+            expires_at: entry.expires_at().map(|ts| datetime_to_timestamp(&ts)),
+            protected_until: entry.protected_until().map(|ts| datetime_to_timestamp(&ts)),
+            version: entry.version(),
+            original_size: entry.original_size(),
+            attributes: entry.attributes().clone().into_iter().collect(),
+            label: entry.label().map(ToString::to_string),
+            available_targets: entry.available_targets().to_vec(),
+            use_count: entry.use_count(),
+            source_window: entry.source_window().cloned().map(WindowInfo::from),
+            private: entry.is_private(),
+            sticky: entry.is_sticky(),
+            auto_tags: entry.auto_tags().iter().cloned().collect(),
+            semantic_kind: entry.semantic_kind().map(|kind| SemanticKind::from(kind).into()),
+        }
     }
 }
 
 impl From<ClipEntry> for clipcat_base::ClipEntry {
-    fn from(ClipEntry { id: _, data, mime, kind, timestamp }: ClipEntry) -> Self {
-        let timestamp = timestamp.and_then(|ts| utils::timestamp_to_datetime(&ts).ok());
-        let kind = clipcat_base::ClipboardKind::from(kind);
+    fn from(
+        ClipEntry {
+            id,
+            data,
+            mime,
+            kind,
+            timestamp,
+            created_at,
+            owned_by_us,
+            tags,
+            expires_at,
+            version,
+            original_size,
+            attributes,
+            label,
+            available_targets,
+            use_count,
+            source_window,
+            private,
+            auto_tags,
+            protected_until,
+            semantic_kind,
+            sticky,
+        }: ClipEntry,
+    ) -> Self {
+        let timestamp = timestamp.and_then(|ts| timestamp_to_datetime(&ts).ok());
+        let created_at = created_at.and_then(|ts| timestamp_to_datetime(&ts).ok());
+        let kind = clipcat_base::ClipboardKind::try_from(kind).unwrap_or_default();
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self::new(&data, &mime, kind, timestamp).unwrap_or_default()
+        let mut entry = Self::new(&data, &mime, kind, timestamp).unwrap_or_default();
+        entry.set_id(id);
+        if let Some(created_at) = created_at {
+            entry.set_created_at(created_at);
+        }
+        entry.set_owned_by_us(owned_by_us);
+        entry.set_tags(tags);
+        entry.set_expires_at(expires_at.and_then(|ts| timestamp_to_datetime(&ts).ok()));
+        entry.set_protected_until(protected_until.and_then(|ts| timestamp_to_datetime(&ts).ok()));
+        entry.set_version(version);
+        entry.set_original_size(original_size);
+        entry.set_attributes(attributes);
+        entry.set_label(label);
+        entry.set_available_targets(available_targets);
+        entry.set_use_count(use_count);
+        entry.set_source_window(source_window.map(clipcat_base::WindowInfo::from));
+        entry.set_private(private);
+        entry.set_sticky(sticky);
+        entry.set_auto_tags(auto_tags);
+        entry.set_semantic_kind(
+            semantic_kind
+                .and_then(|kind| SemanticKind::try_from(kind).ok())
+                .map(clipcat_base::SemanticKind::from),
+        );
+        entry
     }
 }
 
 impl From<clipcat_base::ClipEntryMetadata> for ClipEntryMetadata {
     fn from(metadata: clipcat_base::ClipEntryMetadata) -> Self {
-        let clipcat_base::ClipEntryMetadata { id, kind: clipboard_kind, timestamp, mime, preview } =
-            metadata;
-        let mime = mime.essence_str().to_owned();
-        let timestamp = utils::datetime_to_timestamp(&timestamp);
-        Self { id, preview, kind: clipboard_kind.into(), mime, timestamp: Some(timestamp) }
+        let clipcat_base::ClipEntryMetadata {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            created_at,
+            mime,
+            preview,
+            etag,
+            label,
+            private,
+            sticky,
+        } = metadata;
+        let mime = mime.to_string();
+        let timestamp = datetime_to_timestamp(&timestamp);
+        let created_at = datetime_to_timestamp(&created_at);
+        Self {
+            id,
+            preview,
+            kind: clipboard_kind.into(),
+            mime,
+            timestamp: Some(timestamp),
+            created_at: Some(created_at),
+            etag,
+            label,
+            private,
+            sticky,
+        }
     }
 }
 
 impl From<ClipEntryMetadata> for clipcat_base::ClipEntryMetadata {
-    fn from(ClipEntryMetadata { id, mime, kind, timestamp, preview }: ClipEntryMetadata) -> Self {
+    fn from(
+        ClipEntryMetadata {
+            id,
+            mime,
+            kind,
+            timestamp,
+            preview,
+            created_at,
+            etag,
+            label,
+            private,
+            sticky,
+        }: ClipEntryMetadata,
+    ) -> Self {
         let timestamp = timestamp
-            .and_then(|ts| utils::timestamp_to_datetime(&ts).ok())
+            .and_then(|ts| timestamp_to_datetime(&ts).ok())
             .unwrap_or_else(OffsetDateTime::now_utc);
-        let clipboard_kind = clipcat_base::ClipboardKind::from(kind);
+        // servers without `created_at` only know the last update
+        let created_at =
+            created_at.and_then(|ts| timestamp_to_datetime(&ts).ok()).unwrap_or(timestamp);
+        let clipboard_kind = clipcat_base::ClipboardKind::try_from(kind).unwrap_or_default();
         let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
-        Self { id, kind: clipboard_kind, timestamp, mime, preview }
+        Self {
+            id,
+            kind: clipboard_kind,
+            timestamp,
+            created_at,
+            mime,
+            preview,
+            etag,
+            label,
+            private,
+            sticky,
+        }
+    }
+}
+
+impl From<clipcat_base::ClipboardChange> for clipboard_event::Change {
+    fn from(change: clipcat_base::ClipboardChange) -> Self {
+        match change {
+            clipcat_base::ClipboardChange::Added(entry) => Self::Added(entry.into()),
+            clipcat_base::ClipboardChange::Removed(id) => Self::Removed(id),
+            clipcat_base::ClipboardChange::Evicted { id, reason } => {
+                Self::Evicted(Eviction { id, reason: reason.into() })
+            }
+            clipcat_base::ClipboardChange::Updated(entry) => Self::Updated(entry.into()),
+            clipcat_base::ClipboardChange::AddedMetadata(metadata) => {
+                Self::AddedMetadata(metadata.into())
+            }
+            clipcat_base::ClipboardChange::UpdatedMetadata(metadata) => {
+                Self::UpdatedMetadata(metadata.into())
+            }
+            clipcat_base::ClipboardChange::Heartbeat => Self::Heartbeat(()),
+            clipcat_base::ClipboardChange::Resync => Self::Resync(()),
+            clipcat_base::ClipboardChange::ProfileChanged { profile } => {
+                Self::ProfileChanged(ProfileChange { profile })
+            }
+        }
+    }
+}
+
+impl From<clipboard_event::Change> for clipcat_base::ClipboardChange {
+    fn from(change: clipboard_event::Change) -> Self {
+        match change {
+            clipboard_event::Change::Added(entry) => Self::Added(entry.into()),
+            clipboard_event::Change::Removed(id) => Self::Removed(id),
+            clipboard_event::Change::Evicted(Eviction { id, reason }) => {
+                Self::Evicted { id, reason: reason.into() }
+            }
+            clipboard_event::Change::Updated(entry) => Self::Updated(entry.into()),
+            clipboard_event::Change::AddedMetadata(metadata) => {
+                Self::AddedMetadata(metadata.into())
+            }
+            clipboard_event::Change::UpdatedMetadata(metadata) => {
+                Self::UpdatedMetadata(metadata.into())
+            }
+            clipboard_event::Change::Heartbeat(()) => Self::Heartbeat,
+            clipboard_event::Change::Resync(()) => Self::Resync,
+            clipboard_event::Change::ProfileChanged(ProfileChange { profile }) => {
+                Self::ProfileChanged { profile }
+            }
+        }
+    }
+}
+
+impl From<clipcat_base::ClipboardEvent> for ClipboardEvent {
+    fn from(clipcat_base::ClipboardEvent { cursor, change }: clipcat_base::ClipboardEvent) -> Self {
+        Self { cursor, change: Some(change.into()), shutdown: false }
+    }
+}
+
+impl From<clipcat_base::ServerInfo> for ServerInfo {
+    fn from(
+        clipcat_base::ServerInfo {
+            version,
+            transforms,
+            denied_applications,
+            enabled_kinds,
+            dedupe_scope,
+            normalize_whitespace_for_dedupe,
+            read_only,
+            listeners,
+            max_inserts_per_second,
+            size_limits,
+            capture_paused,
+            features,
+            commit_delay,
+            max_age,
+            config_version,
+            auto_tag_rules,
+            redaction_patterns,
+        }: clipcat_base::ServerInfo,
+    ) -> Self {
+        Self {
+            version: version.to_string(),
+            transforms,
+            denied_applications,
+            enabled_kinds: enabled_kinds.into_iter().map(i32::from).collect(),
+            dedupe_scope: dedupe_scope.into(),
+            normalize_whitespace_for_dedupe,
+            read_only,
+            listeners,
+            max_inserts_per_second,
+            size_limits: size_limits.map(SizeLimits::from),
+            capture_paused,
+            features,
+            commit_delay_ms: commit_delay
+                .map(|delay| u64::try_from(delay.as_millis()).unwrap_or(u64::MAX)),
+            max_age_ms: max_age.map(|age| u64::try_from(age.as_millis()).unwrap_or(u64::MAX)),
+            config_version,
+            auto_tag_rules,
+            redaction_patterns,
+        }
+    }
+}
+
+impl From<ServerInfo> for clipcat_base::ServerInfo {
+    fn from(
+        ServerInfo {
+            version,
+            transforms,
+            denied_applications,
+            enabled_kinds,
+            dedupe_scope,
+            normalize_whitespace_for_dedupe,
+            read_only,
+            listeners,
+            max_inserts_per_second,
+            size_limits,
+            capture_paused,
+            features,
+            commit_delay_ms,
+            max_age_ms,
+            config_version,
+            auto_tag_rules,
+            redaction_patterns,
+        }: ServerInfo,
+    ) -> Self {
+        let version =
+            semver::Version::parse(&version).unwrap_or_else(|_| semver::Version::new(0, 0, 0));
+        let enabled_kinds = enabled_kinds
+            .into_iter()
+            .filter_map(|kind| clipcat_base::ClipboardKind::try_from(kind).ok())
+            .collect();
+        Self {
+            version,
+            transforms,
+            denied_applications,
+            enabled_kinds,
+            dedupe_scope: dedupe_scope.into(),
+            normalize_whitespace_for_dedupe,
+            read_only,
+            listeners,
+            max_inserts_per_second,
+            size_limits: size_limits.map(clipcat_base::SizeLimits::from),
+            capture_paused,
+            features,
+            commit_delay: commit_delay_ms.map(std::time::Duration::from_millis),
+            max_age: max_age_ms.map(std::time::Duration::from_millis),
+            config_version,
+            auto_tag_rules,
+            redaction_patterns,
+        }
+    }
+}
+
+impl From<clipcat_base::WindowInfo> for WindowInfo {
+    fn from(clipcat_base::WindowInfo { title, class }: clipcat_base::WindowInfo) -> Self {
+        Self { title, class }
+    }
+}
+
+impl From<WindowInfo> for clipcat_base::WindowInfo {
+    fn from(WindowInfo { title, class }: WindowInfo) -> Self { Self { title, class } }
+}
+
+impl From<clipcat_base::SizeLimits> for SizeLimits {
+    fn from(clipcat_base::SizeLimits { rules, default_limit }: clipcat_base::SizeLimits) -> Self {
+        Self {
+            rules: rules.into_iter().map(|(rule, limit)| SizeLimit { rule, limit }).collect(),
+            default_limit,
+        }
+    }
+}
+
+impl From<SizeLimits> for clipcat_base::SizeLimits {
+    fn from(SizeLimits { rules, default_limit }: SizeLimits) -> Self {
+        Self {
+            rules: rules.into_iter().map(|SizeLimit { rule, limit }| (rule, limit)).collect(),
+            default_limit,
+        }
+    }
+}
+
+impl From<clipcat_base::ClipEntryFull> for ClipEntryFull {
+    fn from(
+        clipcat_base::ClipEntryFull { entry, metadata, byte_len, snippet }: clipcat_base::ClipEntryFull,
+    ) -> Self {
+        Self { entry: Some(entry.into()), metadata: Some(metadata.into()), byte_len, snippet }
+    }
+}
+
+impl From<ClipEntryFull> for clipcat_base::ClipEntryFull {
+    fn from(ClipEntryFull { entry, metadata, byte_len, snippet }: ClipEntryFull) -> Self {
+        let entry = entry.map(clipcat_base::ClipEntry::from).unwrap_or_default();
+        let metadata = metadata.map_or_else(|| entry.metadata(None), Into::into);
+        Self { entry, metadata, byte_len, snippet }
+    }
+}
+
+impl From<clipcat_base::HistoryDelta> for HistoryDelta {
+    fn from(
+        clipcat_base::HistoryDelta { added, removed, new_cursor, resync_required }: clipcat_base::HistoryDelta,
+    ) -> Self {
+        Self {
+            added: added.into_iter().map(ClipEntry::from).collect(),
+            removed,
+            new_cursor,
+            resync_required,
+        }
+    }
+}
+
+impl From<HistoryDelta> for clipcat_base::HistoryDelta {
+    fn from(HistoryDelta { added, removed, new_cursor, resync_required }: HistoryDelta) -> Self {
+        Self {
+            added: added.into_iter().map(clipcat_base::ClipEntry::from).collect(),
+            removed,
+            new_cursor,
+            resync_required,
+        }
+    }
+}
+
+impl From<clipcat_base::HistoryStats> for HistoryStats {
+    fn from(
+        clipcat_base::HistoryStats {
+            clip_count,
+            snippet_count,
+            capacity,
+            content_bytes,
+            compressed_clip_count,
+            compression_saved_bytes,
+            index_term_count,
+            index_memory_bytes,
+        }: clipcat_base::HistoryStats,
+    ) -> Self {
+        Self {
+            clip_count: clip_count as u64,
+            snippet_count: snippet_count as u64,
+            capacity: capacity as u64,
+            content_bytes: content_bytes as u64,
+            compressed_clip_count: compressed_clip_count as u64,
+            compression_saved_bytes: compression_saved_bytes as u64,
+            index_term_count: index_term_count as u64,
+            index_memory_bytes: index_memory_bytes as u64,
+        }
+    }
+}
+
+impl From<HistoryStats> for clipcat_base::HistoryStats {
+    fn from(
+        HistoryStats {
+            clip_count,
+            index_term_count,
+            index_memory_bytes,
+            snippet_count,
+            capacity,
+            content_bytes,
+            compressed_clip_count,
+            compression_saved_bytes,
+        }: HistoryStats,
+    ) -> Self {
+        Self {
+            clip_count: usize::try_from(clip_count).unwrap_or(usize::MAX),
+            snippet_count: usize::try_from(snippet_count).unwrap_or(usize::MAX),
+            capacity: usize::try_from(capacity).unwrap_or(usize::MAX),
+            content_bytes: usize::try_from(content_bytes).unwrap_or(usize::MAX),
+            compressed_clip_count: usize::try_from(compressed_clip_count).unwrap_or(usize::MAX),
+            compression_saved_bytes: usize::try_from(compression_saved_bytes).unwrap_or(usize::MAX),
+            index_term_count: usize::try_from(index_term_count).unwrap_or(usize::MAX),
+            index_memory_bytes: usize::try_from(index_memory_bytes).unwrap_or(usize::MAX),
+        }
+    }
+}
+
+impl From<clipcat_base::ResourceUsage> for ResourceUsage {
+    fn from(
+        clipcat_base::ResourceUsage { entry_count, store_bytes, compression_ratio }: clipcat_base::ResourceUsage,
+    ) -> Self {
+        Self { entry_count: entry_count as u64, store_bytes: store_bytes as u64, compression_ratio }
+    }
+}
+
+impl From<clipcat_base::ListCursor> for ListCursor {
+    fn from(
+        clipcat_base::ListCursor { sticky, timestamp, kind, id }: clipcat_base::ListCursor,
+    ) -> Self {
+        Self { timestamp: Some(datetime_to_timestamp(&timestamp)), kind: kind.into(), id, sticky }
+    }
+}
+
+impl From<ListCursor> for clipcat_base::ListCursor {
+    fn from(ListCursor { timestamp, kind, id, sticky }: ListCursor) -> Self {
+        Self {
+            sticky,
+            timestamp: timestamp
+                .and_then(|ts| timestamp_to_datetime(&ts).ok())
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            kind: clipcat_base::ClipboardKind::try_from(kind).unwrap_or_default(),
+            id,
+        }
+    }
+}
+
+impl From<clipcat_base::MetadataRecord> for ClipMetadataRecord {
+    fn from(
+        clipcat_base::MetadataRecord {
+            content_hash,
+            tags,
+            attributes,
+            label,
+            protected_until,
+            sticky,
+            semantic_kind,
+        }: clipcat_base::MetadataRecord,
+    ) -> Self {
+        Self {
+            content_hash,
+            tags: tags.into_iter().collect(),
+            attributes: attributes.into_iter().collect(),
+            label,
+            protected_until: protected_until.map(|ts| datetime_to_timestamp(&ts)),
+            sticky,
+            semantic_kind: semantic_kind.map(|kind| SemanticKind::from(kind).into()),
+        }
+    }
+}
+
+impl From<ClipMetadataRecord> for clipcat_base::MetadataRecord {
+    fn from(
+        ClipMetadataRecord {
+            content_hash,
+            tags,
+            attributes,
+            label,
+            protected_until,
+            sticky,
+            semantic_kind,
+        }: ClipMetadataRecord,
+    ) -> Self {
+        Self {
+            content_hash,
+            tags: tags.into_iter().collect(),
+            attributes: attributes.into_iter().collect(),
+            label,
+            protected_until: protected_until.and_then(|ts| timestamp_to_datetime(&ts).ok()),
+            sticky,
+            semantic_kind: semantic_kind
+                .and_then(|kind| SemanticKind::try_from(kind).ok())
+                .map(clipcat_base::SemanticKind::from),
+        }
+    }
+}
+
+impl From<clipcat_base::MetadataImportReport> for ImportMetadataResponse {
+    fn from(
+        clipcat_base::MetadataImportReport { applied, unmatched }: clipcat_base::MetadataImportReport,
+    ) -> Self {
+        Self { applied: applied as u64, unmatched }
+    }
+}
+
+impl From<ImportMetadataResponse> for clipcat_base::MetadataImportReport {
+    fn from(ImportMetadataResponse { applied, unmatched }: ImportMetadataResponse) -> Self {
+        Self { applied: usize::try_from(applied).unwrap_or(usize::MAX), unmatched }
+    }
+}
+
+impl From<ResourceUsage> for clipcat_base::ResourceUsage {
+    fn from(ResourceUsage { entry_count, store_bytes, compression_ratio }: ResourceUsage) -> Self {
+        Self {
+            entry_count: usize::try_from(entry_count).unwrap_or(usize::MAX),
+            store_bytes: usize::try_from(store_bytes).unwrap_or(usize::MAX),
+            compression_ratio,
+        }
+    }
+}
+
+impl From<clipcat_base::Diagnostics> for Diagnostics {
+    fn from(
+        clipcat_base::Diagnostics {
+            worker_running,
+            kinds,
+            recent_errors,
+            self_captures_suppressed,
+            unsaved_changes,
+            forward_failures,
+            replication_failures,
+            replication_lag,
+        }: clipcat_base::Diagnostics,
+    ) -> Self {
+        let kinds = kinds
+            .into_iter()
+            .map(
+                |clipcat_base::KindDiagnostics {
+                     kind,
+                     enabled,
+                     last_capture,
+                     targets_supported,
+                 }| {
+                    KindDiagnostics {
+                        kind: kind.into(),
+                        enabled,
+                        last_capture: last_capture.as_ref().map(datetime_to_timestamp),
+                        targets_supported,
+                    }
+                },
+            )
+            .collect();
+        let recent_errors = recent_errors
+            .into_iter()
+            .map(|clipcat_base::DiagnosticError { timestamp, kind, message }| DiagnosticError {
+                timestamp: Some(datetime_to_timestamp(&timestamp)),
+                kind: kind.map(i32::from),
+                message,
+            })
+            .collect();
+        Self {
+            worker_running,
+            kinds,
+            recent_errors,
+            self_captures_suppressed,
+            unsaved_changes,
+            forward_failures,
+            replication_failures,
+            replication_lag,
+        }
+    }
+}
+
+impl From<Diagnostics> for clipcat_base::Diagnostics {
+    fn from(
+        Diagnostics {
+            worker_running,
+            kinds,
+            recent_errors,
+            self_captures_suppressed,
+            unsaved_changes,
+            forward_failures,
+            replication_failures,
+            replication_lag,
+        }: Diagnostics,
+    ) -> Self {
+        let kinds = kinds
+            .into_iter()
+            .map(|KindDiagnostics { kind, enabled, last_capture, targets_supported }| {
+                clipcat_base::KindDiagnostics {
+                    kind: clipcat_base::ClipboardKind::try_from(kind).unwrap_or_default(),
+                    enabled,
+                    last_capture: last_capture.and_then(|ts| timestamp_to_datetime(&ts).ok()),
+                    targets_supported,
+                }
+            })
+            .collect();
+        let recent_errors = recent_errors
+            .into_iter()
+            .map(|DiagnosticError { timestamp, kind, message }| clipcat_base::DiagnosticError {
+                timestamp: timestamp
+                    .and_then(|ts| timestamp_to_datetime(&ts).ok())
+                    .unwrap_or_else(OffsetDateTime::now_utc),
+                kind: kind.and_then(|kind| clipcat_base::ClipboardKind::try_from(kind).ok()),
+                message,
+            })
+            .collect();
+        Self {
+            worker_running,
+            kinds,
+            recent_errors,
+            self_captures_suppressed,
+            unsaved_changes,
+            forward_failures,
+            replication_failures,
+            replication_lag,
+        }
+    }
+}
+
+impl From<clipcat_base::CompactReport> for CompactReport {
+    fn from(
+        clipcat_base::CompactReport { entries, bytes_before, bytes_after, image_files_removed }: clipcat_base::CompactReport,
+    ) -> Self {
+        Self {
+            entries: entries as u64,
+            bytes_before,
+            bytes_after,
+            image_files_removed: image_files_removed as u64,
+        }
+    }
+}
+
+impl From<CompactReport> for clipcat_base::CompactReport {
+    fn from(
+        CompactReport { entries, bytes_before, bytes_after, image_files_removed }: CompactReport,
+    ) -> Self {
+        Self {
+            entries: usize::try_from(entries).unwrap_or(usize::MAX),
+            bytes_before,
+            bytes_after,
+            image_files_removed: usize::try_from(image_files_removed).unwrap_or(usize::MAX),
+        }
+    }
+}
+
+impl From<clipcat_base::StoreReport> for StoreReport {
+    fn from(
+        clipcat_base::StoreReport {
+            scanned,
+            corrupt_entries,
+            unreadable_bytes,
+            repaired,
+            corrupt_clip_ids,
+        }: clipcat_base::StoreReport,
+    ) -> Self {
+        let corrupt_entries = corrupt_entries
+            .into_iter()
+            .map(|clipcat_base::CorruptEntry { index, timestamp, mime, reason }| CorruptEntry {
+                index: index as u64,
+                timestamp: Some(datetime_to_timestamp(&timestamp)),
+                mime: mime.to_string(),
+                reason,
+            })
+            .collect();
+        Self {
+            scanned: scanned as u64,
+            corrupt_entries,
+            unreadable_bytes,
+            repaired,
+            corrupt_clip_ids,
+        }
+    }
+}
+
+impl From<StoreReport> for clipcat_base::StoreReport {
+    fn from(
+        StoreReport {
+            scanned,
+            corrupt_entries,
+            unreadable_bytes,
+            repaired,
+            corrupt_clip_ids,
+        }: StoreReport,
+    ) -> Self {
+        let corrupt_entries = corrupt_entries
+            .into_iter()
+            .map(|CorruptEntry { index, timestamp, mime, reason }| clipcat_base::CorruptEntry {
+                index: usize::try_from(index).unwrap_or(usize::MAX),
+                timestamp: timestamp
+                    .and_then(|ts| timestamp_to_datetime(&ts).ok())
+                    .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+                mime: mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM),
+                reason,
+            })
+            .collect();
+        Self {
+            scanned: usize::try_from(scanned).unwrap_or(usize::MAX),
+            corrupt_entries,
+            unreadable_bytes,
+            repaired,
+            corrupt_clip_ids,
+        }
     }
 }
 
@@ -121,3 +859,116 @@ impl From<clipcat_base::ClipboardWatcherState> for WatcherState {
         }
     }
 }
+
+const ERROR_DETAIL_TYPE_URL: &str = "type.googleapis.com/clipcat.ErrorDetail";
+
+impl From<clipcat_base::ClipOp> for ClipOp {
+    fn from(op: clipcat_base::ClipOp) -> Self {
+        let op = match op {
+            clipcat_base::ClipOp::Remove { id } => clip_op::Op::Remove(id),
+            clipcat_base::ClipOp::Mark { id, kind } => {
+                clip_op::Op::Mark(MarkRequest { id, kind: kind.into() })
+            }
+            clipcat_base::ClipOp::Promote { id } => clip_op::Op::Promote(id),
+            clipcat_base::ClipOp::SetTags { id, tags } => {
+                clip_op::Op::SetTags(SetTagsOp { id, tags })
+            }
+        };
+        Self { op: Some(op) }
+    }
+}
+
+impl From<clipcat_base::ClipOpResult> for ClipOpResult {
+    fn from(result: clipcat_base::ClipOpResult) -> Self {
+        let (outcome, message) = match result {
+            clipcat_base::ClipOpResult::Applied => (ClipOpOutcome::Applied, String::new()),
+            clipcat_base::ClipOpResult::NotFound => (ClipOpOutcome::NotFound, String::new()),
+            clipcat_base::ClipOpResult::Failed { message } => (ClipOpOutcome::Failed, message),
+        };
+        Self { outcome: outcome.into(), message }
+    }
+}
+
+impl From<ClipOpResult> for clipcat_base::ClipOpResult {
+    fn from(ClipOpResult { outcome, message }: ClipOpResult) -> Self {
+        match ClipOpOutcome::try_from(outcome) {
+            Ok(ClipOpOutcome::Applied) => Self::Applied,
+            Ok(ClipOpOutcome::NotFound) => Self::NotFound,
+            Ok(ClipOpOutcome::Failed) | Err(_) => Self::Failed { message },
+        }
+    }
+}
+
+impl ErrorDetail {
+    #[must_use]
+    pub fn new(code: ErrorCode) -> Self { Self { code: code.into(), ..Self::default() } }
+
+    #[must_use]
+    pub const fn with_id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    #[must_use]
+    pub fn with_kind(mut self, kind: clipcat_base::ClipboardKind) -> Self {
+        self.kind = Some(ClipboardKind::from(kind).into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_usage(mut self, usage: u64) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_expected_version(mut self, expected_version: u64) -> Self {
+        self.expected_version = Some(expected_version);
+        self
+    }
+
+    #[must_use]
+    pub fn with_mime(mut self, mime: impl Into<String>) -> Self {
+        self.mime = Some(mime.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_retry_after(mut self, retry_after: std::time::Duration) -> Self {
+        self.retry_after_ms = Some(u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX));
+        self
+    }
+
+    /// Creates a status carrying the detail in a `google.rpc.Status`, which
+    /// clients in any language can decode.
+    #[must_use]
+    pub fn into_status(self, code: tonic::Code, message: impl Into<String>) -> tonic::Status {
+        let message = message.into();
+        let details = RpcStatus {
+            code: code.into(),
+            message: message.clone(),
+            details: vec![prost_types::Any {
+                type_url: ERROR_DETAIL_TYPE_URL.to_string(),
+                value: prost::Message::encode_to_vec(&self),
+            }],
+        };
+        tonic::Status::with_details(code, message, prost::Message::encode_to_vec(&details).into())
+    }
+
+    /// Returns the detail carried by `status`, or `None` if there is no detail,
+    /// like in the responses of older servers.
+    #[must_use]
+    pub fn from_status(status: &tonic::Status) -> Option<Self> {
+        let RpcStatus { details, .. } = prost::Message::decode(status.details()).ok()?;
+        details
+            .into_iter()
+            .find(|detail| detail.type_url == ERROR_DETAIL_TYPE_URL)
+            .and_then(|detail| prost::Message::decode(detail.value.as_slice()).ok())
+    }
+}
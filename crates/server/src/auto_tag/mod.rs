@@ -0,0 +1,87 @@
+use clipcat_base::{ClipEntry, ClipboardContent};
+use regex::Regex;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoTagRule {
+    /// A regular expression searched in the text of clips.
+    pub pattern: String,
+
+    /// The tag set on the clips matching `pattern`.
+    pub tag: String,
+}
+
+/// Tags new text clips matching the rules before they are stored, the tags
+/// are recorded in [`ClipEntry::auto_tags`] so users can tell them apart.
+#[derive(Clone, Debug, Default)]
+pub struct AutoTagger {
+    rules: Vec<(Regex, String)>,
+}
+
+impl AutoTagger {
+    /// Creates a tagger with the rules, the rules with an invalid pattern or
+    /// an empty tag are ignored.
+    pub fn new(rules: Vec<AutoTagRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter(|AutoTagRule { tag, .. }| !tag.is_empty())
+            .filter_map(|AutoTagRule { pattern, tag }| match Regex::new(&pattern) {
+                Ok(regex) => Some((regex, tag)),
+                Err(err) => {
+                    tracing::warn!("Ignore auto tag rule of `{tag}`, error: {err}");
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn tag(&self, entry: &mut ClipEntry) {
+        let ClipboardContent::Plaintext(text) = entry.as_ref() else {
+            return;
+        };
+        let tags = self
+            .rules
+            .iter()
+            .filter(|(regex, _)| regex.is_match(text))
+            .map(|(_, tag)| tag.clone())
+            .collect::<Vec<_>>();
+        if !tags.is_empty() {
+            entry.set_auto_tags(tags);
+        }
+    }
+
+    /// Describes the active rules, one rule per line like `url: https?://`.
+    pub fn descriptions(&self) -> Vec<String> {
+        self.rules.iter().map(|(regex, tag)| format!("{tag}: {}", regex.as_str())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardKind};
+
+    use super::{AutoTagRule, AutoTagger};
+
+    #[test]
+    fn test_tag() {
+        let tagger = AutoTagger::new(vec![
+            AutoTagRule { pattern: r"https?://".to_string(), tag: "url".to_string() },
+            AutoTagRule { pattern: r"\S+@\S+\.\w+".to_string(), tag: "email".to_string() },
+            AutoTagRule { pattern: r"(".to_string(), tag: "invalid".to_string() },
+        ]);
+        assert_eq!(
+            tagger.descriptions(),
+            vec!["url: https?://".to_string(), r"email: \S+@\S+\.\w+".to_string()]
+        );
+
+        let mut entry = ClipEntry::from_string("see https://example.com", ClipboardKind::Clipboard);
+        entry.set_tags(["mine"]);
+        tagger.tag(&mut entry);
+        assert_eq!(entry.tags().iter().collect::<Vec<_>>(), ["mine", "url"]);
+        assert_eq!(entry.auto_tags().iter().collect::<Vec<_>>(), ["url"]);
+
+        let mut entry = ClipEntry::from_string("plain text", ClipboardKind::Clipboard);
+        tagger.tag(&mut entry);
+        assert!(entry.tags().is_empty());
+    }
+}
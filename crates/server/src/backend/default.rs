@@ -3,6 +3,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use clipcat_base::{ClipFilter, ClipboardContent, ClipboardKind};
 use clipcat_clipboard::{Clipboard, ClipboardLoad, ClipboardStore, ClipboardSubscribe};
+use parking_lot::Mutex;
 use snafu::ResultExt;
 use tokio::task;
 
@@ -13,6 +14,9 @@ pub struct Backend {
     clipboards: Vec<Arc<Clipboard>>,
 
     supported_clipboard_kinds: Vec<ClipboardKind>,
+
+    // contents stored by us, indexed by clipboard kind
+    owned_contents: Arc<Mutex<[Option<ClipboardContent>; ClipboardKind::MAX_LENGTH]>>,
 }
 
 impl Backend {
@@ -57,13 +61,16 @@ impl Backend {
             }
         }
 
-        Ok(Self { clipboards, supported_clipboard_kinds })
+        Ok(Self { clipboards, supported_clipboard_kinds, owned_contents: Arc::default() })
     }
 
     #[inline]
     fn select_clipboard(&self, kind: ClipboardKind) -> Result<Arc<Clipboard>> {
-        self.clipboards
-            .get(usize::from(kind))
+        // clipboards are created only for the enabled kinds
+        self.supported_clipboard_kinds
+            .iter()
+            .position(|&supported| supported == kind)
+            .and_then(|index| self.clipboards.get(index))
             .cloned()
             .ok_or(Error::UnsupportedClipboardKind { kind })
     }
@@ -90,10 +97,13 @@ impl traits::Backend for Backend {
     #[inline]
     async fn store(&self, kind: ClipboardKind, data: ClipboardContent) -> Result<()> {
         if let Ok(clipboard) = self.select_clipboard(kind) {
+            let content = data.clone();
             task::spawn_blocking(move || clipboard.store(data))
                 .await
                 .context(error::SpawnBlockingTaskSnafu)?
-                .context(error::StoreDataToClipboardSnafu)
+                .context(error::StoreDataToClipboardSnafu)?;
+            self.owned_contents.lock()[usize::from(kind)] = Some(content);
+            Ok(())
         } else {
             Ok(())
         }
@@ -102,6 +112,7 @@ impl traits::Backend for Backend {
     #[inline]
     async fn clear(&self, kind: ClipboardKind) -> Result<()> {
         if let Ok(clipboard) = self.select_clipboard(kind) {
+            self.owned_contents.lock()[usize::from(kind)] = None;
             task::spawn_blocking(move || clipboard.clear())
                 .await
                 .context(error::SpawnBlockingTaskSnafu)?
@@ -111,6 +122,16 @@ impl traits::Backend for Backend {
         }
     }
 
+    fn owns(&self, kind: ClipboardKind, content: &ClipboardContent) -> bool {
+        let owned = &mut self.owned_contents.lock()[usize::from(kind)];
+        if owned.as_ref() == Some(content) {
+            true
+        } else {
+            *owned = None;
+            false
+        }
+    }
+
     #[inline]
     fn subscribe(&self) -> Result<Subscriber> {
         let subscribers = self
@@ -125,4 +146,11 @@ impl traits::Backend for Backend {
     fn supported_clipboard_kinds(&self) -> Vec<ClipboardKind> {
         self.supported_clipboard_kinds.clone()
     }
+
+    #[inline]
+    fn supported_mimes(&self) -> Vec<mime::Mime> { vec![mime::TEXT_PLAIN_UTF_8, mime::IMAGE_PNG] }
+
+    fn targets(&self, kind: ClipboardKind) -> Option<Vec<String>> {
+        self.select_clipboard(kind).ok()?.targets().ok().flatten()
+    }
 }
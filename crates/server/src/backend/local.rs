@@ -1,13 +1,21 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use clipcat_base::ClipboardContent;
 use clipcat_clipboard::{ClipboardLoad, ClipboardStore, ClipboardSubscribe, LocalClipboard};
+use parking_lot::Mutex;
 use snafu::ResultExt;
 use tokio::task;
 
 use crate::backend::{error, traits, ClipboardKind, Error, Result, Subscriber};
 
 #[derive(Clone, Debug, Default)]
-pub struct Backend(LocalClipboard);
+pub struct Backend {
+    clipboard: LocalClipboard,
+
+    // content stored by us
+    owned_content: Arc<Mutex<Option<ClipboardContent>>>,
+}
 
 impl Backend {
     #[must_use]
@@ -22,7 +30,7 @@ impl traits::Backend for Backend {
         _kind: ClipboardKind,
         mime: Option<mime::Mime>,
     ) -> Result<ClipboardContent> {
-        let clipboard = self.0.clone();
+        let clipboard = self.clipboard.clone();
         task::spawn_blocking(move || match clipboard.load(mime) {
             Ok(data) => Ok(data),
             Err(clipcat_clipboard::Error::Empty) => Err(Error::EmptyClipboard),
@@ -34,17 +42,21 @@ impl traits::Backend for Backend {
 
     #[inline]
     async fn store(&self, _kind: ClipboardKind, data: ClipboardContent) -> Result<()> {
-        let clipboard = self.0.clone();
+        let clipboard = self.clipboard.clone();
+        let content = data.clone();
 
         task::spawn_blocking(move || clipboard.store(data))
             .await
             .context(error::SpawnBlockingTaskSnafu)?
-            .context(error::StoreDataToClipboardSnafu)
+            .context(error::StoreDataToClipboardSnafu)?;
+        *self.owned_content.lock() = Some(content);
+        Ok(())
     }
 
     #[inline]
     async fn clear(&self, _kind: ClipboardKind) -> Result<()> {
-        let clipboard = self.0.clone();
+        let clipboard = self.clipboard.clone();
+        *self.owned_content.lock() = None;
 
         task::spawn_blocking(move || clipboard.clear())
             .await
@@ -52,9 +64,19 @@ impl traits::Backend for Backend {
             .context(error::ClearClipboardSnafu)
     }
 
+    fn owns(&self, _kind: ClipboardKind, content: &ClipboardContent) -> bool {
+        let mut owned_content = self.owned_content.lock();
+        if owned_content.as_ref() == Some(content) {
+            true
+        } else {
+            *owned_content = None;
+            false
+        }
+    }
+
     #[inline]
     fn subscribe(&self) -> Result<Subscriber> {
-        self.0
+        self.clipboard
             .subscribe()
             .map(|sub| Subscriber::from([sub]))
             .context(error::SubscribeClipboardSnafu)
@@ -62,4 +84,35 @@ impl traits::Backend for Backend {
 
     #[inline]
     fn supported_clipboard_kinds(&self) -> Vec<ClipboardKind> { vec![ClipboardKind::Clipboard] }
+
+    #[inline]
+    fn supported_mimes(&self) -> Vec<mime::Mime> { vec![mime::TEXT_PLAIN_UTF_8, mime::IMAGE_PNG] }
+
+    // the local clipboard is not owned by another program
+    #[inline]
+    fn targets(&self, _kind: ClipboardKind) -> Option<Vec<String>> { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipboardContent, ClipboardKind};
+
+    use super::Backend;
+    use crate::backend::ClipboardBackend;
+
+    #[tokio::test]
+    async fn test_owns() {
+        let backend = Backend::new();
+        let ours = ClipboardContent::Plaintext("ours".to_string());
+        let theirs = ClipboardContent::Plaintext("theirs".to_string());
+        assert!(!backend.owns(ClipboardKind::Clipboard, &ours));
+
+        backend.store(ClipboardKind::Clipboard, ours.clone()).await.unwrap();
+        assert!(backend.owns(ClipboardKind::Clipboard, &ours));
+        assert!(backend.owns(ClipboardKind::Clipboard, &ours));
+
+        // another application took the selection
+        assert!(!backend.owns(ClipboardKind::Clipboard, &theirs));
+        assert!(!backend.owns(ClipboardKind::Clipboard, &ours));
+    }
 }
@@ -8,6 +8,7 @@ use std::sync::Arc;
 
 use clipcat_base::{ClipFilter, ClipboardKind};
 use clipcat_clipboard::EventObserver;
+pub use clipcat_clipboard::SelectionChange;
 
 use self::error::Result;
 pub use self::{
@@ -1,19 +1,16 @@
 use std::iter::IntoIterator;
 
-use clipcat_base::ClipboardKind;
-use clipcat_clipboard::ClipboardWait;
+use clipcat_clipboard::{ClipboardWait, SelectionChange};
 use tokio::{sync::mpsc, task};
 
 #[derive(Debug)]
 pub struct Subscriber {
-    receiver: mpsc::UnboundedReceiver<(ClipboardKind, mime::Mime)>,
+    receiver: mpsc::UnboundedReceiver<SelectionChange>,
     join_handles: task::JoinSet<()>,
 }
 
 impl Subscriber {
-    pub async fn next(&mut self) -> Option<(ClipboardKind, mime::Mime)> {
-        self.receiver.recv().await
-    }
+    pub async fn next(&mut self) -> Option<SelectionChange> { self.receiver.recv().await }
 }
 
 impl Drop for Subscriber {
@@ -35,12 +32,12 @@ where
                 let _unused = join_handles.spawn_blocking({
                     let event_sender = sender.clone();
                     move || {
-                        while let Ok(kind) = subscriber.wait() {
+                        while let Ok(event) = subscriber.wait_for_change() {
                             if event_sender.is_closed() {
                                 break;
                             }
 
-                            if let Err(_err) = event_sender.send(kind) {
+                            if let Err(_err) = event_sender.send(event) {
                                 break;
                             }
                         }
@@ -12,8 +12,22 @@ pub trait Backend: Sync + Send {
 
     async fn clear(&self, kind: ClipboardKind) -> Result<()>;
 
+    /// Returns `true` if `content` is what the backend stored to the clipboard
+    /// of `kind` last, i.e. clipcat still owns the selection.
+    ///
+    /// The ownership is released once a different content is checked.
+    fn owns(&self, kind: ClipboardKind, content: &ClipboardContent) -> bool;
+
     /// # Errors
     fn subscribe(&self) -> Result<Subscriber>;
 
     fn supported_clipboard_kinds(&self) -> Vec<ClipboardKind>;
+
+    /// Returns the mime types of the contents the backend can store into the
+    /// clipboard, text is stored as plain text and images as PNG.
+    fn supported_mimes(&self) -> Vec<mime::Mime>;
+
+    /// Returns the targets offered by the owner of the selection of `kind`,
+    /// or `None` if the backend does not know them.
+    fn targets(&self, kind: ClipboardKind) -> Option<Vec<String>>;
 }
@@ -0,0 +1,160 @@
+use clipcat_base::{ClipEntry, ClipboardContent, SemanticKind};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static URL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:[a-zA-Z][a-zA-Z0-9+.-]*://|www\.|mailto:)\S+$").expect("is valid regex; qed")
+});
+
+static NUMBER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:[+-]?(?:\d[\d,_]*)?\.?\d+(?:[eE][+-]?\d+)?%?|0[xX][0-9a-fA-F]+)$")
+        .expect("is valid regex; qed")
+});
+
+static PATH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:~?/|\.\.?/|[a-zA-Z]:\\)\S").expect("is valid regex; qed"));
+
+// a line looking like source code, by its keywords, its operators or its end
+static CODE_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?:^\s*(?:(?:fn|def|let|const|var|function|class|import|from|use|pub|return)\b|#include|#!))|(?:[;{}]\s*$)|(?:=>|->|::|\(\)|==|!=|&&|\|\|)",
+    )
+    .expect("is valid regex; qed")
+});
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SemanticKindRule {
+    /// A regular expression searched in the text of clips.
+    pub pattern: String,
+
+    /// The kind assigned to the clips matching `pattern`.
+    pub kind: SemanticKind,
+}
+
+/// Assigns a [`SemanticKind`] to new text clips, the first matching rule wins
+/// and the clips matching no rule are classified by heuristics.
+///
+/// The default classifier is disabled and assigns no kind.
+#[derive(Clone, Debug, Default)]
+pub struct Classifier {
+    enabled: bool,
+
+    rules: Vec<(Regex, SemanticKind)>,
+}
+
+impl Classifier {
+    /// Creates an enabled classifier with the rules, the rules with an
+    /// invalid pattern are ignored.
+    pub fn new(rules: Vec<SemanticKindRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|SemanticKindRule { pattern, kind }| match Regex::new(&pattern) {
+                Ok(regex) => Some((regex, kind)),
+                Err(err) => {
+                    tracing::warn!("Ignore semantic kind rule of `{kind}`, error: {err}");
+                    None
+                }
+            })
+            .collect();
+        Self { enabled: true, rules }
+    }
+
+    /// Returns the kind of a text clip, `None` for images or if the
+    /// classifier is disabled.
+    pub fn classify(&self, entry: &ClipEntry) -> Option<SemanticKind> {
+        if !self.enabled {
+            return None;
+        }
+        let ClipboardContent::Plaintext(text) = entry.as_ref() else {
+            return None;
+        };
+        if let Some((_, kind)) = self.rules.iter().find(|(regex, _)| regex.is_match(text)) {
+            return Some(*kind);
+        }
+        Some(classify_text(text))
+    }
+}
+
+fn classify_text(text: &str) -> SemanticKind {
+    let text = text.trim();
+    if text.is_empty() {
+        return SemanticKind::Other;
+    }
+    let single_line = !text.contains('\n');
+    if single_line && URL.is_match(text) {
+        return SemanticKind::Url;
+    }
+    if single_line && NUMBER.is_match(text) {
+        return SemanticKind::Number;
+    }
+    if single_line && PATH.is_match(text) {
+        return SemanticKind::Path;
+    }
+
+    let lines = text.lines().filter(|line| !line.trim().is_empty()).collect::<Vec<_>>();
+    let code_lines = lines.iter().filter(|line| CODE_LINE.is_match(line)).count();
+    if code_lines * 2 >= lines.len() {
+        return SemanticKind::Code;
+    }
+
+    let words = text.split_whitespace().count();
+    let (letters, others) = text.chars().filter(|c| !c.is_whitespace()).fold(
+        (0_usize, 0_usize),
+        |(letters, others), c| {
+            if c.is_alphabetic() || matches!(c, ',' | '.' | '\'' | '"' | '!' | '?' | '-') {
+                (letters + 1, others)
+            } else {
+                (letters, others + 1)
+            }
+        },
+    );
+    if words >= 2 && letters >= others * 4 {
+        SemanticKind::Prose
+    } else {
+        SemanticKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardKind, SemanticKind};
+
+    use super::{Classifier, SemanticKindRule};
+
+    fn classify(classifier: &Classifier, text: &str) -> Option<SemanticKind> {
+        classifier.classify(&ClipEntry::from_string(text, ClipboardKind::Clipboard))
+    }
+
+    #[test]
+    fn test_classify() {
+        let classifier = Classifier::new(Vec::new());
+        for (text, kind) in [
+            ("https://example.com/a?b=c", SemanticKind::Url),
+            ("www.example.com", SemanticKind::Url),
+            ("-12,345.67", SemanticKind::Number),
+            ("0xdeadbeef", SemanticKind::Number),
+            ("/usr/share/clipcat", SemanticKind::Path),
+            ("~/Documents/notes.txt", SemanticKind::Path),
+            (r"C:\Users\clipcat", SemanticKind::Path),
+            ("fn main() {\n    println!(\"hello\");\n}", SemanticKind::Code),
+            ("let x = a.len();", SemanticKind::Code),
+            ("See you tomorrow, the meeting is moved to noon.", SemanticKind::Prose),
+            ("#%$ @@ 12", SemanticKind::Other),
+            ("  ", SemanticKind::Other),
+        ] {
+            assert_eq!(classify(&classifier, text), Some(kind), "{text}");
+        }
+
+        assert_eq!(classify(&Classifier::default(), "https://example.com"), None);
+    }
+
+    #[test]
+    fn test_rules() {
+        let classifier = Classifier::new(vec![
+            SemanticKindRule { pattern: r"^TICKET-\d+$".to_string(), kind: SemanticKind::Url },
+            SemanticKindRule { pattern: r"(".to_string(), kind: SemanticKind::Code },
+        ]);
+        assert_eq!(classify(&classifier, "TICKET-42"), Some(SemanticKind::Url));
+        assert_eq!(classify(&classifier, "42"), Some(SemanticKind::Number));
+    }
+}
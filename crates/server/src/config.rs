@@ -1,7 +1,13 @@
 use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
-use crate::ClipboardWatcherOptions;
+use clipcat_base::DedupeScope;
 
+use crate::{
+    AutoTagRule, ClipboardWatcherOptions, ContentTransformRule, RedactionRule, SemanticKindRule,
+};
+
+// SAFETY: user may use bool to enable/disable the functions
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug)]
 pub struct Config {
     pub grpc_listen_address: Option<SocketAddr>,
@@ -10,14 +16,50 @@ pub struct Config {
 
     pub grpc_access_token: Option<String>,
 
+    /// Serves grpc-web on the HTTP endpoint for browser clients, `None` if
+    /// disabled.
+    pub grpc_web: Option<GrpcWebConfig>,
+
+    /// Inserts each gRPC client may make per second, `None` if not limited.
+    pub grpc_max_inserts_per_second: Option<u32>,
+
+    /// Limits of the size of clips inserted by gRPC clients, `None` if not
+    /// limited.
+    pub size_limits: Option<clipcat_base::SizeLimits>,
+
+    /// Servers the clips inserted or marked with propagation are inserted
+    /// into.
+    pub federation_peers: Vec<FederationPeerConfig>,
+
     pub primary_threshold: time::Duration,
 
     pub max_history: usize,
 
+    /// Clips last updated longer ago than this are removed, snippets are
+    /// kept, `None` if clips are kept regardless of their age.
+    pub max_age: Option<time::Duration>,
+
+    pub dedupe_scope: DedupeScope,
+
+    pub normalize_whitespace_for_dedupe: bool,
+
+    /// A text clip extending the current clip within `primary_threshold`
+    /// replaces it.
+    pub coalesce_growing_edits: bool,
+
+    pub read_only: bool,
+
+    /// Allows the requests meant for tests, like replacing the whole history.
+    pub testing: bool,
+
+    pub compression_threshold: Option<usize>,
+
     pub synchronize_selection_with_clipboard: bool,
 
     pub history_file_path: PathBuf,
 
+    pub history_flush: HistoryFlushConfig,
+
     pub watcher: ClipboardWatcherOptions,
 
     pub dbus: DBusConfig,
@@ -27,6 +69,85 @@ pub struct Config {
     pub metrics: MetricsConfig,
 
     pub snippets: Vec<SnippetConfig>,
+
+    pub content_transform: ContentTransformConfig,
+
+    pub auto_tag: AutoTagConfig,
+
+    pub semantic_kind: SemanticKindConfig,
+
+    pub redaction: RedactionConfig,
+
+    /// Serves the history as NDJSON over HTTP for scripts, `None` if
+    /// disabled.
+    pub http_api: Option<HttpApiConfig>,
+
+    /// Forwards each clip added to the history to an external program, like
+    /// another clipboard manager, `None` if disabled.
+    pub forward: Option<ForwardConfig>,
+
+    /// Mirrors the history into secondary history files, `None` if disabled.
+    pub replication: Option<ReplicationConfig>,
+}
+
+/// When the history is saved while the server is running, besides the clips
+/// captured from the clipboard which are saved at once, the history is saved
+/// on shutdown anyway.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HistoryFlushConfig {
+    /// Saves the history after no change is made for this long, `None` if
+    /// disabled.
+    pub idle: Option<Duration>,
+
+    /// Saves the history once this many changes are not saved, `None` if
+    /// disabled.
+    pub max_unsaved_changes: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpApiConfig {
+    pub listen_address: SocketAddr,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GrpcWebConfig {
+    /// Origins of pages allowed to call the services, `*` allows any origin.
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FederationPeerConfig {
+    /// The HTTP endpoint of the peer, like `http://192.168.1.2:45045`.
+    pub endpoint: http::Uri,
+
+    pub access_token: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ForwardConfig {
+    /// The program run for each clip, the content is written into its stdin,
+    /// the id, the MIME type and the clipboard kind of the clip are passed in
+    /// `CLIPCAT_CLIP_ID`, `CLIPCAT_MIME` and `CLIPCAT_CLIPBOARD_KIND`.
+    pub program: PathBuf,
+
+    pub args: Vec<String>,
+
+    /// The program is killed if it does not exit within this duration.
+    pub timeout: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReplicationConfig {
+    /// The paths of the secondary history files, like on another disk or on a
+    /// share of another machine.
+    pub targets: Vec<PathBuf>,
+
+    /// The replicas are synced at most once per interval.
+    pub interval: Duration,
+
+    /// Restores the history from the first replica holding any clip if the
+    /// history file is missing on startup.
+    pub reconcile_on_startup: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -54,6 +175,38 @@ pub struct MetricsConfig {
     pub listen_address: SocketAddr,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct ContentTransformConfig {
+    pub enable: bool,
+
+    pub rules: Vec<ContentTransformRule>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AutoTagConfig {
+    pub enable: bool,
+
+    /// Rules applied to new text clips, each matching rule sets its tag.
+    pub rules: Vec<AutoTagRule>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SemanticKindConfig {
+    pub enable: bool,
+
+    /// Rules checked in order before the heuristics, the first matching rule
+    /// assigns its kind.
+    pub rules: Vec<SemanticKindRule>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RedactionConfig {
+    pub enable: bool,
+
+    /// Rules applied in order to the previews of clips.
+    pub rules: Vec<RedactionRule>,
+}
+
 #[derive(Clone, Debug)]
 pub enum SnippetConfig {
     Inline { name: String, content: String },
@@ -21,18 +21,27 @@ impl<Notification> ManagerService<Notification>
 where
     Notification: notification::Notification + 'static,
 {
-    async fn insert(&self, kind: dbus_variant::ClipboardKind, data: &[u8], mime: &str) -> u64 {
+    async fn insert(
+        &self,
+        kind: dbus_variant::ClipboardKind,
+        data: &[u8],
+        mime: &str,
+    ) -> zbus::fdo::Result<u64> {
         metrics::dbus::REQUESTS_TOTAL.inc();
         let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
 
-        let mime = mime::Mime::from_str(mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
+        let mime = parse_mime(mime)?;
         let mut manager = self.manager.lock().await;
+        if !manager.is_kind_enabled(kind.into()) {
+            let err = crate::manager::Error::KindDisabled { kind: kind.into() };
+            return Err(zbus::fdo::Error::NotSupported(err.to_string()));
+        }
         let id = manager.insert(
             clipcat_base::ClipEntry::new(data, &mime, kind.into(), None).unwrap_or_default(),
         );
         let _unused = manager.mark(id, kind.into()).await;
         drop(manager);
-        id
+        Ok(id)
     }
 
     #[zbus(property)]
@@ -58,6 +67,7 @@ where
         let manager = self.manager.lock().await;
         manager
             .get_current_clip(clipcat_base::ClipboardKind::Clipboard)
+            .as_ref()
             .map(clipcat_base::ClipEntry::as_utf8_string)
             .unwrap_or_default()
     }
@@ -102,9 +112,7 @@ where
         let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
 
         let manager = self.manager.lock().await;
-        zvariant::Optional::from(
-            manager.get_current_clip(kind.into()).map(|clip| clip.clone().into()),
-        )
+        zvariant::Optional::from(manager.get_current_clip(kind.into()).map(Into::into))
     }
 
     async fn list(&self, preview_length: u64) -> Vec<dbus_variant::ClipEntryMetadata> {
@@ -119,16 +127,16 @@ where
             .collect()
     }
 
-    async fn update(&self, id: u64, data: &[u8], mime: &str) -> (bool, u64) {
+    async fn update(&self, id: u64, data: &[u8], mime: &str) -> zbus::fdo::Result<(bool, u64)> {
         metrics::dbus::REQUESTS_TOTAL.inc();
         let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
 
+        let mime = parse_mime(mime)?;
         let (ok, new_id) = {
-            let mime = mime::Mime::from_str(mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
             let mut manager = self.manager.lock().await;
             manager.replace(id, data, &mime)
         };
-        (ok, new_id)
+        Ok((ok, new_id))
     }
 
     async fn mark(&self, id: u64, kind: dbus_variant::ClipboardKind) -> bool {
@@ -139,6 +147,14 @@ where
         manager.mark(id, kind.into()).await.is_ok()
     }
 
+    async fn touch(&self, id: u64) -> bool {
+        metrics::dbus::REQUESTS_TOTAL.inc();
+        let _histogram_timer = metrics::dbus::REQUEST_DURATION_SECONDS.start_timer();
+
+        let mut manager = self.manager.lock().await;
+        manager.touch(id)
+    }
+
     #[zbus(property)]
     async fn length(&self) -> u64 {
         metrics::dbus::REQUESTS_TOTAL.inc();
@@ -148,3 +164,8 @@ where
         manager.len() as u64
     }
 }
+
+fn parse_mime(mime: &str) -> zbus::fdo::Result<mime::Mime> {
+    mime::Mime::from_str(mime)
+        .map_err(|_| zbus::fdo::Error::InvalidArgs(format!("Invalid mime type `{mime}`")))
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
 use snafu::{Backtrace, Snafu};
 
@@ -39,6 +39,12 @@ pub enum Error {
 
     #[snafu(display("{source}"))]
     Metrics { source: clipcat_metrics::Error },
+
+    #[snafu(display("Could not bind HTTP API server on {listen_address}, error: {source}"))]
+    BindHttpApiServer { listen_address: SocketAddr, source: std::io::Error },
+
+    #[snafu(display("Error occurs while serving HTTP API server, error: {source}"))]
+    ServeHttpApiServer { source: std::io::Error },
 }
 
 impl From<zbus::Error> for Error {
@@ -0,0 +1,25 @@
+use std::{path::PathBuf, process::ExitStatus, time::Duration};
+
+use snafu::Snafu;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub))]
+pub enum Error {
+    #[snafu(display("Error occurs while encoding image of clip {id:016x}, error: {source}"))]
+    EncodeImage { id: u64, source: clipcat_base::ClipEntryError },
+
+    #[snafu(display("Could not spawn {}, error: {source}", program.display()))]
+    SpawnProgram { program: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not write clip into stdin of {}, error: {source}", program.display()))]
+    WriteStdin { program: PathBuf, source: std::io::Error },
+
+    #[snafu(display("Could not wait for {}, error: {source}", program.display()))]
+    WaitProgram { program: PathBuf, source: std::io::Error },
+
+    #[snafu(display("{} does not exit within {timeout:?}", program.display()))]
+    Timeout { program: PathBuf, timeout: Duration },
+
+    #[snafu(display("{} exits with {status}", program.display()))]
+    ProgramFailed { program: PathBuf, status: ExitStatus },
+}
@@ -0,0 +1,149 @@
+mod error;
+
+use std::{io::ErrorKind, path::PathBuf, process::Stdio, time::Duration};
+
+use clipcat_base::{ClipEntry, ClipboardChange, ClipboardEvent};
+use futures::{FutureExt, StreamExt};
+use snafu::ResultExt;
+use tokio::{
+    io::AsyncWriteExt,
+    process::Command,
+    sync::broadcast::{self, error::RecvError},
+};
+
+pub use self::error::Error;
+use crate::{config::ForwardConfig, watcher::ClipboardWatcherHealth};
+
+/// Forwards each clip added to the history to an external program, the
+/// content is written into its stdin.
+///
+/// Clips are forwarded one by one from the changes of the history, a slow or
+/// failing program never blocks capturing clips, the clips missed while it is
+/// running are counted as failures in the diagnostics instead.
+pub struct Forwarder {
+    program: PathBuf,
+
+    args: Vec<String>,
+
+    timeout: Duration,
+
+    health: ClipboardWatcherHealth,
+}
+
+impl Forwarder {
+    pub fn new(
+        ForwardConfig { program, args, timeout }: ForwardConfig,
+        health: ClipboardWatcherHealth,
+    ) -> Self {
+        Self { program, args, timeout, health }
+    }
+
+    #[allow(clippy::redundant_pub_crate)]
+    pub async fn serve(
+        self,
+        mut change_receiver: broadcast::Receiver<ClipboardEvent>,
+        shutdown_signal: sigfinn::Shutdown,
+    ) {
+        let mut shutdown_signal = shutdown_signal.into_stream();
+        loop {
+            let event = tokio::select! {
+                event = change_receiver.recv().fuse() => event,
+                _ = shutdown_signal.next() => break,
+            };
+            match event {
+                // private clips are never handed to other programs
+                Ok(ClipboardEvent { change: ClipboardChange::Added(clip), .. })
+                    if !clip.is_private() =>
+                {
+                    if let Err(err) = self.forward(&clip).await {
+                        self.record_failure(1, format!("Could not forward clip, error: {err}"));
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(count)) => self.record_failure(
+                    count,
+                    format!("{count} clip(s) are not forwarded, the program is too slow"),
+                ),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    fn record_failure(&self, count: u64, message: String) {
+        tracing::warn!("{message}");
+        self.health.record_forward_failures(count);
+        self.health.record_error(None, message);
+    }
+
+    async fn forward(&self, clip: &ClipEntry) -> Result<(), Error> {
+        let data = clip.encoded().context(error::EncodeImageSnafu { id: clip.id() })?;
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env("CLIPCAT_CLIP_ID", format!("{:016x}", clip.id()))
+            .env("CLIPCAT_MIME", clip.mime_full())
+            .env("CLIPCAT_CLIPBOARD_KIND", clip.kind().as_str())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context(error::SpawnProgramSnafu { program: self.program.clone() })?;
+
+        let run = async {
+            if let Some(mut stdin) = child.stdin.take() {
+                // the program may exit without reading the content
+                if let Err(err) = stdin.write_all(&data).await {
+                    if err.kind() != ErrorKind::BrokenPipe {
+                        return Err(err)
+                            .context(error::WriteStdinSnafu { program: self.program.clone() });
+                    }
+                }
+                // stdin is closed here so the program sees the end of the
+                // content
+            }
+            child.wait().await.context(error::WaitProgramSnafu { program: self.program.clone() })
+        };
+        let status = tokio::time::timeout(self.timeout, run).await.map_err(|_| {
+            error::TimeoutSnafu { program: self.program.clone(), timeout: self.timeout }.build()
+        })??;
+        snafu::ensure!(
+            status.success(),
+            error::ProgramFailedSnafu { program: self.program.clone(), status }
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clipcat_base::{ClipEntry, ClipboardKind};
+
+    use super::{Error, Forwarder};
+    use crate::{config::ForwardConfig, watcher::ClipboardWatcherHealth};
+
+    fn forwarder(script: &str) -> Forwarder {
+        let config = ForwardConfig {
+            program: "sh".into(),
+            args: vec!["-c".to_string(), script.to_string()],
+            timeout: Duration::from_millis(500),
+        };
+        Forwarder::new(config, ClipboardWatcherHealth::new([true; 3], [false; 3]))
+    }
+
+    #[tokio::test]
+    async fn test_forward() {
+        let clip = ClipEntry::from_string("hello clipcat", ClipboardKind::Clipboard);
+        let script =
+            r#"test "$(cat)" = "hello clipcat" && test "$CLIPCAT_CLIPBOARD_KIND" = Clipboard"#;
+        forwarder(script).forward(&clip).await.unwrap();
+        forwarder("exit 0").forward(&clip).await.unwrap();
+
+        assert!(matches!(
+            forwarder("exit 3").forward(&clip).await,
+            Err(Error::ProgramFailed { .. })
+        ));
+        assert!(matches!(forwarder("sleep 5").forward(&clip).await, Err(Error::Timeout { .. })));
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use clipcat_proto as proto;
+use time::OffsetDateTime;
+use tonic::{metadata::AsciiMetadataValue, transport::Channel, Code, Request};
+
+use crate::config::FederationPeerConfig;
+
+// count of remembered keys of propagated clips, a clip coming back from a peer
+// after more keys are seen is propagated again
+const MAX_SEEN_KEYS: usize = 1024;
+
+// delays before retrying to propagate a clip to an unreachable peer
+const RETRY_DELAYS: [Duration; 3] =
+    [Duration::from_secs(1), Duration::from_secs(5), Duration::from_secs(30)];
+
+/// Propagates clips to the peer servers.
+///
+/// Each propagated clip carries an idempotency key, a server which sees a key
+/// again drops the clip instead of propagating it, so clips do not loop
+/// between peers which propagate to each other.
+#[derive(Debug)]
+pub struct Federation {
+    peers: Vec<Peer>,
+
+    seen_keys: parking_lot::Mutex<SeenKeys>,
+}
+
+#[derive(Clone, Debug)]
+struct Peer {
+    endpoint: http::Uri,
+
+    // connected on the first request
+    channel: Channel,
+
+    authorization: Option<AsciiMetadataValue>,
+}
+
+#[derive(Debug, Default)]
+struct SeenKeys {
+    // the oldest key first
+    order: VecDeque<String>,
+
+    keys: HashSet<String>,
+}
+
+impl SeenKeys {
+    fn insert(&mut self, key: &str) -> bool {
+        if self.keys.contains(key) {
+            return false;
+        }
+        if self.order.len() >= MAX_SEEN_KEYS {
+            if let Some(oldest) = self.order.pop_front() {
+                let _removed = self.keys.remove(&oldest);
+            }
+        }
+        self.order.push_back(key.to_string());
+        self.keys.insert(key.to_string())
+    }
+}
+
+impl Federation {
+    pub fn new(peers: Vec<FederationPeerConfig>) -> Self {
+        let peers = peers
+            .into_iter()
+            .map(|FederationPeerConfig { endpoint, access_token }| Peer {
+                channel: Channel::builder(endpoint.clone()).connect_lazy(),
+                authorization: access_token
+                    .and_then(|token| AsciiMetadataValue::try_from(format!("Bearer {token}")).ok()),
+                endpoint,
+            })
+            .collect();
+        Self { peers, seen_keys: parking_lot::Mutex::default() }
+    }
+
+    /// Returns a new key for a clip propagated from this server.
+    pub fn new_key() -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        format!(
+            "{:x}-{:x}-{:x}",
+            std::process::id(),
+            OffsetDateTime::now_utc().unix_timestamp_nanos(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// Remembers `key`, returns `false` if it is seen before, i.e. the clip
+    /// comes back from a peer.
+    pub fn first_seen(&self, key: &str) -> bool { self.seen_keys.lock().insert(key) }
+
+    /// Inserts the clip into each peer in the background.
+    ///
+    /// Unreachable peers are retried a few times and skipped then.
+    pub fn propagate(&self, request: &proto::InsertRequest) {
+        for peer in &self.peers {
+            let _handle = tokio::spawn(peer.clone().insert(request.clone()));
+        }
+    }
+}
+
+impl Peer {
+    async fn insert(self, request: proto::InsertRequest) {
+        for delay in std::iter::once(Duration::ZERO).chain(RETRY_DELAYS) {
+            tokio::time::sleep(delay).await;
+            let mut req = Request::new(request.clone());
+            if let Some(ref authorization) = self.authorization {
+                let _previous = req.metadata_mut().insert("authorization", authorization.clone());
+            }
+            let result = proto::ManagerClient::new(self.channel.clone()).insert(req).await;
+            match result {
+                Ok(_) => return,
+                Err(err) if matches!(err.code(), Code::Unavailable | Code::DeadlineExceeded) => {
+                    tracing::warn!(
+                        "Peer `{endpoint}` is unreachable, error: {err}",
+                        endpoint = self.endpoint
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Peer `{endpoint}` rejects the clip, error: {err}",
+                        endpoint = self.endpoint
+                    );
+                    return;
+                }
+            }
+        }
+        tracing::warn!("Skip propagating the clip to peer `{endpoint}`", endpoint = self.endpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Federation, SeenKeys, MAX_SEEN_KEYS};
+
+    #[test]
+    fn test_seen_keys() {
+        let mut seen_keys = SeenKeys::default();
+        let key = Federation::new_key();
+        assert_ne!(key, Federation::new_key());
+        assert!(seen_keys.insert(&key));
+        assert!(!seen_keys.insert(&key));
+
+        // the oldest keys are forgotten
+        for n in 0..MAX_SEEN_KEYS {
+            assert!(seen_keys.insert(&n.to_string()));
+        }
+        assert!(seen_keys.insert(&key));
+    }
+}
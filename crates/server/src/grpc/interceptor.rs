@@ -35,7 +35,10 @@ impl tonic::service::Interceptor for Interceptor {
         if let Some(ref expected) = self.authorization_metadata_value {
             match req.metadata().get("authorization") {
                 Some(token) if expected == token => Ok(req),
-                _ => Err(Status::unauthenticated("No valid authorization token")),
+                _ => {
+                    Err(clipcat_proto::ErrorDetail::new(clipcat_proto::ErrorCode::Unauthenticated)
+                        .into_status(tonic::Code::Unauthenticated, "No valid authorization token"))
+                }
             }
         } else {
             Ok(req)
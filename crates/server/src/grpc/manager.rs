@@ -1,17 +1,106 @@
-use std::{str::FromStr, sync::Arc};
+use std::{
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use clipcat_proto as proto;
-use tokio::sync::Mutex;
-use tonic::{Request, Response, Status};
+use futures::{Stream, StreamExt};
+use tokio::sync::{broadcast, Mutex};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, WatchStream};
+use tonic::{Code, Request, Response, Status};
 
-use crate::{notification, ClipboardManager};
+use super::{
+    rate_limit::{ClientKey, RateLimiter},
+    Federation,
+};
+use crate::{history::HistoryManager, notification, ClipboardManager};
+
+// size of the chunks of `GetStream`, well below the default message size limit
+const GET_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+// upper bound of the total size of the keys and the values of the attributes of
+// a clip
+const MAX_ATTRIBUTES_SIZE: usize = 4 * 1024;
+
+// lower bound of the heartbeat interval of `Watch`
+const MIN_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+// upper bound of the length of mime types sent by clients, RFC 6838 limits the
+// type and the subtype to 127 characters each
+const MAX_MIME_LENGTH: usize = 512;
+
+/// Limits of the clips inserted by clients.
+#[derive(Clone, Debug, Default)]
+pub struct InsertLimits {
+    /// `None` if the inserts are not rate limited.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+
+    /// `None` if the size of clips is not limited.
+    pub size_limits: Option<clipcat_base::SizeLimits>,
+}
 
 pub struct ManagerService<Notification> {
     manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    history: Arc<Mutex<HistoryManager>>,
+    insert_limits: InsertLimits,
+    // `None` if the server has no peers
+    federation: Option<Arc<Federation>>,
 }
 
 impl<Notification> ManagerService<Notification> {
-    pub fn new(manager: Arc<Mutex<ClipboardManager<Notification>>>) -> Self { Self { manager } }
+    pub fn new(
+        manager: Arc<Mutex<ClipboardManager<Notification>>>,
+        history: Arc<Mutex<HistoryManager>>,
+        insert_limits: InsertLimits,
+        federation: Option<Arc<Federation>>,
+    ) -> Self {
+        Self { manager, history, insert_limits, federation }
+    }
+
+    fn check_insert_size(&self, data: &[u8], mime: &mime::Mime) -> Result<(), Status> {
+        let Some(ref size_limits) = self.insert_limits.size_limits else {
+            return Ok(());
+        };
+        let (limit, rule) = size_limits.limit_of(mime);
+        if data.len() as u64 <= limit {
+            return Ok(());
+        }
+        Err(proto::ErrorDetail::new(proto::ErrorCode::TooLarge)
+            .with_limit(limit)
+            .with_usage(data.len() as u64)
+            .with_mime(rule)
+            .into_status(
+                Code::InvalidArgument,
+                format!(
+                    "Clip of {size} bytes exceeds the limit of {limit} bytes of `{rule}`",
+                    size = data.len()
+                ),
+            ))
+    }
+
+    fn check_insert_rate<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let Some(ref limiter) = self.insert_limits.rate_limiter else {
+            return Ok(());
+        };
+        let client = ClientKey::from_request(request);
+        limiter.check(client, Instant::now()).map_err(|retry_after| {
+            tracing::warn!("Client {client:?} exceeds the limit of inserts, reject the insert");
+            proto::ErrorDetail::new(proto::ErrorCode::RateLimited)
+                .with_limit(u64::from(limiter.max_per_second()))
+                // the limit is reached by the inserts of the current window
+                .with_usage(u64::from(limiter.max_per_second()))
+                .with_retry_after(retry_after)
+                .into_status(
+                    Code::ResourceExhausted,
+                    format!(
+                        "Inserts exceed the limit of {limit} per second",
+                        limit = limiter.max_per_second()
+                    ),
+                )
+        })
+    }
 }
 
 #[tonic::async_trait]
@@ -19,22 +108,248 @@ impl<Notification> proto::Manager for ManagerService<Notification>
 where
     Notification: notification::Notification + 'static,
 {
+    type GetStreamStream =
+        Pin<Box<dyn Stream<Item = Result<proto::GetStreamChunk, Status>> + Send>>;
+    type WatchCurrentStream =
+        Pin<Box<dyn Stream<Item = Result<proto::CurrentClipEvent, Status>> + Send>>;
+    type WatchStream = ClipboardEventStream;
+
+    async fn rank(
+        &self,
+        request: Request<proto::RankRequest>,
+    ) -> Result<Response<proto::RankResponse>, Status> {
+        let proto::RankRequest { id } = request.into_inner();
+        let rank = {
+            let manager = self.manager.lock().await;
+            manager.rank(id).map(|rank| rank as u64)
+        };
+        Ok(Response::new(proto::RankResponse { rank }))
+    }
+
+    async fn neighbor(
+        &self,
+        request: Request<proto::NeighborRequest>,
+    ) -> Result<Response<proto::NeighborResponse>, Status> {
+        let proto::NeighborRequest { id, direction } = request.into_inner();
+        let data = {
+            let manager = self.manager.lock().await;
+            manager.neighbor(id, clipcat_base::CycleDirection::from(direction)).map(Into::into)
+        };
+        Ok(Response::new(proto::NeighborResponse { data }))
+    }
+
+    async fn get_image_as(
+        &self,
+        request: Request<proto::GetImageAsRequest>,
+    ) -> Result<Response<proto::GetImageAsResponse>, Status> {
+        let proto::GetImageAsRequest { id, format } = request.into_inner();
+        let format = clipcat_base::ImageFormat::from(format);
+        let data = {
+            let mut manager = self.manager.lock().await;
+            manager.image_as(id, format).map_err(|err| manager_error(&err))?
+        }
+        .ok_or_else(|| {
+            proto::ErrorDetail::new(proto::ErrorCode::ClipNotFound)
+                .with_id(id)
+                .into_status(Code::NotFound, format!("Clip {id:016x} not found"))
+        })?;
+        Ok(Response::new(proto::GetImageAsResponse { data, mime: format.mime().to_string() }))
+    }
+
+    async fn nth_recent(
+        &self,
+        request: Request<proto::NthRecentRequest>,
+    ) -> Result<Response<proto::NthRecentResponse>, Status> {
+        let proto::NthRecentRequest { n, kind } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let data = {
+            let manager = self.manager.lock().await;
+            manager.nth_recent(usize::try_from(n).unwrap_or(usize::MAX), kind).map(Into::into)
+        };
+        Ok(Response::new(proto::NthRecentResponse { data }))
+    }
+
     async fn insert(
         &self,
         request: Request<proto::InsertRequest>,
     ) -> Result<Response<proto::InsertResponse>, Status> {
-        let proto::InsertRequest { data, mime, kind } = request.into_inner();
-        let id = {
-            let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
+        self.check_insert_rate(&request)?;
+        let proto::InsertRequest {
+            data,
+            mime,
+            kind,
+            tags,
+            ttl_ms,
+            silent,
+            attributes,
+            propagate,
+            idempotency_key,
+            private,
+            position,
+            force_new,
+        } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let mime = parse_mime(&mime)?;
+        self.check_insert_size(&data, &mime)?;
+        let mut entry = clipcat_base::ClipEntry::new(&data, &mime, kind, None).unwrap_or_default();
+        let stored_mime = entry.mime().to_string();
+        let propagated = match self.federation {
+            // private clips do not leave the server
+            Some(ref federation) if propagate && !private => {
+                let key = idempotency_key.unwrap_or_else(Federation::new_key);
+                if !federation.first_seen(&key) {
+                    // the clip comes back from a peer, inserting it again could
+                    // overwrite a newer clip
+                    let id = self
+                        .manager
+                        .lock()
+                        .await
+                        .ids_with_digest(entry.sha256_digest())
+                        .first()
+                        .copied()
+                        .unwrap_or_else(|| entry.id());
+                    return Ok(Response::new(proto::InsertResponse {
+                        id,
+                        evicted: Vec::new(),
+                        mime: stored_mime,
+                    }));
+                }
+                Some(proto::InsertRequest {
+                    kind: kind.into(),
+                    data,
+                    mime: mime.to_string(),
+                    tags: tags.clone(),
+                    ttl_ms,
+                    silent,
+                    attributes: attributes.clone(),
+                    propagate: true,
+                    idempotency_key: Some(key),
+                    private: false,
+                    // the histories of the peers differ
+                    position: None,
+                    force_new,
+                })
+            }
+            _ => None,
+        };
+        entry.set_tags(tags);
+        entry.set_attributes(attributes);
+        entry.set_private(private);
+        if entry.attributes_size() > MAX_ATTRIBUTES_SIZE {
+            return Err(proto::ErrorDetail::new(proto::ErrorCode::AttributesTooLarge)
+                .with_limit(MAX_ATTRIBUTES_SIZE as u64)
+                .into_status(
+                    Code::InvalidArgument,
+                    format!("Attributes exceed the limit of {MAX_ATTRIBUTES_SIZE} bytes"),
+                ));
+        }
+        if let Some(ttl_ms) = ttl_ms {
+            let ttl = time::Duration::milliseconds(i64::try_from(ttl_ms).unwrap_or(i64::MAX));
+            entry.set_expires_at(entry.timestamp().checked_add(ttl));
+        }
+        let (id, evicted) = {
             let mut manager = self.manager.lock().await;
-            let id = manager.insert(
-                clipcat_base::ClipEntry::new(&data, &mime, kind.into(), None).unwrap_or_default(),
-            );
-            let _unused = manager.mark(id, kind.into()).await;
+            if !manager.is_kind_enabled(kind) {
+                return Err(kind_disabled(kind));
+            }
+            check_insert_capacity(&manager, &entry, force_new)?;
+            if let Some(position) = position {
+                let timestamp =
+                    manager.make_room_at(usize::try_from(position).unwrap_or(usize::MAX));
+                entry.set_timestamp(timestamp);
+                entry.set_created_at(timestamp);
+            }
+            let insert = if force_new {
+                ClipboardManager::insert_apart
+            } else {
+                ClipboardManager::insert_with_evicted
+            };
+            let inserted = if silent {
+                manager.silently(|manager| insert(manager, entry))
+            } else if position.is_some() {
+                // marking the clip would make it the newest one
+                insert(&mut manager, entry)
+            } else {
+                let (id, evicted) = insert(&mut manager, entry);
+                let _unused = manager.mark(id, kind).await;
+                (id, evicted)
+            };
             drop(manager);
-            id
+            inserted
         };
-        Ok(Response::new(proto::InsertResponse { id }))
+        if let (Some(federation), Some(request)) = (&self.federation, propagated) {
+            federation.propagate(&request);
+        }
+        Ok(Response::new(proto::InsertResponse { id, evicted, mime: stored_mime }))
+    }
+
+    async fn insert_with_id(
+        &self,
+        request: Request<proto::InsertWithIdRequest>,
+    ) -> Result<Response<proto::InsertResponse>, Status> {
+        self.check_insert_rate(&request)?;
+        let proto::InsertWithIdRequest { id, data, mime, kind } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let mime = parse_mime(&mime)?;
+        self.check_insert_size(&data, &mime)?;
+        let entry = clipcat_base::ClipEntry::new(&data, &mime, kind, None).unwrap_or_default();
+        let stored_mime = entry.mime().to_string();
+        let (id, evicted) = {
+            let mut manager = self.manager.lock().await;
+            if !manager.is_kind_enabled(kind) {
+                return Err(kind_disabled(kind));
+            }
+            check_insert_capacity(&manager, &entry, false)?;
+            let (id, evicted) =
+                manager.insert_with_id(id, entry).map_err(|err| manager_error(&err))?;
+            let _unused = manager.mark(id, kind).await;
+            drop(manager);
+            (id, evicted)
+        };
+        Ok(Response::new(proto::InsertResponse { id, evicted, mime: stored_mime }))
+    }
+
+    async fn get_or_insert(
+        &self,
+        request: Request<proto::GetOrInsertRequest>,
+    ) -> Result<Response<proto::GetOrInsertResponse>, Status> {
+        self.check_insert_rate(&request)?;
+        let proto::GetOrInsertRequest { kind, data, mime } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let mime = parse_mime(&mime)?;
+        self.check_insert_size(&data, &mime)?;
+        let entry = clipcat_base::ClipEntry::new(&data, &mime, kind, None).unwrap_or_default();
+        let (id, inserted) = {
+            let mut manager = self.manager.lock().await;
+            if !manager.is_kind_enabled(kind) {
+                return Err(kind_disabled(kind));
+            }
+            check_insert_capacity(&manager, &entry, false)?;
+            let (id, inserted) = manager.get_or_insert(entry);
+            if inserted {
+                let _unused = manager.mark(id, kind).await;
+            }
+            drop(manager);
+            (id, inserted)
+        };
+        Ok(Response::new(proto::GetOrInsertResponse { id, inserted }))
+    }
+
+    async fn duplicate(
+        &self,
+        request: Request<proto::DuplicateRequest>,
+    ) -> Result<Response<proto::DuplicateResponse>, Status> {
+        let id = request.into_inner().id;
+        let copy_id = {
+            let mut manager = self.manager.lock().await;
+            manager.duplicate(id)
+        }
+        .ok_or_else(|| {
+            proto::ErrorDetail::new(proto::ErrorCode::ClipNotFound)
+                .with_id(id)
+                .into_status(Code::NotFound, format!("Clip {id:016x} not found"))
+        })?;
+        Ok(Response::new(proto::DuplicateResponse { id: copy_id }))
     }
 
     async fn remove(
@@ -61,63 +376,558 @@ where
         Ok(Response::new(proto::BatchRemoveResponse { ids }))
     }
 
-    async fn clear(&self, _request: Request<()>) -> Result<Response<()>, Status> {
+    async fn remove_by_content(
+        &self,
+        request: Request<proto::RemoveByContentRequest>,
+    ) -> Result<Response<proto::BatchRemoveResponse>, Status> {
+        let proto::RemoveByContentRequest { data, mime, dry_run } = request.into_inner();
+        let mime = parse_mime(&mime)?;
+        let entry = clipcat_base::ClipEntry::new(
+            &data,
+            &mime,
+            clipcat_base::ClipboardKind::Clipboard,
+            None,
+        )
+        .map_err(|err| invalid_content(&err))?;
+        let ids = {
+            let mut manager = self.manager.lock().await;
+            if dry_run {
+                manager.content_matches(entry)
+            } else {
+                manager.remove_by_content(entry)
+            }
+        };
+        Ok(Response::new(proto::BatchRemoveResponse { ids }))
+    }
+
+    async fn trim_to(
+        &self,
+        request: Request<proto::TrimToRequest>,
+    ) -> Result<Response<proto::BatchRemoveResponse>, Status> {
+        let proto::TrimToRequest { max_count, kind, dry_run } = request.into_inner();
+        let kind = kind.map(parse_kind).transpose()?;
+        let max_count = usize::try_from(max_count).unwrap_or(usize::MAX);
+        let ids = {
+            let mut manager = self.manager.lock().await;
+            if dry_run {
+                manager.trim_candidates(max_count, kind)
+            } else {
+                manager.trim_to(max_count, kind)
+            }
+        };
+        Ok(Response::new(proto::BatchRemoveResponse { ids }))
+    }
+
+    async fn clear(&self, request: Request<proto::ClearRequest>) -> Result<Response<()>, Status> {
+        let proto::ClearRequest { profile } = request.into_inner();
         {
             let mut manager = self.manager.lock().await;
-            manager.clear();
+            if let Some(profile) = profile {
+                manager.clear_profile(Some(profile.as_str()).filter(|p| !p.is_empty()));
+            } else {
+                manager.clear();
+            }
         }
         Ok(Response::new(()))
     }
 
+    async fn drain(
+        &self,
+        request: Request<proto::DrainRequest>,
+    ) -> Result<Response<proto::DrainResponse>, Status> {
+        let proto::DrainRequest { drain_snippets, keep_sticky_notes } = request.into_inner();
+        let entries = {
+            let mut manager = self.manager.lock().await;
+            manager.drain(!drain_snippets, keep_sticky_notes)
+        };
+        Ok(Response::new(proto::DrainResponse {
+            entries: entries.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn set_active_profile(
+        &self,
+        request: Request<proto::SetActiveProfileRequest>,
+    ) -> Result<Response<()>, Status> {
+        let proto::SetActiveProfileRequest { profile } = request.into_inner();
+        {
+            let mut manager = self.manager.lock().await;
+            manager.set_active_profile(profile);
+        }
+        Ok(Response::new(()))
+    }
+
+    async fn get_active_profile(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ActiveProfileResponse>, Status> {
+        let profile = {
+            let manager = self.manager.lock().await;
+            manager.active_profile().map(ToString::to_string)
+        };
+        Ok(Response::new(proto::ActiveProfileResponse { profile }))
+    }
+
     async fn get(
         &self,
         request: Request<proto::GetRequest>,
     ) -> Result<Response<proto::GetResponse>, Status> {
-        let proto::GetRequest { id } = request.into_inner();
+        let proto::GetRequest { id, max_bytes } = request.into_inner();
+        let clip = {
+            let manager = self.manager.lock().await;
+            manager.get(id)
+        };
+        let (data, truncated) = match (clip, max_bytes) {
+            (Some(clip), Some(max_bytes)) => {
+                let (clip, truncated) =
+                    clip.prefix(usize::try_from(max_bytes).unwrap_or(usize::MAX));
+                (Some(clip.into()), truncated)
+            }
+            (clip, _) => (clip.map(Into::into), false),
+        };
+        Ok(Response::new(proto::GetResponse { data, truncated }))
+    }
+
+    async fn get_full(
+        &self,
+        request: Request<proto::GetFullRequest>,
+    ) -> Result<Response<proto::GetFullResponse>, Status> {
+        let proto::GetFullRequest { id, preview_length } = request.into_inner();
         let data = {
             let manager = self.manager.lock().await;
-            manager.get(id).map(Into::into)
+            manager.get_full(id, usize::try_from(preview_length).unwrap_or(usize::MAX))
+        };
+        Ok(Response::new(proto::GetFullResponse { data: data.map(Into::into) }))
+    }
+
+    async fn get_stream(
+        &self,
+        request: Request<proto::GetStreamRequest>,
+    ) -> Result<Response<Self::GetStreamStream>, Status> {
+        let proto::GetStreamRequest { id, offset } = request.into_inner();
+        let clip = {
+            let manager = self.manager.lock().await;
+            manager.get(id)
         };
-        Ok(Response::new(proto::GetResponse { data }))
+        let clip = clip.ok_or_else(|| {
+            proto::ErrorDetail::new(proto::ErrorCode::ClipNotFound)
+                .with_id(id)
+                .into_status(Code::NotFound, format!("Clip {id:016x} not found"))
+        })?;
+        let data = clip.encoded().map_err(|err| {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal)
+                .with_id(id)
+                .into_status(Code::Internal, err.to_string())
+        })?;
+        let total_size = data.len() as u64;
+        let start =
+            usize::try_from(offset).ok().filter(|&start| start <= data.len()).ok_or_else(|| {
+                proto::ErrorDetail::new(proto::ErrorCode::OffsetOutOfRange)
+                    .with_id(id)
+                    .with_limit(total_size)
+                    .into_status(
+                        Code::OutOfRange,
+                        format!("Offset {offset} exceeds the size {total_size}"),
+                    )
+            })?;
+
+        let chunks = data[start..]
+            .chunks(GET_STREAM_CHUNK_SIZE)
+            .scan(offset, |offset, chunk| {
+                let chunk =
+                    proto::GetStreamChunk { offset: *offset, data: chunk.to_vec(), total_size };
+                *offset += chunk.data.len() as u64;
+                Some(Ok(chunk))
+            })
+            .collect::<Vec<_>>();
+        Ok(Response::new(Box::pin(futures::stream::iter(chunks))))
     }
 
     async fn get_current_clip(
         &self,
         request: Request<proto::GetCurrentClipRequest>,
     ) -> Result<Response<proto::GetCurrentClipResponse>, Status> {
+        let kind = parse_kind(request.into_inner().kind)?;
         let data = {
-            let kind = request.into_inner().kind.into();
             let manager = self.manager.lock().await;
-            manager.get_current_clip(kind).map(|clip| clip.clone().into())
+            manager.get_current_clip(kind).map(Into::into)
         };
         Ok(Response::new(proto::GetCurrentClipResponse { data }))
     }
 
+    async fn get_current_any(
+        &self,
+        request: Request<proto::GetCurrentAnyRequest>,
+    ) -> Result<Response<proto::GetCurrentClipResponse>, Status> {
+        let kinds = request
+            .into_inner()
+            .kinds
+            .into_iter()
+            .map(parse_kind)
+            .collect::<Result<Vec<_>, _>>()?;
+        let data = {
+            let manager = self.manager.lock().await;
+            manager.get_current_clip_any(&kinds).map(Into::into)
+        };
+        Ok(Response::new(proto::GetCurrentClipResponse { data }))
+    }
+
+    async fn take_current(
+        &self,
+        request: Request<proto::GetCurrentClipRequest>,
+    ) -> Result<Response<proto::GetCurrentClipResponse>, Status> {
+        let kind = parse_kind(request.into_inner().kind)?;
+        let data = {
+            let mut manager = self.manager.lock().await;
+            manager.take_current_clip(kind).await.map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::GetCurrentClipResponse { data: data.map(Into::into) }))
+    }
+
+    async fn current_entry_id(
+        &self,
+        request: Request<proto::GetCurrentClipRequest>,
+    ) -> Result<Response<proto::CurrentEntryIdResponse>, Status> {
+        let kind = parse_kind(request.into_inner().kind)?;
+        let id = {
+            let manager = self.manager.lock().await;
+            manager.live_clip_id(kind).await.map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::CurrentEntryIdResponse { id }))
+    }
+
+    async fn is_current(
+        &self,
+        request: Request<proto::IsCurrentRequest>,
+    ) -> Result<Response<proto::IsCurrentResponse>, Status> {
+        let proto::IsCurrentRequest { id, kind } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let current = {
+            let manager = self.manager.lock().await;
+            manager.is_current(id, kind).await.map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::IsCurrentResponse { current }))
+    }
+
+    async fn current_targets(
+        &self,
+        request: Request<proto::GetCurrentClipRequest>,
+    ) -> Result<Response<proto::CurrentTargetsResponse>, Status> {
+        let kind = parse_kind(request.into_inner().kind)?;
+        let targets = {
+            let manager = self.manager.lock().await;
+            manager.current_targets(kind).map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::CurrentTargetsResponse { targets }))
+    }
+
+    async fn current_formats(
+        &self,
+        request: Request<proto::GetCurrentClipRequest>,
+    ) -> Result<Response<proto::CurrentFormatsResponse>, Status> {
+        let kind = parse_kind(request.into_inner().kind)?;
+        let formats = {
+            let manager = self.manager.lock().await;
+            manager.current_formats(kind).await.map_err(|err| manager_error(&err))?
+        };
+        let formats = formats
+            .into_iter()
+            .map(|(mime, data)| proto::CurrentFormat { mime: mime.to_string(), data })
+            .collect();
+        Ok(Response::new(proto::CurrentFormatsResponse { formats }))
+    }
+
     async fn list(
         &self,
         request: Request<proto::ListRequest>,
     ) -> Result<Response<proto::ListResponse>, Status> {
-        let proto::ListRequest { preview_length } = request.into_inner();
+        let proto::ListRequest { preview_length, profile, order } = request.into_inner();
+        let preview_length = usize::try_from(preview_length).unwrap_or(30);
+        let order = clipcat_base::SortOrder::from(order);
+        let (metadata, skipped) = {
+            let manager = self.manager.lock().await;
+            profile.map_or_else(
+                || manager.list_skipping_corrupt(preview_length, order),
+                |profile| {
+                    let profile = Some(profile.as_str()).filter(|p| !p.is_empty());
+                    (manager.list_profile(profile, preview_length), 0)
+                },
+            )
+        };
+        if skipped > 0 {
+            tracing::warn!("{skipped} clip(s) with unreadable content are skipped from the list");
+        }
+        let metadata = metadata.into_iter().map(proto::ClipEntryMetadata::from).collect();
+        Ok(Response::new(proto::ListResponse { metadata, sorted: true, skipped: skipped as u64 }))
+    }
+
+    async fn list_range(
+        &self,
+        request: Request<proto::ListRangeRequest>,
+    ) -> Result<Response<proto::ListRangeResponse>, Status> {
+        let proto::ListRangeRequest { offset, limit } = request.into_inner();
+        let (entries, total) = {
+            let manager = self.manager.lock().await;
+            manager.list_range(
+                usize::try_from(offset).unwrap_or(usize::MAX),
+                usize::try_from(limit).unwrap_or(usize::MAX),
+            )
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListRangeResponse { entries, total: total as u64 }))
+    }
+
+    async fn open_snapshot(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::OpenSnapshotResponse>, Status> {
+        let (handle, total) = {
+            let mut manager = self.manager.lock().await;
+            manager.open_snapshot()
+        };
+        Ok(Response::new(proto::OpenSnapshotResponse { handle, total: total as u64 }))
+    }
+
+    async fn list_snapshot_range(
+        &self,
+        request: Request<proto::ListSnapshotRangeRequest>,
+    ) -> Result<Response<proto::ListRangeResponse>, Status> {
+        let proto::ListSnapshotRangeRequest { handle, offset, limit } = request.into_inner();
+        let (entries, total) = {
+            let mut manager = self.manager.lock().await;
+            manager
+                .list_snapshot_range(
+                    handle,
+                    usize::try_from(offset).unwrap_or(usize::MAX),
+                    usize::try_from(limit).unwrap_or(usize::MAX),
+                )
+                .map_err(|err| manager_error(&err))?
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListRangeResponse { entries, total: total as u64 }))
+    }
+
+    async fn close_snapshot(
+        &self,
+        request: Request<proto::CloseSnapshotRequest>,
+    ) -> Result<Response<proto::CloseSnapshotResponse>, Status> {
+        let proto::CloseSnapshotRequest { handle } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.close_snapshot(handle)
+        };
+        Ok(Response::new(proto::CloseSnapshotResponse { ok }))
+    }
+
+    async fn list_most_used(
+        &self,
+        request: Request<proto::ListMostUsedRequest>,
+    ) -> Result<Response<proto::ListMostUsedResponse>, Status> {
+        let proto::ListMostUsedRequest { limit } = request.into_inner();
+        let entries = {
+            let manager = self.manager.lock().await;
+            manager.list_most_used(usize::try_from(limit).unwrap_or(usize::MAX))
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListMostUsedResponse { entries }))
+    }
+
+    async fn list_after(
+        &self,
+        request: Request<proto::ListAfterRequest>,
+    ) -> Result<Response<proto::ListAfterResponse>, Status> {
+        let proto::ListAfterRequest { limit, cursor } = request.into_inner();
+        let cursor = cursor.map(clipcat_base::ListCursor::from);
+        let (entries, next_cursor) = {
+            let manager = self.manager.lock().await;
+            manager.list_after(cursor, usize::try_from(limit).unwrap_or(usize::MAX))
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        let next_cursor = next_cursor.map(proto::ListCursor::from);
+        Ok(Response::new(proto::ListAfterResponse { entries, next_cursor }))
+    }
+
+    async fn list_by_tags(
+        &self,
+        request: Request<proto::ListByTagsRequest>,
+    ) -> Result<Response<proto::ListByTagsResponse>, Status> {
+        let proto::ListByTagsRequest { tags, mode } = request.into_inner();
+        let entries = {
+            let manager = self.manager.lock().await;
+            manager.list_by_tags(&tags, clipcat_base::TagMatch::from(mode))
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListByTagsResponse { entries }))
+    }
+
+    async fn list_by_source_class(
+        &self,
+        request: Request<proto::ListBySourceClassRequest>,
+    ) -> Result<Response<proto::ListBySourceClassResponse>, Status> {
+        let proto::ListBySourceClassRequest { class } = request.into_inner();
+        let entries = {
+            let manager = self.manager.lock().await;
+            manager.list_by_source_class(&class)
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListBySourceClassResponse { entries }))
+    }
+
+    async fn list_by_semantic_kind(
+        &self,
+        request: Request<proto::ListBySemanticKindRequest>,
+    ) -> Result<Response<proto::ListBySemanticKindResponse>, Status> {
+        let kind = parse_semantic_kind(request.into_inner().kind)?;
+        let entries = {
+            let manager = self.manager.lock().await;
+            manager.list_by_semantic_kind(kind)
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListBySemanticKindResponse { entries }))
+    }
+
+    async fn list_by_size(
+        &self,
+        request: Request<proto::ListBySizeRequest>,
+    ) -> Result<Response<proto::ListBySizeResponse>, Status> {
+        let proto::ListBySizeRequest { min, max } = request.into_inner();
+        let entries = {
+            let manager = self.manager.lock().await;
+            manager.list_by_size(min, max)
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListBySizeResponse { entries }))
+    }
+
+    async fn list_since(
+        &self,
+        request: Request<proto::ListSinceRequest>,
+    ) -> Result<Response<proto::ListSinceResponse>, Status> {
+        let since = request
+            .into_inner()
+            .since
+            .map_or(Ok(time::OffsetDateTime::UNIX_EPOCH), |since| {
+                proto::timestamp_to_datetime(&since)
+            })
+            .map_err(|err| {
+                proto::ErrorDetail::new(proto::ErrorCode::InvalidArgument)
+                    .into_status(Code::InvalidArgument, err.to_string())
+            })?;
+        let entries = {
+            let manager = self.manager.lock().await;
+            manager.list_since(since)
+        };
+        let entries = entries.into_iter().map(proto::ClipEntry::from).collect();
+        Ok(Response::new(proto::ListSinceResponse { entries }))
+    }
+
+    async fn list_grouped(
+        &self,
+        request: Request<proto::ListGroupedRequest>,
+    ) -> Result<Response<proto::ListGroupedResponse>, Status> {
+        let proto::ListGroupedRequest { order } = request.into_inner();
+        let groups = {
+            let manager = self.manager.lock().await;
+            manager.list_grouped_sorted(clipcat_base::SortOrder::from(order))
+        };
+        let groups = groups
+            .into_iter()
+            .map(|(kind, entries)| proto::ClipGroup {
+                kind: kind.into(),
+                entries: entries.into_iter().map(proto::ClipEntry::from).collect(),
+            })
+            .collect();
+        Ok(Response::new(proto::ListGroupedResponse { groups }))
+    }
+
+    async fn entries_with_content_hash(
+        &self,
+        request: Request<proto::EntriesWithContentHashRequest>,
+    ) -> Result<Response<proto::EntriesWithContentHashResponse>, Status> {
+        let proto::EntriesWithContentHashRequest { hash } = request.into_inner();
+        let ids = {
+            let manager = self.manager.lock().await;
+            manager.ids_with_content_hash(&hash).map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::EntriesWithContentHashResponse { ids }))
+    }
+
+    async fn list_ids(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ListIdsResponse>, Status> {
+        let ids = {
+            let manager = self.manager.lock().await;
+            manager.list_ids()
+        };
+        let ids = ids
+            .into_iter()
+            .map(|(id, timestamp)| proto::ClipIdTimestamp {
+                id,
+                timestamp: Some(proto::datetime_to_timestamp(&timestamp)),
+            })
+            .collect();
+        Ok(Response::new(proto::ListIdsResponse { ids }))
+    }
+
+    async fn batch_metadata(
+        &self,
+        request: Request<proto::BatchMetadataRequest>,
+    ) -> Result<Response<proto::BatchMetadataResponse>, Status> {
+        let proto::BatchMetadataRequest { ids, preview_length } = request.into_inner();
         let metadata = {
             let manager = self.manager.lock().await;
             manager
-                .list(usize::try_from(preview_length).unwrap_or(30))
+                .batch_metadata(&ids, usize::try_from(preview_length).unwrap_or(30))
                 .into_iter()
                 .map(proto::ClipEntryMetadata::from)
                 .collect()
         };
-        Ok(Response::new(proto::ListResponse { metadata }))
+        Ok(Response::new(proto::BatchMetadataResponse { metadata }))
+    }
+
+    async fn batch_etags(
+        &self,
+        request: Request<proto::BatchEtagsRequest>,
+    ) -> Result<Response<proto::BatchEtagsResponse>, Status> {
+        let proto::BatchEtagsRequest { ids } = request.into_inner();
+        let etags = {
+            let manager = self.manager.lock().await;
+            manager
+                .batch_etags(&ids)
+                .into_iter()
+                .map(|(id, etag)| proto::ClipEtag { id, etag })
+                .collect()
+        };
+        Ok(Response::new(proto::BatchEtagsResponse { etags }))
     }
 
     async fn update(
         &self,
         request: Request<proto::UpdateRequest>,
     ) -> Result<Response<proto::UpdateResponse>, Status> {
-        let proto::UpdateRequest { id, data, mime } = request.into_inner();
+        let proto::UpdateRequest { id, data, mime, expected_version } = request.into_inner();
+        let mime = parse_mime(&mime)?;
+        let (ok, new_id) = {
+            let mut manager = self.manager.lock().await;
+            match expected_version {
+                Some(expected_version) => manager
+                    .replace_if(id, expected_version, &data, &mime)
+                    .map_err(|err| manager_error(&err))?,
+                None => manager.replace(id, &data, &mime),
+            }
+        };
+        Ok(Response::new(proto::UpdateResponse { ok, new_id }))
+    }
+
+    async fn append(
+        &self,
+        request: Request<proto::AppendRequest>,
+    ) -> Result<Response<proto::UpdateResponse>, Status> {
+        let proto::AppendRequest { id, data, separator } = request.into_inner();
         let (ok, new_id) = {
-            let mime = mime::Mime::from_str(&mime).unwrap_or(mime::APPLICATION_OCTET_STREAM);
             let mut manager = self.manager.lock().await;
-            manager.replace(id, &data, &mime)
+            manager.append(id, &data, separator.as_deref()).map_err(|err| manager_error(&err))?
         };
         Ok(Response::new(proto::UpdateResponse { ok, new_id }))
     }
@@ -127,13 +937,358 @@ where
         request: Request<proto::MarkRequest>,
     ) -> Result<Response<proto::MarkResponse>, Status> {
         let proto::MarkRequest { id, kind } = request.into_inner();
-        let ok = {
+        let kind = parse_kind(kind)?;
+        let result = {
+            let mut manager = self.manager.lock().await;
+            manager.mark(id, kind).await
+        };
+        let ok = match result {
+            Ok(()) => true,
+            Err(crate::manager::Error::KindDisabled { kind }) => return Err(kind_disabled(kind)),
+            Err(
+                err @ (crate::manager::Error::ReadOnly
+                | crate::manager::Error::UnsupportedMime { .. }),
+            ) => return Err(manager_error(&err)),
+            Err(_) => false,
+        };
+        Ok(Response::new(proto::MarkResponse { ok }))
+    }
+
+    async fn mark_and_propagate(
+        &self,
+        request: Request<proto::MarkRequest>,
+    ) -> Result<Response<proto::MarkResponse>, Status> {
+        let (id, kind) = (request.get_ref().id, request.get_ref().kind);
+        let response = proto::Manager::mark(self, request).await?;
+        if let (true, Some(federation)) = (response.get_ref().ok, &self.federation) {
+            let clip = self.manager.lock().await.get(id);
+            if let Some(clip) = clip.filter(|clip| !clip.is_private()) {
+                let data = clip.encoded().map_err(|err| invalid_content(&err))?;
+                let key = Federation::new_key();
+                let _unused = federation.first_seen(&key);
+                federation.propagate(&proto::InsertRequest {
+                    kind,
+                    data: data.into(),
+                    mime: clip.mime().to_string(),
+                    tags: clip.tags().iter().cloned().collect(),
+                    ttl_ms: None,
+                    silent: false,
+                    attributes: clip.attributes().clone().into_iter().collect(),
+                    propagate: true,
+                    idempotency_key: Some(key),
+                    private: false,
+                    position: None,
+                    force_new: false,
+                });
+            }
+        }
+        Ok(response)
+    }
+
+    async fn mark_and_remove(
+        &self,
+        request: Request<proto::MarkRequest>,
+    ) -> Result<Response<proto::MarkResponse>, Status> {
+        let proto::MarkRequest { id, kind } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let result = {
+            let mut manager = self.manager.lock().await;
+            manager.mark_and_remove(id, kind).await
+        };
+        let ok = result.map_err(|err| manager_error(&err))?;
+        Ok(Response::new(proto::MarkResponse { ok }))
+    }
+
+    async fn mark_kinds(
+        &self,
+        request: Request<proto::MarkKindsRequest>,
+    ) -> Result<Response<proto::MarkResponse>, Status> {
+        let proto::MarkKindsRequest { id, kinds } = request.into_inner();
+        let kinds = kinds.into_iter().map(parse_kind).collect::<Result<Vec<_>, _>>()?;
+        let result = {
+            let mut manager = self.manager.lock().await;
+            manager.mark_kinds(id, &kinds).await
+        };
+        let ok = result.map_err(|err| manager_error(&err))?;
+        Ok(Response::new(proto::MarkResponse { ok }))
+    }
+
+    async fn cycle(
+        &self,
+        request: Request<proto::CycleRequest>,
+    ) -> Result<Response<proto::CycleResponse>, Status> {
+        let proto::CycleRequest { kind, direction, wrap } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let result = {
+            let mut manager = self.manager.lock().await;
+            manager.cycle(kind, clipcat_base::CycleDirection::from(direction), wrap).await
+        };
+        let id = result.map_err(|err| manager_error(&err))?;
+        Ok(Response::new(proto::CycleResponse { id }))
+    }
+
+    async fn mark_as(
+        &self,
+        request: Request<proto::MarkAsRequest>,
+    ) -> Result<Response<proto::MarkResponse>, Status> {
+        let proto::MarkAsRequest { id, kind, mime } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let mime = parse_mime(&mime)?;
+        let result = {
             let mut manager = self.manager.lock().await;
-            manager.mark(id, kind.into()).await.is_ok()
+            manager.mark_as(id, kind, &mime).await
         };
+        let ok = result.map_err(|err| manager_error(&err))?;
         Ok(Response::new(proto::MarkResponse { ok }))
     }
 
+    async fn notify_resync(&self, _request: Request<()>) -> Result<Response<()>, Status> {
+        self.manager.lock().await.notify_resync();
+        Ok(Response::new(()))
+    }
+
+    async fn wait_active(
+        &self,
+        request: Request<proto::WaitActiveRequest>,
+    ) -> Result<Response<proto::WaitActiveResponse>, Status> {
+        let proto::WaitActiveRequest { id, kind, timeout_ms } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let is_active =
+            |manager: &ClipboardManager<Notification>| manager.current_clip_id(kind) == Some(id);
+
+        // subscribe before checking, a change between them is not missed
+        let receiver = {
+            let manager = self.manager.lock().await;
+            if !manager.is_kind_enabled(kind) {
+                return Err(kind_disabled(kind));
+            }
+            if is_active(&manager) {
+                return Ok(Response::new(proto::WaitActiveResponse { active: true }));
+            }
+            manager.subscribe_changes(None).1
+        };
+        let Some(mut receiver) = receiver else {
+            return Err(shutting_down());
+        };
+
+        let wait = async {
+            loop {
+                match receiver.recv().await {
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                        if is_active(&*self.manager.lock().await) {
+                            return true;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return false,
+                }
+            }
+        };
+        let active =
+            tokio::time::timeout(Duration::from_millis(timeout_ms), wait).await.unwrap_or(false);
+        Ok(Response::new(proto::WaitActiveResponse { active }))
+    }
+
+    async fn set_transient(
+        &self,
+        request: Request<proto::SetTransientRequest>,
+    ) -> Result<Response<()>, Status> {
+        let proto::SetTransientRequest { kind, data, mime } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let mime = parse_mime(&mime)?;
+        let entry = clipcat_base::ClipEntry::new(&data, &mime, kind, None)
+            .map_err(|err| invalid_content(&err))?;
+        let result = {
+            let mut manager = self.manager.lock().await;
+            manager.set_transient(entry).await
+        };
+        result.map_err(|err| manager_error(&err))?;
+        Ok(Response::new(()))
+    }
+
+    async fn replace_active(
+        &self,
+        request: Request<proto::ReplaceActiveRequest>,
+    ) -> Result<Response<proto::InsertResponse>, Status> {
+        let proto::ReplaceActiveRequest { kind, data, mime } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let mime = parse_mime(&mime)?;
+        let entry = clipcat_base::ClipEntry::new(&data, &mime, kind, None)
+            .map_err(|err| invalid_content(&err))?;
+        let stored_mime = entry.mime().to_string();
+        let result = {
+            let mut manager = self.manager.lock().await;
+            manager.replace_active(entry).await
+        };
+        let (id, evicted) = result.map_err(|err| manager_error(&err))?;
+        Ok(Response::new(proto::InsertResponse { id, evicted, mime: stored_mime }))
+    }
+
+    async fn touch(
+        &self,
+        request: Request<proto::TouchRequest>,
+    ) -> Result<Response<proto::TouchResponse>, Status> {
+        let proto::TouchRequest { id } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.touch(id)
+        };
+        Ok(Response::new(proto::TouchResponse { ok }))
+    }
+
+    async fn protect(
+        &self,
+        request: Request<proto::ProtectRequest>,
+    ) -> Result<Response<proto::ProtectResponse>, Status> {
+        let proto::ProtectRequest { id, until } = request.into_inner();
+        let until = until
+            .map_or(Ok(time::OffsetDateTime::UNIX_EPOCH), |until| {
+                proto::timestamp_to_datetime(&until)
+            })
+            .map_err(|err| {
+                proto::ErrorDetail::new(proto::ErrorCode::InvalidArgument)
+                    .into_status(Code::InvalidArgument, err.to_string())
+            })?;
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.protect_until(id, until)
+        };
+        Ok(Response::new(proto::ProtectResponse { ok }))
+    }
+
+    async fn set_label(
+        &self,
+        request: Request<proto::SetLabelRequest>,
+    ) -> Result<Response<proto::SetLabelResponse>, Status> {
+        let proto::SetLabelRequest { id, label } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.set_label(id, label)
+        };
+        Ok(Response::new(proto::SetLabelResponse { ok }))
+    }
+
+    async fn set_sticky(
+        &self,
+        request: Request<proto::SetStickyRequest>,
+    ) -> Result<Response<proto::SetStickyResponse>, Status> {
+        let proto::SetStickyRequest { id, sticky } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.set_sticky(id, sticky).map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::SetStickyResponse { ok }))
+    }
+
+    async fn pin_current(
+        &self,
+        request: Request<proto::GetCurrentClipRequest>,
+    ) -> Result<Response<proto::PinCurrentResponse>, Status> {
+        let proto::GetCurrentClipRequest { kind } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let id = {
+            let mut manager = self.manager.lock().await;
+            manager.pin_current(kind).map_err(|err| manager_error(&err))?
+        };
+        Ok(Response::new(proto::PinCurrentResponse { id }))
+    }
+
+    async fn set_semantic_kind(
+        &self,
+        request: Request<proto::SetSemanticKindRequest>,
+    ) -> Result<Response<proto::SetSemanticKindResponse>, Status> {
+        let proto::SetSemanticKindRequest { id, kind } = request.into_inner();
+        let kind = parse_semantic_kind(kind)?;
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.set_semantic_kind(id, kind)
+        };
+        Ok(Response::new(proto::SetSemanticKindResponse { ok }))
+    }
+
+    async fn swap(
+        &self,
+        request: Request<proto::SwapRequest>,
+    ) -> Result<Response<proto::SwapResponse>, Status> {
+        let proto::SwapRequest { a, b } = request.into_inner();
+        let ok = {
+            let mut manager = self.manager.lock().await;
+            manager.swap(a, b)
+        };
+        Ok(Response::new(proto::SwapResponse { ok }))
+    }
+
+    async fn reorder(
+        &self,
+        request: Request<proto::ReorderRequest>,
+    ) -> Result<Response<()>, Status> {
+        let proto::ReorderRequest { key } = request.into_inner();
+        let key = clipcat_base::SortKey::from(key);
+        // the history file is locked first like when the history is flushed, a
+        // clip captured meanwhile is appended after the reordered history
+        let mut history = self.history.lock().await;
+        let (moved, clips) = {
+            let mut manager = self.manager.lock().await;
+            (manager.reorder(key), manager.persisted_clips())
+        };
+        let _report = history.compact(&clips).await.map_err(|err| {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal)
+                .into_status(Code::Internal, err.to_string())
+        })?;
+        drop(history);
+        tracing::info!("History is reordered by {key:?}, {moved} clip(s) are moved");
+        Ok(Response::new(()))
+    }
+
+    async fn set_history(
+        &self,
+        request: Request<proto::SetHistoryRequest>,
+    ) -> Result<Response<()>, Status> {
+        let proto::SetHistoryRequest { clips } = request.into_inner();
+        let clips = clips.into_iter().map(clipcat_base::ClipEntry::from).collect();
+        let mut history = self.history.lock().await;
+        let (count, clips) = {
+            let mut manager = self.manager.lock().await;
+            let count = manager.set_history(clips).map_err(|err| manager_error(&err))?;
+            (count, manager.persisted_clips())
+        };
+        let _report = history.compact(&clips).await.map_err(|err| {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal)
+                .into_status(Code::Internal, err.to_string())
+        })?;
+        drop(history);
+        tracing::info!("History is replaced with {count} clip(s)");
+        Ok(Response::new(()))
+    }
+
+    async fn apply(
+        &self,
+        request: Request<proto::ApplyRequest>,
+    ) -> Result<Response<proto::ApplyResponse>, Status> {
+        let ops = request
+            .into_inner()
+            .ops
+            .into_iter()
+            .map(|proto::ClipOp { op }| match op {
+                Some(proto::clip_op::Op::Remove(id)) => Ok(clipcat_base::ClipOp::Remove { id }),
+                Some(proto::clip_op::Op::Mark(proto::MarkRequest { id, kind })) => {
+                    Ok(clipcat_base::ClipOp::Mark { id, kind: parse_kind(kind)? })
+                }
+                Some(proto::clip_op::Op::Promote(id)) => Ok(clipcat_base::ClipOp::Promote { id }),
+                Some(proto::clip_op::Op::SetTags(proto::SetTagsOp { id, tags })) => {
+                    Ok(clipcat_base::ClipOp::SetTags { id, tags })
+                }
+                None => Err(proto::ErrorDetail::new(proto::ErrorCode::InvalidArgument)
+                    .into_status(Code::InvalidArgument, "operation is not set")),
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+        let results = {
+            let mut manager = self.manager.lock().await;
+            manager.apply(ops).await
+        };
+        let results = results.into_iter().map(proto::ClipOpResult::from).collect();
+        Ok(Response::new(proto::ApplyResponse { results }))
+    }
+
     async fn length(
         &self,
         _request: Request<()>,
@@ -144,4 +1299,485 @@ where
         };
         Ok(Response::new(proto::LengthResponse { length }))
     }
+
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> Result<Response<proto::SearchResponse>, Status> {
+        let proto::SearchRequest { query, preview_length, fuzzy } = request.into_inner();
+        let matches = {
+            let manager = self.manager.lock().await;
+            manager.search(&query, usize::try_from(preview_length).unwrap_or(30), fuzzy)
+        };
+        let (metadata, scores) = matches
+            .into_iter()
+            .map(|(metadata, score)| (proto::ClipEntryMetadata::from(metadata), score))
+            .unzip();
+        Ok(Response::new(proto::SearchResponse { metadata, scores }))
+    }
+
+    async fn first_matching(
+        &self,
+        request: Request<proto::FirstMatchingRequest>,
+    ) -> Result<Response<proto::FirstMatchingResponse>, Status> {
+        let proto::FirstMatchingRequest { query, fuzzy } = request.into_inner();
+        let data = {
+            let manager = self.manager.lock().await;
+            manager.first_matching(&query, fuzzy).map(Into::into)
+        };
+        Ok(Response::new(proto::FirstMatchingResponse { data }))
+    }
+
+    async fn get_stats(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::HistoryStats>, Status> {
+        let stats = {
+            let manager = self.manager.lock().await;
+            manager.stats()
+        };
+        Ok(Response::new(stats.into()))
+    }
+
+    async fn get_resource_usage(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ResourceUsage>, Status> {
+        let usage = {
+            let manager = self.manager.lock().await;
+            manager.resource_usage()
+        };
+        Ok(Response::new(usage.into()))
+    }
+
+    async fn mime_types(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::MimeTypesResponse>, Status> {
+        let mime_types = {
+            let manager = self.manager.lock().await;
+            manager.mime_types()
+        };
+        let mime_types = mime_types
+            .into_iter()
+            .map(|(mime, count)| proto::MimeTypeCount { mime, count })
+            .collect();
+        Ok(Response::new(proto::MimeTypesResponse { mime_types }))
+    }
+
+    async fn content_manifest(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ContentManifestResponse>, Status> {
+        let manifest = {
+            let manager = self.manager.lock().await;
+            manager.content_manifest()
+        };
+        let entries = manifest
+            .into_iter()
+            .map(|(id, content_hash)| proto::ManifestEntry { id, content_hash })
+            .collect();
+        Ok(Response::new(proto::ContentManifestResponse { entries }))
+    }
+
+    async fn export_metadata(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ClipMetadataRecords>, Status> {
+        let records = {
+            let manager = self.manager.lock().await;
+            manager.export_metadata()
+        };
+        let records = records.into_iter().map(proto::ClipMetadataRecord::from).collect();
+        Ok(Response::new(proto::ClipMetadataRecords { records }))
+    }
+
+    async fn import_metadata(
+        &self,
+        request: Request<proto::ClipMetadataRecords>,
+    ) -> Result<Response<proto::ImportMetadataResponse>, Status> {
+        let proto::ClipMetadataRecords { records } = request.into_inner();
+        let records = records.into_iter().map(clipcat_base::MetadataRecord::from).collect();
+        let report = {
+            let mut manager = self.manager.lock().await;
+            manager.import_metadata(records)
+        };
+        Ok(Response::new(proto::ImportMetadataResponse::from(report)))
+    }
+
+    async fn verify_store(
+        &self,
+        request: Request<proto::VerifyStoreRequest>,
+    ) -> Result<Response<proto::StoreReport>, Status> {
+        let proto::VerifyStoreRequest { repair } = request.into_inner();
+        let mut report = self.history.lock().await.verify(repair).await.map_err(|err| {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal)
+                .into_status(Code::Internal, err.to_string())
+        })?;
+        report.corrupt_clip_ids = {
+            let mut manager = self.manager.lock().await;
+            let ids = manager.corrupt_ids();
+            if repair && !ids.is_empty() {
+                for &id in &ids {
+                    let _ = manager.remove(id);
+                }
+                report.repaired = true;
+            }
+            drop(manager);
+            ids
+        };
+        if !report.is_healthy() {
+            tracing::warn!(
+                "{n} corrupt clip(s) and {bytes} unreadable byte(s) are found in history file, \
+                 {clips} clip(s) with unreadable content are found in history, repaired: \
+                 {repaired}",
+                n = report.corrupt_entries.len(),
+                bytes = report.unreadable_bytes,
+                clips = report.corrupt_clip_ids.len(),
+                repaired = report.repaired
+            );
+        }
+        Ok(Response::new(report.into()))
+    }
+
+    async fn compact_store(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::CompactReport>, Status> {
+        let clips = {
+            let manager = self.manager.lock().await;
+            manager.persisted_clips()
+        };
+        let report = self.history.lock().await.compact(&clips).await.map_err(|err| {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal)
+                .into_status(Code::Internal, err.to_string())
+        })?;
+        tracing::info!(
+            "History file is compacted, {entries} clip(s) are kept, {bytes} byte(s) are reclaimed",
+            entries = report.entries,
+            bytes = report.bytes_reclaimed()
+        );
+        Ok(Response::new(report.into()))
+    }
+
+    async fn get_activity(
+        &self,
+        request: Request<proto::ActivityRequest>,
+    ) -> Result<Response<proto::ActivityResponse>, Status> {
+        let proto::ActivityRequest { bucket_ms, since } = request.into_inner();
+        let bucket = time::Duration::milliseconds(i64::try_from(bucket_ms).unwrap_or(i64::MAX));
+        let since = since
+            .map_or(Ok(time::OffsetDateTime::UNIX_EPOCH), |since| {
+                proto::timestamp_to_datetime(&since)
+            })
+            .map_err(|err| {
+                proto::ErrorDetail::new(proto::ErrorCode::InvalidArgument)
+                    .into_status(Code::InvalidArgument, err.to_string())
+            })?;
+        let activity = {
+            let manager = self.manager.lock().await;
+            manager.activity(bucket, since, time::OffsetDateTime::now_utc())
+        };
+        let buckets = activity
+            .map_err(|err| manager_error(&err))?
+            .into_iter()
+            .map(|(start, count)| proto::ActivityBucket {
+                start: Some(proto::datetime_to_timestamp(&start)),
+                count,
+            })
+            .collect();
+        Ok(Response::new(proto::ActivityResponse { buckets }))
+    }
+
+    async fn time_bounds(
+        &self,
+        request: Request<proto::TimeBoundsRequest>,
+    ) -> Result<Response<proto::TimeBoundsResponse>, Status> {
+        let proto::TimeBoundsRequest { kind } = request.into_inner();
+        let kind = kind.map(parse_kind).transpose()?;
+        let bounds = {
+            let manager = self.manager.lock().await;
+            manager.time_bounds(kind)
+        };
+        let (oldest, newest) = bounds.map_or((None, None), |(oldest, newest)| {
+            (
+                Some(proto::datetime_to_timestamp(&oldest)),
+                Some(proto::datetime_to_timestamp(&newest)),
+            )
+        });
+        Ok(Response::new(proto::TimeBoundsResponse { oldest, newest }))
+    }
+
+    async fn watch(
+        &self,
+        request: Request<proto::WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let proto::WatchRequest { since_cursor, heartbeat_interval_ms, metadata_preview_length } =
+            request.into_inner();
+        let metadata_preview_length =
+            metadata_preview_length.map(|length| usize::try_from(length).unwrap_or(usize::MAX));
+        let (events, receiver, redactor) = {
+            let manager = self.manager.lock().await;
+            let (events, receiver) = manager.subscribe_changes(since_cursor);
+            (events, receiver, manager.redactor().clone())
+        };
+        // the previews are redacted like the listed ones
+        let strip = move |mut event: clipcat_base::ClipboardEvent| {
+            if let Some(preview_length) = metadata_preview_length {
+                event.change = redactor.change_metadata(event.change, preview_length);
+            }
+            proto::ClipboardEvent::from(event)
+        };
+        let receiver = receiver.ok_or_else(shutting_down)?;
+
+        let events = futures::stream::iter(
+            events.into_iter().map(|event| Ok(strip(event))).collect::<Vec<_>>(),
+        );
+        let following_events = BroadcastStream::new(receiver).map(move |event| {
+            event.map(&strip).map_err(|BroadcastStreamRecvError::Lagged(count)| {
+                proto::ErrorDetail::new(proto::ErrorCode::WatcherLagged).into_status(
+                    Code::DataLoss,
+                    format!(
+                        "Watcher lagged behind, {count} change(s) are skipped, please resume from \
+                         the last received cursor"
+                    ),
+                )
+            })
+        });
+        // the broadcast ends when the server is shutting down
+        let shutdown = futures::stream::once(async {
+            Ok(proto::ClipboardEvent { cursor: 0, change: None, shutdown: true })
+        });
+        let stream: Self::WatchStream = Box::pin(events.chain(following_events).chain(shutdown));
+        Ok(Response::new(match heartbeat_interval_ms {
+            Some(interval) => {
+                with_heartbeat(stream, Duration::from_millis(interval).max(MIN_HEARTBEAT_INTERVAL))
+            }
+            None => stream,
+        }))
+    }
+
+    async fn diff_since(
+        &self,
+        request: Request<proto::DiffSinceRequest>,
+    ) -> Result<Response<proto::HistoryDelta>, Status> {
+        let cursor = request.into_inner().cursor;
+        let delta = {
+            let manager = self.manager.lock().await;
+            manager.diff_since(cursor)
+        };
+        Ok(Response::new(delta.into()))
+    }
+
+    async fn watch_current(
+        &self,
+        request: Request<proto::WatchCurrentRequest>,
+    ) -> Result<Response<Self::WatchCurrentStream>, Status> {
+        let proto::WatchCurrentRequest { kind } = request.into_inner();
+        let kind = parse_kind(kind)?;
+        let receiver = {
+            let manager = self.manager.lock().await;
+            if !manager.is_kind_enabled(kind) {
+                return Err(kind_disabled(kind));
+            }
+            manager.subscribe_current_clips()
+        };
+        let receiver = receiver.ok_or_else(shutting_down)?;
+
+        // the receiver yields the current ids first, then each time the id of
+        // any clipboard kind is changed
+        let manager = Arc::clone(&self.manager);
+        let events = WatchStream::new(receiver)
+            .scan(None, move |last_id, ids| {
+                let id = ids[usize::from(kind)];
+                let changed = last_id.replace(id) != Some(id);
+                futures::future::ready(Some(changed.then_some(id)))
+            })
+            .filter_map(futures::future::ready)
+            .then(move |id| {
+                let manager = Arc::clone(&manager);
+                async move {
+                    let entry = match id {
+                        Some(id) => manager.lock().await.get(id).map(proto::ClipEntry::from),
+                        None => None,
+                    };
+                    Ok(proto::CurrentClipEvent { entry, shutdown: false })
+                }
+            });
+        // the receiver ends when the server is shutting down
+        let shutdown = futures::stream::once(async {
+            Ok(proto::CurrentClipEvent { entry: None, shutdown: true })
+        });
+        Ok(Response::new(Box::pin(events.chain(shutdown))))
+    }
+}
+
+type ClipboardEventStream =
+    Pin<Box<dyn Stream<Item = Result<proto::ClipboardEvent, Status>> + Send>>;
+
+/// Interleaves a heartbeat event every `period` into `stream`, a heartbeat
+/// carries the cursor of the last change sent.
+fn with_heartbeat(stream: ClipboardEventStream, period: Duration) -> ClipboardEventStream {
+    let interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    Box::pin(futures::stream::unfold(
+        (stream, interval, 0),
+        |(mut stream, mut interval, cursor)| async move {
+            #[allow(clippy::redundant_pub_crate)]
+            let event = tokio::select! {
+                event = stream.next() => event?,
+                _ = interval.tick() => Ok(proto::ClipboardEvent {
+                    cursor,
+                    change: Some(proto::clipboard_event::Change::Heartbeat(())),
+                    shutdown: false,
+                }),
+            };
+            let cursor = match &event {
+                Ok(proto::ClipboardEvent { cursor, change: Some(_), .. }) => *cursor,
+                _ => cursor,
+            };
+            Some((event, (stream, interval, cursor)))
+        },
+    ))
+}
+
+// attach the detail of the error, so clients can tell the errors apart
+fn manager_error(err: &crate::manager::Error) -> Status {
+    let message = err.to_string();
+    match *err {
+        crate::manager::Error::StoreClipboardContent { .. }
+        | crate::manager::Error::LoadClipboardContent { .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal).into_status(Code::Internal, message)
+        }
+        crate::manager::Error::StoreClipboardKind { kind, .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::StoreFailed)
+                .with_kind(kind)
+                .into_status(Code::Internal, message)
+        }
+        crate::manager::Error::IdTaken { id } => proto::ErrorDetail::new(proto::ErrorCode::IdTaken)
+            .with_id(id)
+            .into_status(Code::AlreadyExists, message),
+        crate::manager::Error::KindDisabled { kind } => kind_disabled(kind),
+        crate::manager::Error::VersionConflict { id, expected_version, .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::VersionConflict)
+                .with_id(id)
+                .with_expected_version(expected_version)
+                .into_status(Code::Aborted, message)
+        }
+        crate::manager::Error::UnsupportedConversion { id, .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::UnsupportedConversion)
+                .with_id(id)
+                .into_status(Code::InvalidArgument, message)
+        }
+        crate::manager::Error::UnsupportedMime { id, ref mime } => {
+            proto::ErrorDetail::new(proto::ErrorCode::UnsupportedMime)
+                .with_id(id)
+                .with_mime(mime.clone())
+                .into_status(Code::FailedPrecondition, message)
+        }
+        crate::manager::Error::ReadOnly => proto::ErrorDetail::new(proto::ErrorCode::ReadOnly)
+            .into_status(Code::FailedPrecondition, message),
+        crate::manager::Error::TestingDisabled => {
+            proto::ErrorDetail::new(proto::ErrorCode::TestingDisabled)
+                .into_status(Code::PermissionDenied, message)
+        }
+        crate::manager::Error::NotAnImage { id } => {
+            proto::ErrorDetail::new(proto::ErrorCode::NotAnImage)
+                .with_id(id)
+                .into_status(Code::FailedPrecondition, message)
+        }
+        crate::manager::Error::NotText { id } => proto::ErrorDetail::new(proto::ErrorCode::NotText)
+            .with_id(id)
+            .into_status(Code::FailedPrecondition, message),
+        crate::manager::Error::EncodeImage { id, .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::Internal)
+                .with_id(id)
+                .into_status(Code::Internal, message)
+        }
+        crate::manager::Error::SnapshotNotFound { handle } => {
+            proto::ErrorDetail::new(proto::ErrorCode::SnapshotNotFound)
+                .with_id(handle)
+                .into_status(Code::NotFound, message)
+        }
+        crate::manager::Error::InvalidBucket | crate::manager::Error::InvalidContentHash { .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::InvalidArgument)
+                .into_status(Code::InvalidArgument, message)
+        }
+        crate::manager::Error::TooManyBuckets { limit, .. } => {
+            proto::ErrorDetail::new(proto::ErrorCode::TooManyBuckets)
+                .with_limit(limit as u64)
+                .into_status(Code::InvalidArgument, message)
+        }
+        crate::manager::Error::HistoryFull { count, capacity } => {
+            proto::ErrorDetail::new(proto::ErrorCode::HistoryFull)
+                .with_usage(count as u64)
+                .with_limit(capacity as u64)
+                .into_status(Code::ResourceExhausted, message)
+        }
+        crate::manager::Error::TooManyStickyNotes { limit } => {
+            proto::ErrorDetail::new(proto::ErrorCode::TooManyStickyNotes)
+                .with_limit(limit as u64)
+                .into_status(Code::ResourceExhausted, message)
+        }
+    }
+}
+
+// rejects inserting a clip into a history full of protected clips, a clip
+// merged into a clip with the same content takes no room
+fn check_insert_capacity<Notification>(
+    manager: &ClipboardManager<Notification>,
+    entry: &clipcat_base::ClipEntry,
+    force_new: bool,
+) -> Result<(), Status>
+where
+    Notification: notification::Notification,
+{
+    if !force_new && !manager.ids_with_digest(entry.sha256_digest()).is_empty() {
+        return Ok(());
+    }
+    manager.check_capacity().map_err(|err| manager_error(&err))
+}
+
+fn kind_disabled(kind: clipcat_base::ClipboardKind) -> Status {
+    proto::ErrorDetail::new(proto::ErrorCode::KindDisabled)
+        .with_kind(kind)
+        .into_status(Code::Unimplemented, crate::manager::Error::KindDisabled { kind }.to_string())
+}
+
+fn invalid_content(err: &clipcat_base::ClipEntryError) -> Status {
+    proto::ErrorDetail::new(proto::ErrorCode::InvalidContent)
+        .into_status(Code::InvalidArgument, err.to_string())
+}
+
+fn shutting_down() -> Status {
+    proto::ErrorDetail::new(proto::ErrorCode::ShuttingDown)
+        .into_status(Code::Unavailable, "Clipcat server is shutting down")
+}
+
+fn parse_mime(mime: &str) -> Result<mime::Mime, Status> {
+    // strings which are not UTF-8 are rejected on decoding and the parser
+    // rejects control characters, but it accepts other non-ASCII characters
+    // in quoted parameters
+    let parsed = (mime.len() <= MAX_MIME_LENGTH && mime.is_ascii())
+        .then(|| mime::Mime::from_str(mime).ok())
+        .flatten();
+    parsed.ok_or_else(|| {
+        let mime = mime.escape_debug().take(MAX_MIME_LENGTH).collect::<String>();
+        tracing::debug!("Reject invalid mime type `{mime}`");
+        proto::ErrorDetail::new(proto::ErrorCode::InvalidMime)
+            .into_status(Code::InvalidArgument, format!("Invalid mime type `{mime}`"))
+    })
+}
+
+fn parse_kind(kind: i32) -> Result<clipcat_base::ClipboardKind, Status> {
+    clipcat_base::ClipboardKind::try_from(kind).map_err(|err| {
+        proto::ErrorDetail::new(proto::ErrorCode::InvalidKind)
+            .into_status(Code::InvalidArgument, err.to_string())
+    })
+}
+
+fn parse_semantic_kind(kind: i32) -> Result<clipcat_base::SemanticKind, Status> {
+    clipcat_base::SemanticKind::try_from(kind).map_err(|err| {
+        proto::ErrorDetail::new(proto::ErrorCode::InvalidArgument)
+            .into_status(Code::InvalidArgument, err.to_string())
+    })
 }
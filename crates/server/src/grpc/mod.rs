@@ -1,9 +1,19 @@
+mod federation;
 mod interceptor;
 mod manager;
+mod rate_limit;
 mod system;
+mod trace;
 mod watcher;
+mod web;
 
 pub use self::{
-    interceptor::Interceptor, manager::ManagerService, system::SystemService,
+    federation::Federation,
+    interceptor::Interceptor,
+    manager::{InsertLimits, ManagerService},
+    rate_limit::RateLimiter,
+    system::SystemService,
+    trace::trace_layer,
     watcher::WatcherService,
+    web::cors_layer,
 };
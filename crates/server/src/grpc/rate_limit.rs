@@ -0,0 +1,110 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use tonic::{transport::server::UdsConnectInfo, Request};
+
+// length of the window in which the inserts of a client are counted
+const WINDOW: Duration = Duration::from_secs(1);
+
+// count of tracked clients above which the windows of idle clients are dropped
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+/// The client a request comes from.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ClientKey {
+    /// A client connected over TCP, by its IP address.
+    Address(IpAddr),
+
+    /// A client connected over the local socket, by its process id.
+    Process(i32),
+
+    /// A client which can not be told apart from the other unknown ones.
+    Unknown,
+}
+
+impl ClientKey {
+    pub fn from_request<T>(request: &Request<T>) -> Self {
+        if let Some(address) = request.remote_addr() {
+            return Self::Address(address.ip());
+        }
+        request
+            .extensions()
+            .get::<UdsConnectInfo>()
+            .and_then(|info| info.peer_cred)
+            .and_then(|cred| cred.pid())
+            .map_or(Self::Unknown, Self::Process)
+    }
+}
+
+/// Limits the count of inserts of each client per second.
+#[derive(Debug)]
+pub struct RateLimiter {
+    max_per_second: u32,
+
+    // the start of the current window of each client and the count of inserts
+    // in it
+    windows: parking_lot::Mutex<HashMap<ClientKey, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_per_second: u32) -> Self {
+        Self { max_per_second, windows: parking_lot::Mutex::default() }
+    }
+
+    pub const fn max_per_second(&self) -> u32 { self.max_per_second }
+
+    /// Counts an insert of `client` at `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns how long the client should wait if it exceeds the limit.
+    pub fn check(&self, client: ClientKey, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock();
+        if windows.len() >= MAX_TRACKED_CLIENTS {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        }
+        let (start, count) = windows.entry(client).or_insert((now, 0));
+        if now.duration_since(*start) >= WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.max_per_second {
+            return Err(WINDOW.saturating_sub(now.duration_since(*start)));
+        }
+        *count += 1;
+        drop(windows);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    use super::{ClientKey, RateLimiter};
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(2);
+        let client = ClientKey::Address(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let now = Instant::now();
+        assert!(limiter.check(client, now).is_ok());
+        assert!(limiter.check(client, now + Duration::from_millis(100)).is_ok());
+        assert_eq!(
+            limiter.check(client, now + Duration::from_millis(400)),
+            Err(Duration::from_millis(600))
+        );
+
+        // clients are limited separately
+        assert!(limiter.check(ClientKey::Process(42), now).is_ok());
+
+        // the count is reset in the next window
+        assert!(limiter.check(client, now + Duration::from_secs(1)).is_ok());
+    }
+}
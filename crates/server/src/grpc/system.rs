@@ -2,6 +2,8 @@ use clipcat_proto as proto;
 use once_cell::sync::Lazy;
 use tonic::{Request, Response, Status};
 
+use crate::{notification, ClipboardWatcherToggle};
+
 static GET_SYSTEM_VERSION_RESPONSE: Lazy<proto::GetSystemVersionResponse> =
     Lazy::new(|| proto::GetSystemVersionResponse {
         major: clipcat_base::PROJECT_SEMVER.major,
@@ -9,19 +11,41 @@ static GET_SYSTEM_VERSION_RESPONSE: Lazy<proto::GetSystemVersionResponse> =
         patch: clipcat_base::PROJECT_SEMVER.patch,
     });
 
-pub struct SystemService {}
+pub struct SystemService<Notification> {
+    server_info: clipcat_base::ServerInfo,
+    watcher_toggle: ClipboardWatcherToggle<Notification>,
+}
 
-impl SystemService {
+impl<Notification> SystemService<Notification> {
     #[inline]
-    pub const fn new() -> Self { Self {} }
+    pub const fn new(
+        server_info: clipcat_base::ServerInfo,
+        watcher_toggle: ClipboardWatcherToggle<Notification>,
+    ) -> Self {
+        Self { server_info, watcher_toggle }
+    }
 }
 
 #[tonic::async_trait]
-impl proto::System for SystemService {
+impl<Notification> proto::System for SystemService<Notification>
+where
+    Notification: notification::Notification + 'static,
+{
     async fn get_version(
         &self,
         _request: Request<()>,
     ) -> Result<Response<proto::GetSystemVersionResponse>, Status> {
         Ok(Response::new(*GET_SYSTEM_VERSION_RESPONSE))
     }
+
+    async fn get_server_info(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::ServerInfo>, Status> {
+        let server_info = clipcat_base::ServerInfo {
+            capture_paused: !self.watcher_toggle.is_watching(),
+            ..self.server_info.clone()
+        };
+        Ok(Response::new(server_info.into()))
+    }
 }
@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tower_http::trace::{DefaultOnFailure, GrpcMakeClassifier, MakeSpan, TraceLayer};
+use tracing::Level;
+
+// ids generated for the requests without an operation id
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Creates a span for each gRPC request with its method and operation id, the
+/// logs of handling the request are recorded in the span.
+///
+/// The operation id is taken from the metadata set by the client, like
+/// `Client::with_operation_id`, a request without one is given an id of the
+/// server.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OperationSpan;
+
+impl<B> MakeSpan<B> for OperationSpan {
+    fn make_span(&mut self, request: &http::Request<B>) -> tracing::Span {
+        let operation_id = operation_id(request.headers());
+        tracing::info_span!("grpc", method = request.uri().path(), operation_id)
+    }
+}
+
+fn operation_id(headers: &http::HeaderMap) -> String {
+    headers
+        .get(clipcat_base::GRPC_OPERATION_ID_KEY)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty())
+        .map_or_else(
+            || format!("server-{}", NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed)),
+            ToString::to_string,
+        )
+}
+
+/// Creates a layer tracing gRPC requests in the spans of [`OperationSpan`].
+///
+/// Failed requests are logged at debug level like the other requests, the
+/// handlers log the errors which matter.
+pub fn trace_layer() -> TraceLayer<GrpcMakeClassifier, OperationSpan> {
+    TraceLayer::new_for_grpc()
+        .make_span_with(OperationSpan)
+        .on_failure(DefaultOnFailure::new().level(Level::DEBUG))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::operation_id;
+
+    #[test]
+    fn test_operation_id() {
+        let mut headers = http::HeaderMap::new();
+        let (first, second) = (operation_id(&headers), operation_id(&headers));
+        assert!(first.starts_with("server-"));
+        assert_ne!(first, second);
+
+        drop(headers.insert(clipcat_base::GRPC_OPERATION_ID_KEY, "copy-1".parse().unwrap()));
+        assert_eq!(operation_id(&headers), "copy-1");
+    }
+}
@@ -53,4 +53,11 @@ where
         let state = proto::WatcherStateReply { state: self.watcher_toggle.state().into() };
         Ok(Response::new(state))
     }
+
+    async fn get_diagnostics(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<proto::Diagnostics>, Status> {
+        Ok(Response::new(self.watcher_toggle.diagnostics().into()))
+    }
 }
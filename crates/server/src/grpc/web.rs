@@ -0,0 +1,84 @@
+use http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+// headers sent by grpc-web clients, besides the access token
+const REQUEST_HEADERS: [HeaderName; 5] = [
+    header::AUTHORIZATION,
+    header::CONTENT_TYPE,
+    HeaderName::from_static("grpc-timeout"),
+    HeaderName::from_static("x-grpc-web"),
+    HeaderName::from_static("x-user-agent"),
+];
+
+// trailers of gRPC are sent as headers of a grpc-web response if there is no
+// message, browsers hide them unless they are exposed
+const RESPONSE_HEADERS: [HeaderName; 3] = [
+    HeaderName::from_static("grpc-status"),
+    HeaderName::from_static("grpc-message"),
+    HeaderName::from_static("grpc-status-details-bin"),
+];
+
+/// Creates the CORS layer in front of the grpc-web services.
+///
+/// Pages from `allowed_origins`, like `http://localhost:8080`, may call the
+/// services, `*` allows any origin. Cross-origin requests are rejected by
+/// browsers if `allowed_origins` is empty.
+pub fn cors_layer(allowed_origins: &[String]) -> CorsLayer {
+    let allow_origin = if allowed_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(allowed_origins.iter().filter_map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|err| {
+                    tracing::warn!("Ignore invalid grpc-web origin `{origin}`, error: {err}");
+                })
+                .ok()
+        }))
+    };
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::POST])
+        .allow_headers(REQUEST_HEADERS)
+        .expose_headers(RESPONSE_HEADERS)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{header, Method, Request, Response};
+    use tower::{ServiceBuilder, ServiceExt};
+
+    use super::cors_layer;
+
+    async fn preflight(allowed_origins: &[String], origin: &str) -> Option<String> {
+        let service = ServiceBuilder::new().layer(cors_layer(allowed_origins)).service_fn(
+            |_request: Request<String>| async {
+                Ok::<_, std::convert::Infallible>(Response::new(String::new()))
+            },
+        );
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/manager.Manager/List")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(String::new())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+    }
+
+    #[tokio::test]
+    async fn test_cors_layer() {
+        let origins = vec!["http://localhost:8080".to_string()];
+        assert_eq!(
+            preflight(&origins, "http://localhost:8080").await.as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(preflight(&origins, "http://example.com").await, None);
+        assert_eq!(preflight(&[], "http://localhost:8080").await, None);
+        assert_eq!(preflight(&["*".to_string()], "http://example.com").await.as_deref(), Some("*"));
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use clipcat_base::{ClipEntry, ClipboardKind};
+use snafu::ResultExt;
+use tokio::fs::OpenOptions;
+
+use crate::history::{
+    driver::fs::{image_file_path_from_digest, model},
+    error, Error,
+};
+
+pub async fn load<P, Q>(clips_file_path: P, image_dir_path: Q) -> Result<Vec<ClipEntry>, Error>
+where
+    P: AsRef<Path> + Send,
+    Q: AsRef<Path> + Send,
+{
+    tracing::info!("Load clips from v4 schema");
+
+    let clips_file_path = clips_file_path.as_ref().to_path_buf();
+    let image_dir_path = image_dir_path.as_ref().to_path_buf();
+    let clips_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .read(true)
+        .append(true)
+        .open(&clips_file_path)
+        .await
+        .context(error::OpenFileSnafu { file_path: clips_file_path })?
+        .into_std()
+        .await;
+
+    tokio::task::spawn_blocking(move || {
+        let mut clips = Vec::new();
+        while let Ok(clip) = bincode::deserialize_from::<_, model::v4::ClipboardValue>(&clips_file)
+        {
+            let model::v4::ClipboardValue { timestamp, created_at, mime, data, use_count } = clip;
+            // images are stored in files named by their digests
+            let data = if mime.type_() == mime::IMAGE {
+                let file_path = image_file_path_from_digest(&image_dir_path, &data);
+                match std::fs::read(&file_path).context(error::ReadFileSnafu { file_path }) {
+                    Ok(data) => data,
+                    Err(err) => {
+                        tracing::error!("{err}");
+                        continue;
+                    }
+                }
+            } else {
+                data
+            };
+
+            if let Ok(mut clip) =
+                ClipEntry::new(&data, &mime, ClipboardKind::Clipboard, Some(timestamp))
+            {
+                clip.set_created_at(created_at);
+                clip.set_use_count(use_count);
+                clips.push(clip);
+            }
+        }
+        Ok(clips)
+    })
+    .await
+    .context(error::JoinTaskSnafu)?
+}
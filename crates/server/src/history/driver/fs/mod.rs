@@ -7,7 +7,7 @@ use std::{
 };
 
 use async_trait::async_trait;
-use clipcat_base::{ClipEntry, ClipboardKind};
+use clipcat_base::{ClipEntry, CompactReport, CorruptEntry, StoreReport};
 use snafu::ResultExt;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime, UtcOffset};
 use tokio::{
@@ -17,7 +17,7 @@ use tokio::{
 
 use crate::history::{driver::Driver, error, Error};
 
-const CURRENT_SCHEMA: u64 = model::v2::FileHeader::SCHEMA_VERSION;
+const CURRENT_SCHEMA: u64 = model::v5::FileHeader::SCHEMA_VERSION;
 
 pub struct FileSystemDriver {
     file_path: PathBuf,
@@ -39,8 +39,8 @@ impl FileSystemDriver {
             .context(error::CreateDirectorySnafu { file_path: file_path.clone() })?;
 
         if let Ok(header_content) = tokio::fs::read(&header_file_path).await {
-            if let Ok(model::v2::FileHeader { schema, last_update }) =
-                serde_json::from_slice::<model::v2::FileHeader>(&header_content)
+            if let Ok(model::v5::FileHeader { schema, last_update }) =
+                serde_json::from_slice::<model::v5::FileHeader>(&header_content)
             {
                 tracing::info!(
                     "Open `{}`, schema: {schema}, last update: {last_update}",
@@ -59,11 +59,23 @@ impl FileSystemDriver {
                         tracing::info!("Clip history schema `{schema}` is out-of-date");
                         Some(migrate::v1::load(&clips_file_path).await?)
                     }
+                    model::v2::FileHeader::SCHEMA_VERSION => {
+                        tracing::info!("Clip history schema `{schema}` is out-of-date");
+                        Some(migrate::v2::load(&clips_file_path, image_dir_path(&file_path)).await?)
+                    }
+                    model::v3::FileHeader::SCHEMA_VERSION => {
+                        tracing::info!("Clip history schema `{schema}` is out-of-date");
+                        Some(migrate::v3::load(&clips_file_path, image_dir_path(&file_path)).await?)
+                    }
+                    model::v4::FileHeader::SCHEMA_VERSION => {
+                        tracing::info!("Clip history schema `{schema}` is out-of-date");
+                        Some(migrate::v4::load(&clips_file_path, image_dir_path(&file_path)).await?)
+                    }
                     _ => None,
                 };
 
                 if let Some(clips) = clips {
-                    migrate::v2::migrate_to(&file_path, &header_file_path, &clips_file_path, clips)
+                    migrate::v5::migrate_to(&file_path, &header_file_path, &clips_file_path, clips)
                         .await?;
                 }
             }
@@ -91,6 +103,13 @@ impl FileSystemDriver {
         Ok(Self { file_path, clips_file, header_file })
     }
 
+    pub fn exists<P>(file_path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        clips_file_path(file_path).is_file()
+    }
+
     async fn update_header(&mut self) -> Result<(), Error> {
         self.header_file
             .set_len(0)
@@ -98,8 +117,8 @@ impl FileSystemDriver {
             .context(error::TruncateFileSnafu { file_path: self.header_file_path() })?;
         drop(self.header_file.seek(SeekFrom::Start(0)).await);
 
-        let content = serde_json::to_string_pretty(&model::v2::FileHeader {
-            schema: model::v2::FileHeader::SCHEMA_VERSION,
+        let content = serde_json::to_string_pretty(&model::v5::FileHeader {
+            schema: model::v5::FileHeader::SCHEMA_VERSION,
             last_update: OffsetDateTime::now_utc(),
         })
         .context(error::SeriailizeHistoryHeaderSnafu)?;
@@ -111,27 +130,11 @@ impl FileSystemDriver {
     }
 
     async fn store_file_content(&mut self, clip: ClipEntry) -> Result<(), Error> {
-        let image_dir_path = self.image_dir_path();
-        if clip.mime().type_() == mime::IMAGE {
-            let content = match clip.encoded() {
-                Ok(content) => content,
-                Err(err) => {
-                    tracing::error!("Error occurs while encoding clip, error: {err}");
-                    return Ok(());
-                }
-            };
-            let file_path = image_file_path_from_digest(image_dir_path, clip.sha256_digest());
-            if let Some(parent) = file_path.parent() {
-                tokio::fs::create_dir_all(parent)
-                    .await
-                    .context(error::CreateDirectorySnafu { file_path: parent.to_path_buf() })?;
-            }
-            tokio::fs::write(&file_path, content)
-                .await
-                .context(error::WriteFileSnafu { file_path })?;
+        if clip.is_image() && write_image_file(&self.image_dir_path(), &clip).await?.is_none() {
+            return Ok(());
         }
 
-        let content = bincode::serialize(&model::v2::ClipboardValue::from(clip))
+        let content = bincode::serialize(&model::v5::ClipboardValue::from(clip))
             .context(error::SeriailizeClipSnafu)?;
         self.clips_file
             .write_all(content.as_ref())
@@ -180,9 +183,18 @@ impl Driver for FileSystemDriver {
             let mut clips = Vec::new();
 
             while let Ok(clip) =
-                bincode::deserialize_from::<_, model::v2::ClipboardValue>(&clips_file)
+                bincode::deserialize_from::<_, model::v5::ClipboardValue>(&clips_file)
             {
-                let model::v2::ClipboardValue { timestamp, mime, data } = clip;
+                let model::v5::ClipboardValue {
+                    id,
+                    kind,
+                    timestamp,
+                    created_at,
+                    mime,
+                    data,
+                    use_count,
+                    sticky,
+                } = clip;
                 let data = if mime.type_() == mime::IMAGE {
                     let file_path = image_file_path_from_digest(&image_dir_path, &data);
                     let maybe_data = std::fs::read(&file_path)
@@ -198,9 +210,12 @@ impl Driver for FileSystemDriver {
                     data
                 };
 
-                if let Ok(clip) =
-                    ClipEntry::new(&data, &mime, ClipboardKind::Clipboard, Some(timestamp))
-                {
+                if let Ok(mut clip) = ClipEntry::new(&data, &mime, kind, Some(timestamp)) {
+                    // clips kept apart by the `DedupeScope` keep their ids
+                    clip.set_id(id);
+                    clip.set_created_at(created_at);
+                    clip.set_use_count(use_count);
+                    clip.set_sticky(sticky);
                     clips.push(clip);
                 }
             }
@@ -246,7 +261,7 @@ impl Driver for FileSystemDriver {
         let mut clips = tokio::task::spawn_blocking(move || {
             let mut clips = Vec::new();
             while let Ok(clip) =
-                bincode::deserialize_from::<_, model::v2::ClipboardValue>(&clips_file)
+                bincode::deserialize_from::<_, model::v5::ClipboardValue>(&clips_file)
             {
                 let serialized_size =
                     usize::try_from(bincode::serialized_size(&clip).unwrap_or_default())
@@ -301,6 +316,177 @@ impl Driver for FileSystemDriver {
 
         self.update_header().await
     }
+
+    async fn verify(&mut self, repair: bool) -> Result<StoreReport, Error> {
+        drop(self.clips_file.flush().await);
+
+        let clips_file_path = self.clips_file_path();
+        let image_dir_path = self.image_dir_path();
+        let (mut report, valid_data) = tokio::task::spawn_blocking(move || {
+            let data = std::fs::read(&clips_file_path)
+                .context(error::ReadFileSnafu { file_path: clips_file_path })?;
+            Ok::<_, Error>(verify_clips(&data, &image_dir_path))
+        })
+        .await
+        .context(error::JoinTaskSnafu)??;
+
+        if repair && !report.is_healthy() {
+            self.clips_file
+                .set_len(0)
+                .await
+                .with_context(|_| error::TruncateFileSnafu { file_path: self.clips_file_path() })?;
+            self.clips_file
+                .write_all(&valid_data)
+                .await
+                .with_context(|_| error::WriteFileSnafu { file_path: self.clips_file_path() })?;
+            drop(self.clips_file.flush().await);
+            self.update_header().await?;
+            report.repaired = true;
+        }
+        Ok(report)
+    }
+
+    async fn compact(&mut self, clips: &[ClipEntry]) -> Result<CompactReport, Error> {
+        drop(self.clips_file.flush().await);
+
+        let clips_file_path = self.clips_file_path();
+        let image_dir_path = self.image_dir_path();
+        let bytes_before =
+            tokio::fs::metadata(&clips_file_path).await.map_or(0, |metadata| metadata.len());
+
+        let mut data = Vec::new();
+        let mut image_files = HashSet::new();
+        let mut entries = 0;
+        for clip in clips {
+            if clip.is_image() {
+                let Some(file_path) = write_image_file(&image_dir_path, clip).await? else {
+                    continue;
+                };
+                let _ = image_files.insert(file_path);
+            }
+            bincode::serialize_into(&mut data, &model::v5::ClipboardValue::from(clip.clone()))
+                .context(error::SeriailizeClipSnafu)?;
+            entries += 1;
+        }
+
+        // the compacted file replaces the history file at once, the history
+        // file is intact if writing is interrupted
+        let temp_file_path = clips_file_path.with_extension("compact");
+        {
+            let mut temp_file = File::create(&temp_file_path)
+                .await
+                .context(error::OpenFileSnafu { file_path: temp_file_path.clone() })?;
+            temp_file
+                .write_all(&data)
+                .await
+                .context(error::WriteFileSnafu { file_path: temp_file_path.clone() })?;
+            temp_file
+                .sync_all()
+                .await
+                .context(error::WriteFileSnafu { file_path: temp_file_path.clone() })?;
+        }
+        tokio::fs::rename(&temp_file_path, &clips_file_path).await.context(
+            error::RenameFileSnafu { from: temp_file_path, to: clips_file_path.clone() },
+        )?;
+        self.clips_file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .read(true)
+            .append(true)
+            .open(&clips_file_path)
+            .await
+            .context(error::OpenFileSnafu { file_path: clips_file_path })?;
+
+        let mut image_files_removed = 0;
+        if let Ok(mut dir_entries) = tokio::fs::read_dir(&image_dir_path).await {
+            while let Ok(Some(entry)) = dir_entries.next_entry().await {
+                let file_path = entry.path();
+                if !image_files.contains(&file_path)
+                    && tokio::fs::remove_file(&file_path).await.is_ok()
+                {
+                    image_files_removed += 1;
+                }
+            }
+        }
+
+        self.update_header().await?;
+        Ok(CompactReport {
+            entries,
+            bytes_before,
+            bytes_after: data.len() as u64,
+            image_files_removed,
+        })
+    }
+}
+
+// writes the image of `clip` into the file named by its digest, returns the
+// path of the file or `None` if the image could not be encoded
+async fn write_image_file(
+    image_dir_path: &Path,
+    clip: &ClipEntry,
+) -> Result<Option<PathBuf>, Error> {
+    let content = match clip.encoded() {
+        Ok(content) => content,
+        Err(err) => {
+            tracing::error!("Error occurs while encoding clip, error: {err}");
+            return Ok(None);
+        }
+    };
+    let file_path = image_file_path_from_digest(image_dir_path, clip.sha256_digest());
+    if let Some(parent) = file_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .context(error::CreateDirectorySnafu { file_path: parent.to_path_buf() })?;
+    }
+    tokio::fs::write(&file_path, content)
+        .await
+        .context(error::WriteFileSnafu { file_path: file_path.clone() })?;
+    Ok(Some(file_path))
+}
+
+// reads the serialized clips in `data`, returns the report and the clips which
+// can be loaded, serialized as they are in `data`
+fn verify_clips(data: &[u8], image_dir_path: &Path) -> (StoreReport, Vec<u8>) {
+    let mut report = StoreReport::default();
+    let mut valid_data = Vec::with_capacity(data.len());
+    let mut reader = std::io::Cursor::new(data);
+    loop {
+        let start = usize::try_from(reader.position()).unwrap_or(data.len());
+        if start >= data.len() {
+            break;
+        }
+        let Ok(model::v5::ClipboardValue { kind, timestamp, mime, data: content, .. }) =
+            bincode::deserialize_from::<_, model::v5::ClipboardValue>(&mut reader)
+        else {
+            // the following clips can not be located
+            report.unreadable_bytes = (data.len() - start) as u64;
+            break;
+        };
+        let end = usize::try_from(reader.position()).unwrap_or(data.len());
+
+        let content = if mime.type_() == mime::IMAGE {
+            let file_path = image_file_path_from_digest(image_dir_path, &content);
+            std::fs::read(&file_path).map_err(|err| {
+                format!("Failed to read image file {}, error: {err}", file_path.display())
+            })
+        } else {
+            Ok(content)
+        };
+        let result = content.and_then(|content| {
+            ClipEntry::new(&content, &mime, kind, Some(timestamp)).map_err(|err| err.to_string())
+        });
+        match result {
+            Ok(_) => valid_data.extend_from_slice(&data[start..end]),
+            Err(reason) => report.corrupt_entries.push(CorruptEntry {
+                index: report.scanned,
+                timestamp,
+                mime,
+                reason,
+            }),
+        }
+        report.scanned += 1;
+    }
+    (report, valid_data)
 }
 
 fn header_file_path<P>(file_path: P) -> PathBuf
@@ -336,3 +522,209 @@ where
 fn image_file_name(digest: &[u8]) -> String {
     format!("{digest}.png", digest = hex::encode(digest))
 }
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardContent, ClipboardKind};
+    use tokio::io::AsyncWriteExt;
+
+    use super::{clips_file_path, header_file_path, image_dir_path, model, FileSystemDriver};
+    use crate::history::driver::Driver;
+
+    #[tokio::test]
+    async fn test_migrate_v3() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-migrate-v3-{}", std::process::id()));
+        tokio::fs::create_dir_all(&file_path).await.unwrap();
+        let clip = ClipEntry::from_string("migrated", ClipboardKind::Clipboard);
+        let header = model::v3::FileHeader {
+            schema: model::v3::FileHeader::SCHEMA_VERSION,
+            last_update: clip.timestamp(),
+        };
+        tokio::fs::write(header_file_path(&file_path), serde_json::to_vec(&header).unwrap())
+            .await
+            .unwrap();
+        let value = model::v3::ClipboardValue::from(clip.clone());
+        tokio::fs::write(clips_file_path(&file_path), bincode::serialize(&value).unwrap())
+            .await
+            .unwrap();
+
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let clips = driver.load().await.unwrap();
+        assert_eq!(clips, vec![clip.clone()]);
+        assert_eq!(clips[0].created_at(), clip.created_at());
+        assert_eq!(clips[0].use_count(), 0);
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_migrate_v4() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-migrate-v4-{}", std::process::id()));
+        tokio::fs::create_dir_all(&file_path).await.unwrap();
+        let mut clip = ClipEntry::from_string("migrated", ClipboardKind::Clipboard);
+        clip.set_use_count(2);
+        let header = model::v4::FileHeader {
+            schema: model::v4::FileHeader::SCHEMA_VERSION,
+            last_update: clip.timestamp(),
+        };
+        tokio::fs::write(header_file_path(&file_path), serde_json::to_vec(&header).unwrap())
+            .await
+            .unwrap();
+        let value = model::v4::ClipboardValue::from(clip.clone());
+        tokio::fs::write(clips_file_path(&file_path), bincode::serialize(&value).unwrap())
+            .await
+            .unwrap();
+
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let clips = driver.load().await.unwrap();
+        assert_eq!(clips, vec![clip.clone()]);
+        assert_eq!(clips[0].id(), clip.id());
+        assert_eq!(clips[0].use_count(), 2);
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_use_count() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-use-count-{}", std::process::id()));
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let mut clip = ClipEntry::from_string("used", ClipboardKind::Clipboard);
+        clip.set_use_count(3);
+        driver.save(&[clip]).await.unwrap();
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let clips = driver.load().await.unwrap();
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].use_count(), 3);
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_kind_and_id() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-kind-and-id-{}", std::process::id()));
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        // the clips with the same content are kept apart
+        let clipboard = ClipEntry::from_string("kept apart", ClipboardKind::Clipboard);
+        let mut primary = ClipEntry::from_string("kept apart", ClipboardKind::Primary);
+        primary.set_id(clipboard.id() ^ 1);
+        driver.save(&[clipboard.clone(), primary.clone()]).await.unwrap();
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let clips = driver.load().await.unwrap();
+        assert_eq!(
+            clips.iter().map(|clip| (clip.id(), clip.kind())).collect::<Vec<_>>(),
+            vec![
+                (clipboard.id(), ClipboardKind::Clipboard),
+                (primary.id(), ClipboardKind::Primary)
+            ]
+        );
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sticky() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-sticky-{}", std::process::id()));
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let mut note = ClipEntry::from_string("note", ClipboardKind::Clipboard);
+        note.set_sticky(true);
+        driver
+            .save(&[note, ClipEntry::from_string("clip", ClipboardKind::Clipboard)])
+            .await
+            .unwrap();
+        drop(driver);
+
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let clips = driver.load().await.unwrap();
+        assert_eq!(clips.iter().map(ClipEntry::is_sticky).collect::<Vec<_>>(), vec![true, false]);
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compact() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-compact-{}", std::process::id()));
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let kept = ClipEntry::from_string("kept", ClipboardKind::Clipboard);
+        let image = ClipEntry::from_clipboard_content(
+            ClipboardContent::Image { width: 1, height: 1, bytes: vec![255; 4].into() },
+            ClipboardKind::Clipboard,
+            None,
+        );
+        // the clip is captured twice and the image is removed later
+        for clip in [&kept, &image, &kept] {
+            driver.put(clip).await.unwrap();
+        }
+
+        let report = driver.compact(std::slice::from_ref(&kept)).await.unwrap();
+        assert_eq!(report.entries, 1);
+        assert_eq!(report.image_files_removed, 1);
+        assert!(report.bytes_reclaimed() > 0);
+        assert_eq!(
+            tokio::fs::metadata(clips_file_path(&file_path)).await.unwrap().len(),
+            report.bytes_after
+        );
+        assert_eq!(driver.load().await.unwrap(), vec![kept.clone()]);
+
+        // clips are appended to the compacted file
+        driver.put(&image).await.unwrap();
+        assert_eq!(driver.load().await.unwrap(), vec![kept, image]);
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        let file_path =
+            std::env::temp_dir().join(format!("clipcat-test-verify-{}", std::process::id()));
+        let mut driver = FileSystemDriver::new(&file_path).await.unwrap();
+        let image = ClipEntry::from_clipboard_content(
+            ClipboardContent::Image { width: 1, height: 1, bytes: vec![255; 4].into() },
+            ClipboardKind::Clipboard,
+            None,
+        );
+        driver
+            .save(&[
+                ClipEntry::from_string("first", ClipboardKind::Clipboard),
+                image,
+                ClipEntry::from_string("second", ClipboardKind::Clipboard),
+            ])
+            .await
+            .unwrap();
+        assert!(driver.verify(false).await.unwrap().is_healthy());
+
+        // the image file is lost and a clip is partially written
+        tokio::fs::remove_dir_all(image_dir_path(&file_path)).await.unwrap();
+        driver.clips_file.write_all(&[1, 2, 3]).await.unwrap();
+        let report = driver.verify(false).await.unwrap();
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.corrupt_entries.len(), 1);
+        assert_eq!(report.corrupt_entries[0].index, 1);
+        assert_eq!(report.unreadable_bytes, 3);
+        assert!(!report.repaired);
+
+        assert!(driver.verify(true).await.unwrap().repaired);
+        let report = driver.verify(false).await.unwrap();
+        assert!(report.is_healthy());
+        assert_eq!(report.scanned, 2);
+        assert_eq!(driver.load().await.unwrap().len(), 2);
+
+        drop(driver);
+        tokio::fs::remove_dir_all(&file_path).await.unwrap();
+    }
+}
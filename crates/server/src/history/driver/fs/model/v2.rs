@@ -28,7 +28,7 @@ pub struct ClipboardValue {
 
 impl From<ClipEntry> for ClipboardValue {
     fn from(entry: ClipEntry) -> Self {
-        if entry.mime().type_() == mime::IMAGE {
+        if entry.is_image() {
             Self {
                 data: entry.sha256_digest().to_vec(),
                 mime: entry.mime(),
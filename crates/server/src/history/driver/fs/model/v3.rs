@@ -0,0 +1,61 @@
+use std::cmp::Ordering;
+
+use clipcat_base::ClipEntry;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileHeader {
+    pub schema: u64,
+
+    #[serde(with = "time::serde::iso8601")]
+    pub last_update: OffsetDateTime,
+}
+
+impl FileHeader {
+    pub const SCHEMA_VERSION: u64 = 3;
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Serialize)]
+pub struct ClipboardValue {
+    pub timestamp: OffsetDateTime,
+
+    pub created_at: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+}
+
+impl From<ClipEntry> for ClipboardValue {
+    fn from(entry: ClipEntry) -> Self {
+        if entry.is_image() {
+            Self {
+                data: entry.sha256_digest().to_vec(),
+                mime: entry.mime(),
+                timestamp: entry.timestamp(),
+                created_at: entry.created_at(),
+            }
+        } else {
+            Self {
+                data: entry.encoded().unwrap_or_default(),
+                mime: entry.mime(),
+                timestamp: entry.timestamp(),
+                created_at: entry.created_at(),
+            }
+        }
+    }
+}
+
+impl PartialOrd for ClipboardValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for ClipboardValue {
+    fn cmp(&self, other: &Self) -> Ordering { other.timestamp.cmp(&self.timestamp) }
+}
+
+impl PartialEq for ClipboardValue {
+    fn eq(&self, other: &Self) -> bool { self.data == other.data }
+}
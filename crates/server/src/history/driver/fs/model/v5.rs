@@ -0,0 +1,70 @@
+use std::cmp::Ordering;
+
+use clipcat_base::{ClipEntry, ClipboardKind};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FileHeader {
+    pub schema: u64,
+
+    #[serde(with = "time::serde::iso8601")]
+    pub last_update: OffsetDateTime,
+}
+
+impl FileHeader {
+    pub const SCHEMA_VERSION: u64 = 5;
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Serialize)]
+pub struct ClipboardValue {
+    pub id: u64,
+
+    #[serde(with = "clipcat_base::serde::clipboard_kind")]
+    pub kind: ClipboardKind,
+
+    pub timestamp: OffsetDateTime,
+
+    pub created_at: OffsetDateTime,
+
+    #[serde(with = "clipcat_base::serde::mime")]
+    pub mime: mime::Mime,
+
+    pub data: Vec<u8>,
+
+    pub use_count: u64,
+
+    pub sticky: bool,
+}
+
+impl From<ClipEntry> for ClipboardValue {
+    fn from(entry: ClipEntry) -> Self {
+        let data = if entry.is_image() {
+            entry.sha256_digest().to_vec()
+        } else {
+            entry.encoded().unwrap_or_default()
+        };
+        Self {
+            id: entry.id(),
+            kind: entry.kind(),
+            timestamp: entry.timestamp(),
+            created_at: entry.created_at(),
+            mime: entry.mime(),
+            data,
+            use_count: entry.use_count(),
+            sticky: entry.is_sticky(),
+        }
+    }
+}
+
+impl PartialOrd for ClipboardValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for ClipboardValue {
+    fn cmp(&self, other: &Self) -> Ordering { other.timestamp.cmp(&self.timestamp) }
+}
+
+impl PartialEq for ClipboardValue {
+    fn eq(&self, other: &Self) -> bool { self.id == other.id }
+}
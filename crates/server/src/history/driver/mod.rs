@@ -1,7 +1,7 @@
 mod fs;
 
 use async_trait::async_trait;
-use clipcat_base::ClipEntry;
+use clipcat_base::{ClipEntry, CompactReport, StoreReport};
 
 pub use self::fs::FileSystemDriver;
 use crate::history::Error;
@@ -18,6 +18,13 @@ pub trait Driver: Send + Sync {
 
     async fn shrink_to(&mut self, min_capacity: usize) -> Result<(), Error>;
 
+    /// Checks the stored clips, drops the corrupt ones if `repair` is set.
+    async fn verify(&mut self, repair: bool) -> Result<StoreReport, Error>;
+
+    /// Rewrites the store with only `clips`, dropping the entries of removed
+    /// clips and the older copies of updated clips.
+    async fn compact(&mut self, clips: &[ClipEntry]) -> Result<CompactReport, Error>;
+
     async fn save_and_shrink_to(
         &mut self,
         data: &[ClipEntry],
@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use crate::config::HistoryFlushConfig;
+
+/// Decides when the history is saved, from the changes of history made since
+/// it is saved last.
+///
+/// Changes are counted by the cursor of the journal of the clipboard manager,
+/// which grows with every change.
+#[derive(Clone, Debug)]
+pub struct FlushPolicy {
+    idle: Option<Duration>,
+
+    max_unsaved_changes: Option<u64>,
+
+    saved_cursor: u64,
+
+    cursor: u64,
+
+    last_change: Instant,
+}
+
+impl FlushPolicy {
+    pub const fn new(config: &HistoryFlushConfig, cursor: u64, now: Instant) -> Self {
+        Self {
+            idle: config.idle,
+            max_unsaved_changes: config.max_unsaved_changes,
+            saved_cursor: cursor,
+            cursor,
+            last_change: now,
+        }
+    }
+
+    /// Records the cursor of the journal at `now`, returns `true` if the
+    /// history should be saved.
+    pub fn check(&mut self, cursor: u64, now: Instant) -> bool {
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            self.last_change = now;
+        }
+        let unsaved_changes = self.unsaved_changes();
+        unsaved_changes > 0
+            && (self.max_unsaved_changes.is_some_and(|max| unsaved_changes >= max)
+                || self.idle.is_some_and(|idle| now.duration_since(self.last_change) >= idle))
+    }
+
+    /// Records that the history is saved with the changes before `cursor`.
+    pub fn saved(&mut self, cursor: u64) {
+        self.saved_cursor = cursor;
+        self.cursor = self.cursor.max(cursor);
+    }
+
+    pub const fn unsaved_changes(&self) -> u64 { self.cursor.saturating_sub(self.saved_cursor) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::FlushPolicy;
+    use crate::config::HistoryFlushConfig;
+
+    #[test]
+    fn test_flush_when_idle() {
+        let config =
+            HistoryFlushConfig { idle: Some(Duration::from_secs(2)), max_unsaved_changes: None };
+        let start = Instant::now();
+        let mut policy = FlushPolicy::new(&config, 5, start);
+        assert!(!policy.check(5, start + Duration::from_secs(10)));
+
+        let changed = start + Duration::from_secs(10);
+        assert!(!policy.check(7, changed));
+        assert_eq!(policy.unsaved_changes(), 2);
+        assert!(!policy.check(7, changed + Duration::from_secs(1)));
+        assert!(policy.check(7, changed + Duration::from_secs(2)));
+
+        policy.saved(7);
+        assert_eq!(policy.unsaved_changes(), 0);
+        assert!(!policy.check(7, changed + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_flush_when_too_many_changes() {
+        let config = HistoryFlushConfig { idle: None, max_unsaved_changes: Some(3) };
+        let start = Instant::now();
+        let mut policy = FlushPolicy::new(&config, 0, start);
+        assert!(!policy.check(2, start));
+        // changes are not saved however long the history is idle without a
+        // delay
+        assert!(!policy.check(2, start + Duration::from_secs(3600)));
+        assert!(policy.check(3, start + Duration::from_secs(3600)));
+
+        let disabled = FlushPolicy::new(&HistoryFlushConfig::default(), 0, start).check(100, start);
+        assert!(!disabled);
+    }
+}
@@ -1,11 +1,12 @@
 mod driver;
 mod error;
+mod flush;
 
 use std::path::{Path, PathBuf};
 
-use clipcat_base::ClipEntry;
+use clipcat_base::{ClipEntry, CompactReport, StoreReport};
 
-pub use self::error::Error;
+pub use self::{error::Error, flush::FlushPolicy};
 
 pub struct HistoryManager {
     file_path: PathBuf,
@@ -24,6 +25,16 @@ impl HistoryManager {
         Ok(Self { file_path, driver: Box::new(driver) })
     }
 
+    /// Returns `true` if a history is stored at `file_path`, opening a
+    /// history creates it otherwise.
+    #[inline]
+    pub fn exists<P>(file_path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        driver::FileSystemDriver::exists(file_path)
+    }
+
     #[inline]
     pub fn path(&self) -> &Path { &self.file_path }
 
@@ -51,6 +62,20 @@ impl HistoryManager {
         self.driver.shrink_to(min_capacity).await
     }
 
+    /// Checks the history file for entries which can not be loaded, drops them
+    /// if `repair` is set.
+    #[inline]
+    pub async fn verify(&mut self, repair: bool) -> Result<StoreReport, Error> {
+        self.driver.verify(repair).await
+    }
+
+    /// Rewrites the history file with only `clips`, the file is replaced at
+    /// once so a crash in between keeps the previous file.
+    #[inline]
+    pub async fn compact(&mut self, clips: &[ClipEntry]) -> Result<CompactReport, Error> {
+        self.driver.compact(clips).await
+    }
+
     #[inline]
     pub async fn save_and_shrink_to(
         &mut self,
@@ -0,0 +1,199 @@
+use std::{future::Future, net::SocketAddr, sync::Arc};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Router,
+};
+use clipcat_base::ClipEntry;
+use futures::StreamExt;
+use serde::Serialize;
+use snafu::ResultExt;
+use time::format_description::well_known::Rfc3339;
+use tokio::{net::TcpListener, sync::Mutex};
+
+use crate::{error, manager::ClipboardManager, notification, Error};
+
+const NDJSON: &str = "application/x-ndjson";
+
+/// A line of `GET /history`, one clip per line.
+#[derive(Debug, Serialize)]
+struct HistoryRecord {
+    id: String,
+
+    kind: String,
+
+    mime: String,
+
+    timestamp: String,
+
+    created_at: String,
+
+    tags: Vec<String>,
+
+    label: Option<String>,
+
+    use_count: u64,
+
+    size: usize,
+
+    // lowercase hex of the SHA-256 digest of the content
+    sha256: String,
+
+    // `None` for images
+    text: Option<String>,
+}
+
+impl From<&ClipEntry> for HistoryRecord {
+    fn from(clip: &ClipEntry) -> Self {
+        Self {
+            id: format!("{:016x}", clip.id()),
+            kind: clip.kind().as_str().to_string(),
+            mime: clip.mime_full().to_string(),
+            timestamp: clip.timestamp().format(&Rfc3339).unwrap_or_default(),
+            created_at: clip.created_at().format(&Rfc3339).unwrap_or_default(),
+            tags: clip.tags().iter().cloned().collect(),
+            label: clip.label().map(ToString::to_string),
+            use_count: clip.use_count(),
+            size: clip.len(),
+            sha256: clip.content_hash(),
+            text: clip.is_text().then(|| clip.as_utf8_string()),
+        }
+    }
+}
+
+struct ApiState<Notification> {
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+
+    // `None` if no token is required
+    authorization: Option<HeaderValue>,
+}
+
+impl<Notification> Clone for ApiState<Notification> {
+    fn clone(&self) -> Self {
+        Self { manager: self.manager.clone(), authorization: self.authorization.clone() }
+    }
+}
+
+impl<Notification> ApiState<Notification> {
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        self.authorization
+            .as_ref()
+            .map_or(true, |expected| headers.get(header::AUTHORIZATION) == Some(expected))
+    }
+}
+
+// streams the clips of the active profile newest first, private clips are
+// never served
+async fn history<Notification>(
+    State(state): State<ApiState<Notification>>,
+    headers: HeaderMap,
+) -> Response
+where
+    Notification: notification::Notification + 'static,
+{
+    if !state.is_authorized(&headers) {
+        return (StatusCode::UNAUTHORIZED, "No valid authorization token").into_response();
+    }
+    let mut clips = {
+        let manager = state.manager.lock().await;
+        manager.export(false).into_iter().filter(|clip| !clip.is_private()).collect::<Vec<_>>()
+    };
+    clips.sort_unstable();
+    let lines = futures::stream::iter(clips).map(|clip| {
+        serde_json::to_vec(&HistoryRecord::from(&clip)).map(|mut line| {
+            line.push(b'\n');
+            line
+        })
+    });
+    ([(header::CONTENT_TYPE, NDJSON)], Body::from_stream(lines)).into_response()
+}
+
+fn router<Notification>(
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    access_token: Option<String>,
+) -> Router
+where
+    Notification: notification::Notification + 'static,
+{
+    let authorization = access_token
+        .filter(|token| !token.is_empty())
+        .and_then(|token| HeaderValue::try_from(format!("Bearer {token}")).ok());
+    // only read-only endpoints are served
+    Router::new()
+        .route("/history", routing::get(history::<Notification>))
+        .with_state(ApiState { manager, authorization })
+}
+
+/// Serves the history as NDJSON on `listen_address`, clients send the same
+/// access token as gRPC clients.
+pub async fn serve<Notification, ShutdownSignal>(
+    listen_address: SocketAddr,
+    access_token: Option<String>,
+    manager: Arc<Mutex<ClipboardManager<Notification>>>,
+    shutdown_signal: ShutdownSignal,
+) -> Result<(), Error>
+where
+    Notification: notification::Notification + 'static,
+    ShutdownSignal: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind(&listen_address)
+        .await
+        .context(error::BindHttpApiServerSnafu { listen_address })?;
+    axum::serve(listener, router(manager, access_token))
+        .with_graceful_shutdown(shutdown_signal)
+        .await
+        .context(error::ServeHttpApiServerSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use clipcat_base::{ClipEntry, ClipboardKind};
+    use tokio::sync::Mutex;
+    use tower::ServiceExt;
+
+    use super::router;
+    use crate::{
+        backend::LocalClipboardBackend, manager::ClipboardManager, notification::DummyNotification,
+    };
+
+    #[tokio::test]
+    async fn test_history() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut manager = ClipboardManager::new(backend, DummyNotification::default());
+        let id = manager.insert(ClipEntry::from_string("hello", ClipboardKind::Clipboard));
+        let mut private = ClipEntry::from_string("secret", ClipboardKind::Clipboard);
+        private.set_private(true);
+        let _id = manager.insert(private);
+        let manager = Arc::new(Mutex::new(manager));
+
+        let request = |token: Option<&str>| {
+            let mut request = Request::get("/history");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        let app = router(manager, Some("token".to_string()));
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.oneshot(request(Some("token"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let lines = std::str::from_utf8(&body).unwrap().lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["id"], format!("{id:016x}"));
+        assert_eq!(record["text"], "hello");
+    }
+}
@@ -1,5 +1,3 @@
-pub mod backend;
-pub mod config;
 #[cfg(all(
     unix,
     not(any(
@@ -9,25 +7,35 @@ pub mod config;
         target_os = "emscripten"
     ))
 ))]
+mod auto_tag;
+pub mod backend;
+mod classify;
+pub mod config;
 mod dbus;
 mod error;
+mod forward;
 mod grpc;
 mod history;
+mod http_api;
 mod manager;
 mod metrics;
 mod notification;
+mod redact;
+mod replicate;
 mod snippets;
+mod transform;
 mod watcher;
 
-use std::{future::Future, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc};
+use std::{future::Future, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc, time::Duration};
 
-use clipcat_base::ClipboardKind;
+use clipcat_base::{ClipEntry, ClipboardKind};
 use clipcat_proto::{ManagerServer, SystemServer, WatcherServer};
 use futures::FutureExt;
 use notification::Notification;
 use sigfinn::{ExitStatus, Handle, LifecycleManager, Shutdown};
 use snafu::ResultExt;
 use snippets::SnippetWatcherEvent;
+use time::OffsetDateTime;
 use tokio::{
     net::UnixListener,
     sync::{broadcast::error::RecvError, Mutex},
@@ -35,18 +43,34 @@ use tokio::{
 use tokio_stream::wrappers::UnixListenerStream;
 
 pub use self::{
+    auto_tag::AutoTagRule,
+    classify::SemanticKindRule,
     config::Config,
     error::{Error, Result},
+    redact::RedactionRule,
+    transform::{ContentTransform, ContentTransformRule},
     watcher::ClipboardWatcherOptions,
 };
 use self::{
-    history::HistoryManager,
+    auto_tag::AutoTagger,
+    classify::Classifier,
+    config::HistoryFlushConfig,
+    forward::Forwarder,
+    history::{FlushPolicy, HistoryManager},
     manager::ClipboardManager,
     metrics::Metrics,
-    watcher::{ClipboardWatcher, ClipboardWatcherToggle, ClipboardWatcherWorker},
+    redact::Redactor,
+    replicate::Replicator,
+    transform::ContentTransformer,
+    watcher::{
+        ClipboardWatcher, ClipboardWatcherHealth, ClipboardWatcherToggle, ClipboardWatcherWorker,
+    },
 };
 use crate::snippets::SnippetWatcherEventReceiver;
 
+// how often the expired clips are removed
+const EXPIRATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 /// # Errors
 ///
 /// This function will return an error if the server fails to start.
@@ -56,15 +80,34 @@ pub async fn serve_with_shutdown(
         grpc_listen_address,
         grpc_local_socket,
         grpc_access_token,
+        grpc_web,
+        grpc_max_inserts_per_second,
+        size_limits,
+        federation_peers,
         primary_threshold,
         max_history,
+        max_age,
+        dedupe_scope,
+        normalize_whitespace_for_dedupe,
+        coalesce_growing_edits,
+        read_only,
+        testing,
+        compression_threshold,
         history_file_path,
+        history_flush,
         synchronize_selection_with_clipboard,
         watcher: watcher_opts,
         desktop_notification: desktop_notification_config,
         dbus,
         metrics: metrics_config,
         snippets,
+        content_transform,
+        auto_tag,
+        semantic_kind,
+        redaction,
+        http_api,
+        forward,
+        replication,
     }: Config,
 ) -> Result<()> {
     let clip_filter =
@@ -84,10 +127,63 @@ pub async fn serve_with_shutdown(
     )
     .context(error::CreateClipboardBackendSnafu)?;
 
+    let content_transformer = if content_transform.enable {
+        ContentTransformer::new(content_transform.rules)
+    } else {
+        ContentTransformer::default()
+    };
+    let auto_tagger =
+        if auto_tag.enable { AutoTagger::new(auto_tag.rules) } else { AutoTagger::default() };
+    let classifier = if semantic_kind.enable {
+        Classifier::new(semantic_kind.rules)
+    } else {
+        Classifier::default()
+    };
+    let redactor =
+        if redaction.enable { Redactor::new(redaction.rules) } else { Redactor::default() };
+    let mut server_info = clipcat_base::ServerInfo {
+        version: clipcat_base::PROJECT_SEMVER.clone(),
+        transforms: content_transformer.descriptions(),
+        auto_tag_rules: auto_tagger.descriptions(),
+        redaction_patterns: redactor.patterns(),
+        denied_applications: {
+            let mut applications =
+                watcher_opts.denied_applications.iter().cloned().collect::<Vec<_>>();
+            applications.sort_unstable();
+            applications
+        },
+        enabled_kinds: watcher_opts.clipboard_kinds(),
+        dedupe_scope,
+        normalize_whitespace_for_dedupe,
+        read_only,
+        listeners: grpc_listen_address
+            .map(|address| format!("http://{address}"))
+            .into_iter()
+            .chain(grpc_local_socket.as_ref().map(|path| format!("unix:{}", path.display())))
+            .collect(),
+        max_inserts_per_second: grpc_max_inserts_per_second,
+        // updated from the watcher whenever the information is requested
+        capture_paused: false,
+        size_limits: size_limits.clone(),
+        features: clipcat_base::features::ALL.iter().map(ToString::to_string).collect(),
+        commit_delay: Some(watcher_opts.commit_delay).filter(|delay| !delay.is_zero()),
+        max_age: max_age.and_then(|max_age| Duration::try_from(max_age).ok()),
+        config_version: 0,
+    };
+    server_info.config_version = server_info.compute_config_version();
+    let insert_limits = grpc::InsertLimits {
+        rate_limiter: grpc_max_inserts_per_second
+            .map(|limit| Arc::new(grpc::RateLimiter::new(limit))),
+        size_limits,
+    };
+    let federation =
+        (!federation_peers.is_empty()).then(|| Arc::new(grpc::Federation::new(federation_peers)));
+
     let (clipboard_manager, history_manager, snippets_watcher, snippet_event_receiver) = {
         let ((snippets_watcher, snippet_event_receiver), snippets) =
             snippets::load_and_create_watcher(&snippets).await?;
         tracing::info!("History file path: `{path}`", path = history_file_path.display());
+        let history_missing = !HistoryManager::exists(&history_file_path);
         let mut history_manager = HistoryManager::new(&history_file_path)
             .await
             .context(error::CreateHistoryManagerSnafu)?;
@@ -104,6 +200,18 @@ pub async fn serve_with_shutdown(
                 );
             })
             .unwrap_or_default();
+        let history_clips = match replication.as_ref() {
+            Some(replication)
+                if replication.reconcile_on_startup
+                    && history_missing
+                    && history_clips.is_empty() =>
+            {
+                restore_history_from_replicas(&mut history_manager, &replication.targets)
+                    .await
+                    .unwrap_or(history_clips)
+            }
+            _ => history_clips,
+        };
         let clip_count = history_clips.len();
         if clip_count > 0 {
             tracing::info!("{clip_count} clip(s) loaded");
@@ -120,6 +228,17 @@ pub async fn serve_with_shutdown(
             primary_threshold,
             desktop_notification.clone(),
         );
+        clipboard_manager.set_content_transformer(content_transformer);
+        clipboard_manager.set_auto_tagger(auto_tagger);
+        clipboard_manager.set_classifier(classifier);
+        clipboard_manager.set_redactor(redactor);
+        clipboard_manager.set_enabled_kinds(watcher_opts.get_enable_kinds());
+        clipboard_manager.set_dedupe_scope(dedupe_scope);
+        clipboard_manager.set_normalize_whitespace_for_dedupe(normalize_whitespace_for_dedupe);
+        clipboard_manager.set_coalesce_growing_edits(coalesce_growing_edits);
+        clipboard_manager.set_read_only(read_only);
+        clipboard_manager.set_testing(testing);
+        clipboard_manager.set_compression_threshold(compression_threshold);
 
         tracing::info!("Import {clip_count} clip(s) into ClipboardManager");
         clipboard_manager.import(&history_clips);
@@ -129,7 +248,7 @@ pub async fn serve_with_shutdown(
 
         (
             Arc::new(Mutex::new(clipboard_manager)),
-            history_manager,
+            Arc::new(Mutex::new(history_manager)),
             snippets_watcher,
             snippet_event_receiver,
         )
@@ -180,8 +299,24 @@ pub async fn serve_with_shutdown(
             create_grpc_http_server_future(
                 grpc_listen_address,
                 grpc_access_token.clone(),
+                grpc_web,
+                server_info.clone(),
                 clipboard_watcher.get_toggle(),
                 clipboard_manager.clone(),
+                history_manager.clone(),
+                insert_limits.clone(),
+                federation.clone(),
+            ),
+        );
+    }
+
+    if let Some(http_api) = http_api {
+        let _handle = lifecycle_manager.spawn(
+            "HTTP API server",
+            create_http_api_server_future(
+                http_api.listen_address,
+                grpc_access_token.clone(),
+                clipboard_manager.clone(),
             ),
         );
     }
@@ -192,11 +327,47 @@ pub async fn serve_with_shutdown(
             create_grpc_local_socket_server_future(
                 grpc_local_socket,
                 grpc_access_token,
+                server_info,
                 clipboard_watcher.get_toggle(),
                 clipboard_manager.clone(),
+                history_manager.clone(),
+                insert_limits.clone(),
+                federation.clone(),
             ),
         );
     }
+    drop(federation);
+
+    if let Some(forward) = forward {
+        tracing::info!("Forward clips to `{program}`", program = forward.program.display());
+        let (_events, change_receiver) = clipboard_manager.lock().await.subscribe_changes(None);
+        if let Some(change_receiver) = change_receiver {
+            let _handle = lifecycle_manager.spawn(
+                "Forward worker",
+                create_forward_worker_future(
+                    Forwarder::new(forward, clipboard_watcher.health()),
+                    change_receiver,
+                ),
+            );
+        }
+    }
+
+    if let Some(replication) = replication {
+        for target in &replication.targets {
+            tracing::info!("Mirror history to `{path}`", path = target.display());
+        }
+        let (_events, change_receiver) = clipboard_manager.lock().await.subscribe_changes(None);
+        if let Some(change_receiver) = change_receiver {
+            let _handle = lifecycle_manager.spawn(
+                "Replication worker",
+                create_replication_worker_future(
+                    Replicator::new(replication, clipboard_watcher.health()),
+                    clipboard_manager.clone(),
+                    change_receiver,
+                ),
+            );
+        }
+    }
 
     if metrics_config.enable {
         let metrics = Metrics::new()?;
@@ -218,7 +389,10 @@ pub async fn serve_with_shutdown(
             clipboard_watcher,
             clipboard_manager,
             history_manager,
-            synchronize_selection_with_clipboard,
+            max_age,
+            history_flush,
+            // the selection is synchronized by storing the clip into it
+            synchronize_selection_with_clipboard && !read_only,
             snippet_event_receiver,
             handle,
         ),
@@ -235,11 +409,16 @@ pub async fn serve_with_shutdown(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_grpc_local_socket_server_future(
     local_socket: PathBuf,
     grpc_access_token: Option<String>,
+    server_info: clipcat_base::ServerInfo,
     clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    insert_limits: grpc::InsertLimits,
+    federation: Option<Arc<grpc::Federation>>,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
@@ -262,8 +441,9 @@ fn create_grpc_local_socket_server_future(
 
             let interceptor = grpc::Interceptor::new(grpc_access_token);
             let result = tonic::transport::Server::builder()
+                .layer(grpc::trace_layer())
                 .add_service(SystemServer::with_interceptor(
-                    grpc::SystemService::new(),
+                    grpc::SystemService::new(server_info, clipboard_watcher_toggle.clone()),
                     interceptor.clone(),
                 ))
                 .add_service(WatcherServer::with_interceptor(
@@ -271,7 +451,12 @@ fn create_grpc_local_socket_server_future(
                     interceptor.clone(),
                 ))
                 .add_service(ManagerServer::with_interceptor(
-                    grpc::ManagerService::new(clipboard_manager),
+                    grpc::ManagerService::new(
+                        clipboard_manager,
+                        history_manager,
+                        insert_limits,
+                        federation,
+                    ),
                     interceptor,
                 ))
                 .serve_with_incoming_shutdown(uds_stream, signal)
@@ -338,6 +523,58 @@ fn create_desktop_notification_worker_future(
     }
 }
 
+fn create_forward_worker_future(
+    forwarder: Forwarder,
+    change_receiver: tokio::sync::broadcast::Receiver<clipcat_base::ClipboardEvent>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |signal| {
+        async move {
+            tracing::info!("Forward worker is started");
+            forwarder.serve(change_receiver, signal).await;
+            tracing::info!("Forward worker is shut down gracefully");
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
+fn create_replication_worker_future(
+    replicator: Replicator,
+    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+    change_receiver: tokio::sync::broadcast::Receiver<clipcat_base::ClipboardEvent>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |signal| {
+        async move {
+            tracing::info!("Replication worker is started");
+            replicator.serve(clipboard_manager, change_receiver, signal).await;
+            tracing::info!("Replication worker is shut down gracefully");
+            ExitStatus::Success
+        }
+        .boxed()
+    }
+}
+
+// loads the clips of the first replica holding any and saves them into the
+// history file, `None` if no replica holds any clip
+async fn restore_history_from_replicas(
+    history_manager: &mut HistoryManager,
+    targets: &[PathBuf],
+) -> Option<Vec<ClipEntry>> {
+    let (path, clips) = replicate::load_from_replicas(targets).await?;
+    tracing::info!(
+        "History file is missing, restore {count} clip(s) from replica `{path}`",
+        count = clips.len(),
+        path = path.display()
+    );
+    if let Err(err) = history_manager.compact(&clips).await {
+        tracing::warn!(
+            "Could not save restored clips into `{path}`, error: {err}",
+            path = history_manager.path().display()
+        );
+    }
+    Some(clips)
+}
+
 fn create_clipboard_watcher_worker_future(
     worker: ClipboardWatcherWorker,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
@@ -358,20 +595,38 @@ fn create_clipboard_watcher_worker_future(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_grpc_http_server_future(
     listen_address: SocketAddr,
     grpc_access_token: Option<String>,
+    grpc_web: Option<config::GrpcWebConfig>,
+    server_info: clipcat_base::ServerInfo,
     clipboard_watcher_toggle: ClipboardWatcherToggle<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    insert_limits: grpc::InsertLimits,
+    federation: Option<Arc<grpc::Federation>>,
 ) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
     move |signal| {
         async move {
             tracing::info!("Listen Clipcat gRPC endpoint on {listen_address}");
 
+            // browsers only speak grpc-web over HTTP/1.1, requests of gRPC
+            // clients pass through the layers unchanged
+            let grpc_web_layer = grpc_web.map(|config| {
+                tracing::info!("Serve grpc-web on {listen_address}");
+                tower::ServiceBuilder::new()
+                    .layer(grpc::cors_layer(&config.allowed_origins))
+                    .layer(tonic_web::GrpcWebLayer::new())
+                    .into_inner()
+            });
             let interceptor = grpc::Interceptor::new(grpc_access_token);
             let result = tonic::transport::Server::builder()
+                .accept_http1(grpc_web_layer.is_some())
+                .layer(grpc::trace_layer())
+                .layer(tower::util::option_layer(grpc_web_layer))
                 .add_service(SystemServer::with_interceptor(
-                    grpc::SystemService::new(),
+                    grpc::SystemService::new(server_info, clipboard_watcher_toggle.clone()),
                     interceptor.clone(),
                 ))
                 .add_service(WatcherServer::with_interceptor(
@@ -379,7 +634,12 @@ fn create_grpc_http_server_future(
                     interceptor.clone(),
                 ))
                 .add_service(ManagerServer::with_interceptor(
-                    grpc::ManagerService::new(clipboard_manager),
+                    grpc::ManagerService::new(
+                        clipboard_manager,
+                        history_manager,
+                        insert_limits,
+                        federation,
+                    ),
                     interceptor,
                 ))
                 .serve_with_shutdown(listen_address, signal)
@@ -398,10 +658,13 @@ fn create_grpc_http_server_future(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_clipboard_worker_future(
     clipboard_watcher: ClipboardWatcher<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
-    history_manager: HistoryManager,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    max_age: Option<time::Duration>,
+    history_flush: HistoryFlushConfig,
     synchronize_selection_with_clipboard: bool,
     snippet_event_receiver: SnippetWatcherEventReceiver,
     handle: Handle<Error>,
@@ -412,6 +675,8 @@ fn create_clipboard_worker_future(
                 clipboard_watcher,
                 clipboard_manager,
                 history_manager,
+                max_age,
+                history_flush,
                 synchronize_selection_with_clipboard,
                 snippet_event_receiver,
                 handle,
@@ -430,6 +695,28 @@ fn create_clipboard_worker_future(
     }
 }
 
+fn create_http_api_server_future(
+    listen_address: SocketAddr,
+    access_token: Option<String>,
+    clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
+) -> impl FnOnce(Shutdown) -> Pin<Box<dyn Future<Output = ExitStatus<Error>> + Send>> {
+    move |signal| {
+        async move {
+            tracing::info!("Listen HTTP API endpoint on {listen_address}");
+            let result =
+                http_api::serve(listen_address, access_token, clipboard_manager, signal).await;
+            match result {
+                Ok(()) => {
+                    tracing::info!("HTTP API server is shut down gracefully");
+                    ExitStatus::Success
+                }
+                Err(err) => ExitStatus::FatalError(err),
+            }
+        }
+        .boxed()
+    }
+}
+
 fn create_metrics_server_future<Metrics>(
     listen_address: SocketAddr,
     metrics: Metrics,
@@ -455,22 +742,33 @@ where
 }
 
 #[allow(clippy::redundant_pub_crate)]
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_lines)]
 async fn serve_worker(
     clipboard_watcher: ClipboardWatcher<notification::DesktopNotification>,
     clipboard_manager: Arc<Mutex<ClipboardManager<notification::DesktopNotification>>>,
-    mut history_manager: HistoryManager,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    max_age: Option<time::Duration>,
+    history_flush: HistoryFlushConfig,
     synchronize_selection_with_clipboard: bool,
     mut snippet_event_receiver: SnippetWatcherEventReceiver,
     handle: Handle<Error>,
     shutdown_signal: Shutdown,
 ) -> Result<()> {
     enum Event {
-        NewClip(clipcat_base::ClipEntry),
-        NewSnippet(clipcat_base::ClipEntry),
+        NewClip(ClipEntry),
+        NewSnippet(ClipEntry),
         RemoveSnippet(u64),
+        RemoveExpired,
         Shutdown,
     }
 
+    let watcher_health = clipboard_watcher.health();
+    let mut flush_policy = FlushPolicy::new(
+        &history_flush,
+        clipboard_manager.lock().await.next_cursor(),
+        std::time::Instant::now(),
+    );
     let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
     let snippets_event_handle = tokio::spawn({
         let send = send.clone();
@@ -507,6 +805,15 @@ async fn serve_worker(
             }
         }
     });
+    let expiration_handle = tokio::spawn({
+        let send = send.clone();
+        async move {
+            let mut interval = tokio::time::interval(EXPIRATION_CHECK_INTERVAL);
+            while send.send(Event::RemoveExpired).is_ok() {
+                let _ = interval.tick().await;
+            }
+        }
+    });
     let shutdown_handle = tokio::spawn(async move {
         shutdown_signal.await;
         drop(send.send(Event::Shutdown));
@@ -519,6 +826,21 @@ async fn serve_worker(
                 let mut clipboard_manager = clipboard_manager.lock().await;
                 let _ = clipboard_manager.remove_snippet(clip_id);
             }
+            Event::RemoveExpired => {
+                let now = OffsetDateTime::now_utc();
+                let mut clipboard_manager = clipboard_manager.lock().await;
+                let expired = clipboard_manager.remove_expired(now);
+                let too_old = max_age
+                    .and_then(|max_age| now.checked_sub(max_age))
+                    .map_or_else(Vec::new, |cutoff| clipboard_manager.remove_older_than(cutoff));
+                drop(clipboard_manager);
+                if !expired.is_empty() {
+                    tracing::debug!("Remove {n} expired clip(s)", n = expired.len());
+                }
+                if !too_old.is_empty() {
+                    tracing::debug!("Remove {n} clip(s) older than max age", n = too_old.len());
+                }
+            }
             Event::NewSnippet(snippet) => {
                 let mut clipboard_manager = clipboard_manager.lock().await;
                 clipboard_manager.insert_snippets(&[snippet]);
@@ -529,43 +851,131 @@ async fn serve_worker(
                     kind = clip.kind(),
                     basic_info = clip.basic_information()
                 );
-                {
-                    let mut clipboard_manager = clipboard_manager.lock().await;
-                    let id = clipboard_manager.insert(clip.clone());
-                    if synchronize_selection_with_clipboard
-                        && clip.kind() == ClipboardKind::Clipboard
-                    {
-                        if let Err(err) = clipboard_manager.mark(id, ClipboardKind::Primary).await {
-                            tracing::warn!("{err}");
-                        }
+                let started = std::time::Instant::now();
+                let clip = store_clip(
+                    &clipboard_manager,
+                    &watcher_health,
+                    clip,
+                    synchronize_selection_with_clipboard,
+                )
+                .await;
+                if let Some(clip) = clip {
+                    tracing::debug!(
+                        event = "clip_stored",
+                        id = format!("{:016x}", clip.id()),
+                        kind = %clip.kind(),
+                        latency_ms = started.elapsed().as_secs_f64() * 1000.0,
+                        "Store clip"
+                    );
+                    if let Err(err) = history_manager.lock().await.put(&clip).await {
+                        tracing::error!("{err}");
                     }
                 }
+            }
+        }
 
-                if let Err(err) = history_manager.put(&clip).await {
-                    tracing::error!("{err}");
-                }
+        // the expiration check wakes the worker up every second
+        let cursor = clipboard_manager.lock().await.next_cursor();
+        if flush_policy.check(cursor, std::time::Instant::now()) {
+            if let Some(cursor) =
+                flush_history(&clipboard_manager, &mut *history_manager.lock().await).await
+            {
+                flush_policy.saved(cursor);
             }
         }
+        watcher_health.set_unsaved_changes(flush_policy.unsaved_changes());
     }
 
+    clipboard_manager.lock().await.close_change_subscribers();
+    save_history(&clipboard_manager, &mut *history_manager.lock().await).await;
+
+    snippets_event_handle.abort();
+    clip_reciever_handle.abort();
+    expiration_handle.abort();
+    shutdown_handle.abort();
+
+    Ok(())
+}
+
+/// Saves the clips of the default profile before shutting down.
+async fn save_history(
+    clipboard_manager: &Mutex<ClipboardManager<notification::DesktopNotification>>,
+    history_manager: &mut HistoryManager,
+) {
     let (clips, history_capacity) = {
         let manager = clipboard_manager.lock().await;
-        (manager.export(false), manager.capacity())
+        (manager.persisted_clips(), manager.capacity())
     };
 
-    {
-        tracing::info!("Save history and shrink to capacity {history_capacity}");
-        if let Err(err) = history_manager.save_and_shrink_to(&clips, history_capacity).await {
-            tracing::warn!("Failed to save history, error: {err}");
-        }
-        tracing::info!("Clips are stored in `{path}`", path = history_manager.path().display());
+    tracing::info!("Save history and shrink to capacity {history_capacity}");
+    if let Err(err) = history_manager.save_and_shrink_to(&clips, history_capacity).await {
+        tracing::warn!("Failed to save history, error: {err}");
     }
+    tracing::info!("Clips are stored in `{path}`", path = history_manager.path().display());
+}
 
-    snippets_event_handle.abort();
-    clip_reciever_handle.abort();
-    shutdown_handle.abort();
+/// Saves the clips of the default profile while the server is running, the
+/// history file is replaced at once so a crash in between keeps the previous
+/// file. Returns the cursor of the next change after the saved clips.
+async fn flush_history(
+    clipboard_manager: &Mutex<ClipboardManager<notification::DesktopNotification>>,
+    history_manager: &mut HistoryManager,
+) -> Option<u64> {
+    let (clips, cursor) = {
+        let manager = clipboard_manager.lock().await;
+        (manager.persisted_clips(), manager.next_cursor())
+    };
+    match history_manager.compact(&clips).await {
+        Ok(_report) => {
+            tracing::debug!("Flush {n} clip(s) to history", n = clips.len());
+            Some(cursor)
+        }
+        Err(err) => {
+            tracing::warn!("Failed to flush history, error: {err}");
+            None
+        }
+    }
+}
 
-    Ok(())
+/// Inserts a new clip into the history, returns the clip to be persisted.
+async fn store_clip(
+    clipboard_manager: &Mutex<ClipboardManager<notification::DesktopNotification>>,
+    watcher_health: &ClipboardWatcherHealth,
+    clip: ClipEntry,
+    synchronize_selection_with_clipboard: bool,
+) -> Option<ClipEntry> {
+    let mut clipboard_manager = clipboard_manager.lock().await;
+    if clipboard_manager.is_transient(&clip) {
+        return None;
+    }
+    // clipcat stores the current clip into the clipboard when it is marked,
+    // inserting it again may duplicate it
+    let current = clipboard_manager
+        .get_current_clip(clip.kind())
+        .filter(|current| clip.owned_by_us() && current.as_ref() == clip.as_ref());
+    let id = current.map_or_else(
+        || clipboard_manager.insert(clip.clone()),
+        |current| {
+            watcher_health.record_self_capture();
+            current.id()
+        },
+    );
+    if synchronize_selection_with_clipboard
+        && clip.kind() == ClipboardKind::Clipboard
+        && clipboard_manager.is_kind_enabled(ClipboardKind::Primary)
+    {
+        if let Err(err) = clipboard_manager.mark(id, ClipboardKind::Primary).await {
+            tracing::warn!("{err}");
+        }
+    }
+    if clipboard_manager.active_profile().is_some() {
+        // only the default profile is persisted
+        return None;
+    }
+    // the stored clip may be transformed, expiring and private clips are not
+    // persisted
+    Some(clipboard_manager.get(id).unwrap_or(clip))
+        .filter(|clip| clip.expires_at().is_none() && !clip.is_private())
 }
 
 #[cfg(all(
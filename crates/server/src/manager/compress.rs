@@ -0,0 +1,104 @@
+use std::io::{Read, Write};
+
+use clipcat_base::ClipboardContent;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+/// The content of a clip compressed at rest.
+#[derive(Clone, Debug)]
+pub struct CompressedContent {
+    data: Vec<u8>,
+
+    // size of the content in bytes before compression
+    original_size: usize,
+
+    // dimensions of an image, `None` for text
+    image_size: Option<(usize, usize)>,
+
+    content_id: u64,
+}
+
+impl CompressedContent {
+    /// Compresses `content`, returns `None` if compression does not save space.
+    pub fn compress(content: &ClipboardContent) -> Option<Self> {
+        let (bytes, image_size) = match content {
+            ClipboardContent::Plaintext(text) => (text.as_bytes(), None),
+            ClipboardContent::Image { width, height, bytes } => {
+                (bytes.as_ref(), Some((*width, *height)))
+            }
+        };
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(bytes).ok()?;
+        let data = encoder.finish().ok()?;
+        (data.len() < bytes.len()).then(|| Self {
+            data,
+            original_size: bytes.len(),
+            image_size,
+            content_id: content.id(),
+        })
+    }
+
+    /// Returns the original content, or `None` if the data is corrupted.
+    pub fn decompress(&self) -> Option<ClipboardContent> {
+        let mut bytes = Vec::with_capacity(self.original_size);
+        let _size = DeflateDecoder::new(self.data.as_slice()).read_to_end(&mut bytes).ok()?;
+        match self.image_size {
+            Some((width, height)) => {
+                Some(ClipboardContent::Image { width, height, bytes: bytes.into() })
+            }
+            None => String::from_utf8(bytes).ok().map(ClipboardContent::Plaintext),
+        }
+    }
+
+    /// Returns an empty content of the same type, kept in place of the
+    /// original content.
+    pub const fn placeholder(&self) -> ClipboardContent {
+        match self.image_size {
+            Some((width, height)) => {
+                ClipboardContent::Image { width, height, bytes: bytes::Bytes::new() }
+            }
+            None => ClipboardContent::Plaintext(String::new()),
+        }
+    }
+
+    /// Returns the id of the original content.
+    pub const fn content_id(&self) -> u64 { self.content_id }
+
+    pub const fn original_size(&self) -> usize { self.original_size }
+
+    pub fn compressed_size(&self) -> usize { self.data.len() }
+
+    /// Returns the number of bytes saved by compression.
+    pub fn saved_bytes(&self) -> usize { self.original_size.saturating_sub(self.data.len()) }
+
+    // replace the data with an invalid deflate block
+    #[cfg(test)]
+    pub fn corrupt(&mut self) { self.data = vec![0xff; 4]; }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::ClipboardContent;
+
+    use super::CompressedContent;
+
+    #[test]
+    fn test_compress() {
+        let content = ClipboardContent::Plaintext("clipcat ".repeat(1000));
+        let compressed = CompressedContent::compress(&content).unwrap();
+        assert_eq!(compressed.original_size(), 8000);
+        assert!(compressed.saved_bytes() > 0);
+        assert_eq!(compressed.content_id(), content.id());
+        assert_eq!(compressed.decompress(), Some(content));
+        assert_eq!(compressed.placeholder(), ClipboardContent::Plaintext(String::new()));
+
+        let content =
+            ClipboardContent::Image { width: 10, height: 10, bytes: vec![255; 400].into() };
+        let compressed = CompressedContent::compress(&content).unwrap();
+        assert_eq!(compressed.decompress(), Some(content));
+
+        // incompressible content is not compressed
+        assert!(
+            CompressedContent::compress(&ClipboardContent::Plaintext("a".to_string())).is_none()
+        );
+    }
+}
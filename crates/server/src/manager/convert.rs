@@ -0,0 +1,66 @@
+use clipcat_base::{utils::html_to_plaintext, ClipEntry, ClipboardContent};
+
+/// Converts the content of the clip to the representation of `target`.
+///
+/// Returns `None` if the clip can not be represented as `target`.
+pub fn convert(clip: &ClipEntry, target: &mime::Mime) -> Option<ClipboardContent> {
+    let source = clip.mime();
+    if source.essence_str() == target.essence_str() {
+        return Some(clip.as_ref().clone());
+    }
+
+    match clip.as_ref() {
+        ClipboardContent::Plaintext(text) if target.essence_str() == "text/plain" => {
+            if source.essence_str() == "text/html" {
+                Some(ClipboardContent::Plaintext(html_to_plaintext(text)))
+            } else if source.type_() == mime::TEXT {
+                Some(ClipboardContent::Plaintext(text.clone()))
+            } else {
+                None
+            }
+        }
+        // images are always stored as PNG
+        content @ ClipboardContent::Image { .. } if target.essence_str() == "image/png" => {
+            Some(content.clone())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{utils::html_to_plaintext, ClipEntry, ClipboardContent, ClipboardKind};
+
+    use super::convert;
+
+    #[test]
+    fn test_html_to_plaintext() {
+        assert_eq!(html_to_plaintext("<b>bold</b> &amp; <i>italic</i>"), "bold & italic");
+        assert_eq!(
+            html_to_plaintext("<p>first</p><p>second<br/>third</p>"),
+            "first\nsecond\nthird"
+        );
+        assert_eq!(
+            html_to_plaintext("<head><title>t</title></head><style>p {}</style><!-- c -->text"),
+            "text"
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let html: mime::Mime = "text/html; charset=utf-8".parse().unwrap();
+        let clip = ClipEntry::new(b"<b>text</b>", &html, ClipboardKind::Clipboard, None).unwrap();
+        assert_eq!(
+            convert(&clip, &mime::TEXT_PLAIN),
+            Some(ClipboardContent::Plaintext("text".to_string()))
+        );
+        assert_eq!(
+            convert(&clip, &mime::TEXT_HTML),
+            Some(ClipboardContent::Plaintext("<b>text</b>".to_string()))
+        );
+        assert_eq!(convert(&clip, &mime::IMAGE_PNG), None);
+
+        let clip = ClipEntry::from_string("text", ClipboardKind::Clipboard);
+        assert_eq!(convert(&clip, &mime::TEXT_HTML), None);
+    }
+}
@@ -1,3 +1,4 @@
+use clipcat_base::ClipboardKind;
 use snafu::Snafu;
 
 use crate::backend;
@@ -7,4 +8,66 @@ use crate::backend;
 pub enum Error {
     #[snafu(display("Error occurs while storing clipboard content, error: {source}"))]
     StoreClipboardContent { source: backend::Error },
+
+    #[snafu(display(
+        "Error occurs while storing clipboard content into {kind}, the other clipboards are \
+         restored, error: {source}"
+    ))]
+    StoreClipboardKind { kind: ClipboardKind, source: backend::Error },
+
+    #[snafu(display("Error occurs while loading clipboard content, error: {source}"))]
+    LoadClipboardContent { source: backend::Error },
+
+    #[snafu(display("Clip with id {id:016x} already exists"))]
+    IdTaken { id: u64 },
+
+    #[snafu(display("Clipboard kind {kind} is not enabled"))]
+    KindDisabled { kind: ClipboardKind },
+
+    #[snafu(display(
+        "Clip with id {id:016x} is changed, expected version {expected_version}, found {}",
+        version.map_or_else(|| "no clip".to_string(), |version| version.to_string())
+    ))]
+    VersionConflict { id: u64, expected_version: u64, version: Option<u64> },
+
+    #[snafu(display("Clip with id {id:016x} can not be converted from {from} to {to}"))]
+    UnsupportedConversion { id: u64, from: String, to: String },
+
+    #[snafu(display(
+        "Clip with id {id:016x} of type {mime} can not be stored into the clipboard"
+    ))]
+    UnsupportedMime { id: u64, mime: String },
+
+    #[snafu(display("Clips can not be stored into the clipboard in read-only mode"))]
+    ReadOnly,
+
+    #[snafu(display("History can be replaced only when testing is enabled"))]
+    TestingDisabled,
+
+    #[snafu(display("Clip with id {id:016x} is not an image"))]
+    NotAnImage { id: u64 },
+
+    #[snafu(display("Clip with id {id:016x} is not a text clip"))]
+    NotText { id: u64 },
+
+    #[snafu(display("Error occurs while encoding image of clip {id:016x}, error: {source}"))]
+    EncodeImage { id: u64, source: clipcat_base::ClipEntryError },
+
+    #[snafu(display("Snapshot {handle} is closed or expired"))]
+    SnapshotNotFound { handle: u64 },
+
+    #[snafu(display("Bucket of activity must be positive"))]
+    InvalidBucket,
+
+    #[snafu(display("Content hash {hash} is not a SHA-256 digest in hex"))]
+    InvalidContentHash { hash: String },
+
+    #[snafu(display("{count} buckets of activity exceed the limit {limit}"))]
+    TooManyBuckets { count: usize, limit: usize },
+
+    #[snafu(display("History is full of protected clips ({count}/{capacity})"))]
+    HistoryFull { count: usize, capacity: usize },
+
+    #[snafu(display("Profile has {limit} sticky notes already"))]
+    TooManyStickyNotes { limit: usize },
 }
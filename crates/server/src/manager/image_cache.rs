@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use clipcat_base::ImageFormat;
+
+// count of converted images kept, converting is expensive but images are large
+const CAPACITY: usize = 8;
+
+/// Images converted to other formats, the most recently used first.
+#[derive(Debug, Default)]
+pub struct ImageCache {
+    // the id and the version of the clip, the format and the encoded image
+    entries: VecDeque<(u64, u64, ImageFormat, Vec<u8>)>,
+}
+
+impl ImageCache {
+    /// Returns the image of the clip `id` at `version` in `format`, converting
+    /// it with `convert` if it is not cached.
+    ///
+    /// A clip which is changed since it is cached is converted again.
+    pub fn get_or_try_insert<E>(
+        &mut self,
+        id: u64,
+        version: u64,
+        format: ImageFormat,
+        convert: impl FnOnce() -> Result<Vec<u8>, E>,
+    ) -> Result<&[u8], E> {
+        let position = self.entries.iter().position(|&(cached_id, cached_version, f, _)| {
+            cached_id == id && cached_version == version && f == format
+        });
+        let entry = match position.and_then(|position| self.entries.remove(position)) {
+            Some(entry) => entry,
+            None => (id, version, format, convert()?),
+        };
+        self.entries.push_front(entry);
+        self.entries.truncate(CAPACITY);
+        Ok(&self.entries[0].3)
+    }
+
+    pub fn clear(&mut self) { self.entries.clear(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::ImageFormat;
+
+    use super::{ImageCache, CAPACITY};
+
+    #[test]
+    fn test_image_cache() {
+        let mut cache = ImageCache::default();
+        let mut conversions = 0;
+        let mut convert = |data: u8| {
+            conversions += 1;
+            Ok::<_, ()>(vec![data])
+        };
+        assert_eq!(cache.get_or_try_insert(1, 0, ImageFormat::Jpeg, || convert(1)), Ok(&[1][..]));
+        assert_eq!(cache.get_or_try_insert(1, 0, ImageFormat::Jpeg, || convert(2)), Ok(&[1][..]));
+        assert_eq!(cache.get_or_try_insert(1, 0, ImageFormat::Gif, || convert(3)), Ok(&[3][..]));
+
+        // the clip is changed
+        assert_eq!(cache.get_or_try_insert(1, 1, ImageFormat::Jpeg, || convert(4)), Ok(&[4][..]));
+
+        for id in 2..2 + CAPACITY as u64 {
+            let _unused = cache.get_or_try_insert(id, 0, ImageFormat::Png, || convert(0));
+        }
+        assert_eq!(cache.get_or_try_insert(1, 1, ImageFormat::Jpeg, || convert(5)), Ok(&[5][..]));
+        assert_eq!(conversions, 4 + CAPACITY);
+
+        cache.clear();
+        assert_eq!(cache.get_or_try_insert(1, 1, ImageFormat::Jpeg, || Err(())), Err(()));
+    }
+}
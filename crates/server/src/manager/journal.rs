@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+use clipcat_base::{ClipboardChange, ClipboardEvent};
+use time::OffsetDateTime;
+use tokio::sync::broadcast;
+
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Keeps the latest changes of the clipboard history and broadcasts new ones to
+/// subscribers.
+pub struct Journal {
+    next_cursor: u64,
+
+    events: VecDeque<ClipboardEvent>,
+
+    capacity: usize,
+
+    sender: Option<broadcast::Sender<ClipboardEvent>>,
+
+    // changes are neither retained nor broadcast, their cursors are skipped
+    muted: bool,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        let (sender, _receiver) = broadcast::channel(DEFAULT_CAPACITY);
+        // start from the current time in microseconds, cursors handed out
+        // before a restart of the server stay smaller than the new ones
+        let next_cursor =
+            u64::try_from(OffsetDateTime::now_utc().unix_timestamp_nanos() / 1000).unwrap_or(0);
+        Self {
+            next_cursor,
+            events: VecDeque::with_capacity(DEFAULT_CAPACITY),
+            capacity: DEFAULT_CAPACITY,
+            sender: Some(sender),
+            muted: false,
+        }
+    }
+
+    /// Returns the cursor of the next recorded change.
+    pub const fn next_cursor(&self) -> u64 { self.next_cursor }
+
+    pub fn record(&mut self, change: ClipboardChange) {
+        let event = ClipboardEvent { cursor: self.next_cursor, change };
+        self.next_cursor += 1;
+        if self.muted {
+            return;
+        }
+
+        if self.events.len() == self.capacity {
+            drop(self.events.pop_front());
+        }
+        self.events.push_back(event.clone());
+
+        if let Some(sender) = &self.sender {
+            // no one is subscribing is not an error
+            drop(sender.send(event));
+        }
+    }
+
+    /// Returns the retained events after `since_cursor` and a receiver of the
+    /// following events.
+    pub fn subscribe(
+        &self,
+        since_cursor: Option<u64>,
+    ) -> (Vec<ClipboardEvent>, Option<broadcast::Receiver<ClipboardEvent>>) {
+        let events = since_cursor.map_or_else(Vec::new, |since_cursor| {
+            self.events.iter().filter(|event| event.cursor > since_cursor).cloned().collect()
+        });
+        (events, self.sender.as_ref().map(broadcast::Sender::subscribe))
+    }
+
+    /// Returns the retained events after `since_cursor`, `None` if some of
+    /// them are no longer retained or were never recorded.
+    pub fn changes_since(&self, since_cursor: u64) -> Option<Vec<ClipboardEvent>> {
+        let last_cursor = self.next_cursor.checked_sub(1)?;
+        if since_cursor >= last_cursor {
+            // cursors of another server may be larger than the ones handed out
+            return (since_cursor == last_cursor).then(Vec::new);
+        }
+        let events = self
+            .events
+            .iter()
+            .filter(|event| event.cursor > since_cursor)
+            .cloned()
+            .collect::<Vec<_>>();
+        // the event right after `since_cursor` is dropped or muted
+        let first_cursor = events.first()?.cursor;
+        (first_cursor == since_cursor + 1
+            && !events.iter().any(|event| event.change == ClipboardChange::Resync))
+        .then_some(events)
+    }
+
+    /// Stops retaining and broadcasting changes while `muted`.
+    pub fn set_muted(&mut self, muted: bool) { self.muted = muted; }
+
+    /// Closes all receivers, no further event will be broadcast.
+    pub fn close(&mut self) { self.sender = None; }
+}
+
+impl Default for Journal {
+    fn default() -> Self { Self::new() }
+}
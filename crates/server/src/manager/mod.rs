@@ -1,19 +1,65 @@
+mod compress;
+mod convert;
 mod error;
+mod image_cache;
+mod journal;
+mod score;
+mod text_index;
 
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::{Hash, Hasher},
     sync::Arc,
 };
 
-use clipcat_base::{ClipEntry, ClipEntryMetadata, ClipboardContent, ClipboardKind};
+use clipcat_base::{
+    ClipEntry, ClipEntryFull, ClipEntryMetadata, ClipOp, ClipOpResult, ClipboardChange,
+    ClipboardContent, ClipboardEvent, ClipboardKind, CycleDirection, DedupeScope, EvictionReason,
+    HistoryDelta, HistoryStats, ImageFormat, ListCursor, MetadataImportReport, MetadataRecord,
+    ResourceUsage, SemanticKind, SortKey, SortOrder, TagMatch,
+};
 use snafu::ResultExt;
 use time::OffsetDateTime;
+use tokio::sync::{broadcast, watch};
 
 pub use self::error::Error;
-use crate::{backend::ClipboardBackend, notification};
+use self::{
+    compress::CompressedContent, image_cache::ImageCache, journal::Journal, text_index::TextIndex,
+};
+use crate::{
+    auto_tag::AutoTagger,
+    backend::{self, ClipboardBackend},
+    classify::Classifier,
+    notification,
+    redact::Redactor,
+    transform::ContentTransformer,
+};
 
 const DEFAULT_CAPACITY: usize = 40;
 
+const MAX_ACTIVITY_BUCKETS: usize = 10_000;
+
+// a snapshot not listed within this time is closed
+const SNAPSHOT_TTL: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+// the oldest snapshot is closed when another one is opened beyond the limit
+const MAX_SNAPSHOTS: usize = 32;
+
+// sticky notes of a profile
+const MAX_STICKY_NOTES: usize = 3;
+
+// a point-in-time view of the history kept for paginating
+struct Snapshot {
+    // newest first
+    clips: Vec<ClipEntry>,
+
+    expires_at: std::time::Instant,
+}
+
+// SAFETY: the flags are enabled/disabled by the user independently
+#[allow(clippy::struct_excessive_bools)]
 pub struct ClipboardManager<Notification> {
     backend: Arc<dyn ClipboardBackend>,
 
@@ -24,14 +70,77 @@ pub struct ClipboardManager<Notification> {
     // use id of ClipEntry as the key
     clips: HashMap<u64, ClipEntry>,
 
+    // content of the large clips, the clips in `clips` hold placeholders
+    compressed: HashMap<u64, CompressedContent>,
+
+    // size in bytes above which the content of clips is compressed
+    compression_threshold: Option<usize>,
+
     // store current clip for each clipboard kind
     current_clips: [Option<u64>; ClipboardKind::MAX_LENGTH],
 
-    // use BTreeMap to store timestamps for remove the oldest clip
-    timestamp_to_id: BTreeMap<OffsetDateTime, u64>,
+    // notifies subscribers of the ids of the current clips, `None` once the
+    // manager is closed
+    current_clips_sender: Option<watch::Sender<[Option<u64>; ClipboardKind::MAX_LENGTH]>>,
+
+    // clipboard kinds which clips can be marked as
+    enabled_kinds: [bool; ClipboardKind::MAX_LENGTH],
+
+    dedupe_scope: DedupeScope,
+
+    // texts differing only in whitespaces are merged, the stored content is
+    // not changed
+    normalize_whitespace_for_dedupe: bool,
+
+    // the current text clip is replaced by a text clip extending it within
+    // `primary_threshold`, like a selection growing while it is made
+    coalesce_growing_edits: bool,
+
+    // clips are not stored into the clipboard, the selections are only read
+    read_only: bool,
+
+    // requests meant for tests, like replacing the whole history, are allowed
+    testing: bool,
+
+    // store id of the content set by `set_transient` for each clipboard kind,
+    // it is not captured into history
+    transient_ids: [Option<u64>; ClipboardKind::MAX_LENGTH],
+
+    // the timestamps and the ids of the clips ordered oldest first for
+    // removing the oldest clip, clips sharing a timestamp are ordered like
+    // their listing, by id descending
+    timestamp_ids: BTreeSet<(OffsetDateTime, Reverse<u64>)>,
 
     snippet_ids: HashSet<u64>,
 
+    // `None` is the default profile
+    active_profile: Option<String>,
+
+    // clips of the profiles which are not active, snippets are shared by all
+    // profiles and not stored here
+    inactive_profiles: HashMap<Option<String>, Vec<ClipEntry>>,
+
+    // inverted index of text clips for searching
+    text_index: TextIndex,
+
+    // images recently converted by `image_as`
+    image_cache: ImageCache,
+
+    journal: Journal,
+
+    content_transformer: ContentTransformer,
+
+    auto_tagger: AutoTagger,
+
+    classifier: Classifier,
+
+    redactor: Redactor,
+
+    // snapshots opened by clients, by handle
+    snapshots: HashMap<u64, Snapshot>,
+
+    next_snapshot_handle: u64,
+
     notification: Notification,
 }
 
@@ -51,9 +160,30 @@ where
             primary_threshold,
             capacity,
             clips: HashMap::new(),
+            compressed: HashMap::new(),
+            compression_threshold: None,
             current_clips: [None; ClipboardKind::MAX_LENGTH],
-            timestamp_to_id: BTreeMap::new(),
+            current_clips_sender: Some(watch::channel([None; ClipboardKind::MAX_LENGTH]).0),
+            enabled_kinds: [true; ClipboardKind::MAX_LENGTH],
+            dedupe_scope: DedupeScope::default(),
+            normalize_whitespace_for_dedupe: false,
+            coalesce_growing_edits: true,
+            read_only: false,
+            testing: false,
+            transient_ids: [None; ClipboardKind::MAX_LENGTH],
+            timestamp_ids: BTreeSet::new(),
             snippet_ids: HashSet::new(),
+            active_profile: None,
+            inactive_profiles: HashMap::new(),
+            text_index: TextIndex::new(),
+            image_cache: ImageCache::default(),
+            journal: Journal::new(),
+            content_transformer: ContentTransformer::default(),
+            auto_tagger: AutoTagger::default(),
+            classifier: Classifier::default(),
+            redactor: Redactor::default(),
+            snapshots: HashMap::new(),
+            next_snapshot_handle: 1,
             notification,
         }
     }
@@ -72,372 +202,4882 @@ where
     #[inline]
     pub const fn capacity(&self) -> usize { self.capacity }
 
+    /// Sets the clipboard kinds which clips can be inserted into or marked as,
+    /// all kinds are enabled by default.
+    #[inline]
+    pub fn set_enabled_kinds(&mut self, enabled_kinds: [bool; ClipboardKind::MAX_LENGTH]) {
+        self.enabled_kinds = enabled_kinds;
+    }
+
+    #[inline]
+    pub fn is_kind_enabled(&self, kind: ClipboardKind) -> bool {
+        self.enabled_kinds[usize::from(kind)]
+    }
+
+    /// Sets which inserted clips with the same content are merged, clips are
+    /// merged globally by default.
+    #[inline]
+    pub fn set_dedupe_scope(&mut self, dedupe_scope: DedupeScope) {
+        self.dedupe_scope = dedupe_scope;
+    }
+
+    /// Sets whether texts differing only in leading, trailing or repeated
+    /// whitespaces are merged like clips with the same content.
+    ///
+    /// Only the key used for deduplication is normalized, the content of the
+    /// newest clip is stored as is.
+    #[inline]
+    pub fn set_normalize_whitespace_for_dedupe(&mut self, normalize: bool) {
+        self.normalize_whitespace_for_dedupe = normalize;
+    }
+
+    /// Sets whether a text clip extending the current clip of its kind, or
+    /// shortening it, within `primary_threshold` replaces the current clip
+    /// instead of being added next to it, so only the final text of a
+    /// selection growing while it is made is kept. It is enabled by default.
+    #[inline]
+    pub fn set_coalesce_growing_edits(&mut self, coalesce: bool) {
+        self.coalesce_growing_edits = coalesce;
+    }
+
+    /// Sets whether clips are only captured, marking a clip or storing
+    /// content into the clipboard fails in read-only mode so the server never
+    /// takes the ownership of a selection.
+    #[inline]
+    pub fn set_read_only(&mut self, read_only: bool) { self.read_only = read_only; }
+
+    /// Sets whether the requests meant for tests are allowed, like
+    /// [`ClipboardManager::set_history`].
+    #[inline]
+    pub fn set_testing(&mut self, testing: bool) { self.testing = testing; }
+
+    /// Sets the size in bytes above which the content of clips is compressed
+    /// in memory, `None` disables compression.
+    ///
+    /// Clips are decompressed when they are read, so clients always receive
+    /// the original content. Only clips inserted afterwards are compressed.
+    #[inline]
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    #[inline]
+    pub fn set_content_transformer(&mut self, content_transformer: ContentTransformer) {
+        self.content_transformer = content_transformer;
+    }
+
+    #[inline]
+    pub fn set_auto_tagger(&mut self, auto_tagger: AutoTagger) { self.auto_tagger = auto_tagger; }
+
+    /// Sets the classifier assigning a [`SemanticKind`] to new text clips.
+    #[inline]
+    pub fn set_classifier(&mut self, classifier: Classifier) { self.classifier = classifier; }
+
+    /// Sets the rules redacting the previews of clips, the content of clips
+    /// is never redacted.
+    #[inline]
+    pub fn set_redactor(&mut self, redactor: Redactor) { self.redactor = redactor; }
+
+    #[inline]
+    pub const fn redactor(&self) -> &Redactor { &self.redactor }
+
     #[inline]
     pub fn import(&mut self, clips: &[ClipEntry]) { self.import_iter(clips.iter()); }
 
     #[inline]
     pub fn import_iter<'a>(&'a mut self, clips_iter: impl Iterator<Item = &'a ClipEntry>) {
         self.clips.clear();
-        self.timestamp_to_id.clear();
+        self.compressed.clear();
+        self.timestamp_ids.clear();
+        self.text_index.clear();
         for clip in clips_iter {
+            let mut clip = clip.clone();
+            // the kinds are not saved with the history
+            if clip.semantic_kind().is_none() {
+                clip.set_semantic_kind(self.classifier.classify(&clip));
+            }
             let (id, timestamp) = (clip.id(), clip.timestamp());
-            let _ = self.timestamp_to_id.insert(timestamp, id);
-            drop(self.clips.insert(id, clip.clone()));
+            self.index_text(&clip);
+            if let Some(existing) = self.store(clip) {
+                let _ = self.timestamp_ids.remove(&(existing.timestamp(), Reverse(id)));
+            }
+            let _ = self.timestamp_ids.insert((timestamp, Reverse(id)));
         }
 
-        self.remove_oldest();
+        drop(self.remove_oldest());
     }
 
     pub fn insert_snippets(&mut self, snippets: &[ClipEntry]) {
         for clip in snippets {
+            let mut clip = clip.clone();
+            clip.set_version(self.journal.next_cursor());
             let (id, timestamp) = (clip.id(), clip.timestamp());
-            let _ = self.timestamp_to_id.insert(timestamp, id);
-            drop(self.clips.insert(id, clip.clone()));
+            let _ = self.timestamp_ids.insert((timestamp, Reverse(id)));
+            self.index_text(&clip);
+            drop(self.store(clip.clone()));
             let _unused = self.snippet_ids.insert(id);
+            self.journal.record(ClipboardChange::Added(clip));
         }
 
-        self.remove_oldest();
+        drop(self.remove_oldest());
     }
 
     #[inline]
     pub fn export(&self, with_snippets: bool) -> Vec<ClipEntry> {
-        self.iter()
+        self.clips
+            .values()
             .filter(|entry| {
                 let is_snippet = self.is_snippet(entry.id());
                 !is_snippet || with_snippets
             })
-            .cloned()
+            .map(|entry| self.load(entry).into_owned())
             .collect()
     }
 
+    /// Returns the metadata of the clips, newest first.
     #[inline]
     pub fn list(&self, preview_length: usize) -> Vec<ClipEntryMetadata> {
-        self.iter().map(|entry| entry.metadata(Some(preview_length))).collect()
+        self.list_sorted(preview_length, SortOrder::Recency)
     }
 
+    /// Returns the metadata of the clips in `order`.
     #[inline]
-    pub fn iter(&self) -> impl Iterator<Item = &ClipEntry> { self.clips.values() }
-
-    #[inline]
-    pub fn get(&self, id: u64) -> Option<ClipEntry> { self.clips.get(&id).cloned() }
-
-    #[inline]
-    pub fn get_current_clip(&self, kind: ClipboardKind) -> Option<&ClipEntry> {
-        self.current_clips[usize::from(kind)].and_then(|id| self.clips.get(&id))
+    pub fn list_sorted(&self, preview_length: usize, order: SortOrder) -> Vec<ClipEntryMetadata> {
+        self.list_skipping_corrupt(preview_length, order).0
     }
 
-    #[inline]
-    pub fn insert(&mut self, data: ClipEntry) -> u64 { self.insert_inner(data) }
-
-    fn insert_inner(&mut self, entry: ClipEntry) -> u64 {
-        // emit notification
-        match entry.as_ref() {
-            ClipboardContent::Image { width, height, bytes } => {
-                self.notification.on_image_fetched(bytes.len(), *width, *height);
-            }
-            ClipboardContent::Plaintext(text) => {
-                self.notification.on_plaintext_fetched(text.chars().count());
-
-                if let Some(id) = self.current_clips[usize::from(entry.kind())] {
-                    if let Some(current_clip) = self.clips.get(&id) {
-                        if entry.timestamp() - current_clip.timestamp() < self.primary_threshold {
-                            if let ClipboardContent::Plaintext(current_text) = current_clip.as_ref()
-                            {
-                                let len = text.len().min(current_text.len());
-                                if text[..len] == current_text[..len] {
-                                    if let Some(clip) = self.clips.remove(&id) {
-                                        let _id = self.timestamp_to_id.remove(&clip.timestamp());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        let (id, timestamp) = (entry.id(), entry.timestamp());
-        self.current_clips[usize::from(entry.kind())] = Some(id);
-        drop(self.clips.insert(id, entry));
-        let _unused = self.timestamp_to_id.insert(timestamp, id);
-        self.remove_oldest();
-        id
+    /// Returns the metadata of the clips in `order` and the number of clips
+    /// skipped because their compressed content can not be decompressed.
+    ///
+    /// The skipped clips are reported by [`Self::corrupt_ids`].
+    pub fn list_skipping_corrupt(
+        &self,
+        preview_length: usize,
+        order: SortOrder,
+    ) -> (Vec<ClipEntryMetadata>, usize) {
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        self.sort_clips(&mut clips, order);
+        let total = clips.len();
+        let metadata = clips
+            .into_iter()
+            .filter_map(|entry| self.try_load(entry))
+            .map(|entry| self.redactor.metadata(&entry, preview_length))
+            .collect::<Vec<_>>();
+        let skipped = total - metadata.len();
+        (metadata, skipped)
     }
 
-    #[inline]
-    pub fn len(&self) -> usize { self.clips.len() }
+    /// Returns the ids of the clips whose compressed content can not be
+    /// decompressed, in ascending order.
+    pub fn corrupt_ids(&self) -> Vec<u64> {
+        let mut ids = self
+            .compressed
+            .iter()
+            .filter(|(_, compressed)| compressed.decompress().is_none())
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    }
 
+    /// Returns the active profile, `None` is the default profile.
     #[inline]
-    pub fn is_empty(&self) -> bool { self.clips.is_empty() }
+    pub fn active_profile(&self) -> Option<&str> { self.active_profile.as_deref() }
 
-    fn remove_oldest(&mut self) {
-        if self.is_empty() {
+    /// Switches the history to `profile`, `None` is the default profile.
+    ///
+    /// The clips of the previous profile are kept aside until it is activated
+    /// again, snippets are shared by all profiles.
+    pub fn set_active_profile(&mut self, profile: Option<String>) {
+        let profile = profile.filter(|profile| !profile.is_empty());
+        if profile == self.active_profile {
             return;
         }
 
-        let snippet_count = self.snippet_ids.len();
-        let now = OffsetDateTime::now_utc();
+        let clips = self.export(false);
+        self.current_clips = [None; ClipboardKind::MAX_LENGTH];
+        for clip in &clips {
+            drop(self.detach(clip.id()));
+        }
+        let previous_profile = std::mem::replace(&mut self.active_profile, profile.clone());
+        let _unused = self.inactive_profiles.insert(previous_profile, clips);
+        self.journal.record(ClipboardChange::ProfileChanged { profile });
 
-        while self.clips.len() > self.capacity + snippet_count {
-            if let Some((timestamp, id)) = self.timestamp_to_id.pop_first() {
-                if self.snippet_ids.contains(&id) {
-                    tracing::trace!("Retain snippet clip and update its timestamp (id: {id})");
-                    let _ = self.timestamp_to_id.insert(now, id);
-                    let _ = self.clips.get_mut(&id).map(|entry| entry.set_timestamp(now));
-                } else {
-                    tracing::trace!("Remove old clip (id: {id}, timestamp: {timestamp})");
-                    drop(self.clips.remove(&id));
-                }
-            }
+        for mut clip in self.inactive_profiles.remove(&self.active_profile).unwrap_or_default() {
+            clip.set_version(self.journal.next_cursor());
+            let (id, timestamp) = (clip.id(), clip.timestamp());
+            let _ = self.timestamp_ids.insert((timestamp, Reverse(id)));
+            self.index_text(&clip);
+            drop(self.store(clip.clone()));
+            self.journal.record(ClipboardChange::Added(clip));
         }
+        drop(self.remove_oldest());
     }
 
-    pub fn remove_snippet(&mut self, id: u64) -> bool {
-        if self.snippet_ids.remove(&id) {
-            self.clips.remove(&id).is_some()
+    /// Returns the clips of `profile` without snippets.
+    pub fn export_profile(&self, profile: Option<&str>) -> Vec<ClipEntry> {
+        if profile == self.active_profile() {
+            self.export(false)
         } else {
-            false
+            self.inactive_profiles
+                .get(&profile.map(ToString::to_string))
+                .cloned()
+                .unwrap_or_default()
         }
     }
 
+    /// Returns the cursor of the next change of history, it grows with every
+    /// change.
     #[inline]
-    pub fn remove(&mut self, id: u64) -> bool { self.remove_inner(id).is_some() }
-
-    #[inline]
-    fn remove_inner(&mut self, id: u64) -> Option<ClipEntry> {
-        if let Some(id) = self.snippet_ids.get(&id) {
-            return self.clips.get(id).cloned();
-        }
+    pub const fn next_cursor(&self) -> u64 { self.journal.next_cursor() }
 
-        for kind in ClipboardKind::all_kinds().map(usize::from) {
-            if Some(id) == self.current_clips[kind] {
-                self.current_clips[kind] = None;
-            }
-        }
+    /// Returns the clips saved into the history file, the clips of the default
+    /// profile without snippets, expiring and private clips are kept in memory
+    /// only.
+    pub fn persisted_clips(&self) -> Vec<ClipEntry> {
+        self.export_profile(None)
+            .into_iter()
+            .filter(|clip| clip.expires_at().is_none() && !clip.is_private())
+            .collect()
+    }
 
-        if let Some(clip) = self.clips.remove(&id) {
-            let _id = self.timestamp_to_id.remove(&clip.timestamp());
-            Some(clip)
+    pub fn list_profile(
+        &self,
+        profile: Option<&str>,
+        preview_length: usize,
+    ) -> Vec<ClipEntryMetadata> {
+        if profile == self.active_profile() {
+            self.list(preview_length)
         } else {
-            None
+            let mut clips = self.export_profile(profile);
+            clips.sort_unstable_by(sticky_first);
+            clips.iter().map(|entry| self.redactor.metadata(entry, preview_length)).collect()
         }
     }
 
-    #[inline]
-    pub fn clear(&mut self) {
-        self.timestamp_to_id.retain(|_, id| self.snippet_ids.contains(id));
-        self.current_clips = [None; ClipboardKind::MAX_LENGTH];
-        self.clips.retain(|id, _| self.snippet_ids.contains(id));
-        self.notification.on_history_cleared();
+    pub fn clear_profile(&mut self, profile: Option<&str>) {
+        if profile == self.active_profile() {
+            self.clear();
+        } else {
+            drop(self.inactive_profiles.remove(&profile.map(ToString::to_string)));
+        }
     }
 
-    pub fn replace(&mut self, old_id: u64, data: &[u8], mime: &mime::Mime) -> (bool, u64) {
-        let kind = self.remove_inner(old_id).map_or(ClipboardKind::Primary, |clip| clip.kind());
-        ClipEntry::new(data, mime, kind, None).map_or((false, old_id), |entry| {
-            let new_id = entry.id();
-            let _ = self.insert_inner(entry);
-            (true, new_id)
-        })
+    /// Returns at most `limit` clips from `offset`, newest first, and the total
+    /// count of clips.
+    pub fn list_range(&self, offset: usize, limit: usize) -> (Vec<ClipEntry>, usize) {
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        self.sort_clips(&mut clips, SortOrder::Recency);
+        let clips = clips
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|clip| self.load(clip).into_owned())
+            .collect();
+        (clips, self.clips.len())
     }
 
-    pub async fn mark(&mut self, id: u64, clipboard_kind: ClipboardKind) -> Result<(), Error> {
-        if let Some(clip) = self.clips.get_mut(&id) {
-            clip.mark(clipboard_kind);
-            self.backend
-                .store(clipboard_kind, clip.as_ref().clone())
-                .await
-                .context(error::StoreClipboardContentSnafu)?;
+    /// Opens a snapshot of the history and returns its handle and the count of
+    /// its clips, pages listed with [`ClipboardManager::list_snapshot_range`]
+    /// do not drift while clips are inserted or removed.
+    ///
+    /// A snapshot is closed if it is not listed within `SNAPSHOT_TTL`, the
+    /// oldest snapshot is closed if more than `MAX_SNAPSHOTS` are open.
+    pub fn open_snapshot(&mut self) -> (u64, usize) {
+        let now = std::time::Instant::now();
+        self.remove_expired_snapshots(now);
+        while self.snapshots.len() >= MAX_SNAPSHOTS {
+            let Some(oldest) = self.snapshots.keys().min().copied() else { break };
+            drop(self.snapshots.remove(&oldest));
         }
 
-        Ok(())
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        self.sort_clips(&mut clips, SortOrder::Recency);
+        let clips = clips.into_iter().map(|clip| self.load(clip).into_owned()).collect::<Vec<_>>();
+        let total = clips.len();
+        let handle = self.next_snapshot_handle;
+        self.next_snapshot_handle += 1;
+        let _unused =
+            self.snapshots.insert(handle, Snapshot { clips, expires_at: now + SNAPSHOT_TTL });
+        (handle, total)
     }
 
-    #[inline]
-    fn is_snippet(&self, id: u64) -> bool { self.snippet_ids.contains(&id) }
-}
+    /// Returns at most `limit` clips from `offset` of the snapshot, newest
+    /// first, and the count of its clips, like
+    /// [`ClipboardManager::list_range`] at the time the snapshot was opened.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SnapshotNotFound`] if the snapshot is closed or
+    /// expired.
+    pub fn list_snapshot_range(
+        &mut self,
+        handle: u64,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<ClipEntry>, usize), Error> {
+        let now = std::time::Instant::now();
+        self.remove_expired_snapshots(now);
+        let snapshot = self.snapshots.get_mut(&handle).ok_or(Error::SnapshotNotFound { handle })?;
+        snapshot.expires_at = now + SNAPSHOT_TTL;
+        let clips = snapshot.clips.iter().skip(offset).take(limit).cloned().collect();
+        Ok((clips, snapshot.clips.len()))
+    }
 
-#[cfg(test)]
-mod tests {
-    use std::{collections::HashSet, sync::Arc, time::Duration};
+    /// Closes the snapshot and frees its clips, returns `false` if it is
+    /// closed or expired already.
+    pub fn close_snapshot(&mut self, handle: u64) -> bool {
+        self.remove_expired_snapshots(std::time::Instant::now());
+        self.snapshots.remove(&handle).is_some()
+    }
 
-    use clipcat_base::{ClipEntry, ClipboardKind};
+    fn remove_expired_snapshots(&mut self, now: std::time::Instant) {
+        self.snapshots.retain(|_, snapshot| snapshot.expires_at > now);
+    }
 
-    use crate::{
-        backend::LocalClipboardBackend,
-        manager::{ClipboardManager, DEFAULT_CAPACITY},
-        notification::DummyNotification,
-    };
+    /// Returns at most `limit` clips which were marked or touched, the most
+    /// used first, clips used equally often are ordered newest first.
+    pub fn list_most_used(&self, limit: usize) -> Vec<ClipEntry> {
+        let mut clips = self.clips.values().filter(|clip| clip.use_count() > 0).collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| b.use_count().cmp(&a.use_count()).then_with(|| a.cmp(b)));
+        clips.into_iter().take(limit).map(|clip| self.load(clip).into_owned()).collect()
+    }
 
-    fn create_clips(n: usize) -> Vec<ClipEntry> {
-        (0..n)
-            .map(|i| {
-                // sleep for 1 millisecond, avoiding duplicated timestamp
-                std::thread::sleep(Duration::from_millis(1));
+    /// Returns at most `limit` clips listed after `cursor`, sorted like
+    /// [`Self::list`], and the cursor of the next page, `None` after the last
+    /// page.
+    ///
+    /// The cursor is the sort key of the last clip of a page, so pages are
+    /// consistent while clips are inserted or removed, a clip promoted in
+    /// between is not listed again. At least one clip is listed.
+    pub fn list_after(
+        &self,
+        cursor: Option<ListCursor>,
+        limit: usize,
+    ) -> (Vec<ClipEntry>, Option<ListCursor>) {
+        let mut clips = self
+            .clips
+            .values()
+            .filter(|clip| cursor.map_or(true, |cursor| cursor.precedes(clip)))
+            .collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        let limit = limit.max(1);
+        let next_cursor = (clips.len() > limit).then(|| ListCursor::from(clips[limit - 1]));
+        let clips =
+            clips.into_iter().take(limit).map(|clip| self.load(clip).into_owned()).collect();
+        (clips, next_cursor)
+    }
 
-                ClipEntry::from_string(i, ClipboardKind::Primary)
-            })
+    /// Returns the metadata of the clips with `ids` in the same order, missing
+    /// clips are omitted.
+    pub fn batch_metadata(&self, ids: &[u64], preview_length: usize) -> Vec<ClipEntryMetadata> {
+        ids.iter()
+            .filter_map(|id| self.clips.get(id))
+            .map(|clip| self.redactor.metadata(&self.load(clip), preview_length))
             .collect()
     }
 
-    #[test]
-    fn test_construction() {
-        let notification = DummyNotification::default();
-        let backend = Arc::new(LocalClipboardBackend::new());
-        let mgr = ClipboardManager::new(backend, notification);
-        assert!(mgr.is_empty());
-        assert_eq!(mgr.len(), 0);
-        assert_eq!(mgr.capacity(), DEFAULT_CAPACITY);
-        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
-        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
-
-        let cap = 20;
-        let backend = Arc::new(LocalClipboardBackend::new());
-        let mgr = ClipboardManager::with_capacity(
-            backend,
-            cap,
-            time::Duration::milliseconds(0),
-            notification,
-        );
-        assert!(mgr.is_empty());
-        assert_eq!(mgr.len(), 0);
-        assert_eq!(mgr.capacity(), cap);
-        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
-        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+    /// Returns the etags of the clips with `ids` in the same order, missing
+    /// clips are omitted.
+    pub fn batch_etags(&self, ids: &[u64]) -> Vec<(u64, u64)> {
+        // the digest is kept in place of a compressed content
+        ids.iter()
+            .filter_map(|id| self.clips.get(id))
+            .map(|clip| (clip.id(), clip.etag()))
+            .collect()
     }
 
-    #[test]
-    fn test_capacity() {
-        let backend = Arc::new(LocalClipboardBackend::new());
-        let notification = DummyNotification::default();
-        let cap = 10;
-        let mut mgr = ClipboardManager::with_capacity(
-            backend,
-            cap,
-            time::Duration::milliseconds(0),
-            notification,
-        );
-        assert_eq!(mgr.len(), 0);
-        assert_eq!(mgr.capacity(), cap);
+    /// Returns the ids and the timestamps of all clips, sorted like
+    /// [`Self::list`].
+    pub fn list_ids(&self) -> Vec<(u64, OffsetDateTime)> {
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(|clip| (clip.id(), clip.timestamp())).collect()
+    }
 
-        let n = 20;
-        let clips = create_clips(n);
+    /// Returns the clips of each clipboard kind, each group in `order`, all
+    /// kinds are present even without clips.
+    pub fn list_grouped_sorted(&self, order: SortOrder) -> HashMap<ClipboardKind, Vec<ClipEntry>> {
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        self.sort_clips(&mut clips, order);
+        let mut groups = ClipboardKind::all_kinds()
+            .into_iter()
+            .map(|kind| (kind, Vec::new()))
+            .collect::<HashMap<_, _>>();
         for clip in clips {
-            let _ = mgr.insert(clip);
+            groups.entry(clip.kind()).or_default().push(self.load(clip).into_owned());
         }
+        groups
+    }
 
-        assert_eq!(mgr.len(), cap);
-        assert_eq!(mgr.capacity(), cap);
+    /// Returns the ids of the clips whose content has `hash`, sorted like
+    /// [`Self::list`], see [`ClipEntry::content_hash`].
+    ///
+    /// More than one clip has the same content only if they are kept apart by
+    /// the `DedupeScope`. All clips are scanned, the content hashes are not
+    /// indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `hash` is not a SHA-256 digest in hex.
+    pub fn ids_with_content_hash(&self, hash: &str) -> Result<Vec<u64>, Error> {
+        // SHA-256 digests are 32 bytes long
+        let digest = hex::decode(hash)
+            .ok()
+            .filter(|digest| digest.len() == 32)
+            .ok_or_else(|| Error::InvalidContentHash { hash: hash.to_string() })?;
+        Ok(self.ids_with_digest(&digest))
+    }
 
-        let n = 25;
-        let clips = create_clips(n);
-        mgr.import(&clips);
+    /// Returns the ids of the clips whose content has the SHA-256 `digest`,
+    /// sorted like [`Self::list`], see [`ClipEntry::sha256_digest`].
+    pub fn ids_with_digest(&self, digest: &[u8]) -> Vec<u64> {
+        let mut clips =
+            self.clips.values().filter(|clip| clip.sha256_digest() == digest).collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(ClipEntry::id).collect()
+    }
 
-        assert_eq!(mgr.len(), cap);
-        assert_eq!(mgr.capacity(), cap);
+    /// Returns the metadata users organize the clips with, like their tags and
+    /// labels, keyed by the content hashes of the clips, see
+    /// [`MetadataRecord`]. Snippets, private clips and the clips without such
+    /// metadata are skipped.
+    pub fn export_metadata(&self) -> Vec<MetadataRecord> {
+        let mut records = self
+            .clips
+            .values()
+            .filter(|clip| !self.is_snippet(clip.id()) && !clip.is_private())
+            .map(MetadataRecord::from)
+            .filter(|record| !record.is_empty())
+            .collect::<Vec<_>>();
+        records.sort_unstable_by(|a, b| a.content_hash.cmp(&b.content_hash));
+        // the clips kept apart with the same content share a hash
+        records.dedup_by(|a, b| a.content_hash == b.content_hash);
+        records
+    }
 
-        let mut exported = mgr.export(false);
-        exported.sort_unstable();
-        let mut clips = clips[(n - mgr.capacity())..].to_vec();
-        clips.sort_unstable();
-        assert_eq!(exported.len(), clips.len());
-        assert_eq!(exported, clips);
+    /// Applies the metadata records to the clips with the same content
+    /// hashes, like the records exported from another history by
+    /// [`ClipboardManager::export_metadata`], the content is not changed.
+    ///
+    /// The tags and the attributes are added to the ones of the clips, the
+    /// other metadata replaces the metadata of the clips if it is set. A
+    /// record makes a text clip a sticky note only if the profile has room
+    /// for it. The records without a matching clip are skipped and reported.
+    pub fn import_metadata(&mut self, records: Vec<MetadataRecord>) -> MetadataImportReport {
+        let mut ids_by_hash = HashMap::<String, Vec<u64>>::new();
+        for clip in self.clips.values() {
+            ids_by_hash.entry(clip.content_hash()).or_default().push(clip.id());
+        }
+        let mut sticky_count = self.clips.values().filter(|clip| clip.is_sticky()).count();
+        let mut report = MetadataImportReport::default();
+        for record in records {
+            let Some(ids) = ids_by_hash.get(&record.content_hash) else {
+                report.unmatched.push(record.content_hash);
+                continue;
+            };
+            for &id in ids {
+                if self.apply_metadata(id, &record, &mut sticky_count) {
+                    report.applied += 1;
+                }
+            }
+        }
+        report
     }
 
-    #[allow(clippy::mutable_key_type)]
-    #[test]
-    fn test_insert() {
-        let n = 20;
-        let clips = create_clips(n);
-        let backend = Arc::new(LocalClipboardBackend::new());
-        let notification = DummyNotification::default();
-        let mut mgr = ClipboardManager::new(backend, notification);
-        for clip in &clips {
-            let _ = mgr.insert(clip.clone());
+    // `sticky_count` is the number of sticky notes, counting the notes made
+    // sticky by the record
+    fn apply_metadata(
+        &mut self,
+        id: u64,
+        record: &MetadataRecord,
+        sticky_count: &mut usize,
+    ) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        let mut tags = clip.tags().clone();
+        tags.extend(record.tags.iter().cloned());
+        clip.set_tags(tags);
+        let mut attributes = clip.attributes().clone();
+        attributes.extend(record.attributes.clone());
+        clip.set_attributes(attributes);
+        if record.label.is_some() {
+            clip.set_label(record.label.clone());
+        }
+        if let Some(until) =
+            record.protected_until.filter(|&until| until > OffsetDateTime::now_utc())
+        {
+            clip.set_protected_until(Some(until));
+        }
+        if record.sticky && clip.is_text() && !clip.is_sticky() && *sticky_count < MAX_STICKY_NOTES
+        {
+            clip.set_sticky(true);
+            clip.set_expires_at(None);
+            *sticky_count += 1;
         }
+        if record.semantic_kind.is_some() {
+            clip.set_semantic_kind(record.semantic_kind);
+        }
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
 
-        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_some());
-        assert_eq!(mgr.get_current_clip(ClipboardKind::Primary), clips.last());
-        assert_eq!(mgr.len(), n);
+    /// Returns the clips added or updated at or after `since`, sorted like
+    /// [`Self::list`].
+    ///
+    /// Removed clips are not tracked, they are simply missing.
+    pub fn list_since(&self, since: OffsetDateTime) -> Vec<ClipEntry> {
+        let mut clips =
+            self.clips.values().filter(|clip| clip.updated_at() >= since).collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(|clip| self.load(clip).into_owned()).collect()
+    }
 
-        let dumped = mgr.export(false).into_iter().collect::<HashSet<_>>();
-        let clips = clips.into_iter().collect::<HashSet<_>>();
+    /// Returns the clips whose tags match `tags`, sorted like [`Self::list`],
+    /// all clips match an empty set of tags.
+    pub fn list_by_tags(&self, tags: &[String], mode: TagMatch) -> Vec<ClipEntry> {
+        let mut clips =
+            self.clips.values().filter(|clip| mode.matches(clip.tags(), tags)).collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(|clip| self.load(clip).into_owned()).collect()
+    }
 
-        assert_eq!(dumped, clips);
+    /// Returns the clips captured from windows of `class`, sorted like
+    /// [`Self::list`], the class is matched ignoring ASCII case.
+    pub fn list_by_source_class(&self, class: &str) -> Vec<ClipEntry> {
+        let mut clips = self
+            .clips
+            .values()
+            .filter(|clip| clip.source_window().is_some_and(|window| window.has_class(class)))
+            .collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(|clip| self.load(clip).into_owned()).collect()
     }
 
-    #[test]
-    fn test_import() {
-        let n = 10;
-        let mut clips = create_clips(n);
-        let backend = Arc::new(LocalClipboardBackend::new());
-        let notification = DummyNotification::default();
-        let mut mgr = ClipboardManager::with_capacity(
-            backend,
-            20,
-            time::Duration::milliseconds(0),
-            notification,
-        );
+    /// Returns the clips of `kind`, sorted like [`Self::list`], see
+    /// [`ClipEntry::semantic_kind`].
+    pub fn list_by_semantic_kind(&self, kind: SemanticKind) -> Vec<ClipEntry> {
+        let mut clips = self
+            .clips
+            .values()
+            .filter(|clip| clip.semantic_kind() == Some(kind))
+            .collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(|clip| self.load(clip).into_owned()).collect()
+    }
 
-        mgr.import(&clips);
-        assert_eq!(mgr.len(), n);
+    /// Returns the clips whose size in bytes is within `min` and `max`, both
+    /// inclusive, sorted like [`Self::list`], a missing bound is not checked.
+    ///
+    /// The size of a compressed clip is the size of its original content.
+    pub fn list_by_size(&self, min: Option<u64>, max: Option<u64>) -> Vec<ClipEntry> {
+        let mut clips = self
+            .clips
+            .values()
+            .filter(|clip| {
+                let len = self.content_len(clip) as u64;
+                min.map_or(true, |min| len >= min) && max.map_or(true, |max| len <= max)
+            })
+            .collect::<Vec<_>>();
+        clips.sort_unstable_by(|a, b| sticky_first(a, b));
+        clips.into_iter().map(|clip| self.load(clip).into_owned()).collect()
+    }
+
+    /// Returns the clips matching `query` with their scores, newest first with
+    /// sticky notes first, a higher score is a better match.
+    ///
+    /// With `fuzzy`, text clips containing the characters of `query` in order
+    /// match. Otherwise clips containing all words of `query` match, words are
+    /// matched case-insensitively with the inverted index of text clips, and
+    /// clips are scored by the position of the first word of `query`.
+    pub fn search(
+        &self,
+        query: &str,
+        preview_length: usize,
+        fuzzy: bool,
+    ) -> Vec<(ClipEntryMetadata, f32)> {
+        let mut matches = if fuzzy {
+            self.clips
+                .values()
+                .filter_map(|clip| match &*self.load_content(clip) {
+                    ClipboardContent::Plaintext(text) => {
+                        score::fuzzy_score(query, text).map(|score| (clip, score))
+                    }
+                    ClipboardContent::Image { .. } => None,
+                })
+                .collect::<Vec<_>>()
+        } else {
+            self.text_index
+                .search(query)
+                .into_iter()
+                .filter_map(|id| self.clips.get(&id))
+                .map(|clip| match &*self.load_content(clip) {
+                    ClipboardContent::Plaintext(text) => (clip, score::position_score(query, text)),
+                    ClipboardContent::Image { .. } => (clip, 0.0),
+                })
+                .collect::<Vec<_>>()
+        };
+        matches.sort_unstable_by_key(|&(clip, _)| (Reverse(clip.is_sticky()), clip));
+        matches
+            .into_iter()
+            .map(|(clip, score)| (self.redactor.metadata(&self.load(clip), preview_length), score))
+            .collect()
+    }
+
+    /// Returns the first clip matching `query` in the order of
+    /// [`Self::search`], or `None` if no clip matches.
+    ///
+    /// Clips are checked in the order of [`Self::list`] and the scan stops at
+    /// the first match.
+    pub fn first_matching(&self, query: &str, fuzzy: bool) -> Option<ClipEntry> {
+        let clip = if fuzzy {
+            let mut clips = self.clips.values().collect::<Vec<_>>();
+            clips.sort_unstable_by(|a, b| sticky_first(a, b));
+            clips.into_iter().find(|clip| match &*self.load_content(clip) {
+                ClipboardContent::Plaintext(text) => score::fuzzy_score(query, text).is_some(),
+                ClipboardContent::Image { .. } => false,
+            })?
+        } else {
+            self.text_index
+                .search(query)
+                .into_iter()
+                .filter_map(|id| self.clips.get(&id))
+                .min_by(|a, b| sticky_first(a, b))?
+        };
+        Some(self.load(clip).into_owned())
+    }
+
+    /// Counts the clips copied in each `bucket` from `since` to `now`, oldest
+    /// bucket first, the buckets without clips are counted as 0.
+    ///
+    /// The history keeps one clip per content, so a clip is counted once in
+    /// the bucket of its latest copy. Snippets are not counted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bucket` is not positive or the count of buckets
+    /// exceeds the limit.
+    pub fn activity(
+        &self,
+        bucket: time::Duration,
+        since: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Vec<(OffsetDateTime, u64)>, Error> {
+        snafu::ensure!(bucket.is_positive(), error::InvalidBucketSnafu);
+        if since > now {
+            return Ok(Vec::new());
+        }
+        let bucket_index = |timestamp: OffsetDateTime| {
+            usize::try_from((timestamp - since).whole_nanoseconds() / bucket.whole_nanoseconds())
+                .unwrap_or(usize::MAX)
+        };
+        let count = bucket_index(now).saturating_add(1);
+        snafu::ensure!(
+            count <= MAX_ACTIVITY_BUCKETS,
+            error::TooManyBucketsSnafu { count, limit: MAX_ACTIVITY_BUCKETS }
+        );
+
+        let mut counts = vec![0; count];
+        for clip in self.clips.values() {
+            let timestamp = clip.timestamp();
+            if self.is_snippet(clip.id()) || timestamp < since || timestamp > now {
+                continue;
+            }
+            counts[bucket_index(timestamp)] += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .scan(since, |start, count| {
+                let bucket_start = *start;
+                *start += bucket;
+                Some((bucket_start, count))
+            })
+            .collect())
+    }
+
+    /// Returns the timestamps of the oldest and the newest clips, only the
+    /// clips of `kind` are counted if it is set, `None` if there are no such
+    /// clips. Snippets are not counted.
+    pub fn time_bounds(
+        &self,
+        kind: Option<ClipboardKind>,
+    ) -> Option<(OffsetDateTime, OffsetDateTime)> {
+        let mut timestamps = self
+            .timestamp_ids
+            .iter()
+            .filter(|(_, Reverse(id))| {
+                !self.is_snippet(*id)
+                    && self
+                        .clips
+                        .get(id)
+                        .is_some_and(|clip| kind.map_or(true, |kind| clip.kind() == kind))
+            })
+            .map(|&(timestamp, _)| timestamp);
+        let oldest = timestamps.next()?;
+        let newest = timestamps.next_back().unwrap_or(oldest);
+        Some((oldest, newest))
+    }
+
+    pub fn stats(&self) -> HistoryStats {
+        HistoryStats {
+            clip_count: self.clips.len(),
+            snippet_count: self.snippet_ids.len(),
+            capacity: self.capacity,
+            content_bytes: self
+                .clips
+                .values()
+                .map(|clip| {
+                    self.compressed
+                        .get(&clip.id())
+                        .map_or_else(|| clip.len(), CompressedContent::original_size)
+                })
+                .sum(),
+            compressed_clip_count: self.compressed.len(),
+            compression_saved_bytes: self
+                .compressed
+                .values()
+                .map(CompressedContent::saved_bytes)
+                .sum(),
+            index_term_count: self.text_index.term_count(),
+            index_memory_bytes: self.text_index.memory_usage(),
+        }
+    }
+
+    /// Returns the distinct mime types of the clips with the number of clips
+    /// of each, the most common first, snippets included.
+    pub fn mime_types(&self) -> Vec<(String, u64)> {
+        let mut counts = BTreeMap::<&str, u64>::new();
+        for clip in self.clips.values() {
+            *counts.entry(clip.mime_full()).or_default() += 1;
+        }
+        let mut mime_types =
+            counts.into_iter().map(|(mime, count)| (mime.to_string(), count)).collect::<Vec<_>>();
+        // the mime types of the same count stay sorted by name
+        mime_types.sort_by(|(_, a), (_, b)| b.cmp(a));
+        mime_types
+    }
+
+    /// Returns the id and the content hash of each clip ordered by id,
+    /// snippets included, see [`ClipEntry::content_hash`].
+    pub fn content_manifest(&self) -> Vec<(u64, String)> {
+        let mut manifest =
+            self.clips.values().map(|clip| (clip.id(), clip.content_hash())).collect::<Vec<_>>();
+        manifest.sort_unstable();
+        manifest
+    }
+
+    // the ratio is an estimate, the precision lost converting sizes does not
+    // matter
+    #[allow(clippy::cast_precision_loss)]
+    pub fn resource_usage(&self) -> ResourceUsage {
+        let (original_bytes, held_bytes) =
+            self.clips.values().fold((0, 0), |(original, held), clip| {
+                self.compressed.get(&clip.id()).map_or_else(
+                    || (original + clip.len(), held + clip.len()),
+                    |compressed| {
+                        (original + compressed.original_size(), held + compressed.compressed_size())
+                    },
+                )
+            });
+        ResourceUsage {
+            entry_count: self.clips.len(),
+            store_bytes: held_bytes + self.text_index.memory_usage(),
+            compression_ratio: self.compression_threshold.map(|_| {
+                if held_bytes == 0 {
+                    1.0
+                } else {
+                    original_bytes as f64 / held_bytes as f64
+                }
+            }),
+        }
+    }
+
+    /// Returns the 0-based position of the clip in the history sorted like
+    /// [`Self::list`], or `None` if the clip does not exist.
+    pub fn rank(&self, id: u64) -> Option<usize> {
+        let clip = self.clips.get(&id)?;
+        Some(
+            self.clips
+                .values()
+                .filter(|other| sticky_first(other, clip) == std::cmp::Ordering::Less)
+                .count(),
+        )
+    }
+
+    /// Returns the clip next to the clip in the history sorted like
+    /// [`Self::list`], towards older clips for [`CycleDirection::Next`], or
+    /// `None` at the ends of history or if the clip does not exist.
+    pub fn neighbor(&self, id: u64, direction: CycleDirection) -> Option<ClipEntry> {
+        let clip = self.clips.get(&id)?;
+        let others = self.clips.values();
+        let neighbor = match direction {
+            CycleDirection::Next => others
+                .filter(|other| sticky_first(other, clip) == std::cmp::Ordering::Greater)
+                .min_by(|a, b| sticky_first(a, b)),
+            CycleDirection::Previous => others
+                .filter(|other| sticky_first(other, clip) == std::cmp::Ordering::Less)
+                .max_by(|a, b| sticky_first(a, b)),
+        }?;
+        Some(self.load(neighbor).into_owned())
+    }
+
+    /// Returns the `n`-th newest clip of `kind`, `n` is 1-based.
+    pub fn nth_recent(&self, n: usize, kind: ClipboardKind) -> Option<ClipEntry> {
+        let index = n.checked_sub(1)?;
+        let mut clips = self.clips.values().filter(|clip| clip.kind() == kind).collect::<Vec<_>>();
+        if index >= clips.len() {
+            return None;
+        }
+        let (_, clip, _) = clips.select_nth_unstable(index);
+        Some(self.load(clip).into_owned())
+    }
+
+    /// Returns the image of the clip encoded in `format`, or `None` if the
+    /// clip does not exist.
+    ///
+    /// The recently converted images are cached until the clips are changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clip is not an image or it can not be encoded.
+    pub fn image_as(&mut self, id: u64, format: ImageFormat) -> Result<Option<Vec<u8>>, Error> {
+        let Some(clip) = self.clips.get(&id) else {
+            return Ok(None);
+        };
+        snafu::ensure!(
+            matches!(clip.as_ref(), ClipboardContent::Image { .. }),
+            error::NotAnImageSnafu { id }
+        );
+        let clip = self.load(clip).into_owned();
+        self.image_cache
+            .get_or_try_insert(id, clip.version(), format, || {
+                clip.encoded_as(format).context(error::EncodeImageSnafu { id })
+            })
+            .map(|data| Some(data.to_vec()))
+    }
+
+    #[inline]
+    pub fn get(&self, id: u64) -> Option<ClipEntry> {
+        self.clips.get(&id).map(|clip| self.load(clip).into_owned())
+    }
+
+    /// Returns the clip with its metadata, or `None` if there is no clip with
+    /// `id`.
+    pub fn get_full(&self, id: u64, preview_length: usize) -> Option<ClipEntryFull> {
+        let clip = self.clips.get(&id)?;
+        let entry = self.load(clip).into_owned();
+        Some(ClipEntryFull {
+            metadata: self.redactor.metadata(&entry, preview_length),
+            byte_len: entry.original_size().unwrap_or_else(|| self.content_len(clip) as u64),
+            snippet: self.snippet_ids.contains(&id),
+            entry,
+        })
+    }
+
+    /// Returns the id of the newest clip whose content is the content of the
+    /// clipboard of `kind`, which may be set outside of clipcat, or `None` if
+    /// the clipboard is empty or no clip matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `kind` is not enabled or the clipboard could not be
+    /// read.
+    pub async fn live_clip_id(&self, kind: ClipboardKind) -> Result<Option<u64>, Error> {
+        let Some(clip) = self.load_live(kind).await? else {
+            return Ok(None);
+        };
+        Ok(self.ids_with_digest(clip.sha256_digest()).first().copied())
+    }
+
+    /// Returns whether the content of the clip is the content of the
+    /// clipboard of `kind`, comparing their content hashes, so marking the
+    /// clip would not change the clipboard. Returns `false` if the clip does
+    /// not exist or the clipboard is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `kind` is not enabled or the clipboard could not be
+    /// read.
+    pub async fn is_current(&self, id: u64, kind: ClipboardKind) -> Result<bool, Error> {
+        snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        let Some(clip) = self.clips.get(&id) else {
+            return Ok(false);
+        };
+        Ok(self
+            .load_live(kind)
+            .await?
+            .is_some_and(|live| live.sha256_digest() == clip.sha256_digest()))
+    }
+
+    // return the content of the clipboard of `kind` as a clip, `None` if the
+    // clipboard is empty
+    async fn load_live(&self, kind: ClipboardKind) -> Result<Option<ClipEntry>, Error> {
+        snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        match self.backend.load(kind, None).await {
+            Ok(content) => Ok(Some(ClipEntry::from_clipboard_content(content, kind, None))),
+            Err(backend::Error::EmptyClipboard) => Ok(None),
+            Err(source) => Err(Error::LoadClipboardContent { source }),
+        }
+    }
+
+    /// Returns the targets offered by the owner of the selection of `kind`,
+    /// whether the selection is captured or not, empty if the backend does not
+    /// know them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `kind` is not enabled.
+    pub fn current_targets(&self, kind: ClipboardKind) -> Result<Vec<String>, Error> {
+        snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        Ok(self.backend.targets(kind).unwrap_or_default())
+    }
+
+    /// Returns every representation of the content of the clipboard of `kind`
+    /// which the backend can read, like plain text and PNG, whether the
+    /// selection is captured or not, empty if the clipboard is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `kind` is not enabled, the clipboard could not be
+    /// read or an image could not be encoded.
+    pub async fn current_formats(
+        &self,
+        kind: ClipboardKind,
+    ) -> Result<Vec<(mime::Mime, Vec<u8>)>, Error> {
+        snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        let mut formats = Vec::new();
+        for mime in self.backend.supported_mimes() {
+            let content = match self.backend.load(kind, Some(mime.clone())).await {
+                Ok(content) => content,
+                Err(backend::Error::EmptyClipboard) => continue,
+                Err(source) => return Err(Error::LoadClipboardContent { source }),
+            };
+            let clip = ClipEntry::from_clipboard_content(content, kind, None);
+            let data = clip.encoded().context(error::EncodeImageSnafu { id: clip.id() })?;
+            formats.push((mime, data));
+        }
+        Ok(formats)
+    }
+
+    #[inline]
+    pub fn get_current_clip(&self, kind: ClipboardKind) -> Option<ClipEntry> {
+        self.current_clips[usize::from(kind)].and_then(|id| self.get(id))
+    }
+
+    /// Returns the current clip of `kind` and clears the clipboard of `kind`,
+    /// the clip is kept in history. Returns `None` if there is no current
+    /// clip, the clipboard is not cleared then.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server is read-only, `kind` is not enabled or
+    /// the clipboard could not be cleared.
+    pub async fn take_current_clip(
+        &mut self,
+        kind: ClipboardKind,
+    ) -> Result<Option<ClipEntry>, Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        let Some(clip) = self.get_current_clip(kind) else {
+            return Ok(None);
+        };
+        self.backend.clear(kind).await.context(error::StoreClipboardContentSnafu)?;
+        self.current_clips[usize::from(kind)] = None;
+        self.notify_current_clips();
+        Ok(Some(clip))
+    }
+
+    /// Returns the current clip of the first of `kinds` which has one.
+    #[inline]
+    pub fn get_current_clip_any(&self, kinds: &[ClipboardKind]) -> Option<ClipEntry> {
+        kinds.iter().find_map(|&kind| self.get_current_clip(kind))
+    }
+
+    /// Returns the id of the current clip of `kind` without decompressing it.
+    #[inline]
+    pub fn current_clip_id(&self, kind: ClipboardKind) -> Option<u64> {
+        self.current_clips[usize::from(kind)].filter(|id| self.clips.contains_key(id))
+    }
+
+    #[inline]
+    pub fn insert(&mut self, data: ClipEntry) -> u64 { self.insert_with_evicted(data).0 }
+
+    /// Inserts a clip, returns its id and the ids of the clips removed by the
+    /// insertion.
+    ///
+    /// Clips are removed when the history exceeds its capacity, or when the
+    /// text of the current clip is extended in a short time, see
+    /// [`ClipboardManager::set_coalesce_growing_edits`].
+    pub fn insert_with_evicted(&mut self, data: ClipEntry) -> (u64, Vec<u64>) {
+        let data = self.content_transformer.transform(data);
+        self.insert_inner(data)
+    }
+
+    /// Runs `f` without sending the changes it makes to subscribers, for bulk
+    /// imports.
+    ///
+    /// Subscribers miss the changes, see [`ClipboardManager::notify_resync`].
+    pub fn silently<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.journal.set_muted(true);
+        let result = f(self);
+        self.journal.set_muted(false);
+        result
+    }
+
+    /// Inserts a clip like [`ClipboardManager::insert_with_evicted`] as a new
+    /// clip even if a clip with the same content exists, returns the id of
+    /// the new clip.
+    ///
+    /// The clip gets the id of its content if no clip has it, so capturing the
+    /// clip again after it is marked promotes it instead of adding another
+    /// one.
+    pub fn insert_apart(&mut self, data: ClipEntry) -> (u64, Vec<u64>) {
+        let mut entry = self.content_transformer.transform(data);
+        let mut id = self.assign_id(&entry);
+        while self.clips.contains_key(&id) {
+            id = mix_id(id, self.journal.next_cursor());
+        }
+        entry.set_id(id);
+        self.insert_as(entry)
+    }
+
+    /// Tells subscribers to list the history again after changes which are not
+    /// sent to them.
+    pub fn notify_resync(&mut self) { self.journal.record(ClipboardChange::Resync); }
+
+    /// Inserts a clip copied from another history under `id`, so the clip has
+    /// the same id in both histories.
+    ///
+    /// The id need not be derived from the content. Content transforms are
+    /// not applied, the clip keeps the content it has in the other history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a clip with `id` already exists.
+    pub fn insert_with_id(
+        &mut self,
+        id: u64,
+        mut data: ClipEntry,
+    ) -> Result<(u64, Vec<u64>), Error> {
+        snafu::ensure!(!self.clips.contains_key(&id), error::IdTakenSnafu { id });
+        data.set_id(id);
+        Ok(self.insert_as(data))
+    }
+
+    /// Returns the id of the clip `entry` is merged into, or a new id if it is
+    /// kept apart from the clips with the same content.
+    fn assign_id(&self, entry: &ClipEntry) -> u64 {
+        let content_id = self.dedupe_key(entry.as_ref());
+        // the current clip is captured again after it is marked
+        if let Some(id) = self.current_clips[usize::from(entry.kind())] {
+            if self.clips.get(&id).is_some_and(|clip| self.clip_dedupe_key(clip) == content_id) {
+                return id;
+            }
+        }
+
+        let id = match self.dedupe_scope {
+            DedupeScope::Global | DedupeScope::None => content_id,
+            DedupeScope::PerMode => mix_id(content_id, i32::from(entry.kind())),
+        };
+        match self.clips.get(&id) {
+            // sticky notes are never merged with other clips
+            Some(clip) if clip.is_sticky() => mix_id(id, self.journal.next_cursor()),
+            Some(_) if self.dedupe_scope == DedupeScope::Global => id,
+            Some(clip)
+                if self.dedupe_scope == DedupeScope::PerMode
+                    && self.clip_dedupe_key(clip) == content_id =>
+            {
+                id
+            }
+            Some(_) => mix_id(id, self.journal.next_cursor()),
+            None => id,
+        }
+    }
+
+    fn insert_inner(&mut self, mut entry: ClipEntry) -> (u64, Vec<u64>) {
+        entry.set_id(self.assign_id(&entry));
+        self.insert_as(entry)
+    }
+
+    /// Inserts a clip under the id it is given, promoting the clip with the
+    /// same id.
+    fn insert_as(&mut self, mut entry: ClipEntry) -> (u64, Vec<u64>) {
+        let mut evicted = Vec::new();
+        if let Some(existing) = self.clips.get(&entry.id()) {
+            entry.set_created_at(existing.created_at().min(entry.created_at()));
+            // a clip is captured again after it is marked, keep its metadata
+            let mut tags = existing.tags().clone();
+            tags.extend(entry.tags().iter().cloned());
+            entry.set_tags(tags);
+            // the tags removed or confirmed by users are not set again
+            let mut auto_tags = existing.auto_tags().clone();
+            auto_tags.extend(entry.auto_tags().iter().cloned());
+            entry.set_auto_tags(auto_tags);
+            let mut attributes = existing.attributes().clone();
+            attributes.extend(entry.attributes().clone());
+            entry.set_attributes(attributes);
+            if entry.label().is_none() {
+                entry.set_label(existing.label().map(ToString::to_string));
+            }
+            // clipcat offers its own targets when the clip is marked
+            if entry.owned_by_us() || entry.available_targets().is_empty() {
+                entry.set_available_targets(existing.available_targets().to_vec());
+            }
+            if entry.expires_at().is_none() {
+                entry.set_expires_at(existing.expires_at());
+            }
+            if entry.protected_until().is_none() {
+                entry.set_protected_until(existing.protected_until());
+            }
+            entry.set_use_count(existing.use_count().max(entry.use_count()));
+            if entry.owned_by_us() || entry.source_window().is_none() {
+                entry.set_source_window(existing.source_window().cloned());
+            }
+            // capturing a private clip again must not persist it
+            entry.set_private(entry.is_private() || existing.is_private());
+            // a sticky note is captured again after it is marked
+            entry.set_sticky(existing.is_sticky());
+            // the kind may be set by users
+            entry.set_semantic_kind(existing.semantic_kind().or_else(|| entry.semantic_kind()));
+        } else {
+            self.auto_tagger.tag(&mut entry);
+        }
+        if entry.semantic_kind().is_none() {
+            entry.set_semantic_kind(self.classifier.classify(&entry));
+        }
+        // emit notification
+        match entry.as_ref() {
+            ClipboardContent::Image { width, height, bytes } => {
+                self.notification.on_image_fetched(bytes.len(), *width, *height);
+            }
+            ClipboardContent::Plaintext(text) => {
+                self.notification.on_plaintext_fetched(text.chars().count());
+
+                if let Some(id) = self.current_clips[usize::from(entry.kind())]
+                    .filter(|_| self.coalesce_growing_edits)
+                {
+                    let is_extended = self.clips.get(&id).is_some_and(|current_clip| {
+                        !current_clip.is_sticky()
+                            && entry.timestamp() - current_clip.timestamp() < self.primary_threshold
+                            && match &*self.load_content(current_clip) {
+                                ClipboardContent::Plaintext(current_text) => {
+                                    text.starts_with(current_text.as_str())
+                                        || current_text.starts_with(text.as_str())
+                                }
+                                ClipboardContent::Image { .. } => false,
+                            }
+                    });
+                    // the clip is replaced below if the text is the same
+                    if is_extended && id != entry.id() {
+                        drop(self.detach(id));
+                        evicted.push(id);
+                    }
+                }
+            }
+        }
+
+        entry.set_version(self.journal.next_cursor());
+        let (id, timestamp) = (entry.id(), entry.timestamp());
+        self.current_clips[usize::from(entry.kind())] = Some(id);
+        self.transient_ids[usize::from(entry.kind())] = None;
+        // clips are keyed by content, an identical clip anywhere in history is
+        // promoted instead of duplicated, drop its stale timestamp so
+        // it is not evicted as the oldest
+        self.index_text(&entry);
+        let change = if let Some(existing) = self.store(entry.clone()) {
+            let _ = self.timestamp_ids.remove(&(existing.timestamp(), Reverse(id)));
+            ClipboardChange::Updated(entry)
+        } else {
+            ClipboardChange::Added(entry)
+        };
+        self.journal.record(change);
+        let _unused = self.timestamp_ids.insert((timestamp, Reverse(id)));
+        evicted.extend(self.remove_oldest());
+        self.notify_current_clips();
+        (id, evicted)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize { self.clips.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.clips.is_empty() }
+
+    /// Removes the oldest clips exceeding the capacity, returns their ids.
+    ///
+    /// Protected clips and sticky notes are kept, the history exceeds its
+    /// capacity by them.
+    fn remove_oldest(&mut self) -> Vec<u64> {
+        let mut removed = Vec::new();
+        if self.is_empty() {
+            return removed;
+        }
+
+        let snippet_count = self.snippet_ids.len();
+        let now = OffsetDateTime::now_utc();
+        let mut protected = Vec::new();
+
+        while self.clips.len() > self.capacity + snippet_count + protected.len() {
+            let Some((timestamp, Reverse(id))) = self.timestamp_ids.pop_first() else {
+                break;
+            };
+            if self.snippet_ids.contains(&id) {
+                tracing::trace!("Retain snippet clip and update its timestamp (id: {id})");
+                let _ = self.timestamp_ids.insert((now, Reverse(id)));
+                let _ = self.clips.get_mut(&id).map(|entry| entry.set_timestamp(now));
+            } else if self
+                .clips
+                .get(&id)
+                .is_some_and(|clip| clip.is_protected(now) || clip.is_sticky())
+            {
+                tracing::trace!("Retain protected clip (id: {id}, timestamp: {timestamp})");
+                protected.push((timestamp, Reverse(id)));
+            } else {
+                tracing::trace!("Remove old clip (id: {id}, timestamp: {timestamp})");
+                drop(self.detach_as(id, Some(EvictionReason::Capacity)));
+                removed.push(id);
+            }
+        }
+        self.timestamp_ids.extend(protected);
+        removed
+    }
+
+    /// Checks that a clip can be inserted without exceeding the capacity.
+    ///
+    /// The history is full only if all of its clips besides the snippets are
+    /// protected or sticky notes, the oldest clip is removed to make room
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history is full.
+    pub fn check_capacity(&self) -> Result<(), Error> {
+        let count = self.clips.len().saturating_sub(self.snippet_ids.len());
+        let now = OffsetDateTime::now_utc();
+        let full = count >= self.capacity
+            && self.clips.values().all(|clip| {
+                self.snippet_ids.contains(&clip.id()) || clip.is_protected(now) || clip.is_sticky()
+            });
+        snafu::ensure!(!full, error::HistoryFullSnafu { count, capacity: self.capacity });
+        Ok(())
+    }
+
+    /// Removes the clips which expire at or before `now`, returns their ids.
+    pub fn remove_expired(&mut self, now: OffsetDateTime) -> Vec<u64> {
+        let mut expired_ids = self
+            .clips
+            .values()
+            .filter(|clip| clip.expires_at().is_some_and(|expires_at| expires_at <= now))
+            .map(ClipEntry::id)
+            .collect::<Vec<_>>();
+        expired_ids.retain(|&id| self.remove_inner(id, Some(EvictionReason::Ttl)).is_some());
+        expired_ids
+    }
+
+    /// Removes the clips last updated before `cutoff`, returns their ids.
+    /// Snippets, protected clips and sticky notes are kept.
+    pub fn remove_older_than(&mut self, cutoff: OffsetDateTime) -> Vec<u64> {
+        let now = OffsetDateTime::now_utc();
+        let mut old_ids = self
+            .timestamp_ids
+            .range(..(cutoff, Reverse(u64::MAX)))
+            .map(|&(_, Reverse(id))| id)
+            .filter(|id| !self.snippet_ids.contains(id))
+            .filter(|id| {
+                self.clips.get(id).is_some_and(|clip| !clip.is_protected(now) && !clip.is_sticky())
+            })
+            .collect::<Vec<_>>();
+        old_ids.retain(|&id| self.remove_inner(id, Some(EvictionReason::Age)).is_some());
+        old_ids
+    }
+
+    /// Removes the clips beyond the newest `max_count` ones, only the clips of
+    /// `kind` are counted if it is set, returns the ids of the removed clips.
+    ///
+    /// Snippets and sticky notes are neither counted nor removed.
+    pub fn trim_to(&mut self, max_count: usize, kind: Option<ClipboardKind>) -> Vec<u64> {
+        let mut ids = self.trim_candidates(max_count, kind);
+        ids.retain(|&id| self.remove(id));
+        ids
+    }
+
+    /// Returns the ids of the clips [`Self::trim_to`] would remove, newest
+    /// first, without removing them.
+    pub fn trim_candidates(&self, max_count: usize, kind: Option<ClipboardKind>) -> Vec<u64> {
+        let mut clips = self
+            .clips
+            .values()
+            .filter(|clip| !self.is_snippet(clip.id()) && !clip.is_sticky())
+            .filter(|clip| kind.map_or(true, |kind| clip.kind() == kind))
+            .collect::<Vec<_>>();
+        clips.sort_unstable();
+        clips.into_iter().skip(max_count).map(ClipEntry::id).collect()
+    }
+
+    pub fn remove_snippet(&mut self, id: u64) -> bool {
+        if self.snippet_ids.remove(&id) {
+            self.detach(id).is_some()
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, id: u64) -> bool { self.remove_inner(id, None).is_some() }
+
+    /// Removes the clips with the same content as `data` and returns their ids.
+    ///
+    /// Clips are matched by id like deduplication, `data` matches both a clip
+    /// stored as is and a clip stored after the content transforms.
+    pub fn remove_by_content(&mut self, data: ClipEntry) -> Vec<u64> {
+        let mut ids = self.content_matches(data);
+        ids.retain(|&id| self.remove(id));
+        ids
+    }
+
+    /// Returns the ids of the clips [`Self::remove_by_content`] would remove,
+    /// without removing them.
+    pub fn content_matches(&self, data: ClipEntry) -> Vec<u64> {
+        let raw_id = data.as_ref().id();
+        let transformed_id = self.content_transformer.transform(data).as_ref().id();
+        // clips with the same content may be kept apart, see `DedupeScope`
+        self.clips
+            .values()
+            .filter(|clip| {
+                let content_id = self.content_id(clip);
+                content_id == raw_id || content_id == transformed_id
+            })
+            .map(ClipEntry::id)
+            .filter(|id| !self.snippet_ids.contains(id))
+            .collect()
+    }
+
+    /// Returns the id of the newest clip matching the content of `data` like
+    /// [`Self::content_matches`] with `false`, otherwise inserts `data` and
+    /// returns its id with `true`.
+    pub fn get_or_insert(&mut self, data: ClipEntry) -> (u64, bool) {
+        let existing = self
+            .content_matches(data.clone())
+            .into_iter()
+            .filter_map(|id| self.clips.get(&id))
+            .min()
+            .map(ClipEntry::id);
+        existing.map_or_else(|| (self.insert(data), true), |id| (id, false))
+    }
+
+    /// Inserts a copy of the content of the clip with `id` as the newest clip,
+    /// returns the id of the copy or `None` if there is no clip with `id`.
+    ///
+    /// The copy is kept apart from the original regardless of the dedupe
+    /// scope and it is not stored into the clipboard.
+    pub fn duplicate(&mut self, id: u64) -> Option<u64> {
+        let clip = self.get(id)?;
+        let mut copy = ClipEntry::from_clipboard_content(clip.as_ref().clone(), clip.kind(), None);
+        copy.set_text_mime(clip.mime());
+        copy.set_original_size(clip.original_size());
+        copy.set_private(clip.is_private());
+        copy.set_id(mix_id(clip.id(), self.journal.next_cursor()));
+        copy.set_version(self.journal.next_cursor());
+
+        let (copy_id, timestamp) = (copy.id(), copy.timestamp());
+        self.index_text(&copy);
+        drop(self.store(copy.clone()));
+        self.journal.record(ClipboardChange::Added(copy));
+        let _unused = self.timestamp_ids.insert((timestamp, Reverse(copy_id)));
+        drop(self.remove_oldest());
+        Some(copy_id)
+    }
+
+    // the removal is recorded as an eviction if `reason` is set
+    #[inline]
+    fn remove_inner(&mut self, id: u64, reason: Option<EvictionReason>) -> Option<ClipEntry> {
+        if let Some(id) = self.snippet_ids.get(&id) {
+            return self.clips.get(id).cloned();
+        }
+
+        for kind in ClipboardKind::all_kinds().map(usize::from) {
+            if Some(id) == self.current_clips[kind] {
+                self.current_clips[kind] = None;
+            }
+        }
+
+        self.detach_as(id, reason)
+    }
+
+    // remove the clip from the history and the indices, and record the change,
+    // the content of the returned clip may be a placeholder
+    #[inline]
+    fn detach(&mut self, id: u64) -> Option<ClipEntry> { self.detach_as(id, None) }
+
+    // like `detach`, the removal is recorded as an eviction if `reason` is set
+    fn detach_as(&mut self, id: u64, reason: Option<EvictionReason>) -> Option<ClipEntry> {
+        let clip = self.clips.remove(&id)?;
+        drop(self.compressed.remove(&id));
+        let _ = self.timestamp_ids.remove(&(clip.timestamp(), Reverse(id)));
+        self.text_index.remove(id);
+        self.journal.record(reason.map_or(ClipboardChange::Removed(id), |reason| {
+            ClipboardChange::Evicted { id, reason }
+        }));
+        self.notify_current_clips();
+        Some(clip)
+    }
+
+    // store the clip into the history, the content of a large clip is
+    // compressed and replaced with a placeholder
+    fn store(&mut self, mut clip: ClipEntry) -> Option<ClipEntry> {
+        let id = clip.id();
+        drop(self.compressed.remove(&id));
+        if self.compression_threshold.is_some_and(|threshold| clip.len() > threshold) {
+            if let Some(compressed) = CompressedContent::compress(clip.as_ref()) {
+                drop(clip.swap_content(compressed.placeholder()));
+                let _unused = self.compressed.insert(id, compressed);
+            }
+        }
+        self.clips.insert(id, clip)
+    }
+
+    // return the clip with its original content
+    fn load<'a>(&self, clip: &'a ClipEntry) -> Cow<'a, ClipEntry> {
+        match self.load_content(clip) {
+            Cow::Owned(content) => {
+                let mut clip = clip.clone();
+                drop(clip.swap_content(content));
+                Cow::Owned(clip)
+            }
+            Cow::Borrowed(_) => Cow::Borrowed(clip),
+        }
+    }
+
+    // return the clip with its original content, or `None` if its compressed
+    // content is corrupted
+    fn try_load<'a>(&self, clip: &'a ClipEntry) -> Option<Cow<'a, ClipEntry>> {
+        match self.compressed.get(&clip.id()) {
+            Some(compressed) => {
+                let content = compressed.decompress()?;
+                let mut clip = clip.clone();
+                drop(clip.swap_content(content));
+                Some(Cow::Owned(clip))
+            }
+            None => Some(Cow::Borrowed(clip)),
+        }
+    }
+
+    // return the original content of the clip
+    fn load_content<'a>(&self, clip: &'a ClipEntry) -> Cow<'a, ClipboardContent> {
+        self.compressed
+            .get(&clip.id())
+            .and_then(CompressedContent::decompress)
+            .map_or_else(|| Cow::Borrowed(clip.as_ref()), Cow::Owned)
+    }
+
+    // return the id of the original content of the clip
+    fn content_id(&self, clip: &ClipEntry) -> u64 {
+        self.compressed
+            .get(&clip.id())
+            .map_or_else(|| clip.as_ref().id(), CompressedContent::content_id)
+    }
+
+    // return the size in bytes of the original content of the clip
+    fn content_len(&self, clip: &ClipEntry) -> usize {
+        self.compressed.get(&clip.id()).map_or_else(|| clip.len(), CompressedContent::original_size)
+    }
+
+    // clips with the same key are merged, it is the id of the content unless
+    // whitespaces are normalized
+    fn dedupe_key(&self, content: &ClipboardContent) -> u64 {
+        match content {
+            ClipboardContent::Plaintext(text) if self.normalize_whitespace_for_dedupe => {
+                let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                ClipboardContent::Plaintext(normalized).id()
+            }
+            _ => content.id(),
+        }
+    }
+
+    fn clip_dedupe_key(&self, clip: &ClipEntry) -> u64 {
+        if self.normalize_whitespace_for_dedupe {
+            self.dedupe_key(&self.load_content(clip))
+        } else {
+            self.content_id(clip)
+        }
+    }
+
+    fn index_text(&mut self, clip: &ClipEntry) {
+        if let ClipboardContent::Plaintext(text) = clip.as_ref() {
+            self.text_index.insert(clip.id(), text);
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.current_clips = [None; ClipboardKind::MAX_LENGTH];
+        let removed_ids = self
+            .clips
+            .keys()
+            .filter(|id| !self.snippet_ids.contains(id))
+            .copied()
+            .collect::<Vec<_>>();
+        for id in removed_ids {
+            drop(self.detach(id));
+        }
+        self.image_cache.clear();
+        self.notification.on_history_cleared();
+    }
+
+    /// Replaces the history with `clips` at once, their ids, timestamps and
+    /// the other fields are kept as they are. Snippets are kept, clips with
+    /// the id of a snippet are ignored. Returns the number of stored clips.
+    ///
+    /// It is meant for tests putting the server into a known state, so it
+    /// fails unless testing is enabled with
+    /// [`ClipboardManager::set_testing`].
+    pub fn set_history(&mut self, clips: Vec<ClipEntry>) -> Result<usize, Error> {
+        snafu::ensure!(self.testing, error::TestingDisabledSnafu);
+        self.clear();
+        for mut clip in clips {
+            if self.snippet_ids.contains(&clip.id()) {
+                continue;
+            }
+            clip.set_version(self.journal.next_cursor());
+            let (id, timestamp) = (clip.id(), clip.timestamp());
+            if let Some(existing) = self.store(clip.clone()) {
+                let _ = self.timestamp_ids.remove(&(existing.timestamp(), Reverse(id)));
+            }
+            let _ = self.timestamp_ids.insert((timestamp, Reverse(id)));
+            self.index_text(&clip);
+            self.journal.record(ClipboardChange::Added(clip));
+        }
+        drop(self.remove_oldest());
+        Ok(self.clips.len() - self.snippet_ids.len())
+    }
+
+    /// Clears the history like [`ClipboardManager::clear`], returns the
+    /// removed clips sorted like [`Self::list`].
+    ///
+    /// Snippets are removed too unless `keep_snippets`, sticky notes are kept
+    /// if `keep_sticky`.
+    pub fn drain(&mut self, keep_snippets: bool, keep_sticky: bool) -> Vec<ClipEntry> {
+        let mut clips = self.export(!keep_snippets);
+        clips.retain(|clip| !(keep_sticky && clip.is_sticky()));
+        clips.sort_unstable_by(sticky_first);
+        let ids = clips.iter().map(ClipEntry::id).collect::<HashSet<_>>();
+        for current in &mut self.current_clips {
+            if current.is_some_and(|id| ids.contains(&id)) {
+                *current = None;
+            }
+        }
+        for &id in &ids {
+            let _ = self.snippet_ids.remove(&id);
+            drop(self.detach(id));
+        }
+        self.image_cache.clear();
+        self.notification.on_history_cleared();
+        clips
+    }
+
+    /// Replaces the content of the clip, the clip is inserted again with the
+    /// new content and keeps its metadata, returns whether it is replaced and
+    /// its new id.
+    ///
+    /// A sticky note is edited in place, it keeps its id and its place in
+    /// history, only text can be stored in it.
+    pub fn replace(&mut self, old_id: u64, data: &[u8], mime: &mime::Mime) -> (bool, u64) {
+        if self.clips.get(&old_id).is_some_and(ClipEntry::is_sticky) {
+            return (self.edit_sticky_note(old_id, data, mime), old_id);
+        }
+        let old_clip = self.remove_inner(old_id, None);
+        let kind = old_clip.as_ref().map_or(ClipboardKind::Primary, ClipEntry::kind);
+        ClipEntry::new(data, mime, kind, None).map_or((false, old_id), |mut entry| {
+            if let Some(old_clip) = old_clip {
+                entry.set_created_at(old_clip.created_at());
+                entry.set_tags(old_clip.tags().clone());
+                entry.set_attributes(old_clip.attributes().clone());
+                entry.set_label(old_clip.label().map(ToString::to_string));
+                entry.set_expires_at(old_clip.expires_at());
+                entry.set_protected_until(old_clip.protected_until());
+                entry.set_use_count(old_clip.use_count());
+                entry.set_source_window(old_clip.source_window().cloned());
+                entry.set_private(old_clip.is_private());
+            }
+            let (new_id, _evicted) = self.insert_inner(entry);
+            (true, new_id)
+        })
+    }
+
+    // replace the text of the sticky note without inserting it again
+    fn edit_sticky_note(&mut self, id: u64, data: &[u8], mime: &mime::Mime) -> bool {
+        if mime.type_() != mime::TEXT {
+            return false;
+        }
+        let Some(clip) = self.clips.get(&id) else {
+            return false;
+        };
+        let mut clip = clip.with_text(String::from_utf8_lossy(data).to_string());
+        clip.set_text_mime(mime.clone());
+        clip.set_original_size(None);
+        clip.set_version(self.journal.next_cursor());
+        self.text_index.remove(id);
+        self.index_text(&clip);
+        drop(self.store(clip.clone()));
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
+
+    /// Replaces the clip like [`ClipboardManager::replace`] if its version is
+    /// `expected_version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clip has another version or does not exist.
+    pub fn replace_if(
+        &mut self,
+        old_id: u64,
+        expected_version: u64,
+        data: &[u8],
+        mime: &mime::Mime,
+    ) -> Result<(bool, u64), Error> {
+        let version = self.clips.get(&old_id).map(ClipEntry::version);
+        snafu::ensure!(
+            version == Some(expected_version),
+            error::VersionConflictSnafu { id: old_id, expected_version, version }
+        );
+        Ok(self.replace(old_id, data, mime))
+    }
+
+    /// Appends `data` to the text of the clip, after `separator` if any, and
+    /// updates the clip like [`ClipboardManager::replace`].
+    ///
+    /// Returns `(false, id)` if the clip does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clip is not a text clip.
+    pub fn append(
+        &mut self,
+        id: u64,
+        data: &[u8],
+        separator: Option<&[u8]>,
+    ) -> Result<(bool, u64), Error> {
+        let Some(clip) = self.clips.get(&id) else {
+            return Ok((false, id));
+        };
+        let clip = self.load(clip).into_owned();
+        let ClipboardContent::Plaintext(text) = clip.as_ref() else {
+            return error::NotTextSnafu { id }.fail();
+        };
+        let data = [text.as_bytes(), separator.unwrap_or_default(), data].concat();
+        Ok(self.replace(id, &data, &clip.mime()))
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if `clipboard_kind` is not enabled or the content
+    /// could not be stored into the clipboard.
+    pub async fn mark(&mut self, id: u64, clipboard_kind: ClipboardKind) -> Result<(), Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(
+            self.is_kind_enabled(clipboard_kind),
+            error::KindDisabledSnafu { kind: clipboard_kind }
+        );
+        if let Some(clip) = self.clips.get(&id) {
+            self.ensure_supported_mime(id, &clip.mime(), clip.as_ref())?;
+        }
+        if let Some(clip) = self.clips.get_mut(&id) {
+            let timestamp = clip.timestamp();
+            clip.mark(clipboard_kind);
+            clip.increment_use_count();
+            clip.set_version(self.journal.next_cursor());
+            let clip = clip.clone();
+            let clip = self.load(&clip).into_owned();
+            self.move_timestamp(id, timestamp, clip.timestamp());
+            self.journal.record(ClipboardChange::Updated(clip.clone()));
+            // the clip is not inserted again when the watcher captures it
+            self.current_clips[usize::from(clipboard_kind)] = Some(id);
+            self.notify_current_clips();
+            self.backend
+                .store(clipboard_kind, clip.as_ref().clone())
+                .await
+                .context(error::StoreClipboardContentSnafu)?;
+        }
+
+        Ok(())
+    }
+
+    /// Marks the clip as the current clip of each of `clipboard_kinds`, all or
+    /// none of the clipboards are changed. Returns `false` if the clip does not
+    /// exist.
+    ///
+    /// If storing into a clipboard fails, the content of the clipboards
+    /// stored before it is restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of `clipboard_kinds` is not enabled or the
+    /// content could not be stored into one of the clipboards, which is named
+    /// by the error.
+    pub async fn mark_kinds(
+        &mut self,
+        id: u64,
+        clipboard_kinds: &[ClipboardKind],
+    ) -> Result<bool, Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        for &kind in clipboard_kinds {
+            snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        }
+        let Some(clip) = self.get(id) else {
+            return Ok(false);
+        };
+        self.ensure_supported_mime(id, &clip.mime(), clip.as_ref())?;
+
+        let mut kinds = clipboard_kinds.to_vec();
+        kinds.sort_unstable();
+        kinds.dedup();
+        let mut previous_contents = Vec::with_capacity(kinds.len());
+        for &kind in &kinds {
+            let previous = self.backend.load(kind, None).await.ok();
+            if let Err(source) = self.backend.store(kind, clip.as_ref().clone()).await {
+                for (kind, previous) in previous_contents.into_iter().rev() {
+                    let result = match previous {
+                        Some(content) => self.backend.store(kind, content).await,
+                        None => self.backend.clear(kind).await,
+                    };
+                    if let Err(err) = result {
+                        tracing::warn!("Failed to restore clipboard {kind}, error: {err}");
+                    }
+                }
+                return Err(Error::StoreClipboardKind { kind, source });
+            }
+            previous_contents.push((kind, previous));
+        }
+
+        if let Some(clip) = self.clips.get_mut(&id) {
+            let timestamp = clip.timestamp();
+            if let Some(&kind) = kinds.first() {
+                clip.mark(kind);
+            }
+            clip.increment_use_count();
+            clip.set_version(self.journal.next_cursor());
+            let clip = clip.clone();
+            let clip = self.load(&clip).into_owned();
+            self.move_timestamp(id, timestamp, clip.timestamp());
+            self.journal.record(ClipboardChange::Updated(clip));
+        }
+        for kind in kinds {
+            self.current_clips[usize::from(kind)] = Some(id);
+        }
+        self.notify_current_clips();
+        Ok(true)
+    }
+
+    /// Marks the clip next to the current clip of `clipboard_kind` in
+    /// `direction` as the current clip, returns its id or `None` if there is
+    /// no clip to move to.
+    ///
+    /// Unlike [`ClipboardManager::mark`], the timestamp of the clip is kept, so
+    /// the order of history does not change while cycling through it. Clips are
+    /// cycled through in the order of [`Self::list`], without a current clip
+    /// cycling starts at the first clip. With `wrap`, cycling past one end of
+    /// history continues at the other end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `clipboard_kind` is not enabled or the content
+    /// could not be stored into the clipboard.
+    pub async fn cycle(
+        &mut self,
+        clipboard_kind: ClipboardKind,
+        direction: CycleDirection,
+        wrap: bool,
+    ) -> Result<Option<u64>, Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(
+            self.is_kind_enabled(clipboard_kind),
+            error::KindDisabledSnafu { kind: clipboard_kind }
+        );
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        self.sort_clips(&mut clips, SortOrder::Recency);
+        let position = self
+            .current_clip_id(clipboard_kind)
+            .and_then(|id| clips.iter().position(|clip| clip.id() == id));
+        let index = match (position, direction) {
+            _ if clips.is_empty() => None,
+            (None, _) => Some(0),
+            (Some(position), CycleDirection::Next) if position + 1 < clips.len() => {
+                Some(position + 1)
+            }
+            (Some(position), CycleDirection::Previous) if position > 0 => Some(position - 1),
+            (Some(_), CycleDirection::Next) => wrap.then_some(0),
+            (Some(_), CycleDirection::Previous) => wrap.then_some(clips.len() - 1),
+        };
+        let Some(clip) = index.and_then(|index| self.get(clips[index].id())) else {
+            return Ok(None);
+        };
+        let id = clip.id();
+        self.ensure_supported_mime(id, &clip.mime(), clip.as_ref())?;
+        self.backend
+            .store(clipboard_kind, clip.as_ref().clone())
+            .await
+            .context(error::StoreClipboardContentSnafu)?;
+
+        if let Some(clip) = self.clips.get_mut(&id) {
+            clip.increment_use_count();
+            clip.set_version(self.journal.next_cursor());
+            let clip = clip.clone();
+            let clip = self.load(&clip).into_owned();
+            self.journal.record(ClipboardChange::Updated(clip));
+        }
+        self.current_clips[usize::from(clipboard_kind)] = Some(id);
+        self.notify_current_clips();
+        Ok(Some(id))
+    }
+
+    /// Marks the clip as the current clip of `clipboard_kind` and stores it
+    /// into the clipboard as `target_mime`, converting the content if needed.
+    ///
+    /// The converted content is not captured by the clipboard watcher.
+    /// Returns `false` if the clip does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `clipboard_kind` is not enabled, the clip can not be
+    /// converted to `target_mime` or the content could not be stored into the
+    /// clipboard.
+    pub async fn mark_as(
+        &mut self,
+        id: u64,
+        clipboard_kind: ClipboardKind,
+        target_mime: &mime::Mime,
+    ) -> Result<bool, Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(
+            self.is_kind_enabled(clipboard_kind),
+            error::KindDisabledSnafu { kind: clipboard_kind }
+        );
+        let Some(clip) = self.clips.get(&id) else {
+            return Ok(false);
+        };
+        let content = convert::convert(&self.load(clip), target_mime).ok_or_else(|| {
+            Error::UnsupportedConversion {
+                id,
+                from: clip.mime().essence_str().to_string(),
+                to: target_mime.essence_str().to_string(),
+            }
+        })?;
+        self.ensure_supported_mime(id, target_mime, &content)?;
+
+        if let Some(clip) = self.clips.get_mut(&id) {
+            let timestamp = clip.timestamp();
+            clip.mark(clipboard_kind);
+            clip.increment_use_count();
+            clip.set_version(self.journal.next_cursor());
+            let clip = clip.clone();
+            let clip = self.load(&clip).into_owned();
+            self.move_timestamp(id, timestamp, clip.timestamp());
+            self.journal.record(ClipboardChange::Updated(clip));
+        }
+        let kind = usize::from(clipboard_kind);
+        self.current_clips[kind] = Some(id);
+        self.transient_ids[kind] = Some(content.id());
+        self.notify_current_clips();
+        self.backend
+            .store(clipboard_kind, content)
+            .await
+            .context(error::StoreClipboardContentSnafu)?;
+        Ok(true)
+    }
+
+    /// Stores the clip into the clipboard and removes it from history in one
+    /// step, returns `false` if the clip does not exist.
+    ///
+    /// The content stays in the clipboard until it is overwritten, it is not
+    /// captured again like the content set by `set_transient`. Snippets are
+    /// stored but not removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `clipboard_kind` is not enabled or the content
+    /// could not be stored into the clipboard, the clip is kept then.
+    pub async fn mark_and_remove(
+        &mut self,
+        id: u64,
+        clipboard_kind: ClipboardKind,
+    ) -> Result<bool, Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(
+            self.is_kind_enabled(clipboard_kind),
+            error::KindDisabledSnafu { kind: clipboard_kind }
+        );
+        let Some(clip) = self.get(id) else {
+            return Ok(false);
+        };
+        self.ensure_supported_mime(id, &clip.mime(), clip.as_ref())?;
+        self.backend
+            .store(clipboard_kind, clip.as_ref().clone())
+            .await
+            .context(error::StoreClipboardContentSnafu)?;
+        let kind = usize::from(clipboard_kind);
+        self.current_clips[kind] = None;
+        self.transient_ids[kind] = Some(clip.as_ref().id());
+        Ok(self.remove(id))
+    }
+
+    /// Inserts the clip and marks it as the current clip of its clipboard kind
+    /// in one step, returns its id and the ids of the clips removed by the
+    /// insertion.
+    ///
+    /// The previous current clip stays in history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clipboard kind is not enabled or the content
+    /// could not be stored into the clipboard.
+    pub async fn replace_active(&mut self, data: ClipEntry) -> Result<(u64, Vec<u64>), Error> {
+        let kind = data.kind();
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(self.is_kind_enabled(kind), error::KindDisabledSnafu { kind });
+        let (id, evicted) = self.insert_with_evicted(data);
+        self.mark(id, kind).await?;
+        Ok((id, evicted))
+    }
+
+    /// Stores the content into the clipboard without adding it to history.
+    ///
+    /// The content is remembered until another clip of the same clipboard kind
+    /// is inserted, so the clipboard watcher does not capture it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clipboard kind is not enabled or the content
+    /// could not be stored into the clipboard.
+    pub async fn set_transient(&mut self, data: ClipEntry) -> Result<(), Error> {
+        snafu::ensure!(!self.read_only, error::ReadOnlySnafu);
+        snafu::ensure!(
+            self.is_kind_enabled(data.kind()),
+            error::KindDisabledSnafu { kind: data.kind() }
+        );
+        self.ensure_supported_mime(data.id(), &data.mime(), data.as_ref())?;
+        let kind = usize::from(data.kind());
+        self.transient_ids[kind] = Some(data.id());
+        self.current_clips[kind] = None;
+        self.notify_current_clips();
+        self.backend
+            .store(data.kind(), data.as_ref().clone())
+            .await
+            .context(error::StoreClipboardContentSnafu)
+    }
+
+    /// Returns `true` if the clip is the content set by `set_transient`.
+    #[inline]
+    pub fn is_transient(&self, clip: &ClipEntry) -> bool {
+        self.transient_ids[usize::from(clip.kind())] == Some(clip.id())
+    }
+
+    // the backend stores text as plain text and images as PNG, whatever the
+    // mime type of the clip is
+    fn ensure_supported_mime(
+        &self,
+        id: u64,
+        mime: &mime::Mime,
+        content: &ClipboardContent,
+    ) -> Result<(), Error> {
+        let content_mime = content.mime();
+        snafu::ensure!(
+            self.backend
+                .supported_mimes()
+                .iter()
+                .any(|supported| supported.essence_str() == content_mime.essence_str()),
+            error::UnsupportedMimeSnafu { id, mime: mime.essence_str() }
+        );
+        Ok(())
+    }
+
+    /// Bumps the timestamp of the clip to now without changing its content or
+    /// storing it into the clipboard, returns `false` if the clip does not
+    /// exist.
+    ///
+    /// The clip is counted as used, like when it is marked.
+    pub fn touch(&mut self, id: u64) -> bool { self.bump_timestamp(id, true) }
+
+    fn bump_timestamp(&mut self, id: u64, count_use: bool) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        let timestamp = clip.timestamp();
+        clip.set_timestamp(OffsetDateTime::now_utc());
+        if count_use {
+            clip.increment_use_count();
+        }
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.move_timestamp(id, timestamp, clip.timestamp());
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
+
+    /// Protects the clip from eviction for the capacity or the maximum age of
+    /// history until `until`, a time in the past removes the protection.
+    /// Returns `false` if the clip does not exist.
+    pub fn protect_until(&mut self, id: u64, until: OffsetDateTime) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        let protected_until = Some(until).filter(|&until| until > OffsetDateTime::now_utc());
+        clip.set_protected_until(protected_until);
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
+
+    /// Replaces the tags of the clip, returns `false` if the clip does not
+    /// exist.
+    pub fn set_tags(&mut self, id: u64, tags: Vec<String>) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        clip.set_tags(tags);
+        // the tags are set by the user now
+        clip.set_auto_tags(Vec::<String>::new());
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
+
+    /// Sets the label shown in place of the preview of the clip, `None` removes
+    /// it. Returns `false` if the clip does not exist.
+    pub fn set_label(&mut self, id: u64, label: Option<String>) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        clip.set_label(label);
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
+
+    /// Makes the text clip a sticky note, or a regular clip again, returns
+    /// `Ok(false)` if the clip does not exist.
+    ///
+    /// A sticky note is listed first, it never expires nor is evicted, and it
+    /// is edited in place by [`ClipboardManager::replace`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the clip is not a text clip or the active profile
+    /// has `MAX_STICKY_NOTES` sticky notes already.
+    pub fn set_sticky(&mut self, id: u64, sticky: bool) -> Result<bool, Error> {
+        let count = self.clips.values().filter(|clip| clip.is_sticky() && clip.id() != id).count();
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return Ok(false);
+        };
+        if sticky {
+            snafu::ensure!(clip.is_text(), error::NotTextSnafu { id });
+            snafu::ensure!(
+                count < MAX_STICKY_NOTES,
+                error::TooManyStickyNotesSnafu { limit: MAX_STICKY_NOTES }
+            );
+            clip.set_expires_at(None);
+        }
+        clip.set_sticky(sticky);
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.journal.record(ClipboardChange::Updated(clip));
+        Ok(true)
+    }
+
+    /// Makes the current clip of `kind` a sticky note like
+    /// [`ClipboardManager::set_sticky`], returns its id, or `None` if there is
+    /// no current clip.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error like [`ClipboardManager::set_sticky`].
+    pub fn pin_current(&mut self, kind: ClipboardKind) -> Result<Option<u64>, Error> {
+        let Some(id) = self.current_clip_id(kind) else {
+            return Ok(None);
+        };
+        let _ = self.set_sticky(id, true)?;
+        Ok(Some(id))
+    }
+
+    /// Overrides the kind assigned to the clip by the classifier, returns
+    /// `false` if there is no clip with `id`.
+    ///
+    /// The kind is kept when the clip is captured again.
+    pub fn set_semantic_kind(&mut self, id: u64, kind: SemanticKind) -> bool {
+        let Some(clip) = self.clips.get_mut(&id) else {
+            return false;
+        };
+        clip.set_semantic_kind(Some(kind));
+        clip.set_version(self.journal.next_cursor());
+        let clip = clip.clone();
+        let clip = self.load(&clip).into_owned();
+        self.journal.record(ClipboardChange::Updated(clip));
+        true
+    }
+
+    /// Applies `ops` in order, returns the result of each operation.
+    ///
+    /// An operation failing does not stop the following ones, an operation on
+    /// a clip removed by a previous one results in [`ClipOpResult::NotFound`].
+    ///
+    /// No other change is made between the operations, but each operation is
+    /// sent to the watchers as a change of its own.
+    pub async fn apply(&mut self, ops: Vec<ClipOp>) -> Vec<ClipOpResult> {
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            let result = if self.clips.contains_key(&op.id()) {
+                match op {
+                    ClipOp::Remove { id } => {
+                        let _unused = self.remove(id);
+                        ClipOpResult::Applied
+                    }
+                    ClipOp::Mark { id, kind } => match self.mark(id, kind).await {
+                        Ok(()) => ClipOpResult::Applied,
+                        Err(err) => ClipOpResult::Failed { message: err.to_string() },
+                    },
+                    ClipOp::Promote { id } => {
+                        let _unused = self.bump_timestamp(id, false);
+                        ClipOpResult::Applied
+                    }
+                    ClipOp::SetTags { id, tags } => {
+                        let _unused = self.set_tags(id, tags);
+                        ClipOpResult::Applied
+                    }
+                }
+            } else {
+                ClipOpResult::NotFound
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Exchanges the positions of two clips in the history sorted newest
+    /// first by swapping their timestamps, returns `false` if either clip does
+    /// not exist.
+    pub fn swap(&mut self, a: u64, b: u64) -> bool {
+        let (Some(first), Some(second)) = (self.clips.get(&a), self.clips.get(&b)) else {
+            return false;
+        };
+        if a == b {
+            return true;
+        }
+        let (first_timestamp, second_timestamp) = (first.timestamp(), second.timestamp());
+        for (id, from, to) in
+            [(a, first_timestamp, second_timestamp), (b, second_timestamp, first_timestamp)]
+        {
+            if let Some(clip) = self.clips.get_mut(&id) {
+                clip.set_timestamp(to);
+                clip.set_version(self.journal.next_cursor());
+                let clip = clip.clone();
+                let clip = self.load(&clip).into_owned();
+                self.move_timestamp(id, from, to);
+                self.journal.record(ClipboardChange::Updated(clip));
+            }
+        }
+        true
+    }
+
+    /// Rewrites the timestamps of the clips so the history sorted newest first
+    /// follows `key`, the clip with the largest key becomes the newest, clips
+    /// with the same key keep their order. Returns the number of moved clips.
+    ///
+    /// The timestamps in use are handed out again in the new order, so the
+    /// history keeps its time span, unlike [`Self::list_sorted`] the order
+    /// stays once the history is saved. Sticky notes get timestamps like other
+    /// clips, they are listed first whatever their timestamps are.
+    pub fn reorder(&mut self, key: SortKey) -> usize {
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        clips.sort_unstable();
+        let timestamps = clips.iter().map(|clip| clip.timestamp()).collect::<Vec<_>>();
+        match key {
+            SortKey::CreatedAt => clips.sort_by_key(|clip| Reverse(clip.created_at())),
+            SortKey::UseCount => clips.sort_by_key(|clip| Reverse(clip.use_count())),
+            SortKey::Size => clips.sort_by_key(|clip| Reverse(self.content_len(clip))),
+        }
+        let moves = clips
+            .into_iter()
+            .zip(timestamps)
+            .filter(|(clip, timestamp)| clip.timestamp() != *timestamp)
+            .map(|(clip, timestamp)| (clip.id(), clip.timestamp(), timestamp))
+            .collect::<Vec<_>>();
+
+        for &(id, from, to) in &moves {
+            if let Some(clip) = self.clips.get_mut(&id) {
+                clip.set_timestamp(to);
+                clip.set_version(self.journal.next_cursor());
+                let clip = clip.clone();
+                let clip = self.load(&clip).into_owned();
+                self.move_timestamp(id, from, to);
+                self.journal.record(ClipboardChange::Updated(clip));
+            }
+        }
+        moves.len()
+    }
+
+    /// Returns a timestamp which places a clip at `position` of history sorted
+    /// like [`Self::list`], out-of-range positions are clamped to the ends. A
+    /// clip is never placed before the sticky notes.
+    ///
+    /// The clips from `position` on are moved back if there is no timestamp
+    /// left strictly between the neighbours, e.g. if they have the same
+    /// timestamp.
+    pub fn make_room_at(&mut self, position: usize) -> OffsetDateTime {
+        let nanosecond = time::Duration::nanoseconds(1);
+        let mut clips = self.clips.values().collect::<Vec<_>>();
+        self.sort_clips(&mut clips, SortOrder::Recency);
+        let sticky_count = clips.iter().take_while(|clip| clip.is_sticky()).count();
+        let clips = clips[sticky_count..]
+            .iter()
+            .map(|clip| (clip.id(), clip.timestamp()))
+            .collect::<Vec<_>>();
+        let position = position.saturating_sub(sticky_count);
+        let (Some(&(_, newest)), Some(&(_, oldest))) = (clips.first(), clips.last()) else {
+            return OffsetDateTime::now_utc();
+        };
+        if position == 0 {
+            return OffsetDateTime::now_utc().max(newest + nanosecond);
+        }
+        let Some(&(_, older)) = clips.get(position) else {
+            return oldest - nanosecond;
+        };
+        let (_, newer) = clips[position - 1];
+        if newer - older > nanosecond {
+            return older + (newer - older) / 2;
+        }
+
+        // move back the clips from `position` on, with the following clips
+        // they would reach, so they keep their order
+        let shift = older - newer + nanosecond * 2;
+        let mut end = position + 1;
+        while end < clips.len() && clips[end].1 >= clips[end - 1].1 - shift {
+            end += 1;
+        }
+        for &(id, timestamp) in &clips[position..end] {
+            if let Some(clip) = self.clips.get_mut(&id) {
+                clip.set_timestamp(timestamp - shift);
+                clip.set_version(self.journal.next_cursor());
+                let clip = clip.clone();
+                let clip = self.load(&clip).into_owned();
+                self.move_timestamp(id, timestamp, clip.timestamp());
+                self.journal.record(ClipboardChange::Updated(clip));
+            }
+        }
+        newer - nanosecond
+    }
+
+    fn move_timestamp(&mut self, id: u64, from: OffsetDateTime, to: OffsetDateTime) {
+        let _ = self.timestamp_ids.remove(&(from, Reverse(id)));
+        let _unused = self.timestamp_ids.insert((to, Reverse(id)));
+    }
+
+    /// Returns the retained changes after `since_cursor` and a receiver of the
+    /// following changes, the receiver is `None` once the manager is closed.
+    #[inline]
+    pub fn subscribe_changes(
+        &self,
+        since_cursor: Option<u64>,
+    ) -> (Vec<ClipboardEvent>, Option<broadcast::Receiver<ClipboardEvent>>) {
+        self.journal.subscribe(since_cursor)
+    }
+
+    /// Returns the net changes after `since_cursor`, computed from the
+    /// retained changes.
+    ///
+    /// Added and updated clips are returned as they are now, a full resync is
+    /// required if the changes are no longer retained.
+    pub fn diff_since(&self, since_cursor: u64) -> HistoryDelta {
+        let new_cursor = self.journal.next_cursor().saturating_sub(1);
+        let Some(events) = self.journal.changes_since(since_cursor) else {
+            return HistoryDelta { new_cursor, resync_required: true, ..HistoryDelta::default() };
+        };
+        let changed_ids = events
+            .into_iter()
+            .filter_map(|event| match event.change {
+                ClipboardChange::Added(clip) | ClipboardChange::Updated(clip) => Some(clip.id()),
+                ClipboardChange::AddedMetadata(metadata)
+                | ClipboardChange::UpdatedMetadata(metadata) => Some(metadata.id),
+                ClipboardChange::Removed(id) | ClipboardChange::Evicted { id, .. } => Some(id),
+                ClipboardChange::Heartbeat
+                | ClipboardChange::Resync
+                | ClipboardChange::ProfileChanged { .. } => None,
+            })
+            .collect::<HashSet<_>>();
+        let (mut added, mut removed) = (Vec::new(), Vec::new());
+        for id in changed_ids {
+            match self.get(id) {
+                Some(clip) => added.push(clip),
+                None => removed.push(id),
+            }
+        }
+        added.sort_unstable();
+        removed.sort_unstable();
+        HistoryDelta { added, removed, new_cursor, resync_required: false }
+    }
+
+    /// Returns a receiver of the ids of the current clips of all clipboard
+    /// kinds, the receiver is `None` once the manager is closed.
+    #[inline]
+    pub fn subscribe_current_clips(
+        &self,
+    ) -> Option<watch::Receiver<[Option<u64>; ClipboardKind::MAX_LENGTH]>> {
+        self.current_clips_sender.as_ref().map(watch::Sender::subscribe)
+    }
+
+    // send the ids of the current clips to subscribers if they are changed
+    fn notify_current_clips(&self) {
+        let Some(sender) = &self.current_clips_sender else {
+            return;
+        };
+        let current_clips = ClipboardKind::all_kinds().map(|kind| self.current_clip_id(kind));
+        let _modified = sender.send_if_modified(|ids| {
+            let modified = *ids != current_clips;
+            *ids = current_clips;
+            modified
+        });
+    }
+
+    /// Ends the change streams of all subscribers.
+    #[inline]
+    pub fn close_change_subscribers(&mut self) {
+        self.journal.close();
+        self.current_clips_sender = None;
+    }
+
+    #[inline]
+    fn is_snippet(&self, id: u64) -> bool { self.snippet_ids.contains(&id) }
+
+    // clips are ordered by their timestamps, kinds and ids, so the order is
+    // the same between listings, sticky notes are listed first in any order
+    fn sort_clips(&self, clips: &mut [&ClipEntry], order: SortOrder) {
+        match order {
+            SortOrder::Recency => clips.sort_unstable_by(|a, b| sticky_first(a, b)),
+            SortOrder::SnippetsFirst => clips.sort_unstable_by(|a, b| {
+                b.is_sticky()
+                    .cmp(&a.is_sticky())
+                    .then_with(|| self.is_snippet(b.id()).cmp(&self.is_snippet(a.id())))
+                    .then_with(|| a.cmp(b))
+            }),
+        }
+    }
+}
+
+// orders the sticky notes before the other clips, newest first
+fn sticky_first(a: &ClipEntry, b: &ClipEntry) -> std::cmp::Ordering {
+    b.is_sticky().cmp(&a.is_sticky()).then_with(|| a.cmp(b))
+}
+
+// derives the id of a clip kept apart from the clips with the same content
+fn mix_id(content_id: u64, salt: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content_id.hash(&mut hasher);
+    salt.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Arc, time::Duration};
+
+    use clipcat_base::{
+        ClipEntry, ClipEntryMetadata, ClipOp, ClipOpResult, ClipboardChange, ClipboardContent,
+        ClipboardKind, CycleDirection, DedupeScope, EvictionReason, ImageFormat, MetadataRecord,
+        SemanticKind, SortOrder, TagMatch, WindowInfo,
+    };
+    use time::OffsetDateTime;
+
+    use crate::{
+        auto_tag::{AutoTagRule, AutoTagger},
+        backend::{ClipboardBackend, LocalClipboardBackend},
+        classify::Classifier,
+        manager::{
+            ClipboardManager, Error, DEFAULT_CAPACITY, MAX_SNAPSHOTS, MAX_STICKY_NOTES,
+            SNAPSHOT_TTL,
+        },
+        notification::DummyNotification,
+        redact::{RedactionRule, Redactor},
+    };
+
+    fn create_clips(n: usize) -> Vec<ClipEntry> {
+        (0..n)
+            .map(|i| {
+                // sleep for 1 millisecond, avoiding duplicated timestamp
+                std::thread::sleep(Duration::from_millis(1));
+
+                ClipEntry::from_string(i, ClipboardKind::Primary)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_construction() {
+        let notification = DummyNotification::default();
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mgr = ClipboardManager::new(backend, notification);
+        assert!(mgr.is_empty());
+        assert_eq!(mgr.len(), 0);
+        assert_eq!(mgr.capacity(), DEFAULT_CAPACITY);
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+
+        let cap = 20;
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mgr = ClipboardManager::with_capacity(
+            backend,
+            cap,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        assert!(mgr.is_empty());
+        assert_eq!(mgr.len(), 0);
+        assert_eq!(mgr.capacity(), cap);
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let cap = 10;
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            cap,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        assert_eq!(mgr.len(), 0);
+        assert_eq!(mgr.capacity(), cap);
+
+        let n = 20;
+        let clips = create_clips(n);
+        for clip in clips {
+            let _ = mgr.insert(clip);
+        }
+
+        assert_eq!(mgr.len(), cap);
+        assert_eq!(mgr.capacity(), cap);
+
+        let n = 25;
+        let clips = create_clips(n);
+        mgr.import(&clips);
+
+        assert_eq!(mgr.len(), cap);
+        assert_eq!(mgr.capacity(), cap);
+
+        let mut exported = mgr.export(false);
+        exported.sort_unstable();
+        let mut clips = clips[(n - mgr.capacity())..].to_vec();
+        clips.sort_unstable();
+        assert_eq!(exported.len(), clips.len());
+        assert_eq!(exported, clips);
+    }
+
+    #[test]
+    fn test_insert_with_evicted() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let cap = 10;
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            cap,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let n = 20;
+        let clips = create_clips(n);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+
+        let (_, evicted) =
+            mgr.insert_with_evicted(ClipEntry::from_string("new", ClipboardKind::Primary));
+        assert_eq!(evicted, vec![clips[n - cap].id()]);
+        assert_eq!(mgr.len(), cap);
+    }
+
+    #[allow(clippy::mutable_key_type)]
+    #[test]
+    fn test_insert() {
+        let n = 20;
+        let clips = create_clips(n);
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_some());
+        assert_eq!(mgr.get_current_clip(ClipboardKind::Primary).as_ref(), clips.last());
+        assert_eq!(mgr.len(), n);
+
+        let dumped = mgr.export(false).into_iter().collect::<HashSet<_>>();
+        let clips = clips.into_iter().collect::<HashSet<_>>();
+
+        assert_eq!(dumped, clips);
+    }
+
+    #[test]
+    fn test_import() {
+        let n = 10;
+        let mut clips = create_clips(n);
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            20,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+
+        mgr.import(&clips);
+        assert_eq!(mgr.len(), n);
+
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+        assert_eq!(mgr.len(), n);
+
+        let mut exported = mgr.export(false);
+        clips.sort_unstable();
+        exported.sort_unstable();
+
+        assert_eq!(exported.len(), clips.len());
+        // assert_eq!(exported, clips);
+    }
+
+    #[test]
+    fn test_insert_duplicated_content() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            2,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+
+        let clips = create_clips(3);
+        let first_id = mgr.insert(clips[0].clone());
+        let _ = mgr.insert(clips[1].clone());
+        std::thread::sleep(Duration::from_millis(1));
+        let id = mgr.insert(ClipEntry::from_string(0, ClipboardKind::Clipboard));
+        assert_eq!(id, first_id);
+        assert_eq!(mgr.len(), 2);
+        assert_eq!(mgr.get(id).map(|clip| clip.kind()), Some(ClipboardKind::Clipboard));
+
+        // the promoted clip is the newest one, the second clip is evicted
+        // instead
+        let _ = mgr.insert(clips[2].clone());
+        assert_eq!(mgr.len(), 2);
+        assert!(mgr.get(first_id).is_some());
+        assert!(mgr.get(clips[1].id()).is_none());
+    }
+
+    #[test]
+    fn test_insert_attributes() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+
+        let mut clip = ClipEntry::from_string("text", ClipboardKind::Clipboard);
+        clip.set_attributes([("source", "https://example.com"), ("app", "browser")]);
+        let id = mgr.insert(clip);
+
+        // attributes of a duplicated clip are merged, the new values win
+        let mut clip = ClipEntry::from_string("text", ClipboardKind::Clipboard);
+        clip.set_attributes([("app", "editor")]);
+        assert_eq!(mgr.insert(clip), id);
+        let clip = mgr.get(id).unwrap();
+        assert_eq!(
+            clip.attributes().get("source").map(String::as_str),
+            Some("https://example.com")
+        );
+        assert_eq!(clip.attributes().get("app").map(String::as_str), Some("editor"));
+        assert_eq!(clip.attributes_size(), "source".len() + 19 + "app".len() + 6);
+    }
+
+    #[test]
+    fn test_set_label() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let id = mgr.insert(ClipEntry::from_string("ssh-ed25519 AAAA", ClipboardKind::Clipboard));
+        assert!(mgr.set_label(id, Some("my SSH key".to_string())));
+        assert!(!mgr.set_label(id + 1, Some("missing".to_string())));
+
+        let metadata = mgr.batch_metadata(&[id], 30).remove(0);
+        assert_eq!(metadata.label.as_deref(), Some("my SSH key"));
+        assert_eq!(metadata.display_text(), "my SSH key");
+
+        // the label is kept when the clip is captured again
+        let _ = mgr.insert(ClipEntry::from_string("ssh-ed25519 AAAA", ClipboardKind::Clipboard));
+        assert_eq!(mgr.get(id).unwrap().label(), Some("my SSH key"));
+
+        assert!(mgr.set_label(id, None));
+        let metadata = mgr.batch_metadata(&[id], 30).remove(0);
+        assert_eq!(metadata.display_text(), metadata.preview);
+    }
+
+    #[test]
+    fn test_list_grouped() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let first = mgr.insert(ClipEntry::from_string("first", ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let second = mgr.insert(ClipEntry::from_string("second", ClipboardKind::Clipboard));
+        let primary = mgr.insert(ClipEntry::from_string("primary", ClipboardKind::Primary));
+
+        let groups = mgr.list_grouped_sorted(SortOrder::Recency);
+        let ids = |kind| groups[&kind].iter().map(ClipEntry::id).collect::<Vec<_>>();
+        assert_eq!(ids(ClipboardKind::Clipboard), vec![second, first]);
+        assert_eq!(ids(ClipboardKind::Primary), vec![primary]);
+        assert!(ids(ClipboardKind::Secondary).is_empty());
+    }
+
+    #[test]
+    fn test_ids_with_content_hash() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        mgr.set_dedupe_scope(DedupeScope::None);
+        let clip = ClipEntry::from_string("duplicate", ClipboardKind::Clipboard);
+        let first = mgr.insert(clip.clone());
+        let _ = mgr.insert(ClipEntry::from_string("other", ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let second = mgr.insert(ClipEntry::from_string("duplicate", ClipboardKind::Clipboard));
+        assert_ne!(first, second);
+
+        assert_eq!(mgr.ids_with_content_hash(&clip.content_hash()).unwrap(), vec![second, first]);
+        assert_eq!(
+            mgr.ids_with_content_hash(&clip.content_hash().to_uppercase()).unwrap(),
+            vec![second, first]
+        );
+        assert_eq!(mgr.ids_with_digest(clip.sha256_digest()), vec![second, first]);
+        for hash in ["not a hash", "00", &"0".repeat(66)] {
+            assert!(matches!(
+                mgr.ids_with_content_hash(hash),
+                Err(Error::InvalidContentHash { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_image_as() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let image = ClipboardContent::Image { width: 2, height: 2, bytes: vec![255; 16].into() };
+        let id =
+            mgr.insert(ClipEntry::from_clipboard_content(image, ClipboardKind::Clipboard, None));
+        let jpeg = mgr.image_as(id, ImageFormat::Jpeg).unwrap().unwrap();
+        assert!(jpeg.starts_with(&[0xff, 0xd8]));
+        assert_eq!(mgr.image_as(id, ImageFormat::Jpeg).unwrap().unwrap(), jpeg);
+        assert!(mgr.image_as(id + 1, ImageFormat::Jpeg).unwrap().is_none());
+
+        let text_id = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        assert!(matches!(
+            mgr.image_as(text_id, ImageFormat::Png),
+            Err(Error::NotAnImage { id }) if id == text_id
+        ));
+    }
+
+    #[test]
+    fn test_append() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let mut clip = ClipEntry::from_string("first", ClipboardKind::Clipboard);
+        clip.set_tags(["note"]);
+        let id = mgr.insert(clip);
+
+        let (ok, id) = mgr.append(id, b"second", Some(b"\n")).unwrap();
+        assert!(ok);
+        let (ok, id) = mgr.append(id, b" third", None).unwrap();
+        assert!(ok);
+        let clip = mgr.get(id).unwrap();
+        assert_eq!(clip.as_ref(), &ClipboardContent::Plaintext("first\nsecond third".to_string()));
+        assert!(clip.tags().contains("note"));
+        assert_eq!(mgr.len(), 1);
+
+        assert_eq!(mgr.append(id + 1, b"clipcat", None).unwrap(), (false, id + 1));
+
+        let image = ClipboardContent::Image { width: 1, height: 1, bytes: vec![255; 4].into() };
+        let image_id =
+            mgr.insert(ClipEntry::from_clipboard_content(image, ClipboardKind::Clipboard, None));
+        assert!(matches!(
+            mgr.append(image_id, b"clipcat", None),
+            Err(Error::NotText { id }) if id == image_id
+        ));
+    }
+
+    #[test]
+    fn test_available_targets() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let targets = vec!["UTF8_STRING".to_string(), "text/html".to_string()];
+        let mut clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        clip.set_available_targets(targets.clone());
+        let id = mgr.insert(clip);
+
+        // the targets are kept when the marked clip is captured again
+        let mut clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        clip.set_owned_by_us(true);
+        clip.set_available_targets(vec!["UTF8_STRING".to_string()]);
+        let _ = mgr.insert(clip);
+        assert_eq!(mgr.get(id).unwrap().available_targets(), targets.as_slice());
+
+        let mut clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        clip.set_available_targets(vec!["STRING".to_string()]);
+        let _ = mgr.insert(clip);
+        assert_eq!(mgr.get(id).unwrap().available_targets(), ["STRING".to_string()]);
+    }
+
+    #[test]
+    fn test_replace() {
+        const MIME: mime::Mime = mime::TEXT_PLAIN_UTF_8;
+
+        let data1 = "ABCDEFG";
+        let data2 = "АБВГД";
+        let clip = ClipEntry::new(data1.as_bytes(), &MIME, ClipboardKind::Clipboard, None).unwrap();
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let old_id = mgr.insert(clip);
+        assert_eq!(mgr.len(), 1);
+
+        let (ok, new_id) = mgr.replace(old_id, data2.as_bytes(), &MIME);
+        assert!(ok);
+        assert_ne!(old_id, new_id);
+        assert_eq!(mgr.len(), 1);
+
+        let clip = mgr.get(new_id).unwrap();
+        assert_eq!(clip.as_bytes(), data2.as_bytes());
+        assert_eq!(clip.kind(), ClipboardKind::Clipboard);
+    }
+
+    #[test]
+    fn test_replace_keeps_created_at() {
+        let clip = ClipEntry::from_string("ABCDEFG", ClipboardKind::Clipboard);
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let created_at = clip.created_at();
+        let old_id = mgr.insert(clip);
+
+        let (_, new_id) = mgr.replace(old_id, "АБВГД".as_bytes(), &mime::TEXT_PLAIN_UTF_8);
+        let clip = mgr.get(new_id).unwrap();
+        assert_eq!(clip.created_at(), created_at);
+        assert!(clip.updated_at() >= created_at);
+    }
+
+    #[test]
+    fn test_replace_if() {
+        const MIME: mime::Mime = mime::TEXT_PLAIN_UTF_8;
+
+        let data1 = "ABCDEFG";
+        let data2 = "АБВГД";
+        let clip = ClipEntry::new(data1.as_bytes(), &MIME, ClipboardKind::Clipboard, None).unwrap();
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let old_id = mgr.insert(clip);
+        let (_, new_id) = mgr.replace(old_id, data2.as_bytes(), &MIME);
+
+        let version = mgr.get(new_id).unwrap().version();
+        assert!(mgr.touch(new_id));
+        assert!(mgr.get(new_id).unwrap().version() > version);
+        assert!(matches!(
+            mgr.replace_if(new_id, version, data1.as_bytes(), &MIME),
+            Err(Error::VersionConflict { .. })
+        ));
+        let version = mgr.get(new_id).unwrap().version();
+        let (ok, id) = mgr.replace_if(new_id, version, data1.as_bytes(), &MIME).unwrap();
+        assert!(ok);
+        assert_eq!(id, old_id);
+        assert!(matches!(
+            mgr.replace_if(new_id, version, data2.as_bytes(), &MIME),
+            Err(Error::VersionConflict { version: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_preserve_mime_parameters() {
+        let mime: mime::Mime = "text/html; charset=utf-8".parse().unwrap();
+        let clip =
+            ClipEntry::new(b"<b>clipcat</b>", &mime, ClipboardKind::Clipboard, None).unwrap();
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let id = mgr.insert(clip);
+
+        let clip = mgr.get(id).unwrap();
+        assert_eq!(clip.mime_full(), "text/html; charset=utf-8");
+        assert_eq!(clip.mime().essence_str(), "text/html");
+    }
+
+    #[test]
+    fn test_list_equal_timestamps() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let timestamp = OffsetDateTime::now_utc();
+        let mut ids = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|text| {
+                let clip = ClipEntry::new(
+                    text.as_bytes(),
+                    &mime::TEXT_PLAIN_UTF_8,
+                    ClipboardKind::Clipboard,
+                    Some(timestamp),
+                )
+                .unwrap();
+                mgr.insert(clip)
+            })
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        for _ in 0..3 {
+            assert_eq!(mgr.list(10).iter().map(|clip| clip.id).collect::<Vec<_>>(), ids);
+        }
+    }
+
+    #[test]
+    fn test_evict_equal_timestamps() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            2,
+            time::Duration::milliseconds(0),
+            DummyNotification::default(),
+        );
+        let timestamp = OffsetDateTime::now_utc();
+        let clip = |text: &str| {
+            ClipEntry::new(
+                text.as_bytes(),
+                &mime::TEXT_PLAIN_UTF_8,
+                ClipboardKind::Clipboard,
+                Some(timestamp),
+            )
+            .unwrap()
+        };
+        let mut ids = ["a", "b", "c"]
+            .into_iter()
+            .map(|text| {
+                let (id, evicted) = mgr.insert_with_evicted(clip(text));
+                assert!(evicted.iter().all(|&evicted| mgr.get(evicted).is_none()));
+                id
+            })
+            .collect::<Vec<_>>();
+        // the clip listed last is evicted, the others are kept
+        ids.sort_unstable();
+        let evicted = ids.pop().unwrap();
+        assert!(mgr.get(evicted).is_none());
+        assert_eq!(mgr.list(10).iter().map(|clip| clip.id).collect::<Vec<_>>(), ids);
+
+        mgr.set_testing(true);
+        let clips = (0..6).map(|n| clip(&n.to_string())).collect::<Vec<_>>();
+        assert_eq!(mgr.set_history(clips).unwrap(), 2);
+        assert_eq!(mgr.len(), 2);
+        assert_eq!(mgr.list(10).len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(3);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+
+        let (handle, total) = mgr.open_snapshot();
+        assert_eq!(total, 3);
+        let _ = mgr.insert(ClipEntry::from_string("new", ClipboardKind::Clipboard));
+        assert!(mgr.remove(clips[1].id()));
+
+        // pages are listed from the history at the time the snapshot is opened
+        let (page, total) = mgr.list_snapshot_range(handle, 1, 2).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(page, vec![clips[1].clone(), clips[0].clone()]);
+        assert_eq!(mgr.list_range(0, 10).1, 3);
+
+        assert!(mgr.close_snapshot(handle));
+        assert!(!mgr.close_snapshot(handle));
+        assert!(matches!(
+            mgr.list_snapshot_range(handle, 0, 2),
+            Err(Error::SnapshotNotFound { handle: h }) if h == handle
+        ));
+
+        let (handle, _) = mgr.open_snapshot();
+        mgr.remove_expired_snapshots(std::time::Instant::now() + SNAPSHOT_TTL);
+        assert!(mgr.list_snapshot_range(handle, 0, 2).is_err());
+
+        let handles = (0..=MAX_SNAPSHOTS).map(|_| mgr.open_snapshot().0).collect::<Vec<_>>();
+        assert!(mgr.list_snapshot_range(handles[0], 0, 2).is_err());
+        assert!(mgr.list_snapshot_range(handles[MAX_SNAPSHOTS], 0, 2).is_ok());
+    }
+
+    #[test]
+    fn test_list_range() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(5);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+
+        let (page, total) = mgr.list_range(1, 2);
+        assert_eq!(total, 5);
+        assert_eq!(page, vec![clips[3].clone(), clips[2].clone()]);
+
+        let (page, total) = mgr.list_range(4, 2);
+        assert_eq!(total, 5);
+        assert_eq!(page, vec![clips[0].clone()]);
+
+        assert!(mgr.list_range(5, 2).0.is_empty());
+
+        assert_eq!(mgr.list_since(clips[3].updated_at()), vec![clips[4].clone(), clips[3].clone()]);
+        assert_eq!(mgr.list_since(clips[0].updated_at()).len(), 5);
+        let ids = [clips[2].id(), 0, clips[0].id()];
+        let metadata = mgr.batch_metadata(&ids, 10);
+        assert_eq!(
+            metadata.into_iter().map(|metadata| metadata.id).collect::<Vec<_>>(),
+            vec![clips[2].id(), clips[0].id()]
+        );
+        assert_eq!(
+            mgr.list_ids(),
+            clips.iter().rev().map(|clip| (clip.id(), clip.timestamp())).collect::<Vec<_>>()
+        );
+
+        let ids = mgr.list(10).into_iter().map(|metadata| metadata.id).collect::<Vec<_>>();
+        assert_eq!(ids, clips.iter().rev().map(ClipEntry::id).collect::<Vec<_>>());
+
+        for (rank, clip) in clips.iter().rev().enumerate() {
+            assert_eq!(mgr.rank(clip.id()), Some(rank));
+            assert_eq!(mgr.nth_recent(rank + 1, ClipboardKind::Primary).as_ref(), Some(clip));
+        }
+        assert_eq!(mgr.nth_recent(0, ClipboardKind::Primary), None);
+        assert_eq!(mgr.nth_recent(6, ClipboardKind::Primary), None);
+        assert_eq!(mgr.nth_recent(1, ClipboardKind::Clipboard), None);
+        assert_eq!(mgr.rank(0), None);
+    }
+
+    #[test]
+    fn test_list_after() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(5);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+
+        let (page, cursor) = mgr.list_after(None, 2);
+        assert_eq!(
+            page.iter().map(ClipEntry::id).collect::<Vec<_>>(),
+            vec![clips[4].id(), clips[3].id()]
+        );
+        assert!(cursor.is_some());
+
+        // the clips are not shifted by the inserted and removed clips
+        std::thread::sleep(Duration::from_millis(1));
+        let _ = mgr.insert(ClipEntry::from_string("new", ClipboardKind::Primary));
+        assert!(mgr.remove(clips[4].id()));
+        let (page, cursor) = mgr.list_after(cursor, 2);
+        assert_eq!(
+            page.iter().map(ClipEntry::id).collect::<Vec<_>>(),
+            vec![clips[2].id(), clips[1].id()]
+        );
+
+        let (page, cursor) = mgr.list_after(cursor, 2);
+        assert_eq!(page.iter().map(ClipEntry::id).collect::<Vec<_>>(), vec![clips[0].id()]);
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn test_list_after_equal_timestamps() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let timestamp = OffsetDateTime::now_utc();
+        for text in ["a", "b", "c", "d", "e"] {
+            let clip = ClipEntry::new(
+                text.as_bytes(),
+                &mime::TEXT_PLAIN_UTF_8,
+                ClipboardKind::Clipboard,
+                Some(timestamp),
+            )
+            .unwrap();
+            let _id = mgr.insert(clip);
+        }
+
+        let mut listed = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = mgr.list_after(cursor, 2);
+            listed.extend(page.iter().map(ClipEntry::id));
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(listed, mgr.list(10).iter().map(|clip| clip.id).collect::<Vec<_>>());
+        assert_eq!(listed.len(), 5);
+    }
+
+    #[test]
+    fn test_touch() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            3,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let clips = create_clips(3);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+        let current = mgr.get_current_clip(ClipboardKind::Primary).as_ref().map(ClipEntry::id);
+
+        assert!(mgr.touch(clips[0].id()));
+        assert!(!mgr.touch(0));
+        assert_eq!(mgr.list_range(0, 1).0[0].as_ref(), clips[0].as_ref());
+        assert_eq!(
+            mgr.get_current_clip(ClipboardKind::Primary).as_ref().map(ClipEntry::id),
+            current
+        );
+
+        // the touched clip is no longer the oldest one
+        let _ = mgr.insert(ClipEntry::from_string("new", ClipboardKind::Clipboard));
+        assert!(mgr.get(clips[0].id()).is_some());
+        assert!(mgr.get(clips[1].id()).is_none());
+    }
+
+    #[test]
+    fn test_swap() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            3,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let clips = create_clips(3);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+
+        assert!(mgr.swap(clips[0].id(), clips[2].id()));
+        assert!(!mgr.swap(clips[0].id(), 0));
+        assert!(mgr.swap(clips[1].id(), clips[1].id()));
+        let ids = mgr.list_range(0, 3).0.iter().map(ClipEntry::id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![clips[0].id(), clips[1].id(), clips[2].id()]);
+
+        // the swapped clip is now the oldest one
+        let _ = mgr.insert(ClipEntry::from_string("new", ClipboardKind::Clipboard));
+        assert!(mgr.get(clips[0].id()).is_some());
+        assert!(mgr.get(clips[2].id()).is_none());
+    }
+
+    #[test]
+    fn test_reorder() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let ids = ["a", "ccc", "bb"]
+            .into_iter()
+            .map(|text| {
+                std::thread::sleep(Duration::from_millis(1));
+                mgr.insert(ClipEntry::from_string(text, ClipboardKind::Clipboard))
+            })
+            .collect::<Vec<_>>();
+        let timestamps = |mgr: &ClipboardManager<DummyNotification>| {
+            let mut timestamps =
+                mgr.list_range(0, 3).0.iter().map(ClipEntry::timestamp).collect::<Vec<_>>();
+            timestamps.sort_unstable();
+            timestamps
+        };
+        let before = timestamps(&mgr);
+        let listed = |mgr: &ClipboardManager<DummyNotification>| {
+            mgr.list_range(0, 3).0.iter().map(ClipEntry::id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(mgr.reorder(clipcat_base::SortKey::Size), 2);
+        assert_eq!(listed(&mgr), vec![ids[1], ids[2], ids[0]]);
+        assert_eq!(timestamps(&mgr), before);
+        assert_eq!(mgr.reorder(clipcat_base::SortKey::Size), 0);
+
+        // clips are restored in the order they were captured
+        assert_eq!(mgr.reorder(clipcat_base::SortKey::CreatedAt), 2);
+        assert_eq!(listed(&mgr), vec![ids[2], ids[1], ids[0]]);
+    }
+
+    #[test]
+    fn test_semantic_kind() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_classifier(Classifier::new(Vec::new()));
+        let url =
+            mgr.insert(ClipEntry::from_string("https://example.com", ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let number = mgr.insert(ClipEntry::from_string("42", ClipboardKind::Clipboard));
+        assert_eq!(mgr.get(url).unwrap().semantic_kind(), Some(SemanticKind::Url));
+        let ids = |mgr: &ClipboardManager<DummyNotification>, kind| {
+            mgr.list_by_semantic_kind(kind).iter().map(ClipEntry::id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&mgr, SemanticKind::Number), [number]);
+
+        // the kind set by users is kept when the clip is captured again
+        assert!(mgr.set_semantic_kind(number, SemanticKind::Code));
+        assert!(!mgr.set_semantic_kind(0, SemanticKind::Code));
+        let _id = mgr.insert(ClipEntry::from_string("42", ClipboardKind::Clipboard));
+        assert_eq!(ids(&mgr, SemanticKind::Code), [number]);
+        assert!(ids(&mgr, SemanticKind::Number).is_empty());
+    }
+
+    #[test]
+    fn test_set_history() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let _id = mgr.insert(ClipEntry::from_string("old", ClipboardKind::Clipboard));
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let clips = [("a", 0), ("b", 1)]
+            .into_iter()
+            .map(|(text, secs)| {
+                let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+                clip.set_timestamp(timestamp + time::Duration::seconds(secs));
+                clip
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(mgr.set_history(clips.clone()), Err(Error::TestingDisabled)));
+        assert_eq!(mgr.len(), 1);
+
+        mgr.set_testing(true);
+        assert_eq!(mgr.set_history(clips.clone()).unwrap(), 2);
+        let listed = mgr.list_range(0, 10).0;
+        assert_eq!(
+            listed.iter().map(ClipEntry::id).collect::<Vec<_>>(),
+            [clips[1].id(), clips[0].id()]
+        );
+        assert_eq!(listed[1].timestamp(), timestamp);
+    }
+
+    #[test]
+    fn test_list_preview() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let html: mime::Mime = "text/html; charset=utf-8".parse().unwrap();
+        let id = mgr.insert(
+            ClipEntry::new(b"<p>hello &amp; <b>bye</b></p>", &html, ClipboardKind::Clipboard, None)
+                .unwrap(),
+        );
+        let preview = |mgr: &ClipboardManager<_>, id, length| {
+            mgr.list(length).into_iter().find(|clip| clip.id == id).unwrap().preview
+        };
+        assert_eq!(preview(&mgr, id, 100), "hello & bye");
+
+        let id = mgr.insert(ClipEntry::from_string("\nfirst\nsecond", ClipboardKind::Clipboard));
+        assert_eq!(preview(&mgr, id, 100), "first...(3 lines)");
+        assert_eq!(preview(&mgr, id, 16), "firs...(3 lines)");
+
+        let id = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        assert_eq!(preview(&mgr, id, 100), "clipcat");
+        assert_eq!(preview(&mgr, id, 6), "cli...");
+
+        let image = ClipEntry::from_clipboard_content(
+            ClipboardContent::Image { width: 2, height: 3, bytes: vec![0; 24].into() },
+            ClipboardKind::Clipboard,
+            None,
+        );
+        let id = mgr.insert(image);
+        assert!(preview(&mgr, id, 0).starts_with("[image/png 2x3 "));
+    }
+
+    #[test]
+    fn test_insert_with_id() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+
+        // the id is kept even if it is not the id of the content
+        let id = clip.id() ^ 1;
+        assert_eq!(mgr.insert_with_id(id, clip.clone()).ok(), Some((id, Vec::new())));
+        assert_eq!(mgr.get(id).map(|entry| entry.as_ref().clone()), Some(clip.as_ref().clone()));
+        assert!(matches!(mgr.insert_with_id(id, clip), Err(Error::IdTaken { .. })));
+        assert_eq!(mgr.len(), 1);
+    }
+
+    #[test]
+    fn test_batch_etags() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let id = mgr.insert(ClipEntry::from_string("<b>clipcat</b>", ClipboardKind::Clipboard));
+        let etags = mgr.batch_etags(&[0, id]);
+        assert_eq!(etags.len(), 1);
+        assert_eq!(etags[0].0, id);
+        assert_eq!(mgr.batch_metadata(&[id], 10)[0].etag, etags[0].1);
+
+        // changing the mime type changes the etag
+        let (ok, html_id) = mgr.replace(id, b"<b>clipcat</b>", &mime::TEXT_HTML_UTF_8);
+        assert!(ok);
+        assert_ne!(mgr.batch_etags(&[html_id])[0].1, etags[0].1);
+
+        let (ok, new_id) = mgr.replace(html_id, b"clipcat", &mime::TEXT_PLAIN_UTF_8);
+        assert!(ok);
+        assert_ne!(mgr.batch_etags(&[new_id])[0].1, etags[0].1);
+    }
+
+    #[test]
+    fn test_trim_to() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let snippet = ClipEntry::from_string("snippet", ClipboardKind::Clipboard);
+        mgr.insert_snippets(std::slice::from_ref(&snippet));
+        let clips = create_clips(4);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+        let id = mgr.insert(ClipEntry::from_string("clipboard", ClipboardKind::Clipboard));
+
+        assert_eq!(
+            mgr.trim_to(2, Some(ClipboardKind::Primary)),
+            vec![clips[1].id(), clips[0].id()]
+        );
+        assert_eq!(mgr.trim_to(2, Some(ClipboardKind::Primary)), Vec::<u64>::new());
+        assert_eq!(mgr.trim_candidates(1, None), vec![clips[3].id(), clips[2].id()]);
+        assert_eq!(mgr.len(), 4);
+        assert_eq!(mgr.trim_to(1, None), vec![clips[3].id(), clips[2].id()]);
+        assert!(mgr.get(id).is_some());
+        assert!(mgr.get(snippet.id()).is_some());
+        assert_eq!(mgr.len(), 2);
+    }
+
+    #[test]
+    fn test_remove_by_content() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let id = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        let _ = mgr.insert(ClipEntry::from_string("another", ClipboardKind::Clipboard));
+
+        assert_eq!(
+            mgr.content_matches(ClipEntry::from_string("clipcat", ClipboardKind::Primary)),
+            vec![id]
+        );
+        assert_eq!(mgr.len(), 2);
+        assert_eq!(
+            mgr.remove_by_content(ClipEntry::from_string("clipcat", ClipboardKind::Primary)),
+            vec![id]
+        );
+        assert!(mgr
+            .remove_by_content(ClipEntry::from_string("clipcat", ClipboardKind::Primary))
+            .is_empty());
+        assert_eq!(mgr.len(), 1);
+    }
+
+    #[test]
+    fn test_get_or_insert() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let id = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+
+        assert_eq!(
+            mgr.get_or_insert(ClipEntry::from_string("clipcat", ClipboardKind::Primary)),
+            (id, false)
+        );
+        assert_eq!(mgr.len(), 1);
+
+        let (new_id, inserted) =
+            mgr.get_or_insert(ClipEntry::from_string("another", ClipboardKind::Clipboard));
+        assert!(inserted);
+        assert_ne!(new_id, id);
+        assert_eq!(mgr.len(), 2);
+        assert_eq!(
+            mgr.get_or_insert(ClipEntry::from_string("another", ClipboardKind::Clipboard)),
+            (new_id, false)
+        );
+    }
+
+    #[test]
+    fn test_normalize_whitespace_for_dedupe() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let _ = mgr.insert(ClipEntry::from_string("foo", ClipboardKind::Clipboard));
+        let _ = mgr.insert(ClipEntry::from_string("foo ", ClipboardKind::Clipboard));
+        assert_eq!(mgr.len(), 2);
+
+        mgr.clear();
+        mgr.set_normalize_whitespace_for_dedupe(true);
+        let first = mgr.insert(ClipEntry::from_string("foo  bar", ClipboardKind::Clipboard));
+        let second = mgr.insert(ClipEntry::from_string(" foo\tbar\n", ClipboardKind::Clipboard));
+        assert_eq!(first, second);
+        assert_eq!(mgr.len(), 1);
+        // the content is not normalized
+        assert_eq!(
+            mgr.get(first).map(|clip| clip.as_ref().clone()),
+            Some(ClipboardContent::Plaintext(" foo\tbar\n".to_string()))
+        );
+
+        let _ = mgr.insert(ClipEntry::from_string("foobar", ClipboardKind::Clipboard));
+        assert_eq!(mgr.len(), 2);
+    }
+
+    #[test]
+    fn test_dedupe_scope() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let first = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        let second = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Primary));
+        assert_eq!(first, second);
+        assert_eq!(mgr.len(), 1);
+
+        mgr.clear();
+        mgr.set_dedupe_scope(DedupeScope::PerMode);
+        let clipboard = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        let primary = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Primary));
+        let _ = mgr.insert(ClipEntry::from_string("another", ClipboardKind::Clipboard));
+        assert_ne!(clipboard, primary);
+        assert_eq!(
+            mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard)),
+            clipboard
+        );
+        assert_eq!(mgr.len(), 3);
+        assert_eq!(mgr.get(primary).map(|clip| clip.kind()), Some(ClipboardKind::Primary));
+
+        mgr.clear();
+        mgr.set_dedupe_scope(DedupeScope::None);
+        let first = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        // the current clip captured again is merged
+        assert_eq!(mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard)), first);
+        let _ = mgr.insert(ClipEntry::from_string("another", ClipboardKind::Clipboard));
+        let second = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        assert_ne!(first, second);
+        assert_eq!(mgr.len(), 3);
+
+        let mut removed =
+            mgr.remove_by_content(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        removed.sort_unstable();
+        let mut expected = vec![first, second];
+        expected.sort_unstable();
+        assert_eq!(removed, expected);
+        assert_eq!(mgr.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_kinds() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_enabled_kinds([true, false, false]);
+        let id = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+
+        assert!(mgr.mark(id, ClipboardKind::Clipboard).await.is_ok());
+        assert!(matches!(
+            mgr.mark(id, ClipboardKind::Primary).await,
+            Err(Error::KindDisabled { kind: ClipboardKind::Primary })
+        ));
+        assert!(matches!(
+            mgr.set_transient(ClipEntry::from_string("123456", ClipboardKind::Secondary)).await,
+            Err(Error::KindDisabled { kind: ClipboardKind::Secondary })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_set_transient() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let _ = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        let secret = ClipEntry::from_string("123456", ClipboardKind::Clipboard);
+
+        mgr.set_transient(secret.clone()).await.unwrap();
+        assert_eq!(backend.load(ClipboardKind::Clipboard, None).await.unwrap(), *secret.as_ref());
+        assert!(mgr.is_transient(&secret));
+        assert!(!mgr.is_transient(&ClipEntry::from_string("123456", ClipboardKind::Primary)));
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get(secret.id()).is_none());
+
+        let _ = mgr.insert(ClipEntry::from_string("another", ClipboardKind::Clipboard));
+        assert!(!mgr.is_transient(&secret));
+    }
+
+    #[tokio::test]
+    async fn test_live_clip_id() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        let id = mgr.insert(clip.clone());
+        assert_eq!(mgr.live_clip_id(ClipboardKind::Clipboard).await.unwrap(), None);
+
+        // the content is set outside of clipcat
+        backend.store(ClipboardKind::Clipboard, clip.as_ref().clone()).await.unwrap();
+        assert_eq!(mgr.live_clip_id(ClipboardKind::Clipboard).await.unwrap(), Some(id));
+        backend
+            .store(ClipboardKind::Clipboard, ClipboardContent::Plaintext("another".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(mgr.live_clip_id(ClipboardKind::Clipboard).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_is_current() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        mgr.set_enabled_kinds([true, true, false]);
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        let id = mgr.insert(clip.clone());
+        assert!(!mgr.is_current(id, ClipboardKind::Clipboard).await.unwrap());
+
+        mgr.mark(id, ClipboardKind::Clipboard).await.unwrap();
+        assert!(mgr.is_current(id, ClipboardKind::Clipboard).await.unwrap());
+        assert!(!mgr.is_current(0, ClipboardKind::Clipboard).await.unwrap());
+
+        // the content is replaced outside of clipcat
+        backend
+            .store(ClipboardKind::Clipboard, ClipboardContent::Plaintext("another".to_string()))
+            .await
+            .unwrap();
+        assert!(!mgr.is_current(id, ClipboardKind::Clipboard).await.unwrap());
+        assert!(matches!(
+            mgr.is_current(id, ClipboardKind::Secondary).await,
+            Err(Error::KindDisabled { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mark_and_remove() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let secret = ClipEntry::from_string("123456", ClipboardKind::Clipboard);
+        let id = mgr.insert(secret.clone());
+
+        assert!(mgr.mark_and_remove(id, ClipboardKind::Primary).await.unwrap());
+        assert_eq!(backend.load(ClipboardKind::Primary, None).await.unwrap(), *secret.as_ref());
+        assert!(mgr.get(id).is_none());
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        // the content is not captured again from the clipboard
+        assert!(mgr.is_transient(&ClipEntry::from_string("123456", ClipboardKind::Primary)));
+        assert!(!mgr.mark_and_remove(id, ClipboardKind::Primary).await.unwrap());
+
+        let id = mgr.insert(secret);
+        mgr.set_enabled_kinds([true, false, true]);
+        assert!(mgr.mark_and_remove(id, ClipboardKind::Primary).await.is_err());
+        assert!(mgr.get(id).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_apply() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        mgr.set_enabled_kinds([true, false, true]);
+        let clips = create_clips(3);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+        let (a, b, c) = (clips[0].id(), clips[1].id(), clips[2].id());
+
+        let results = mgr
+            .apply(vec![
+                ClipOp::Remove { id: a },
+                ClipOp::Remove { id: a },
+                ClipOp::SetTags { id: b, tags: vec!["work".to_string()] },
+                ClipOp::Mark { id: b, kind: ClipboardKind::Primary },
+                ClipOp::Mark { id: b, kind: ClipboardKind::Clipboard },
+                ClipOp::Promote { id: c },
+            ])
+            .await;
+        assert_eq!(results[0], ClipOpResult::Applied);
+        assert_eq!(results[1], ClipOpResult::NotFound);
+        assert_eq!(results[2], ClipOpResult::Applied);
+        assert!(matches!(results[3], ClipOpResult::Failed { .. }));
+        assert_eq!(results[4], ClipOpResult::Applied);
+        assert_eq!(results[5], ClipOpResult::Applied);
+
+        assert!(mgr.get(a).is_none());
+        assert_eq!(mgr.get(b).unwrap().tags().iter().collect::<Vec<_>>(), vec!["work"]);
+        assert_eq!(backend.load(ClipboardKind::Clipboard, None).await.unwrap(), *clips[1].as_ref());
+        assert_eq!(mgr.list_range(0, 1).0[0].id(), c);
+        assert_eq!(mgr.get(c).unwrap().use_count(), 0);
+    }
+
+    #[test]
+    fn test_get_current_clip_any() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let order = [ClipboardKind::Clipboard, ClipboardKind::Primary];
+        assert!(mgr.get_current_clip_any(&order).is_none());
+
+        let primary = ClipEntry::from_string("primary", ClipboardKind::Primary);
+        let _ = mgr.insert(primary.clone());
+        assert_eq!(mgr.get_current_clip_any(&order), Some(primary));
+
+        let clip = ClipEntry::from_string("clipboard", ClipboardKind::Clipboard);
+        let _ = mgr.insert(clip.clone());
+        assert_eq!(mgr.get_current_clip_any(&order), Some(clip));
+        assert!(mgr.get_current_clip_any(&[ClipboardKind::Secondary]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_current_clips() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let mut receiver = mgr.subscribe_current_clips().unwrap();
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        let id = mgr.insert(clip);
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(*receiver.borrow_and_update(), [Some(id), None, None]);
+
+        // inserting the same clip again does not change the current clip
+        let _ = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        assert!(!receiver.has_changed().unwrap());
+
+        mgr.set_transient(ClipEntry::from_string("secret", ClipboardKind::Clipboard))
+            .await
+            .unwrap();
+        assert_eq!(*receiver.borrow_and_update(), [None, None, None]);
+
+        let id = mgr.insert(ClipEntry::from_string("primary", ClipboardKind::Primary));
+        assert_eq!(*receiver.borrow_and_update(), [None, Some(id), None]);
+        assert!(mgr.remove(id));
+        assert_eq!(*receiver.borrow_and_update(), [None, None, None]);
+
+        mgr.close_change_subscribers();
+        assert!(receiver.has_changed().is_err());
+        assert!(mgr.subscribe_current_clips().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        mgr.set_read_only(true);
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        let id = mgr.insert(clip.clone());
+
+        assert!(matches!(mgr.mark(id, ClipboardKind::Clipboard).await, Err(Error::ReadOnly)));
+        assert!(matches!(
+            mgr.mark_as(id, ClipboardKind::Clipboard, &mime::TEXT_PLAIN).await,
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(
+            mgr.mark_and_remove(id, ClipboardKind::Clipboard).await,
+            Err(Error::ReadOnly)
+        ));
+        assert!(matches!(mgr.set_transient(clip.clone()).await, Err(Error::ReadOnly)));
+        let another = ClipEntry::from_string("another", ClipboardKind::Clipboard);
+        assert!(matches!(mgr.replace_active(another.clone()).await, Err(Error::ReadOnly)));
+        assert!(mgr.get(another.id()).is_none());
+        assert!(mgr.get(id).is_some());
+        assert!(backend.load(ClipboardKind::Clipboard, None).await.is_err());
+    }
+
+    // a backend which stores only text into the clipboard
+    struct TextOnlyBackend(LocalClipboardBackend);
+
+    #[async_trait::async_trait]
+    impl ClipboardBackend for TextOnlyBackend {
+        async fn load(
+            &self,
+            kind: ClipboardKind,
+            mime: Option<mime::Mime>,
+        ) -> Result<ClipboardContent, crate::backend::Error> {
+            self.0.load(kind, mime).await
+        }
+
+        async fn store(
+            &self,
+            kind: ClipboardKind,
+            data: ClipboardContent,
+        ) -> Result<(), crate::backend::Error> {
+            self.0.store(kind, data).await
+        }
+
+        async fn clear(&self, kind: ClipboardKind) -> Result<(), crate::backend::Error> {
+            self.0.clear(kind).await
+        }
+
+        fn owns(&self, kind: ClipboardKind, content: &ClipboardContent) -> bool {
+            self.0.owns(kind, content)
+        }
+
+        fn subscribe(&self) -> Result<crate::backend::Subscriber, crate::backend::Error> {
+            self.0.subscribe()
+        }
+
+        fn supported_clipboard_kinds(&self) -> Vec<ClipboardKind> {
+            self.0.supported_clipboard_kinds()
+        }
+
+        fn supported_mimes(&self) -> Vec<mime::Mime> { vec![mime::TEXT_PLAIN_UTF_8] }
+
+        fn targets(&self, _kind: ClipboardKind) -> Option<Vec<String>> {
+            Some(vec!["UTF8_STRING".to_string(), "TARGETS".to_string()])
+        }
+    }
+
+    #[test]
+    fn test_current_targets() {
+        let notification = DummyNotification::default();
+        let mgr = ClipboardManager::new(Arc::new(LocalClipboardBackend::new()), notification);
+        assert!(mgr.current_targets(ClipboardKind::Clipboard).unwrap().is_empty());
+
+        let backend = Arc::new(TextOnlyBackend(LocalClipboardBackend::new()));
+        let mgr = ClipboardManager::new(backend, DummyNotification::default());
+        assert_eq!(
+            mgr.current_targets(ClipboardKind::Clipboard).unwrap(),
+            ["UTF8_STRING".to_string(), "TARGETS".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_current_formats() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        assert!(mgr.current_formats(ClipboardKind::Clipboard).await.unwrap().is_empty());
+
+        let id = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        mgr.mark(id, ClipboardKind::Clipboard).await.unwrap();
+        assert_eq!(
+            mgr.current_formats(ClipboardKind::Clipboard).await.unwrap(),
+            [(mime::TEXT_PLAIN_UTF_8, b"clipcat".to_vec())]
+        );
+
+        mgr.set_enabled_kinds([true, false, false]);
+        assert!(matches!(
+            mgr.current_formats(ClipboardKind::Primary).await,
+            Err(Error::KindDisabled { kind: ClipboardKind::Primary })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mark_unsupported_mime() {
+        let backend = Arc::new(TextOnlyBackend(LocalClipboardBackend::new()));
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let image = ClipEntry::from_clipboard_content(
+            ClipboardContent::Image { width: 1, height: 1, bytes: vec![0; 4].into() },
+            ClipboardKind::Clipboard,
+            None,
+        );
+        let image_id = mgr.insert(image.clone());
+        let _ = mgr.insert(ClipEntry::from_string("clipcat", ClipboardKind::Clipboard));
+        let version = mgr.get(image_id).unwrap().version();
+
+        assert!(matches!(
+            mgr.mark(image_id, ClipboardKind::Clipboard).await,
+            Err(Error::UnsupportedMime { id, ref mime }) if id == image_id && mime == "image/png"
+        ));
+        // the clip is not changed
+        assert_eq!(mgr.get(image_id).unwrap().version(), version);
+        assert!(matches!(
+            mgr.mark_and_remove(image_id, ClipboardKind::Clipboard).await,
+            Err(Error::UnsupportedMime { .. })
+        ));
+        assert!(mgr.get(image_id).is_some());
+        assert!(matches!(mgr.set_transient(image).await, Err(Error::UnsupportedMime { .. })));
+
+        let html = "text/html".parse().unwrap();
+        let id =
+            mgr.insert(ClipEntry::new(b"<b>a</b>", &html, ClipboardKind::Clipboard, None).unwrap());
+        assert!(mgr.mark(id, ClipboardKind::Clipboard).await.is_ok());
+    }
+
+    // a backend whose secondary clipboard fails to store
+    struct FailingSecondaryBackend([LocalClipboardBackend; ClipboardKind::MAX_LENGTH]);
+
+    #[async_trait::async_trait]
+    impl ClipboardBackend for FailingSecondaryBackend {
+        async fn load(
+            &self,
+            kind: ClipboardKind,
+            mime: Option<mime::Mime>,
+        ) -> Result<ClipboardContent, crate::backend::Error> {
+            self.0[usize::from(kind)].load(kind, mime).await
+        }
+
+        async fn store(
+            &self,
+            kind: ClipboardKind,
+            data: ClipboardContent,
+        ) -> Result<(), crate::backend::Error> {
+            if kind == ClipboardKind::Secondary {
+                return Err(crate::backend::Error::EmptyClipboard);
+            }
+            self.0[usize::from(kind)].store(kind, data).await
+        }
+
+        async fn clear(&self, kind: ClipboardKind) -> Result<(), crate::backend::Error> {
+            self.0[usize::from(kind)].clear(kind).await
+        }
+
+        fn owns(&self, kind: ClipboardKind, content: &ClipboardContent) -> bool {
+            self.0[usize::from(kind)].owns(kind, content)
+        }
+
+        fn subscribe(&self) -> Result<crate::backend::Subscriber, crate::backend::Error> {
+            self.0[0].subscribe()
+        }
+
+        fn supported_clipboard_kinds(&self) -> Vec<ClipboardKind> {
+            self.0[0].supported_clipboard_kinds()
+        }
+
+        fn supported_mimes(&self) -> Vec<mime::Mime> { self.0[0].supported_mimes() }
+
+        fn targets(&self, kind: ClipboardKind) -> Option<Vec<String>> {
+            self.0[usize::from(kind)].targets(kind)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mark_kinds() {
+        let backend = Arc::new(FailingSecondaryBackend(Default::default()));
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let previous = ClipEntry::from_string("previous", ClipboardKind::Clipboard);
+        let previous_id = mgr.insert(previous.clone());
+        mgr.mark(previous_id, ClipboardKind::Clipboard).await.unwrap();
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+        let id = mgr.insert(clip.clone());
+        let version = mgr.get(id).unwrap().version();
+
+        assert!(matches!(
+            mgr.mark_kinds(id, &[ClipboardKind::Clipboard, ClipboardKind::Secondary]).await,
+            Err(Error::StoreClipboardKind { kind: ClipboardKind::Secondary, .. })
+        ));
+        // the clipboard stored before the failure is restored
+        assert_eq!(backend.load(ClipboardKind::Clipboard, None).await.unwrap(), *previous.as_ref());
+        assert_eq!(mgr.get(id).unwrap().version(), version);
+
+        assert!(mgr
+            .mark_kinds(id, &[ClipboardKind::Primary, ClipboardKind::Clipboard])
+            .await
+            .unwrap());
+        for kind in [ClipboardKind::Clipboard, ClipboardKind::Primary] {
+            assert_eq!(backend.load(kind, None).await.unwrap(), *clip.as_ref());
+            assert_eq!(mgr.current_clip_id(kind), Some(id));
+        }
+        assert!(!mgr.mark_kinds(0, &[ClipboardKind::Clipboard]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_cycle() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let kind = ClipboardKind::Clipboard;
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, true).await.unwrap(), None);
+
+        // newest first
+        let clips = create_clips(3);
+        let ids = clips.iter().rev().map(ClipEntry::id).collect::<Vec<_>>();
+        for clip in clips {
+            let _ = mgr.insert(clip);
+        }
+        assert_eq!(mgr.list_range(0, 3).0.iter().map(ClipEntry::id).collect::<Vec<_>>(), ids);
+
+        // without a current clip, cycling starts at the newest clip
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, false).await.unwrap(), Some(ids[0]));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, false).await.unwrap(), Some(ids[1]));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, false).await.unwrap(), Some(ids[2]));
+        assert_eq!(backend.load(kind, None).await.unwrap(), *mgr.get(ids[2]).unwrap().as_ref());
+        assert_eq!(mgr.current_clip_id(kind), Some(ids[2]));
+
+        // the order of history is kept
+        assert_eq!(mgr.list_range(0, 3).0.iter().map(ClipEntry::id).collect::<Vec<_>>(), ids);
+
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, false).await.unwrap(), None);
+        assert_eq!(mgr.current_clip_id(kind), Some(ids[2]));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, true).await.unwrap(), Some(ids[0]));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Previous, false).await.unwrap(), None);
+        assert_eq!(mgr.cycle(kind, CycleDirection::Previous, true).await.unwrap(), Some(ids[2]));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Previous, true).await.unwrap(), Some(ids[1]));
+    }
+
+    #[tokio::test]
+    async fn test_cycle_sticky_notes() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let kind = ClipboardKind::Clipboard;
+        let note = mgr.insert(ClipEntry::from_string("note", kind));
+        assert!(mgr.set_sticky(note, true).unwrap());
+        let clips = create_clips(2);
+        for clip in clips {
+            let _ = mgr.insert(clip);
+        }
+        let ids = mgr.list(0).into_iter().map(|clip| clip.id).collect::<Vec<_>>();
+        assert_eq!(ids[0], note);
+
+        // the clips are cycled through in the listed order, the older sticky
+        // note is the first one
+        mgr.mark(ids[1], kind).await.unwrap();
+        assert_eq!(mgr.list(0)[1].id, ids[1]);
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, false).await.unwrap(), Some(ids[2]));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, false).await.unwrap(), None);
+        assert_eq!(mgr.cycle(kind, CycleDirection::Next, true).await.unwrap(), Some(note));
+        assert_eq!(mgr.cycle(kind, CycleDirection::Previous, false).await.unwrap(), None);
+        assert_eq!(mgr.cycle(kind, CycleDirection::Previous, true).await.unwrap(), Some(ids[2]));
+    }
+
+    #[tokio::test]
+    async fn test_list_most_used() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let clips = create_clips(3);
+        for clip in &clips {
+            let _ = mgr.insert(clip.clone());
+        }
+        assert!(mgr.list_most_used(10).is_empty());
+
+        mgr.mark(clips[0].id(), ClipboardKind::Clipboard).await.unwrap();
+        assert!(mgr.touch(clips[0].id()));
+        assert!(mgr.touch(clips[1].id()));
+        assert_eq!(mgr.get(clips[0].id()).unwrap().use_count(), 2);
+
+        let most_used = mgr.list_most_used(10);
+        assert_eq!(
+            most_used.iter().map(ClipEntry::id).collect::<Vec<_>>(),
+            vec![clips[0].id(), clips[1].id()]
+        );
+        assert_eq!(mgr.list_most_used(1).len(), 1);
+
+        // capturing the clip again keeps the count
+        let _ = mgr.insert(clips[0].clone());
+        assert_eq!(mgr.get(clips[0].id()).unwrap().use_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replace_active() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        let previous_id = mgr.insert(ClipEntry::from_string("previous", ClipboardKind::Clipboard));
+        let clip = ClipEntry::from_string("clipcat", ClipboardKind::Clipboard);
+
+        let (id, evicted) = mgr.replace_active(clip.clone()).await.unwrap();
+        assert_eq!(id, clip.id());
+        assert!(evicted.is_empty());
+        assert_eq!(mgr.get_current_clip(ClipboardKind::Clipboard), Some(clip.clone()));
+        assert_eq!(backend.load(ClipboardKind::Clipboard, None).await.unwrap(), *clip.as_ref());
+        assert!(mgr.get(previous_id).is_some());
+
+        mgr.set_enabled_kinds([true, false, false]);
+        assert!(matches!(
+            mgr.replace_active(ClipEntry::from_string("clipcat", ClipboardKind::Primary)).await,
+            Err(Error::KindDisabled { kind: ClipboardKind::Primary })
+        ));
+    }
+
+    #[test]
+    fn test_list_by_tags() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let tagged = |text: &str, tags: &[&str]| {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_tags(tags.iter().copied());
+            clip
+        };
+        let both = mgr.insert(tagged("both", &["work", "urgent"]));
+        std::thread::sleep(Duration::from_millis(1));
+        let work = mgr.insert(tagged("work", &["work"]));
+        std::thread::sleep(Duration::from_millis(1));
+        let untagged = mgr.insert(tagged("untagged", &[]));
+
+        let ids = |tags: &[&str], mode| {
+            let tags = tags.iter().map(ToString::to_string).collect::<Vec<_>>();
+            mgr.list_by_tags(&tags, mode).iter().map(ClipEntry::id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&["work", "urgent"], TagMatch::All), vec![both]);
+        assert_eq!(ids(&["urgent", "work"], TagMatch::Any), vec![work, both]);
+        assert_eq!(ids(&["other"], TagMatch::Any), Vec::<u64>::new());
+        assert_eq!(ids(&[], TagMatch::All), vec![untagged, work, both]);
+        assert_eq!(ids(&[], TagMatch::Any), vec![untagged, work, both]);
+    }
+
+    #[test]
+    fn test_list_by_source_class() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let captured = |text: &str, class: Option<&str>| {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_source_window(class.map(|class| WindowInfo {
+                title: format!("{text} - {class}"),
+                class: class.to_string(),
+            }));
+            clip
+        };
+        let firefox = mgr.insert(captured("first", Some("firefox")));
+        std::thread::sleep(Duration::from_millis(1));
+        let _unused = mgr.insert(captured("second", Some("Alacritty")));
+        std::thread::sleep(Duration::from_millis(1));
+        let unknown = mgr.insert(captured("third", None));
+        std::thread::sleep(Duration::from_millis(1));
+        let newer_firefox = mgr.insert(captured("fourth", Some("Firefox")));
+
+        let ids = |class: &str| {
+            mgr.list_by_source_class(class).iter().map(ClipEntry::id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids("firefox"), vec![newer_firefox, firefox]);
+        assert_eq!(ids("chromium"), Vec::<u64>::new());
+        assert_eq!(ids(""), Vec::<u64>::new());
+
+        // the source window is kept when the clip is captured again by clipcat
+        let mut clip = captured("first", None);
+        clip.set_owned_by_us(true);
+        let _unused = mgr.insert(clip);
+        assert_eq!(mgr.get(firefox).unwrap().source_window().unwrap().class, "firefox");
+        assert!(mgr.get(unknown).unwrap().source_window().is_none());
+    }
+
+    #[test]
+    fn test_get_full() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_compression_threshold(Some(64));
+        let mut clip = ClipEntry::from_string("a".repeat(1024), ClipboardKind::Clipboard);
+        clip.set_label(Some("long".to_string()));
+        let id = mgr.insert(clip);
+        let snippet = ClipEntry::from_string("snippet", ClipboardKind::Clipboard);
+        mgr.insert_snippets(std::slice::from_ref(&snippet));
+
+        let full = mgr.get_full(id, 8).unwrap();
+        assert_eq!(full.entry.as_bytes(), "a".repeat(1024).as_bytes());
+        assert_eq!(full.metadata.id, id);
+        assert_eq!(full.metadata.label.as_deref(), Some("long"));
+        assert_eq!(full.metadata.preview, mgr.batch_metadata(&[id], 8)[0].preview);
+        // the size of the content before it is compressed
+        assert_eq!(full.byte_len, 1024);
+        assert!(!full.snippet);
+
+        assert!(mgr.get_full(snippet.id(), 8).unwrap().snippet);
+        assert!(mgr.get_full(0xdead, 8).is_none());
+    }
+
+    #[test]
+    fn test_neighbor() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let ids = create_clips(3).into_iter().map(|clip| mgr.insert(clip)).collect::<Vec<_>>();
+        let (oldest, middle, newest) = (ids[0], ids[1], ids[2]);
+
+        let neighbor =
+            |id: u64, direction: CycleDirection| mgr.neighbor(id, direction).map(|clip| clip.id());
+        assert_eq!(neighbor(newest, CycleDirection::Next), Some(middle));
+        assert_eq!(neighbor(middle, CycleDirection::Next), Some(oldest));
+        assert_eq!(neighbor(oldest, CycleDirection::Next), None);
+        assert_eq!(neighbor(oldest, CycleDirection::Previous), Some(middle));
+        assert_eq!(neighbor(newest, CycleDirection::Previous), None);
+        assert_eq!(neighbor(0xdead, CycleDirection::Next), None);
+    }
+
+    #[test]
+    fn test_neighbor_list_order() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let timestamp = OffsetDateTime::now_utc();
+        for text in ["a", "b", "c", "d"] {
+            let clip = ClipEntry::new(
+                text.as_bytes(),
+                &mime::TEXT_PLAIN_UTF_8,
+                ClipboardKind::Clipboard,
+                Some(timestamp),
+            )
+            .unwrap();
+            let _id = mgr.insert(clip);
+        }
+        let note = mgr.insert(ClipEntry::from_string("note", ClipboardKind::Clipboard));
+        assert!(mgr.set_sticky(note, true).unwrap());
+        let _id = mgr.insert(ClipEntry::from_string("newest", ClipboardKind::Clipboard));
+
+        // the clips with equal timestamps are walked in the order they are listed
+        let listed = mgr.list(0).into_iter().map(|clip| clip.id).collect::<Vec<_>>();
+        assert_eq!(listed[0], note);
+        let mut walked = vec![note];
+        while let Some(clip) = mgr.neighbor(walked[walked.len() - 1], CycleDirection::Next) {
+            walked.push(clip.id());
+        }
+        assert_eq!(walked, listed);
+        let mut walked = vec![listed[listed.len() - 1]];
+        while let Some(clip) = mgr.neighbor(walked[walked.len() - 1], CycleDirection::Previous) {
+            walked.push(clip.id());
+        }
+        walked.reverse();
+        assert_eq!(walked, listed);
+    }
+
+    #[test]
+    fn test_insert_apart() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let first = mgr.insert(ClipEntry::from_string("same", ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let (second, _) =
+            mgr.insert_apart(ClipEntry::from_string("same", ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let (third, _) = mgr.insert_apart(ClipEntry::from_string("same", ClipboardKind::Clipboard));
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        assert_ne!(first, third);
+        assert_eq!(mgr.len(), 3);
+        assert_eq!(
+            mgr.list(10).iter().map(|clip| clip.id).collect::<Vec<_>>(),
+            vec![third, second, first]
+        );
+
+        // the newest clip is promoted when it is captured again
+        let recaptured = mgr.insert(ClipEntry::from_string("same", ClipboardKind::Clipboard));
+        assert_eq!(recaptured, third);
+        assert_eq!(mgr.len(), 3);
+    }
+
+    #[test]
+    fn test_list_by_size() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_compression_threshold(Some(64));
+        let small = mgr.insert(ClipEntry::from_string("small", ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let medium = mgr.insert(ClipEntry::from_string("a".repeat(32), ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        // compressed, the size of the original content is checked
+        let large = mgr.insert(ClipEntry::from_string("b".repeat(1024), ClipboardKind::Clipboard));
+
+        let ids = |min: Option<u64>, max: Option<u64>| {
+            mgr.list_by_size(min, max).iter().map(ClipEntry::id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(None, None), vec![large, medium, small]);
+        assert_eq!(ids(Some(32), None), vec![large, medium]);
+        assert_eq!(ids(None, Some(32)), vec![medium, small]);
+        assert_eq!(ids(Some(1024), Some(1024)), vec![large]);
+        assert_eq!(ids(Some(6), Some(31)), Vec::<u64>::new());
+        assert_eq!(mgr.list_by_size(Some(1024), None)[0].as_bytes(), "b".repeat(1024).as_bytes());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.insert_snippets(&[ClipEntry::from_string("snippet", ClipboardKind::Clipboard)]);
+        let personal_id = mgr.insert(ClipEntry::from_string("personal", ClipboardKind::Clipboard));
+
+        mgr.set_active_profile(Some("work".to_string()));
+        assert_eq!(mgr.active_profile(), Some("work"));
+        assert_eq!(mgr.len(), 1);
+        let work_id = mgr.insert(ClipEntry::from_string("work", ClipboardKind::Clipboard));
+        assert!(mgr.get(personal_id).is_none());
+        assert_eq!(
+            mgr.list_profile(None, 10).iter().map(|m| m.id).collect::<Vec<_>>(),
+            vec![personal_id]
+        );
+
+        mgr.set_active_profile(None);
+        assert!(mgr.get(personal_id).is_some());
+        assert!(mgr.get(work_id).is_none());
+        assert_eq!(mgr.export_profile(Some("work")).len(), 1);
+
+        mgr.clear_profile(Some("work"));
+        assert!(mgr.export_profile(Some("work")).is_empty());
+        assert_eq!(mgr.len(), 2);
+    }
+
+    #[test]
+    fn test_profile_changed_event() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let personal_id = mgr.insert(ClipEntry::from_string("personal", ClipboardKind::Clipboard));
+        let (_events, receiver) = mgr.subscribe_changes(None);
+        let mut receiver = receiver.unwrap();
+
+        mgr.set_active_profile(Some("work".to_string()));
+        let changes = std::iter::from_fn(|| receiver.try_recv().ok())
+            .map(|event| event.change)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                ClipboardChange::Removed(personal_id),
+                ClipboardChange::ProfileChanged { profile: Some("work".to_string()) }
+            ]
+        );
+
+        mgr.set_active_profile(None);
+        let changes = std::iter::from_fn(|| receiver.try_recv().ok())
+            .map(|event| event.change)
+            .collect::<Vec<_>>();
+        assert_eq!(changes[0], ClipboardChange::ProfileChanged { profile: None });
+        assert!(matches!(changes[1], ClipboardChange::Added(ref clip) if clip.id() == personal_id));
+
+        // switching to the active profile changes nothing
+        mgr.set_active_profile(None);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_search() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let first_id = mgr.insert(ClipEntry::from_string("Hello World", ClipboardKind::Primary));
+        std::thread::sleep(Duration::from_millis(1));
+        let second_id =
+            mgr.insert(ClipEntry::from_string("hello clipcat", ClipboardKind::Clipboard));
+
+        let ids = mgr
+            .search("HELLO", 10, false)
+            .into_iter()
+            .map(|(metadata, _score)| metadata.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![second_id, first_id]);
+        assert_eq!(mgr.stats().clip_count, 2);
+        assert_eq!(mgr.stats().index_term_count, 3);
+        assert_eq!(mgr.stats().content_bytes, "Hello World".len() + "hello clipcat".len());
+        assert_eq!(mgr.stats().capacity, DEFAULT_CAPACITY);
+
+        let matches = mgr.search("wrld", 10, true);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0.id, first_id);
+        assert!(mgr.search("wrld", 10, false).is_empty());
+        let scores = mgr
+            .search("hello", 10, false)
+            .into_iter()
+            .map(|(_metadata, score)| score)
+            .collect::<Vec<_>>();
+        assert_eq!(scores, vec![1.0, 1.0]);
+
+        assert_eq!(mgr.first_matching("hello", false).unwrap().id(), second_id);
+        assert_eq!(mgr.first_matching("wrld", true).unwrap().id(), first_id);
+        assert!(mgr.first_matching("wrld", false).is_none());
+
+        assert!(mgr.remove(first_id));
+        assert!(mgr.search("world", 10, false).is_empty());
+        mgr.clear();
+        assert!(mgr.search("hello", 10, false).is_empty());
+        assert_eq!(mgr.stats().index_term_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_compression() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend.clone(), notification);
+        mgr.set_compression_threshold(Some(100));
+        let text = "clipcat ".repeat(100);
+        let large = ClipEntry::from_string(&text, ClipboardKind::Clipboard);
+        let small = ClipEntry::from_string("hello clipcat", ClipboardKind::Clipboard);
+        let large_id = mgr.insert(large.clone());
+        let small_id = mgr.insert(small);
+
+        let stats = mgr.stats();
+        assert_eq!(stats.compressed_clip_count, 1);
+        assert!(stats.compression_saved_bytes > 0);
+        assert_eq!(stats.content_bytes, text.len() + "hello clipcat".len());
+
+        assert_eq!(mgr.get(large_id).unwrap().as_ref(), large.as_ref());
+        assert_eq!(mgr.export(false).len(), 2);
+        assert!(mgr.export(false).iter().any(|clip| clip.as_ref() == large.as_ref()));
+        assert_eq!(mgr.search("clipcat", 10, false).len(), 2);
+        assert_eq!(mgr.insert(large.clone()), large_id);
+
+        mgr.mark(large_id, ClipboardKind::Clipboard).await.unwrap();
+        assert_eq!(backend.load(ClipboardKind::Clipboard, None).await.unwrap(), *large.as_ref());
+        assert_eq!(
+            mgr.get_current_clip(ClipboardKind::Clipboard).unwrap().as_ref(),
+            large.as_ref()
+        );
+
+        assert_eq!(mgr.remove_by_content(large), vec![large_id]);
+        assert_eq!(mgr.stats().compressed_clip_count, 0);
+        assert!(mgr.get(small_id).is_some());
+    }
+
+    #[test]
+    fn test_list_skipping_corrupt() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_compression_threshold(Some(100));
+        let large_id =
+            mgr.insert(ClipEntry::from_string("clipcat ".repeat(100), ClipboardKind::Clipboard));
+        std::thread::sleep(Duration::from_millis(1));
+        let small_id =
+            mgr.insert(ClipEntry::from_string("hello clipcat", ClipboardKind::Clipboard));
+        assert!(mgr.corrupt_ids().is_empty());
+
+        mgr.compressed.get_mut(&large_id).unwrap().corrupt();
+        let (metadata, skipped) = mgr.list_skipping_corrupt(30, SortOrder::Recency);
+        assert_eq!(skipped, 1);
+        assert_eq!(metadata.iter().map(|m| m.id).collect::<Vec<_>>(), vec![small_id]);
+        assert_eq!(mgr.list(30).len(), 1);
+        assert_eq!(mgr.corrupt_ids(), vec![large_id]);
+
+        assert!(mgr.remove(large_id));
+        assert!(mgr.corrupt_ids().is_empty());
+        assert_eq!(mgr.list_skipping_corrupt(30, SortOrder::Recency).1, 0);
+    }
+
+    #[test]
+    fn test_content_manifest() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_compression_threshold(Some(100));
+        let clips = [
+            ClipEntry::from_string("hello clipcat", ClipboardKind::Clipboard),
+            ClipEntry::from_string("clipcat ".repeat(100), ClipboardKind::Clipboard),
+        ];
+        let mut expected = clips
+            .iter()
+            .map(|clip| {
+                std::thread::sleep(Duration::from_millis(1));
+                (mgr.insert(clip.clone()), clip.content_hash())
+            })
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+
+        // the hash of a compressed clip is the one of its original content
+        assert_eq!(mgr.stats().compressed_clip_count, 1);
+        assert_eq!(mgr.content_manifest(), expected);
+    }
+
+    #[test]
+    fn test_mime_types() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        assert!(mgr.mime_types().is_empty());
+
+        let html = "text/html".parse().unwrap();
+        let _id =
+            mgr.insert(ClipEntry::new(b"<b>a</b>", &html, ClipboardKind::Clipboard, None).unwrap());
+        let _id = mgr.insert(ClipEntry::from_string("a", ClipboardKind::Clipboard));
+        let _id = mgr.insert(ClipEntry::from_string("b", ClipboardKind::Primary));
+        let _id = mgr.insert(ClipEntry::from_clipboard_content(
+            ClipboardContent::Image { width: 1, height: 1, bytes: vec![0; 4].into() },
+            ClipboardKind::Clipboard,
+            None,
+        ));
+        assert_eq!(
+            mgr.mime_types(),
+            vec![
+                (mime::TEXT_PLAIN_UTF_8.to_string(), 2),
+                ("image/png".to_string(), 1),
+                ("text/html".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resource_usage() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let text = "clipcat ".repeat(100);
+        let _id = mgr.insert(ClipEntry::from_string(&text, ClipboardKind::Clipboard));
+        let usage = mgr.resource_usage();
+        assert_eq!(usage.entry_count, 1);
+        assert!(usage.store_bytes >= text.len());
+        assert_eq!(usage.compression_ratio, None);
+
+        mgr.set_compression_threshold(Some(100));
+        let _id = mgr.insert(ClipEntry::from_string(text.repeat(2), ClipboardKind::Clipboard));
+        let usage = mgr.resource_usage();
+        assert_eq!(usage.entry_count, 2);
+        assert!(usage.store_bytes < text.len() * 3);
+        assert!(usage.compression_ratio.is_some_and(|ratio| ratio > 1.0));
+    }
+
+    #[test]
+    fn test_remove() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        assert_eq!(mgr.len(), 0);
+        assert!(!mgr.remove(43));
+
+        let clip = ClipEntry::from_string("АБВГДЕ", ClipboardKind::Primary);
+        let id = mgr.insert(clip);
+        assert_eq!(mgr.len(), 1);
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_some());
+
+        let ok = mgr.remove(id);
+        assert!(ok);
+        assert_eq!(mgr.len(), 0);
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+
+        let ok = mgr.remove(id);
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_remove_expired() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let now = OffsetDateTime::now_utc();
+
+        let mut clip = ClipEntry::from_string("expiring", ClipboardKind::Clipboard);
+        clip.set_tags(["secret"]);
+        clip.set_expires_at(Some(now + time::Duration::minutes(1)));
+        let expiring_id = mgr.insert(clip);
+        let kept_id = mgr.insert(ClipEntry::from_string("kept", ClipboardKind::Clipboard));
+
+        // capturing the clip again keeps its metadata
+        let _ = mgr.insert(ClipEntry::from_string("expiring", ClipboardKind::Clipboard));
+        let clip = mgr.get(expiring_id).unwrap();
+        assert!(clip.tags().contains("secret"));
+        assert!(clip.expires_at().is_some());
+
+        assert!(mgr.remove_expired(now).is_empty());
+        assert_eq!(mgr.remove_expired(now + time::Duration::minutes(1)), vec![expiring_id]);
+        assert!(mgr.get(expiring_id).is_none());
+        assert!(mgr.get(kept_id).is_some());
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+
+        let mut clip = ClipEntry::from_string("original", ClipboardKind::Clipboard);
+        clip.set_tags(["tag"]);
+        let id = mgr.insert(clip);
+        let _unused = mgr.insert(ClipEntry::from_string("newer", ClipboardKind::Clipboard));
+
+        let copy_id = mgr.duplicate(id).unwrap();
+        assert_ne!(copy_id, id);
+        assert_eq!(mgr.len(), 3);
+        assert_eq!(mgr.list(10)[0].id, copy_id);
+        let copy = mgr.get(copy_id).unwrap();
+        assert_eq!(copy.as_utf8_string(), "original");
+        assert!(copy.tags().is_empty());
+        assert_eq!(mgr.get(id).unwrap().tags().len(), 1);
+        assert!(mgr.duplicate(0xdead).is_none());
+    }
+
+    #[test]
+    fn test_remove_older_than() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let now = OffsetDateTime::now_utc();
 
-        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
-        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
-        assert_eq!(mgr.len(), n);
+        let mut clip = ClipEntry::from_string("old", ClipboardKind::Clipboard);
+        clip.set_timestamp(now - time::Duration::days(2));
+        let old_id = mgr.insert(clip);
+        let mut snippet = ClipEntry::from_string("snippet", ClipboardKind::Clipboard);
+        snippet.set_timestamp(now - time::Duration::days(3));
+        mgr.insert_snippets(std::slice::from_ref(&snippet));
+        let kept_id = mgr.insert(ClipEntry::from_string("kept", ClipboardKind::Clipboard));
 
-        let mut exported = mgr.export(false);
-        clips.sort_unstable();
-        exported.sort_unstable();
+        assert_eq!(mgr.remove_older_than(now - time::Duration::days(1)), vec![old_id]);
+        assert!(mgr.get(old_id).is_none());
+        assert!(mgr.get(snippet.id()).is_some());
+        assert!(mgr.get(kept_id).is_some());
+        assert!(mgr.remove_older_than(now - time::Duration::days(1)).is_empty());
+    }
 
-        assert_eq!(exported.len(), clips.len());
-        // assert_eq!(exported, clips);
+    #[test]
+    fn test_protect_until() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            1,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let now = OffsetDateTime::now_utc();
+
+        let protected_id =
+            mgr.insert(ClipEntry::from_string("protected", ClipboardKind::Clipboard));
+        assert!(mgr.protect_until(protected_id, now + time::Duration::hours(1)));
+        assert!(mgr.get(protected_id).unwrap().protected_until().is_some());
+        let evicted_id = mgr.insert(ClipEntry::from_string("evicted", ClipboardKind::Clipboard));
+        let newest_id = mgr.insert(ClipEntry::from_string("newest", ClipboardKind::Clipboard));
+        assert!(mgr.get(protected_id).is_some());
+        assert!(mgr.get(evicted_id).is_none());
+        assert_eq!(mgr.remove_older_than(now + time::Duration::days(1)), vec![newest_id]);
+        assert!(mgr.get(protected_id).is_some());
+
+        // a time in the past removes the protection
+        assert!(mgr.protect_until(protected_id, now - time::Duration::hours(1)));
+        assert!(mgr.get(protected_id).unwrap().protected_until().is_none());
+        assert_eq!(mgr.remove_older_than(now + time::Duration::days(1)), vec![protected_id]);
+        assert!(!mgr.protect_until(protected_id, now + time::Duration::hours(1)));
     }
 
     #[test]
-    fn test_replace() {
-        const MIME: mime::Mime = mime::TEXT_PLAIN_UTF_8;
+    fn test_check_capacity() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            2,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let until = OffsetDateTime::now_utc() + time::Duration::hours(1);
 
-        let data1 = "ABCDEFG";
-        let data2 = "АБВГД";
-        let clip = ClipEntry::new(data1.as_bytes(), &MIME, ClipboardKind::Clipboard, None).unwrap();
+        let first = mgr.insert(ClipEntry::from_string("first", ClipboardKind::Clipboard));
+        assert!(mgr.protect_until(first, until));
+        let second = mgr.insert(ClipEntry::from_string("second", ClipboardKind::Clipboard));
+        assert!(mgr.check_capacity().is_ok());
+
+        assert!(mgr.protect_until(second, until));
+        assert!(matches!(mgr.check_capacity(), Err(Error::HistoryFull { count: 2, capacity: 2 })));
+    }
+
+    #[test]
+    fn test_metadata_records() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend.clone(), DummyNotification::default());
+        let mut clip = ClipEntry::from_string("ssh-ed25519 AAAA", ClipboardKind::Clipboard);
+        clip.set_tags(["keys"]);
+        let id = mgr.insert(clip);
+        assert!(mgr.set_label(id, Some("my SSH key".to_string())));
+        assert!(mgr.set_sticky(id, true).unwrap());
+        let mut private = ClipEntry::from_string("secret", ClipboardKind::Clipboard);
+        private.set_private(true);
+        private.set_tags(["keys"]);
+        let private_id = mgr.insert(private);
+        let private_hash = mgr.get(private_id).unwrap().content_hash();
+        let records = mgr.export_metadata();
+        let record = records.iter().find(|record| record.label.is_some()).unwrap();
+        assert_eq!(record.content_hash, mgr.get(id).unwrap().content_hash());
+        assert!(record.tags.contains("keys"));
+        assert!(record.sticky);
+        assert!(records.iter().all(|record| record.content_hash != private_hash));
+
+        let mut other = ClipboardManager::new(backend, DummyNotification::default());
+        let mut clip = ClipEntry::from_string("ssh-ed25519 AAAA", ClipboardKind::Clipboard);
+        clip.set_tags(["imported"]);
+        let other_id = other.insert(clip);
+        let mut records = records;
+        records.push(MetadataRecord {
+            content_hash: "00".repeat(32),
+            label: Some("missing".to_string()),
+            ..MetadataRecord::default()
+        });
+        let report = other.import_metadata(records);
+        assert_eq!(report.applied, 1);
+        assert_eq!(report.unmatched, vec!["00".repeat(32)]);
+        let clip = other.get(other_id).unwrap();
+        assert_eq!(clip.label(), Some("my SSH key"));
+        assert!(clip.is_sticky());
+        assert!(clip.tags().contains("keys") && clip.tags().contains("imported"));
+        assert_eq!(clip.as_utf8_string(), "ssh-ed25519 AAAA");
+    }
+
+    #[test]
+    fn test_import_metadata_sticky_limit() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        let records = (0..=MAX_STICKY_NOTES)
+            .map(|index| {
+                let clip = ClipEntry::from_string(index.to_string(), ClipboardKind::Clipboard);
+                let _id = mgr.insert(clip.clone());
+                MetadataRecord {
+                    content_hash: clip.content_hash(),
+                    sticky: true,
+                    ..MetadataRecord::default()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let report = mgr.import_metadata(records);
+        assert_eq!(report.applied, MAX_STICKY_NOTES + 1);
+        assert_eq!(mgr.list(10).iter().filter(|clip| clip.sticky).count(), MAX_STICKY_NOTES);
+    }
+
+    #[test]
+    fn test_coalesce_growing_edits() {
+        for coalesce in [true, false] {
+            let backend = Arc::new(LocalClipboardBackend::new());
+            let mut mgr = ClipboardManager::with_capacity(
+                backend,
+                DEFAULT_CAPACITY,
+                time::Duration::seconds(5),
+                DummyNotification::default(),
+            );
+            mgr.set_coalesce_growing_edits(coalesce);
+            let mut evicted = Vec::new();
+            for text in ["h", "he", "hel", "hello"] {
+                let (_id, ids) =
+                    mgr.insert_with_evicted(ClipEntry::from_string(text, ClipboardKind::Primary));
+                evicted.extend(ids);
+            }
+            let _id = mgr.insert(ClipEntry::from_string("world", ClipboardKind::Primary));
+            if coalesce {
+                assert_eq!(evicted.len(), 3);
+                assert_eq!(mgr.len(), 2);
+                assert_eq!(mgr.list(100)[1].preview, "hello");
+            } else {
+                assert!(evicted.is_empty());
+                assert_eq!(mgr.len(), 5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_coalesce_growing_edits_non_ascii() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            DEFAULT_CAPACITY,
+            time::Duration::seconds(5),
+            DummyNotification::default(),
+        );
+        for text in ["é", "éé", "ééa", "a", "日本", "日"] {
+            let _id = mgr.insert(ClipEntry::from_string(text, ClipboardKind::Primary));
+        }
+        let texts = mgr.list(100).into_iter().map(|clip| clip.preview).collect::<Vec<_>>();
+        assert_eq!(texts, ["日", "a", "ééa"]);
+    }
+
+    #[test]
+    fn test_time_bounds() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        assert_eq!(mgr.time_bounds(None), None);
+
+        let now = OffsetDateTime::now_utc();
+        let timestamps = [now - time::Duration::days(2), now - time::Duration::days(1), now];
+        let kinds = [ClipboardKind::Clipboard, ClipboardKind::Primary, ClipboardKind::Clipboard];
+        for (i, (timestamp, kind)) in timestamps.into_iter().zip(kinds).enumerate() {
+            let mut clip = ClipEntry::from_string(i, kind);
+            clip.set_timestamp(timestamp);
+            let _id = mgr.insert(clip);
+        }
+        mgr.insert_snippets(&[ClipEntry::from_string("snippet", ClipboardKind::Clipboard)]);
+
+        assert_eq!(mgr.time_bounds(None), Some((timestamps[0], timestamps[2])));
+        assert_eq!(
+            mgr.time_bounds(Some(ClipboardKind::Primary)),
+            Some((timestamps[1], timestamps[1]))
+        );
+        assert_eq!(mgr.time_bounds(Some(ClipboardKind::Secondary)), None);
+    }
+
+    #[test]
+    fn test_sticky_notes() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            2,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+
+        let note = mgr.insert(ClipEntry::from_string("note", ClipboardKind::Clipboard));
+        assert!(mgr.set_sticky(note, true).unwrap());
+        for text in ["a", "b", "c"] {
+            let _id = mgr.insert(ClipEntry::from_string(text, ClipboardKind::Clipboard));
+        }
+        // the sticky note is neither evicted nor merged, and it is listed first
+        assert_eq!(mgr.len(), 3);
+        let copy = mgr.insert(ClipEntry::from_string("note", ClipboardKind::Clipboard));
+        assert_ne!(copy, note);
+        assert_eq!(mgr.list(100)[0].id, note);
+        assert!(mgr.list(100)[0].sticky);
+        assert_eq!(mgr.list_range(0, 1).0[0].id(), note);
+        assert!(mgr.trim_candidates(0, None).iter().all(|&id| id != note));
+
+        // the sticky note is edited in place
+        let timestamp = mgr.get(note).unwrap().timestamp();
+        let cursor = mgr.next_cursor();
+        assert_eq!(mgr.replace(note, b"edited", &mime::TEXT_PLAIN_UTF_8), (true, note));
+        let edited = mgr.get(note).unwrap();
+        assert_eq!(edited.as_utf8_string(), "edited");
+        assert_eq!(edited.timestamp(), timestamp);
+        assert!(edited.is_sticky());
+        assert_eq!(mgr.next_cursor(), cursor + 1);
+        assert_eq!(mgr.search("edited", 100, false).len(), 1);
+        assert_eq!(mgr.replace(note, b"\x89PNG", &mime::IMAGE_PNG), (false, note));
+
+        let image = ClipboardContent::Image { width: 2, height: 2, bytes: vec![255; 16].into() };
+        let image_id =
+            mgr.insert(ClipEntry::from_clipboard_content(image, ClipboardKind::Clipboard, None));
+        assert!(matches!(mgr.set_sticky(image_id, true), Err(Error::NotText { .. })));
+
+        for text in ["x", "y"] {
+            let id = mgr.insert(ClipEntry::from_string(text, ClipboardKind::Clipboard));
+            assert!(mgr.set_sticky(id, true).unwrap());
+        }
+        let id = mgr.insert(ClipEntry::from_string("z", ClipboardKind::Clipboard));
+        assert!(matches!(
+            mgr.set_sticky(id, true),
+            Err(Error::TooManyStickyNotes { limit: MAX_STICKY_NOTES })
+        ));
+        assert!(mgr.set_sticky(note, false).unwrap());
+        assert!(mgr.set_sticky(id, true).unwrap());
+        assert!(!mgr.set_sticky(0, true).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_pin_current() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let kind = ClipboardKind::Clipboard;
+        assert_eq!(mgr.pin_current(kind).unwrap(), None);
+
+        let id = mgr.insert(ClipEntry::from_string("important", kind));
+        let _ = mgr.insert(ClipEntry::from_string("newer", kind));
+        mgr.mark(id, kind).await.unwrap();
+        assert_eq!(mgr.pin_current(kind).unwrap(), Some(id));
+        assert!(mgr.get(id).unwrap().is_sticky());
+        assert_eq!(mgr.rank(id), Some(0));
+
+        let image = ClipboardContent::Image { width: 2, height: 2, bytes: vec![255; 16].into() };
+        let image_id = mgr.insert(ClipEntry::from_clipboard_content(image, kind, None));
+        mgr.mark(image_id, kind).await.unwrap();
+        assert!(matches!(mgr.pin_current(kind), Err(Error::NotText { id }) if id == image_id));
+    }
+
+    #[test]
+    fn test_sticky_notes_listed_first() {
         let backend = Arc::new(LocalClipboardBackend::new());
         let notification = DummyNotification::default();
         let mut mgr = ClipboardManager::new(backend, notification);
+        let note = mgr.insert(ClipEntry::from_string("note word", ClipboardKind::Clipboard));
+        assert!(mgr.set_sticky(note, true).unwrap());
+        std::thread::sleep(Duration::from_millis(1));
+        let newer = mgr.insert(ClipEntry::from_string("newer word", ClipboardKind::Clipboard));
+
+        let ids =
+            |clips: &[ClipEntryMetadata]| clips.iter().map(|clip| clip.id).collect::<Vec<_>>();
+        assert_eq!(ids(&mgr.list(0)), vec![note, newer]);
+        let matches = mgr.search("word", 0, false);
+        assert_eq!(matches.iter().map(|(clip, _)| clip.id).collect::<Vec<_>>(), vec![note, newer]);
+        let matches = mgr.search("word", 0, true);
+        assert_eq!(matches.iter().map(|(clip, _)| clip.id).collect::<Vec<_>>(), vec![note, newer]);
+
+        let (page, cursor) = mgr.list_after(None, 1);
+        assert_eq!(page[0].id(), note);
+        let (page, cursor) = mgr.list_after(cursor, 1);
+        assert_eq!(page[0].id(), newer);
+        assert_eq!(cursor, None);
+
+        assert_eq!(mgr.rank(note), Some(0));
+        assert_eq!(mgr.rank(newer), Some(1));
+        assert_eq!(mgr.neighbor(note, CycleDirection::Next).map(|clip| clip.id()), Some(newer));
+
+        let ids = |clips: Vec<ClipEntry>| clips.iter().map(ClipEntry::id).collect::<Vec<_>>();
+        assert_eq!(
+            mgr.list_ids().into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![note, newer]
+        );
+        assert_eq!(ids(mgr.list_since(OffsetDateTime::UNIX_EPOCH)), vec![note, newer]);
+        assert_eq!(ids(mgr.list_by_tags(&[], TagMatch::All)), vec![note, newer]);
+        assert_eq!(ids(mgr.list_by_size(None, None)), vec![note, newer]);
+        assert_eq!(mgr.first_matching("word", false).map(|clip| clip.id()), Some(note));
+        assert_eq!(mgr.first_matching("word", true).map(|clip| clip.id()), Some(note));
+        assert_eq!(ids(mgr.drain(true, false)), vec![note, newer]);
+    }
+
+    #[test]
+    fn test_evictions() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::with_capacity(
+            backend,
+            2,
+            time::Duration::milliseconds(0),
+            notification,
+        );
+        let now = OffsetDateTime::now_utc();
+        let (_events, receiver) = mgr.subscribe_changes(None);
+        let mut receiver = receiver.unwrap();
+        let mut removals = || {
+            std::iter::from_fn(|| receiver.try_recv().ok())
+                .filter(|event| {
+                    matches!(
+                        event.change,
+                        ClipboardChange::Removed(_) | ClipboardChange::Evicted { .. }
+                    )
+                })
+                .map(|event| event.change)
+                .collect::<Vec<_>>()
+        };
+
+        let mut clip = ClipEntry::from_string("old", ClipboardKind::Clipboard);
+        clip.set_timestamp(now - time::Duration::days(2));
         let old_id = mgr.insert(clip);
-        assert_eq!(mgr.len(), 1);
+        let mut clip = ClipEntry::from_string("expiring", ClipboardKind::Clipboard);
+        clip.set_expires_at(Some(now));
+        let expiring_id = mgr.insert(clip);
+        let removed_id = mgr.insert(ClipEntry::from_string("removed", ClipboardKind::Clipboard));
+        assert_eq!(
+            removals(),
+            vec![ClipboardChange::Evicted { id: old_id, reason: EvictionReason::Capacity }]
+        );
 
-        let (ok, new_id) = mgr.replace(old_id, data2.as_bytes(), &MIME);
-        assert!(ok);
-        assert_ne!(old_id, new_id);
-        assert_eq!(mgr.len(), 1);
+        let _unused = mgr.remove_expired(now);
+        let old_id = mgr.insert(ClipEntry::from_string("newer", ClipboardKind::Clipboard));
+        assert!(mgr.remove(removed_id));
+        let _unused = mgr.remove_older_than(now + time::Duration::days(1));
+        assert_eq!(
+            removals(),
+            vec![
+                ClipboardChange::Evicted { id: expiring_id, reason: EvictionReason::Ttl },
+                ClipboardChange::Removed(removed_id),
+                ClipboardChange::Evicted { id: old_id, reason: EvictionReason::Age },
+            ]
+        );
+    }
 
-        let clip = mgr.get(new_id).unwrap();
-        assert_eq!(clip.as_bytes(), data2.as_bytes());
-        assert_eq!(clip.kind(), ClipboardKind::Clipboard);
+    #[test]
+    fn test_private_clips() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+
+        let mut clip = ClipEntry::from_string("secret", ClipboardKind::Clipboard);
+        clip.set_private(true);
+        let private_id = mgr.insert(clip);
+        let kept_id = mgr.insert(ClipEntry::from_string("kept", ClipboardKind::Clipboard));
+
+        // capturing the clip again keeps it private
+        let _ = mgr.insert(ClipEntry::from_string("secret", ClipboardKind::Clipboard));
+        assert!(mgr.get(private_id).unwrap().is_private());
+        assert_eq!(mgr.len(), 2);
+
+        let persisted = mgr.persisted_clips();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].id(), kept_id);
     }
 
     #[test]
-    fn test_remove() {
+    fn test_activity() {
         let backend = Arc::new(LocalClipboardBackend::new());
         let notification = DummyNotification::default();
         let mut mgr = ClipboardManager::new(backend, notification);
-        assert_eq!(mgr.len(), 0);
-        assert!(!mgr.remove(43));
+        let since = OffsetDateTime::now_utc() - time::Duration::days(3);
+        let day = time::Duration::days(1);
+        let insert = |mgr: &mut ClipboardManager<_>, text: &str, timestamp| {
+            let clip = ClipEntry::new(
+                text.as_bytes(),
+                &mime::TEXT_PLAIN_UTF_8,
+                ClipboardKind::Clipboard,
+                Some(timestamp),
+            )
+            .unwrap();
+            mgr.insert(clip)
+        };
+        let _ = insert(&mut mgr, "before", since - day);
+        let _ = insert(&mut mgr, "first", since);
+        let _ = insert(&mut mgr, "second", since + time::Duration::hours(1));
+        let _ = insert(&mut mgr, "third", since + day * 2);
+        mgr.insert_snippets(&[ClipEntry::from_string("snippet", ClipboardKind::Clipboard)]);
 
-        let clip = ClipEntry::from_string("АБВГДЕ", ClipboardKind::Primary);
-        let id = mgr.insert(clip);
-        assert_eq!(mgr.len(), 1);
-        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
-        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_some());
+        let now = since + day * 3;
+        let activity = mgr.activity(day, since, now).unwrap();
+        assert_eq!(
+            activity,
+            vec![(since, 2), (since + day, 0), (since + day * 2, 1), (since + day * 3, 0)]
+        );
+        assert!(mgr.activity(day, now + day, now).unwrap().is_empty());
+        assert!(mgr.activity(time::Duration::ZERO, since, now).is_err());
+        assert!(mgr.activity(time::Duration::milliseconds(1), since, now).is_err());
+    }
 
-        let ok = mgr.remove(id);
-        assert!(ok);
-        assert_eq!(mgr.len(), 0);
-        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
-        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+    #[test]
+    fn test_subscribe_changes() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
 
-        let ok = mgr.remove(id);
-        assert!(!ok);
+        let (events, receiver) = mgr.subscribe_changes(None);
+        assert!(events.is_empty());
+        let mut receiver = receiver.unwrap();
+
+        let clips = create_clips(2);
+        let id = mgr.insert(clips[0].clone());
+        let _ = mgr.insert(clips[1].clone());
+        assert!(mgr.remove(id));
+
+        let first = receiver.try_recv().unwrap();
+        assert_eq!(first.change, ClipboardChange::Added(clips[0].clone()));
+        assert_eq!(receiver.try_recv().unwrap().change, ClipboardChange::Added(clips[1].clone()));
+        assert_eq!(receiver.try_recv().unwrap().change, ClipboardChange::Removed(id));
+
+        let (events, _receiver) = mgr.subscribe_changes(Some(first.cursor));
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| event.cursor > first.cursor));
+
+        let (silent_id, _evicted) = mgr.silently(|mgr| {
+            mgr.insert_with_evicted(ClipEntry::from_string("silent", ClipboardKind::Clipboard))
+        });
+        assert!(mgr.get(silent_id).is_some());
+        mgr.notify_resync();
+        let resync = receiver.try_recv().unwrap();
+        assert_eq!(resync.change, ClipboardChange::Resync);
+        assert!(resync.cursor > mgr.get(silent_id).unwrap().version());
+
+        mgr.close_change_subscribers();
+        assert!(receiver.try_recv().is_err());
+        assert!(mgr.subscribe_changes(None).1.is_none());
+    }
+
+    #[test]
+    fn test_watch_redacted_previews() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut mgr = ClipboardManager::new(backend, DummyNotification::default());
+        mgr.set_redactor(Redactor::new(vec![RedactionRule {
+            pattern: r"(token=)\w+".to_string(),
+            replacement: "${1}***".to_string(),
+        }]));
+        let mut receiver = mgr.subscribe_changes(None).1.unwrap();
+
+        let _id = mgr.insert(ClipEntry::from_string("token=secret", ClipboardKind::Clipboard));
+        let change = receiver.try_recv().unwrap().change;
+        let ClipboardChange::AddedMetadata(metadata) = mgr.redactor().change_metadata(change, 100)
+        else {
+            panic!("the change is not the metadata of an added clip");
+        };
+        assert_eq!(metadata.preview, "token=***");
     }
 
     #[test]
@@ -456,4 +5096,224 @@ mod tests {
         assert!(mgr.is_empty());
         assert_eq!(mgr.len(), 0);
     }
+
+    #[test]
+    fn test_list_sorted() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let now = OffsetDateTime::now_utc();
+
+        let snippet = ClipEntry::from_string("snippet", ClipboardKind::Clipboard);
+        mgr.insert_snippets(std::slice::from_ref(&snippet));
+        let mut ids = Vec::new();
+        for (text, seconds) in [("older", 1), ("newer", 2)] {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_timestamp(now + time::Duration::seconds(seconds));
+            ids.push(mgr.insert(clip));
+        }
+        let (older, newer) = (ids[0], ids[1]);
+
+        let listed_ids = |metadata: Vec<ClipEntryMetadata>| {
+            metadata.into_iter().map(|metadata| metadata.id).collect::<Vec<_>>()
+        };
+        assert_eq!(listed_ids(mgr.list(10)), vec![newer, older, snippet.id()]);
+        assert_eq!(
+            listed_ids(mgr.list_sorted(10, SortOrder::SnippetsFirst)),
+            vec![snippet.id(), newer, older]
+        );
+        let groups = mgr.list_grouped_sorted(SortOrder::SnippetsFirst);
+        assert_eq!(
+            groups[&ClipboardKind::Clipboard].iter().map(ClipEntry::id).collect::<Vec<_>>(),
+            vec![snippet.id(), newer, older]
+        );
+    }
+
+    #[test]
+    fn test_drain() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let n = 20;
+        let clips = create_clips(n);
+        let mut mgr = ClipboardManager::new(backend, notification);
+
+        mgr.import(&clips);
+        let snippet = ClipEntry::from_string("snippet", ClipboardKind::Clipboard);
+        mgr.insert_snippets(std::slice::from_ref(&snippet));
+
+        let drained = mgr.drain(true, false);
+        assert_eq!(drained.len(), n);
+        assert!(drained.windows(2).all(|pair| pair[0].timestamp() >= pair[1].timestamp()));
+        assert!(drained.iter().all(|clip| clip.id() != snippet.id()));
+        assert_eq!(mgr.len(), 1);
+        assert!(mgr.get(snippet.id()).is_some());
+        assert!(mgr.drain(true, false).is_empty());
+
+        let drained = mgr.drain(false, false);
+        assert_eq!(drained.iter().map(ClipEntry::id).collect::<Vec<_>>(), vec![snippet.id()]);
+        assert_eq!(mgr.len(), 0);
+        assert!(!mgr.is_snippet(snippet.id()));
+    }
+
+    #[tokio::test]
+    async fn test_drain_keep_sticky() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let note = mgr.insert(ClipEntry::from_string("note", ClipboardKind::Clipboard));
+        assert!(mgr.set_sticky(note, true).unwrap());
+        mgr.mark(note, ClipboardKind::Clipboard).await.unwrap();
+        let id = mgr.insert(ClipEntry::from_string("clip", ClipboardKind::Primary));
+        mgr.mark(id, ClipboardKind::Primary).await.unwrap();
+
+        let drained = mgr.drain(true, true);
+        assert_eq!(drained.iter().map(ClipEntry::id).collect::<Vec<_>>(), vec![id]);
+        assert!(mgr.get(note).is_some());
+        assert_eq!(
+            mgr.get_current_clip(ClipboardKind::Clipboard).map(|clip| clip.id()),
+            Some(note)
+        );
+        assert!(mgr.get_current_clip(ClipboardKind::Primary).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_take_current_clip() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+
+        let id = mgr.insert(ClipEntry::from_string("cut", ClipboardKind::Clipboard));
+        mgr.mark(id, ClipboardKind::Clipboard).await.unwrap();
+
+        let clip = mgr.take_current_clip(ClipboardKind::Clipboard).await.unwrap().unwrap();
+        assert_eq!(clip.id(), id);
+        assert!(mgr.get_current_clip(ClipboardKind::Clipboard).is_none());
+        assert!(mgr.get(id).is_some());
+        assert!(mgr.take_current_clip(ClipboardKind::Clipboard).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_auto_tags() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        mgr.set_auto_tagger(AutoTagger::new(vec![AutoTagRule {
+            pattern: "https?://".to_string(),
+            tag: "url".to_string(),
+        }]));
+
+        let url = ClipEntry::from_string("https://example.com", ClipboardKind::Clipboard);
+        let id = mgr.insert(url.clone());
+        assert!(mgr.get(id).unwrap().auto_tags().contains("url"));
+
+        // the user removes the tag, capturing the clip again does not set it
+        assert!(mgr.set_tags(id, vec!["site".to_string()]));
+        let _id = mgr.insert(url);
+        let clip = mgr.get(id).unwrap();
+        assert_eq!(clip.tags().iter().collect::<Vec<_>>(), ["site"]);
+        assert!(clip.auto_tags().is_empty());
+    }
+
+    #[test]
+    fn test_make_room_at() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let now = OffsetDateTime::now_utc();
+
+        let mut ids = Vec::new();
+        for (i, text) in ["first", "second", "third"].into_iter().enumerate() {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_timestamp(now - time::Duration::nanoseconds(i64::try_from(i).unwrap()));
+            ids.push(mgr.insert(clip));
+        }
+        let mut insert_at = |mgr: &mut ClipboardManager<_>, position: usize, text: &str| {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            let timestamp = mgr.make_room_at(position);
+            clip.set_timestamp(timestamp);
+            let id = mgr.insert(clip);
+            ids.insert(position.min(ids.len()), id);
+        };
+
+        // the clips are a nanosecond apart, the older ones are moved back
+        insert_at(&mut mgr, 1, "between");
+        insert_at(&mut mgr, 0, "newest");
+        insert_at(&mut mgr, 10, "oldest");
+        let listed = mgr.list(10).into_iter().map(|clip| clip.id).collect::<Vec<_>>();
+        assert_eq!(listed, ids);
+    }
+
+    #[test]
+    fn test_make_room_at_equal_timestamps() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let now = OffsetDateTime::now_utc();
+        for text in ["first", "second", "third"] {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_timestamp(now);
+            let _id = mgr.insert(clip);
+        }
+
+        for (position, text) in [(1, "between"), (3, "after"), (2, "again")] {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_timestamp(mgr.make_room_at(position));
+            let id = mgr.insert(clip);
+            assert_eq!(mgr.rank(id), Some(position));
+        }
+        assert_eq!(mgr.len(), 6);
+    }
+
+    #[test]
+    fn test_make_room_at_sticky_notes() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+        let note = mgr.insert(ClipEntry::from_string("note", ClipboardKind::Clipboard));
+        assert!(mgr.set_sticky(note, true).unwrap());
+        let clips = create_clips(3);
+        for clip in clips {
+            let _ = mgr.insert(clip);
+        }
+
+        // positions count the sticky note listed first
+        for (position, text) in [(2, "second"), (0, "first")] {
+            let mut clip = ClipEntry::from_string(text, ClipboardKind::Clipboard);
+            clip.set_timestamp(mgr.make_room_at(position));
+            let id = mgr.insert(clip);
+            assert_eq!(mgr.rank(id), Some(position.max(1)));
+        }
+        assert_eq!(mgr.rank(note), Some(0));
+    }
+
+    #[test]
+    fn test_diff_since() {
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let notification = DummyNotification::default();
+        let mut mgr = ClipboardManager::new(backend, notification);
+
+        let removed_id = mgr.insert(ClipEntry::from_string("removed", ClipboardKind::Clipboard));
+        let cursor = mgr.diff_since(0).new_cursor;
+        let added_id = mgr.insert(ClipEntry::from_string("added", ClipboardKind::Clipboard));
+        assert!(mgr.remove(removed_id));
+        let transient_id =
+            mgr.insert(ClipEntry::from_string("transient", ClipboardKind::Clipboard));
+        assert!(mgr.remove(transient_id));
+
+        let delta = mgr.diff_since(cursor);
+        assert!(!delta.resync_required);
+        assert_eq!(delta.added.iter().map(ClipEntry::id).collect::<Vec<_>>(), vec![added_id]);
+        let mut removed = vec![removed_id, transient_id];
+        removed.sort_unstable();
+        assert_eq!(delta.removed, removed);
+
+        let up_to_date = mgr.diff_since(delta.new_cursor);
+        assert!(up_to_date.added.is_empty() && up_to_date.removed.is_empty());
+        assert!(!up_to_date.resync_required);
+
+        // changes before the first retained one are unknown
+        assert!(mgr.diff_since(0).resync_required);
+        mgr.notify_resync();
+        assert!(mgr.diff_since(delta.new_cursor).resync_required);
+    }
 }
@@ -0,0 +1,89 @@
+use super::text_index::tokenize;
+
+// points of a matched character, and the bonuses of a character following the
+// previous matched one or starting a word
+const MATCH_POINTS: usize = 1;
+const CONSECUTIVE_BONUS: usize = 2;
+const WORD_START_BONUS: usize = 2;
+
+/// Scores `text` by matching the characters of `query` in order,
+/// case-insensitively, whitespaces of `query` are ignored.
+///
+/// Returns `None` if the characters of `query` are not found in order,
+/// otherwise a score of at most 1, higher if the characters are consecutive or
+/// start words.
+#[allow(clippy::cast_precision_loss)]
+pub fn fuzzy_score(query: &str, text: &str) -> Option<f32> {
+    let query = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut matched = 0;
+    let mut points = 0;
+    let mut previous_matched = false;
+    let mut previous = None;
+    for c in text.to_lowercase().chars() {
+        if matched == query.len() {
+            break;
+        }
+        if c == query[matched] {
+            points += MATCH_POINTS;
+            if previous_matched {
+                points += CONSECUTIVE_BONUS;
+            }
+            if previous.map_or(true, |previous: char| !previous.is_alphanumeric()) {
+                points += WORD_START_BONUS;
+            }
+            matched += 1;
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+        previous = Some(c);
+    }
+
+    let max_points = query.len() * (MATCH_POINTS + CONSECUTIVE_BONUS + WORD_START_BONUS);
+    (matched == query.len()).then(|| points as f32 / max_points as f32)
+}
+
+/// Scores `text` by the position of the first word of `query`, 1 if `text`
+/// starts with it and lower the later it is found.
+#[allow(clippy::cast_precision_loss)]
+pub fn position_score(query: &str, text: &str) -> f32 {
+    let position = tokenize(query)
+        .next()
+        .and_then(|term| text.to_lowercase().find(&term))
+        .unwrap_or(text.len());
+    1.0 / (1.0 + position as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fuzzy_score, position_score};
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("xyz", "clipcat"), None);
+        assert_eq!(fuzzy_score("tac", "clipcat"), None);
+        assert_eq!(fuzzy_score("", "clipcat"), None);
+
+        let contiguous = fuzzy_score("clip", "clipcat").unwrap();
+        let scattered = fuzzy_score("cpct", "clipcat").unwrap();
+        assert!(contiguous > scattered);
+        assert!(contiguous <= 1.0);
+
+        let word_starts = fuzzy_score("cm", "clipboard manager").unwrap();
+        let inside_words = fuzzy_score("pa", "clipboard manager").unwrap();
+        assert!(word_starts > inside_words);
+
+        assert_eq!(fuzzy_score("Clip Cat", "clipcat"), fuzzy_score("clipcat", "CLIPCAT"));
+    }
+
+    #[test]
+    fn test_position_score() {
+        assert!((position_score("hello", "Hello World") - 1.0).abs() < f32::EPSILON);
+        assert!(position_score("world", "Hello World") < position_score("hello", "Hello World"));
+        assert!(position_score("clipcat", "Hello World") < position_score("world", "Hello World"));
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::{HashMap, HashSet};
+
+/// An inverted index of the terms of text clips.
+///
+/// Terms are the alphanumeric words of a text folded to lowercase, a clip
+/// matches a query if it contains all terms of the query.
+#[derive(Debug, Default)]
+pub struct TextIndex {
+    postings: HashMap<String, HashSet<u64>>,
+
+    terms: HashMap<u64, Vec<String>>,
+}
+
+impl TextIndex {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn clear(&mut self) {
+        self.postings.clear();
+        self.terms.clear();
+    }
+
+    pub fn insert(&mut self, id: u64, text: &str) {
+        // ids are derived from content, the text of an indexed clip never
+        // changes
+        if self.terms.contains_key(&id) {
+            return;
+        }
+
+        let terms = tokenize(text).collect::<HashSet<_>>().into_iter().collect::<Vec<_>>();
+        for term in &terms {
+            let _ = self.postings.entry(term.clone()).or_default().insert(id);
+        }
+        drop(self.terms.insert(id, terms));
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        let Some(terms) = self.terms.remove(&id) else {
+            return;
+        };
+        for term in terms {
+            if let Some(ids) = self.postings.get_mut(&term) {
+                let _ = ids.remove(&id);
+                if ids.is_empty() {
+                    drop(self.postings.remove(&term));
+                }
+            }
+        }
+    }
+
+    /// Returns the ids of clips containing all terms of `query`.
+    pub fn search(&self, query: &str) -> HashSet<u64> {
+        let mut postings =
+            tokenize(query).map(|term| self.postings.get(&term)).collect::<Option<Vec<_>>>();
+        let Some(postings) = postings.as_mut() else {
+            return HashSet::new();
+        };
+
+        // start from the rarest term to keep intersections small
+        postings.sort_unstable_by_key(|ids| ids.len());
+        let Some((first, rest)) = postings.split_first() else {
+            return HashSet::new();
+        };
+        first.iter().filter(|id| rest.iter().all(|ids| ids.contains(id))).copied().collect()
+    }
+
+    pub fn term_count(&self) -> usize { self.postings.len() }
+
+    /// Returns the approximate memory used by the index in bytes.
+    pub fn memory_usage(&self) -> usize {
+        let id_size = size_of::<u64>();
+        let postings = self
+            .postings
+            .iter()
+            .map(|(term, ids)| term.capacity() + ids.capacity() * id_size)
+            .sum::<usize>();
+        let terms = self
+            .terms
+            .values()
+            .map(|terms| {
+                id_size
+                    + terms.iter().map(|term| term.capacity() + size_of::<String>()).sum::<usize>()
+            })
+            .sum::<usize>();
+        postings + terms
+    }
+}
+
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::TextIndex;
+
+    #[test]
+    fn test_search() {
+        let mut index = TextIndex::new();
+        index.insert(1, "Hello, World!");
+        index.insert(2, "hello clipcat");
+        index.insert(3, "Ünïcode WORLD");
+
+        assert_eq!(index.search("HELLO"), HashSet::from([1, 2]));
+        assert_eq!(index.search("world hello"), HashSet::from([1]));
+        assert_eq!(index.search("ünïcode"), HashSet::from([3]));
+        assert!(index.search("missing hello").is_empty());
+        assert!(index.search("  ").is_empty());
+        assert_eq!(index.term_count(), 4);
+
+        index.remove(1);
+        assert!(index.search("world hello").is_empty());
+        assert_eq!(index.search("world"), HashSet::from([3]));
+        assert_eq!(index.term_count(), 4);
+
+        index.clear();
+        assert_eq!(index.term_count(), 0);
+        assert_eq!(index.memory_usage(), 0);
+    }
+}
@@ -0,0 +1,119 @@
+use clipcat_base::{ClipEntry, ClipEntryMetadata, ClipboardChange};
+use regex::Regex;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedactionRule {
+    /// A regular expression searched in the previews of clips.
+    pub pattern: String,
+
+    /// The text replacing each match of `pattern`, groups of the match may
+    /// be referred like `$1`.
+    pub replacement: String,
+}
+
+/// Redacts the previews of clips sent to clients, the stored content and the
+/// content returned by `get` are never changed.
+#[derive(Clone, Debug, Default)]
+pub struct Redactor {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    /// Creates a redactor with the rules, the rules with an invalid pattern
+    /// are ignored.
+    pub fn new(rules: Vec<RedactionRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|RedactionRule { pattern, replacement }| match Regex::new(&pattern) {
+                Ok(regex) => Some((regex, replacement)),
+                Err(err) => {
+                    tracing::warn!("Ignore redaction rule of `{pattern}`, error: {err}");
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Applies the rules in order to `text`.
+    pub fn redact(&self, mut text: String) -> String {
+        for (regex, replacement) in &self.rules {
+            if regex.is_match(&text) {
+                text = regex.replace_all(&text, replacement.as_str()).into_owned();
+            }
+        }
+        text
+    }
+
+    /// Returns the metadata of `entry` with the preview redacted.
+    pub fn metadata(&self, entry: &ClipEntry, preview_length: usize) -> ClipEntryMetadata {
+        if self.rules.is_empty() {
+            entry.metadata(Some(preview_length))
+        } else {
+            entry.redacted_metadata(Some(preview_length), |line| self.redact(line))
+        }
+    }
+
+    /// Returns the change with the added or updated clip replaced by its
+    /// metadata like [`ClipboardChange::into_metadata`], with the preview
+    /// redacted.
+    pub fn change_metadata(
+        &self,
+        change: ClipboardChange,
+        preview_length: usize,
+    ) -> ClipboardChange {
+        match change {
+            ClipboardChange::Added(entry) => {
+                ClipboardChange::AddedMetadata(self.metadata(&entry, preview_length))
+            }
+            ClipboardChange::Updated(entry) => {
+                ClipboardChange::UpdatedMetadata(self.metadata(&entry, preview_length))
+            }
+            change => change,
+        }
+    }
+
+    /// Returns the patterns of the active rules.
+    pub fn patterns(&self) -> Vec<String> {
+        self.rules.iter().map(|(regex, _)| regex.as_str().to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardKind};
+
+    use super::{RedactionRule, Redactor};
+
+    #[test]
+    fn test_redact() {
+        let redactor = Redactor::new(vec![
+            RedactionRule {
+                pattern: r"(token=)\w+".to_string(),
+                replacement: "${1}***".to_string(),
+            },
+            RedactionRule { pattern: r"\d{4}-\d{4}".to_string(), replacement: "****".to_string() },
+            RedactionRule { pattern: r"(".to_string(), replacement: String::new() },
+        ]);
+        assert_eq!(
+            redactor.patterns(),
+            vec![r"(token=)\w+".to_string(), r"\d{4}-\d{4}".to_string()]
+        );
+
+        let entry = ClipEntry::from_string(
+            "curl example.com?token=abcdefghijklmnop card 1234-5678",
+            ClipboardKind::Clipboard,
+        );
+        assert_eq!(redactor.metadata(&entry, 100).preview, "curl example.com?token=*** card ****");
+        // the text matching a rule is redacted before the preview is truncated
+        assert_eq!(redactor.metadata(&entry, 33).preview, "curl example.com?token=*** car...");
+        assert_eq!(
+            entry.as_utf8_string(),
+            "curl example.com?token=abcdefghijklmnop card 1234-5678"
+        );
+
+        let entry = ClipEntry::from_string("plain text", ClipboardKind::Clipboard);
+        assert_eq!(redactor.metadata(&entry, 100).preview, "plain text");
+        assert_eq!(Redactor::default().metadata(&entry, 100).preview, "plain text");
+    }
+}
@@ -0,0 +1,208 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use clipcat_base::ClipEntry;
+use futures::{FutureExt, StreamExt};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    Mutex,
+};
+
+use crate::{
+    config::ReplicationConfig, history::HistoryManager, manager::ClipboardManager, notification,
+    watcher::ClipboardWatcherHealth,
+};
+
+/// A secondary history file mirroring the history file.
+struct Replica {
+    path: PathBuf,
+
+    // opened on the first sync, opened again after it fails
+    history: Option<HistoryManager>,
+
+    // whether the replica holds the latest clips
+    synced: bool,
+}
+
+impl Replica {
+    const fn new(path: PathBuf) -> Self { Self { path, history: None, synced: false } }
+
+    async fn sync(&mut self, clips: &[ClipEntry]) -> Result<(), crate::history::Error> {
+        let history = match self.history.as_mut() {
+            Some(history) => history,
+            None => self.history.insert(HistoryManager::new(&self.path).await?),
+        };
+        if let Err(err) = history.compact(clips).await {
+            self.history = None;
+            return Err(err);
+        }
+        self.synced = true;
+        Ok(())
+    }
+}
+
+/// Mirrors the history into secondary history files, like on another disk or
+/// on a share of another machine, to fail over if the history file is lost.
+///
+/// The changes of the history are followed from the journal of the clipboard
+/// manager, the replicas are rewritten with the saved clips at most once per
+/// interval. Mirroring is best effort, a failing replica never blocks the
+/// history, the failures and the changes not mirrored yet are reported in the
+/// diagnostics.
+pub struct Replicator {
+    replicas: Vec<Replica>,
+
+    interval: Duration,
+
+    // changes received since all replicas were synced
+    pending_changes: u64,
+
+    health: ClipboardWatcherHealth,
+}
+
+impl Replicator {
+    pub fn new(
+        ReplicationConfig { targets, interval, .. }: ReplicationConfig,
+        health: ClipboardWatcherHealth,
+    ) -> Self {
+        let replicas = targets.into_iter().map(Replica::new).collect();
+        Self { replicas, interval, pending_changes: 0, health }
+    }
+
+    #[allow(clippy::redundant_pub_crate)]
+    pub async fn serve<Notification>(
+        mut self,
+        clipboard_manager: Arc<Mutex<ClipboardManager<Notification>>>,
+        mut change_receiver: broadcast::Receiver<clipcat_base::ClipboardEvent>,
+        shutdown_signal: sigfinn::Shutdown,
+    ) where
+        Notification: notification::Notification,
+    {
+        let mut shutdown_signal = shutdown_signal.into_stream();
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // the replicas are synced at start in case they are behind
+        self.mark_unsynced(0);
+        loop {
+            tokio::select! {
+                event = change_receiver.recv().fuse() => match event {
+                    Ok(_) => self.mark_unsynced(1),
+                    Err(RecvError::Lagged(count)) => self.mark_unsynced(count),
+                    Err(RecvError::Closed) => break,
+                },
+                _ = interval.tick() => self.sync(&clipboard_manager).await,
+                _ = shutdown_signal.next() => break,
+            }
+        }
+        // mirror the last changes before shutting down
+        self.sync(&clipboard_manager).await;
+    }
+
+    fn mark_unsynced(&mut self, count: u64) {
+        for replica in &mut self.replicas {
+            replica.synced = false;
+        }
+        self.pending_changes += count;
+        self.health.set_replication_lag(self.pending_changes);
+    }
+
+    async fn sync<Notification>(
+        &mut self,
+        clipboard_manager: &Mutex<ClipboardManager<Notification>>,
+    ) where
+        Notification: notification::Notification,
+    {
+        if self.replicas.iter().all(|replica| replica.synced) {
+            return;
+        }
+        let clips = clipboard_manager.lock().await.persisted_clips();
+        for replica in self.replicas.iter_mut().filter(|replica| !replica.synced) {
+            if let Err(err) = replica.sync(&clips).await {
+                let message = format!(
+                    "Could not mirror history to `{path}`, error: {err}",
+                    path = replica.path.display()
+                );
+                tracing::warn!("{message}");
+                self.health.record_replication_failure();
+                self.health.record_error(None, message);
+            }
+        }
+        if self.replicas.iter().all(|replica| replica.synced) {
+            self.pending_changes = 0;
+        }
+        self.health.set_replication_lag(self.pending_changes);
+    }
+}
+
+/// Loads the clips of the first replica holding any, to restore the history
+/// if the history file is missing.
+pub async fn load_from_replicas(targets: &[PathBuf]) -> Option<(PathBuf, Vec<ClipEntry>)> {
+    for path in targets {
+        if !HistoryManager::exists(path) {
+            continue;
+        }
+        match HistoryManager::new(path).await {
+            Ok(mut history) => match history.load().await {
+                Ok(clips) if !clips.is_empty() => return Some((path.clone(), clips)),
+                Ok(_) => {}
+                Err(err) => tracing::warn!(
+                    "Could not load history from replica `{path}`, error: {err}",
+                    path = path.display()
+                ),
+            },
+            Err(err) => tracing::warn!(
+                "Could not open replica `{path}`, error: {err}",
+                path = path.display()
+            ),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use clipcat_base::{ClipEntry, ClipboardKind};
+    use tokio::sync::Mutex;
+
+    use super::{load_from_replicas, Replicator};
+    use crate::{
+        backend::LocalClipboardBackend, config::ReplicationConfig, manager::ClipboardManager,
+        notification::DummyNotification, watcher::ClipboardWatcherHealth,
+    };
+
+    #[tokio::test]
+    async fn test_replicate() {
+        let dir =
+            std::env::temp_dir().join(format!("clipcat-test-replicate-{}", std::process::id()));
+        let targets = vec![dir.join("a"), dir.join("b")];
+        let health = ClipboardWatcherHealth::new([true; 3], [false; 3]);
+        let mut replicator = Replicator::new(
+            ReplicationConfig {
+                targets: targets.clone(),
+                interval: std::time::Duration::from_secs(1),
+                reconcile_on_startup: true,
+            },
+            health.clone(),
+        );
+
+        let backend = Arc::new(LocalClipboardBackend::new());
+        let mut manager = ClipboardManager::new(backend, DummyNotification::default());
+        let _id = manager.insert(ClipEntry::from_string("hello", ClipboardKind::Clipboard));
+        let manager = Mutex::new(manager);
+
+        assert!(load_from_replicas(&targets).await.is_none());
+        replicator.mark_unsynced(1);
+        assert_eq!(health.diagnostics().replication_lag, 1);
+        replicator.sync(&manager).await;
+        assert_eq!(health.diagnostics().replication_lag, 0);
+        assert_eq!(health.diagnostics().replication_failures, 0);
+
+        let (path, clips) = load_from_replicas(&targets).await.unwrap();
+        assert_eq!(path, targets[0]);
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].as_utf8_string(), "hello");
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+use clipcat_base::{ClipEntry, ClipboardContent};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static HORIZONTAL_WHITESPACES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[^\S\r\n]+").expect("regular expression is valid; qed"));
+
+// scheme and authority of URLs, the path and the query are case sensitive
+static URL_SCHEME_AND_AUTHORITY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b[A-Za-z][A-Za-z0-9+.\-]*://[^\s/?#]+").expect("regular expression is valid; qed")
+});
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ContentTransform {
+    /// Removes leading and trailing whitespaces.
+    Trim,
+
+    /// Replaces runs of spaces and tabs with a single space, line breaks are
+    /// kept.
+    CollapseWhitespace,
+
+    /// Lowercases the scheme and the host of URLs.
+    LowercaseUrls,
+}
+
+impl ContentTransform {
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Trim => "trim",
+            Self::CollapseWhitespace => "collapse-whitespace",
+            Self::LowercaseUrls => "lowercase-urls",
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Self::Trim => text.trim().to_string(),
+            Self::CollapseWhitespace => HORIZONTAL_WHITESPACES.replace_all(text, " ").into_owned(),
+            Self::LowercaseUrls => URL_SCHEME_AND_AUTHORITY
+                .replace_all(text, |caps: &regex::Captures<'_>| caps[0].to_lowercase())
+                .into_owned(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ContentTransformRule {
+    /// The mime type of clips to transform, `text/*` matches all text clips.
+    pub mime: mime::Mime,
+
+    /// Transforms applied in order.
+    pub transforms: Vec<ContentTransform>,
+}
+
+impl ContentTransformRule {
+    fn matches(&self, mime: &mime::Mime) -> bool {
+        self.mime.type_() == mime.type_()
+            && (self.mime.subtype() == mime::STAR || self.mime.subtype() == mime.subtype())
+    }
+}
+
+/// Transforms text clips before they are stored, with the first rule matching
+/// the mime type of a clip.
+#[derive(Clone, Debug, Default)]
+pub struct ContentTransformer {
+    rules: Vec<ContentTransformRule>,
+}
+
+impl ContentTransformer {
+    pub const fn new(rules: Vec<ContentTransformRule>) -> Self { Self { rules } }
+
+    pub fn transform(&self, entry: ClipEntry) -> ClipEntry {
+        let ClipboardContent::Plaintext(text) = entry.as_ref() else {
+            return entry;
+        };
+        let mime = entry.mime();
+        let Some(rule) = self.rules.iter().find(|rule| rule.matches(&mime)) else {
+            return entry;
+        };
+
+        let transformed =
+            rule.transforms.iter().fold(text.clone(), |text, transform| transform.apply(&text));
+        if &transformed == text {
+            return entry;
+        }
+        entry.with_text(transformed)
+    }
+
+    /// Describes the active transforms, one rule per line like
+    /// `text/plain: trim, lowercase-urls`.
+    pub fn descriptions(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter(|rule| !rule.transforms.is_empty())
+            .map(|rule| {
+                let names = rule
+                    .transforms
+                    .iter()
+                    .map(|transform| transform.name())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{}: {names}", rule.mime.essence_str())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::{ClipEntry, ClipboardKind};
+
+    use super::{ContentTransform, ContentTransformRule, ContentTransformer};
+
+    #[test]
+    fn test_transform() {
+        let transformer = ContentTransformer::new(vec![ContentTransformRule {
+            mime: "text/*".parse().unwrap(),
+            transforms: vec![
+                ContentTransform::Trim,
+                ContentTransform::CollapseWhitespace,
+                ContentTransform::LowercaseUrls,
+            ],
+        }]);
+        let entry = ClipEntry::from_string(
+            "  see \t HTTPS://Example.COM/Path?Q=1\nnext   line \n",
+            ClipboardKind::Clipboard,
+        );
+        let clip = transformer.transform(entry.clone());
+        assert_eq!(clip.as_utf8_string(), "see https://example.com/Path?Q=1\nnext line");
+        assert_eq!(clip.kind(), entry.kind());
+        assert_eq!(clip.timestamp(), entry.timestamp());
+        assert_eq!(
+            transformer.descriptions(),
+            vec!["text/*: trim, collapse-whitespace, lowercase-urls".to_string()]
+        );
+
+        let entry = ClipEntry::from_string("  raw  ", ClipboardKind::Clipboard);
+        assert_eq!(ContentTransformer::default().transform(entry.clone()), entry);
+    }
+}
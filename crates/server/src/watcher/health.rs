@@ -0,0 +1,151 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use clipcat_base::{ClipboardKind, DiagnosticError, Diagnostics, KindDiagnostics};
+use parking_lot::Mutex;
+use time::OffsetDateTime;
+
+const MAX_RECENT_ERRORS: usize = 16;
+
+/// Records the health of the clipboard watcher worker.
+#[derive(Clone)]
+pub struct Health {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    worker_running: bool,
+
+    enabled_kinds: [bool; ClipboardKind::MAX_LENGTH],
+
+    last_captures: [Option<OffsetDateTime>; ClipboardKind::MAX_LENGTH],
+
+    targets_supported: [bool; ClipboardKind::MAX_LENGTH],
+
+    recent_errors: VecDeque<DiagnosticError>,
+
+    self_captures_suppressed: u64,
+
+    unsaved_changes: u64,
+
+    forward_failures: u64,
+
+    replication_failures: u64,
+
+    replication_lag: u64,
+}
+
+impl Health {
+    pub fn new(
+        enabled_kinds: [bool; ClipboardKind::MAX_LENGTH],
+        targets_supported: [bool; ClipboardKind::MAX_LENGTH],
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                worker_running: false,
+                enabled_kinds,
+                last_captures: [None; ClipboardKind::MAX_LENGTH],
+                targets_supported,
+                recent_errors: VecDeque::with_capacity(MAX_RECENT_ERRORS),
+                self_captures_suppressed: 0,
+                unsaved_changes: 0,
+                forward_failures: 0,
+                replication_failures: 0,
+                replication_lag: 0,
+            })),
+        }
+    }
+
+    pub fn set_worker_running(&self, running: bool) { self.inner.lock().worker_running = running; }
+
+    pub fn record_capture(&self, kind: ClipboardKind) {
+        self.inner.lock().last_captures[usize::from(kind)] = Some(OffsetDateTime::now_utc());
+    }
+
+    /// Records a captured clip which clipcat stored into the clipboard itself.
+    pub fn record_self_capture(&self) { self.inner.lock().self_captures_suppressed += 1; }
+
+    /// Records the number of changes of history which are not saved yet.
+    pub fn set_unsaved_changes(&self, count: u64) { self.inner.lock().unsaved_changes = count; }
+
+    /// Records clips which could not be forwarded to the forward program.
+    pub fn record_forward_failures(&self, count: u64) {
+        self.inner.lock().forward_failures += count;
+    }
+
+    /// Records a replica which could not be synced.
+    pub fn record_replication_failure(&self) { self.inner.lock().replication_failures += 1; }
+
+    /// Records the number of changes of history which are not mirrored to all
+    /// replicas yet.
+    pub fn set_replication_lag(&self, count: u64) { self.inner.lock().replication_lag = count; }
+
+    pub fn record_error(&self, kind: Option<ClipboardKind>, message: String) {
+        let error = DiagnosticError { timestamp: OffsetDateTime::now_utc(), kind, message };
+        let mut inner = self.inner.lock();
+        if inner.recent_errors.len() == MAX_RECENT_ERRORS {
+            drop(inner.recent_errors.pop_front());
+        }
+        inner.recent_errors.push_back(error);
+    }
+
+    pub fn diagnostics(&self) -> Diagnostics {
+        let inner = self.inner.lock();
+        let kinds = (0..ClipboardKind::MAX_LENGTH)
+            .map(|i| KindDiagnostics {
+                kind: ClipboardKind::from(i),
+                enabled: inner.enabled_kinds[i],
+                last_capture: inner.last_captures[i],
+                targets_supported: inner.targets_supported[i],
+            })
+            .collect();
+        Diagnostics {
+            worker_running: inner.worker_running,
+            kinds,
+            recent_errors: inner.recent_errors.iter().cloned().collect(),
+            self_captures_suppressed: inner.self_captures_suppressed,
+            unsaved_changes: inner.unsaved_changes,
+            forward_failures: inner.forward_failures,
+            replication_failures: inner.replication_failures,
+            replication_lag: inner.replication_lag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clipcat_base::ClipboardKind;
+
+    use super::{Health, MAX_RECENT_ERRORS};
+
+    #[test]
+    fn test_diagnostics() {
+        let health = Health::new([true, false, false], [true, false, false]);
+        health.set_worker_running(true);
+        health.record_capture(ClipboardKind::Clipboard);
+        health.record_self_capture();
+        health.set_unsaved_changes(3);
+        health.record_forward_failures(2);
+        health.record_replication_failure();
+        health.set_replication_lag(4);
+        for i in 0..=MAX_RECENT_ERRORS {
+            health.record_error(Some(ClipboardKind::Primary), i.to_string());
+        }
+
+        let diagnostics = health.diagnostics();
+        assert!(diagnostics.worker_running);
+        assert_eq!(diagnostics.kinds.len(), ClipboardKind::MAX_LENGTH);
+        assert!(diagnostics.kinds[0].enabled);
+        assert!(diagnostics.kinds[0].last_capture.is_some());
+        assert!(diagnostics.kinds[0].targets_supported);
+        assert!(!diagnostics.kinds[1].enabled);
+        assert!(diagnostics.kinds[1].last_capture.is_none());
+        assert!(!diagnostics.kinds[1].targets_supported);
+        assert_eq!(diagnostics.recent_errors.len(), MAX_RECENT_ERRORS);
+        assert_eq!(diagnostics.recent_errors[0].message, "1");
+        assert_eq!(diagnostics.self_captures_suppressed, 1);
+        assert_eq!(diagnostics.unsaved_changes, 3);
+        assert_eq!(diagnostics.forward_failures, 2);
+        assert_eq!(diagnostics.replication_failures, 1);
+        assert_eq!(diagnostics.replication_lag, 4);
+    }
+}
@@ -1,10 +1,14 @@
 mod error;
+mod health;
 mod options;
 mod toggle;
 
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 use clipcat_base::{ClipEntry, ClipFilter, ClipboardContent, ClipboardKind};
@@ -14,17 +18,19 @@ use tokio::sync::broadcast;
 
 pub use self::{
     error::Error,
+    health::Health as ClipboardWatcherHealth,
     options::{Error as ClipboardWatcherOptionsError, Options as ClipboardWatcherOptions},
     toggle::Toggle as ClipboardWatcherToggle,
     Worker as ClipboardWatcherWorker,
 };
 use crate::{
-    backend::{ClipboardBackend, Error as BackendError},
+    backend::{ClipboardBackend, Error as BackendError, SelectionChange},
     notification,
 };
 
 pub struct ClipboardWatcher<Notification> {
     is_watching: Arc<AtomicBool>,
+    health: ClipboardWatcherHealth,
     clip_sender: broadcast::Sender<ClipEntry>,
     notification: Notification,
 }
@@ -41,22 +47,33 @@ where
     ) -> (Self, ClipboardWatcherWorker) {
         let (clip_sender, _event_receiver) = broadcast::channel(16);
         let is_watching = Arc::new(AtomicBool::new(true));
+        let targets_supported =
+            std::array::from_fn(|i| backend.targets(ClipboardKind::from(i)).is_some());
+        let health = ClipboardWatcherHealth::new(opts.get_enable_kinds(), targets_supported);
         let watcher = Self {
             is_watching: is_watching.clone(),
+            health: health.clone(),
             clip_sender: clip_sender.clone(),
             notification,
         };
         let worker =
-            ClipboardWatcherWorker { backend, clip_sender, clip_filter, is_watching, opts };
+            ClipboardWatcherWorker { backend, clip_sender, clip_filter, is_watching, health, opts };
         (watcher, worker)
     }
 
+    #[inline]
+    pub fn health(&self) -> ClipboardWatcherHealth { self.health.clone() }
+
     #[inline]
     pub fn subscribe(&self) -> broadcast::Receiver<ClipEntry> { self.clip_sender.subscribe() }
 
     #[inline]
     pub fn get_toggle(&self) -> ClipboardWatcherToggle<Notification> {
-        ClipboardWatcherToggle::new(self.is_watching.clone(), self.notification.clone())
+        ClipboardWatcherToggle::new(
+            self.is_watching.clone(),
+            self.health.clone(),
+            self.notification.clone(),
+        )
     }
 }
 
@@ -65,15 +82,29 @@ pub struct Worker {
     clip_sender: broadcast::Sender<ClipEntry>,
     clip_filter: Arc<ClipFilter>,
     is_watching: Arc<AtomicBool>,
+    health: ClipboardWatcherHealth,
     opts: ClipboardWatcherOptions,
 }
 
 impl Worker {
     /// # Errors
-    #[allow(clippy::redundant_pub_crate)]
     pub async fn serve(self, shutdown_signal: sigfinn::Shutdown) -> Result<(), Error> {
+        let health = self.health.clone();
+        health.set_worker_running(true);
+        let result = self.watch(shutdown_signal).await;
+        health.set_worker_running(false);
+        if let Err(err) = &result {
+            health.record_error(None, err.to_string());
+        }
+        result
+    }
+
+    #[allow(clippy::redundant_pub_crate)]
+    async fn watch(self, shutdown_signal: sigfinn::Shutdown) -> Result<(), Error> {
         let enabled_kinds = self.opts.get_enable_kinds();
-        let Self { backend, is_watching, clip_sender, clip_filter, .. } = self;
+        let truncate_length = self.opts.truncate_text.then_some(self.opts.filter_text_max_length);
+        let commit_delay = self.opts.commit_delay;
+        let Self { backend, is_watching, clip_sender, clip_filter, health, .. } = self;
         let mut subscriber = backend.subscribe()?;
         let mut shutdown_signal = shutdown_signal.into_stream();
         let mut current_contents: [ClipboardContent; ClipboardKind::MAX_LENGTH] =
@@ -87,14 +118,14 @@ impl Worker {
             if enable {
                 match backend.load(kind, None).await {
                     Ok(data) => {
+                        let (data, original_size) = truncate_text(data, truncate_length);
                         if !clip_filter.filter_clipboard_content(data.as_ref()) {
                             current_contents[usize::from(kind)] = data.clone();
-                            if let Err(_err) = clip_sender
-                                .send(ClipEntry::from_clipboard_content(data, kind, None))
-                            {
-                                tracing::info!("ClipEntry receiver is closed.");
-                                return Err(Error::SendClipEntry);
-                            }
+                            let owned_by_us = backend.owns(kind, &data);
+                            let mut clip = ClipEntry::from_clipboard_content(data, kind, None);
+                            clip.set_owned_by_us(owned_by_us);
+                            clip.set_original_size(original_size);
+                            send_clip(&clip_sender, &health, clip)?;
                         }
                     }
                     Err(
@@ -103,9 +134,7 @@ impl Worker {
                         | BackendError::UnknownContentType
                         | BackendError::UnsupportedClipboardKind { .. },
                     ) => continue,
-                    Err(error) => {
-                        tracing::error!("Failed to load clipboard, error: {error}");
-                    }
+                    Err(error) => record_load_error(&health, kind, &error),
                 }
             }
         }
@@ -115,19 +144,48 @@ impl Worker {
                 event = subscriber.next() => event,
                 _ = shutdown_signal.next() => return Ok(()),
             };
-            let (kind, mime) = maybe_event.context(error::SubscriberClosedSnafu)?;
+            let SelectionChange { kind, mime, targets, source_window } =
+                maybe_event.context(error::SubscriberClosedSnafu)?;
             if is_watching.load(Ordering::Relaxed) && enabled_kinds[usize::from(kind)] {
-                match backend.load(kind, Some(mime)).await {
-                    Ok(new_content)
+                match backend
+                    .load(kind, Some(mime.clone()))
+                    .await
+                    .map(|content| truncate_text(content, truncate_length))
+                {
+                    Ok((new_content, original_size))
                         if !clip_filter.filter_clipboard_content(new_content.as_ref())
                             && current_contents[usize::from(kind)] != new_content =>
                     {
+                        if !commit_delay.is_zero()
+                            && !is_still_selected(
+                                &*backend,
+                                kind,
+                                &mime,
+                                &new_content,
+                                commit_delay,
+                                truncate_length,
+                            )
+                            .await
+                        {
+                            // the selection is set and cleared again at once
+                            continue;
+                        }
                         current_contents[usize::from(kind)] = new_content.clone();
-                        let clip = ClipEntry::from_clipboard_content(new_content, kind, None);
-                        if let Err(_err) = clip_sender.send(clip) {
-                            tracing::info!("ClipEntry receiver is closed.");
-                            return Err(Error::SendClipEntry);
+                        let owned_by_us = backend.owns(kind, &new_content);
+                        let mut clip = ClipEntry::from_clipboard_content(new_content, kind, None);
+                        clip.set_owned_by_us(owned_by_us);
+                        clip.set_original_size(original_size);
+                        clip.set_available_targets(targets);
+                        clip.set_source_window(source_window);
+                        // copied files are loaded as text, keep their format if
+                        // the text is indeed a list of files
+                        if clipcat_base::utils::is_uri_list(&mime)
+                            && !clipcat_base::utils::uri_list_to_paths(&clip.as_utf8_string())
+                                .is_empty()
+                        {
+                            clip.set_text_mime(mime);
                         }
+                        send_clip(&clip_sender, &health, clip)?;
                     }
                     Ok(_)
                     | Err(
@@ -135,11 +193,107 @@ impl Worker {
                         | BackendError::MatchMime { .. }
                         | BackendError::UnknownContentType,
                     ) => continue,
-                    Err(error) => {
-                        tracing::error!("Failed to load clipboard, error: {error}");
-                    }
+                    Err(error) => record_load_error(&health, kind, &error),
+                }
+            }
+        }
+    }
+}
+
+/// Records the capture of `clip` in the diagnostics and sends it.
+fn send_clip(
+    clip_sender: &broadcast::Sender<ClipEntry>,
+    health: &ClipboardWatcherHealth,
+    clip: ClipEntry,
+) -> Result<(), Error> {
+    health.record_capture(clip.kind());
+    if let Err(_err) = clip_sender.send(clip) {
+        tracing::info!("ClipEntry receiver is closed.");
+        return Err(Error::SendClipEntry);
+    }
+    Ok(())
+}
+
+/// Logs the error of loading the clipboard of `kind` and records it in the
+/// diagnostics.
+fn record_load_error(health: &ClipboardWatcherHealth, kind: ClipboardKind, error: &BackendError) {
+    tracing::error!("Failed to load clipboard, error: {error}");
+    health.record_error(Some(kind), error.to_string());
+}
+
+/// Waits for `delay`, returns `true` if `content` is still the selection of
+/// `kind` then.
+async fn is_still_selected(
+    backend: &dyn ClipboardBackend,
+    kind: ClipboardKind,
+    mime: &mime::Mime,
+    content: &ClipboardContent,
+    delay: Duration,
+    truncate_length: Option<usize>,
+) -> bool {
+    tokio::time::sleep(delay).await;
+    backend
+        .load(kind, Some(mime.clone()))
+        .await
+        .is_ok_and(|live| truncate_text(live, truncate_length).0 == *content)
+}
+
+/// Truncates text longer than `max_length` characters, returns the size in
+/// bytes of the original text if it is truncated.
+fn truncate_text(
+    content: ClipboardContent,
+    max_length: Option<usize>,
+) -> (ClipboardContent, Option<u64>) {
+    match (content, max_length) {
+        (ClipboardContent::Plaintext(text), Some(max_length)) => {
+            match text.char_indices().nth(max_length) {
+                Some((end, _)) => {
+                    let original_size = u64::try_from(text.len()).unwrap_or(u64::MAX);
+                    (ClipboardContent::Plaintext(text[..end].to_string()), Some(original_size))
                 }
+                None => (ClipboardContent::Plaintext(text), None),
             }
         }
+        (content, _) => (content, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use clipcat_base::{ClipboardContent, ClipboardKind};
+
+    use super::{is_still_selected, truncate_text};
+    use crate::backend::{ClipboardBackend, LocalClipboardBackend};
+
+    #[test]
+    fn test_truncate_text() {
+        let text = ClipboardContent::Plaintext("АБВГД".to_string());
+        assert_eq!(truncate_text(text.clone(), None), (text.clone(), None));
+        assert_eq!(truncate_text(text.clone(), Some(5)), (text.clone(), None));
+        assert_eq!(
+            truncate_text(text, Some(2)),
+            (ClipboardContent::Plaintext("АБ".to_string()), Some(10))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_is_still_selected() {
+        let backend = LocalClipboardBackend::new();
+        let kind = ClipboardKind::Clipboard;
+        let mime = mime::TEXT_PLAIN_UTF_8;
+        let content = ClipboardContent::Plaintext("kept".to_string());
+        backend.store(kind, content.clone()).await.unwrap();
+        assert!(
+            is_still_selected(&backend, kind, &mime, &content, Duration::from_millis(1), None)
+                .await
+        );
+
+        let flicker = ClipboardContent::Plaintext("flicker".to_string());
+        assert!(
+            !is_still_selected(&backend, kind, &mime, &flicker, Duration::from_millis(1), None)
+                .await
+        );
     }
 }
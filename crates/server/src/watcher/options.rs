@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 use clipcat_base::{ClipFilter, ClipboardKind};
 use snafu::Snafu;
@@ -19,11 +19,22 @@ pub struct Options {
 
     pub filter_text_max_length: usize,
 
+    // truncate text clips longer than `filter_text_max_length` instead of
+    // ignoring them
+    pub truncate_text: bool,
+
     pub filter_image_max_size: usize,
 
     pub denied_text_regex_patterns: HashSet<String>,
 
     pub sensitive_x11_atoms: HashSet<String>,
+
+    pub denied_applications: HashSet<String>,
+
+    // a captured change is stored only if it is still the selection after
+    // this delay, so a selection set and cleared again at once is dropped,
+    // zero to store changes at once
+    pub commit_delay: Duration,
 }
 
 impl Options {
@@ -36,6 +47,7 @@ impl Options {
         filter.deny_image(!self.capture_image);
         filter.set_regex_patterns(regex::RegexSet::new(&self.denied_text_regex_patterns)?);
         filter.add_sensitive_atoms(self.sensitive_x11_atoms.clone());
+        filter.add_denied_applications(self.denied_applications.clone());
         Ok(filter)
     }
 
@@ -64,10 +76,13 @@ impl Default for Options {
             capture_image: true,
             filter_text_min_length: 1,
             filter_text_max_length: 5 * (1 << 20),
+            truncate_text: false,
             // 5 MiB
             filter_image_max_size: 5 * (1 << 20),
             denied_text_regex_patterns: HashSet::new(),
             sensitive_x11_atoms: HashSet::new(),
+            denied_applications: HashSet::new(),
+            commit_delay: Duration::ZERO,
         }
     }
 }
@@ -82,8 +97,11 @@ impl Options {
         if self.enable_primary {
             kinds[usize::from(ClipboardKind::Primary)] = true;
         }
+        if self.enable_secondary {
+            kinds[usize::from(ClipboardKind::Secondary)] = true;
+        }
         if kinds.iter().all(|x| !x) {
-            tracing::warn!("Both clipboard and primary are not watched");
+            tracing::warn!("None of clipboard, primary and secondary are watched");
         }
         kinds
     }
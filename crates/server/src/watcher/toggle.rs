@@ -3,12 +3,14 @@ use std::sync::{
     Arc,
 };
 
-use clipcat_base::ClipboardWatcherState;
+use clipcat_base::{ClipboardWatcherState, Diagnostics};
 
-use crate::notification;
+use crate::{notification, watcher::ClipboardWatcherHealth};
 
+#[derive(Clone)]
 pub struct Toggle<Notification> {
     is_watching: Arc<AtomicBool>,
+    health: ClipboardWatcherHealth,
     notification: Notification,
 }
 
@@ -16,8 +18,12 @@ impl<Notification> Toggle<Notification>
 where
     Notification: notification::Notification,
 {
-    pub fn new(is_watching: Arc<AtomicBool>, notification: Notification) -> Self {
-        Self { is_watching, notification }
+    pub fn new(
+        is_watching: Arc<AtomicBool>,
+        health: ClipboardWatcherHealth,
+        notification: Notification,
+    ) -> Self {
+        Self { is_watching, health, notification }
     }
 
     #[inline]
@@ -47,6 +53,10 @@ where
     #[must_use]
     pub fn is_watching(&self) -> bool { self.is_watching.load(Ordering::Acquire) }
 
+    #[inline]
+    #[must_use]
+    pub fn diagnostics(&self) -> Diagnostics { self.health.diagnostics() }
+
     #[inline]
     #[must_use]
     pub fn state(&self) -> ClipboardWatcherState {